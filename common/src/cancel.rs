@@ -0,0 +1,50 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+// Lets another thread abort a long-running search. Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel_after(&self, timeout: Duration) {
+        let token = self.clone();
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            token.cancel();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel() {
+        let cancel = CancellationToken::default();
+        let clone = cancel.clone();
+        assert!(!cancel.is_cancelled());
+        clone.cancel();
+        assert!(cancel.is_cancelled());
+
+        let cancel = CancellationToken::default();
+        cancel.cancel_after(Duration::ZERO);
+        while !cancel.is_cancelled() {
+            thread::yield_now();
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+// Visits everything reachable from the start cells, where a step from a visited cell to one of
+// its neighbours is only taken if `passable(from, to)`. Each cell is visited once, in the order
+// it is reached, and `passable` is only asked for cells which have not been visited yet.
+// Returns the visited cells.
+pub fn flood_fill<C, N>(
+    start: impl IntoIterator<Item = C>,
+    mut neighbours: impl FnMut(C) -> N,
+    mut passable: impl FnMut(C, C) -> bool,
+    mut visit: impl FnMut(C),
+) -> HashSet<C>
+where
+    C: Copy + Eq + Hash,
+    N: IntoIterator<Item = C>,
+{
+    let mut visited = HashSet::new();
+    let mut queue = Vec::new();
+    for cell in start {
+        if visited.insert(cell) {
+            visit(cell);
+            queue.push(cell);
+        }
+    }
+
+    while let Some(cell) = queue.pop() {
+        for neighbour in neighbours(cell) {
+            if !visited.contains(&neighbour) && passable(cell, neighbour) {
+                visited.insert(neighbour);
+                visit(neighbour);
+                queue.push(neighbour);
+            }
+        }
+    }
+
+    visited
+}
+
+// All cells of a grid of the given width and height, row by row.
+pub fn cells(width: usize, height: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+}
+
+// The up to 4 cells sharing an edge with (x, y) in a grid of the given width and height.
+pub fn orthogonal_neighbours(
+    (x, y): (usize, usize),
+    width: usize,
+    height: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    let left = (x > 0).then(|| (x - 1, y));
+    let right = (x + 1 < width).then_some((x + 1, y));
+    let up = (y > 0).then(|| (x, y - 1));
+    let down = (y + 1 < height).then_some((x, y + 1));
+    [left, right, up, down].into_iter().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The cells of a drawn shape which are not '#'.
+    fn fill_shape(shape: &[&str], start: (usize, usize)) -> Vec<(usize, usize)> {
+        let (width, height) = (shape[0].len(), shape.len());
+        let open = |(x, y): (usize, usize)| shape[y].as_bytes()[x] != b'#';
+        let mut filled: Vec<_> = flood_fill(
+            [start],
+            |cell| orthogonal_neighbours(cell, width, height),
+            |_, to| open(to),
+            |_| {},
+        )
+        .into_iter()
+        .collect();
+        filled.sort_unstable_by_key(|&(x, y)| (y, x));
+        filled
+    }
+
+    #[test]
+    fn test_flood_fill_shapes() {
+        // the inside of a ring stays unreached
+        let ring = [".....", ".###.", ".#.#.", ".###.", "....."];
+        assert_eq!(fill_shape(&ring, (0, 0)).len(), 16);
+        assert_eq!(fill_shape(&ring, (2, 2)), vec![(2, 2)]);
+
+        // diagonal gaps don't connect
+        let stairs = ["..#", ".#.", "#.."];
+        assert_eq!(fill_shape(&stairs, (0, 0)), vec![(0, 0), (1, 0), (0, 1)]);
+        assert_eq!(fill_shape(&stairs, (2, 2)).len(), 3);
+
+        // a winding corridor is followed to its end
+        let corridor = [".#...", ".#.#.", "...#.", "####."];
+        assert_eq!(fill_shape(&corridor, (0, 0)).len(), 12);
+    }
+
+    #[test]
+    fn test_flood_fill_visits() {
+        // only uphill moves on a line, starting from two valleys
+        let heights = [3, 1, 2, 4, 0, 5, 5];
+        let mut visited = Vec::new();
+        let filled = flood_fill(
+            [1, 4, 1],
+            |i: usize| {
+                [i.checked_sub(1), Some(i + 1).filter(|&j| j < heights.len())]
+                    .into_iter()
+                    .flatten()
+            },
+            |from, to| heights[to] > heights[from],
+            |i| visited.push(i),
+        );
+        assert_eq!(filled, HashSet::from([0, 1, 2, 3, 4, 5]));
+        // every cell is visited once, starting with the start cells
+        assert_eq!(visited.len(), 6);
+        assert_eq!(visited[..2], [1, 4]);
+    }
+
+    #[test]
+    fn test_cells() {
+        assert_eq!(
+            cells(2, 3).collect::<Vec<_>>(),
+            vec![(0, 0), (1, 0), (0, 1), (1, 1), (0, 2), (1, 2)]
+        );
+        assert_eq!(cells(0, 3).count(), 0);
+    }
+
+    #[test]
+    fn test_orthogonal_neighbours() {
+        let neighbours = |cell| orthogonal_neighbours(cell, 3, 2).collect::<Vec<_>>();
+        assert_eq!(neighbours((0, 0)), vec![(1, 0), (0, 1)]);
+        assert_eq!(neighbours((1, 1)), vec![(0, 1), (2, 1), (1, 0)]);
+        assert_eq!(neighbours((2, 0)), vec![(1, 0), (2, 1)]);
+    }
+}
@@ -0,0 +1,8 @@
+// The `deterministic` feature replaces the randomly seeded hasher by a fixed one, so iterating
+// over the maps and sets visits their entries in the same order in every run.
+#[cfg(feature = "deterministic")]
+pub type HashState = std::hash::BuildHasherDefault<std::collections::hash_map::DefaultHasher>;
+#[cfg(not(feature = "deterministic"))]
+pub type HashState = std::collections::hash_map::RandomState;
+pub type HashMap<K, V> = std::collections::HashMap<K, V, HashState>;
+pub type HashSet<T> = std::collections::HashSet<T, HashState>;
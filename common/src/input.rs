@@ -0,0 +1,105 @@
+use std::fmt;
+use std::str::FromStr;
+
+// Parses a whole puzzle input (or a block of it) into a typed structure.
+pub trait FromInput: Sized {
+    type Err;
+
+    fn from_input(input: &str) -> Result<Self, Self::Err>;
+}
+
+// One record per line, blank lines are skipped.
+impl<T: FromStr> FromInput for Vec<T> {
+    type Err = T::Err;
+
+    fn from_input(input: &str) -> Result<Self, Self::Err> {
+        input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+// Panics on malformed input, like `Solution::parse`.
+pub fn parse<T>(input: &str) -> T
+where
+    T: FromInput,
+    T::Err: fmt::Debug,
+{
+    T::from_input(input).unwrap()
+}
+
+// Blocks of lines separated by blank lines, lines of whitespace only count as blank.
+pub fn blocks(input: &str) -> Vec<&str> {
+    let mut blocks = Vec::new();
+    let mut start = None;
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if let Some(start) = start.take() {
+                blocks.push(&input[start..offset]);
+            }
+        } else if start.is_none() {
+            start = Some(offset);
+        }
+        offset += line.len();
+    }
+    if let Some(start) = start {
+        blocks.push(&input[start..]);
+    }
+    blocks
+}
+
+// The first block and everything after the blank lines following it, None if there is nothing
+// after the first block.
+pub fn header_and_body(input: &str) -> Option<(&str, &str)> {
+    let mut start = None;
+    let mut end = None;
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        let blank = line.trim().is_empty();
+        match (start, end) {
+            (None, _) if !blank => start = Some(offset),
+            (Some(_), None) if blank => end = Some(offset),
+            (Some(start), Some(end)) if !blank => {
+                return Some((&input[start..end], &input[offset..]));
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        assert_eq!(Vec::<u32>::from_input("1\n2\n\n3\n"), Ok(vec![1, 2, 3]));
+        assert!(Vec::<u32>::from_input("1\nx\n").is_err());
+        assert_eq!(parse::<Vec<u32>>("4\r\n5\r\n"), vec![4, 5]);
+    }
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(blocks("a\nb\n\nc\n  \n\nd"), vec!["a\nb\n", "c\n", "d"]);
+        assert_eq!(blocks("\n\na\r\n\r\nb\r\n"), vec!["a\r\n", "b\r\n"]);
+        assert!(blocks("\n \n").is_empty());
+    }
+
+    #[test]
+    fn test_header_and_body() {
+        assert_eq!(
+            header_and_body("NNCB\n\nCH -> B\nHH -> N\n"),
+            Some(("NNCB\n", "CH -> B\nHH -> N\n"))
+        );
+        assert_eq!(
+            header_and_body("\nhead\nline\n\n\nbody\n\nmore"),
+            Some(("head\nline\n", "body\n\nmore"))
+        );
+        assert_eq!(header_and_body("head\n\n"), None);
+    }
+}
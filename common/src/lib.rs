@@ -0,0 +1,218 @@
+pub mod cancel;
+pub mod grid;
+pub mod hash;
+pub mod input;
+pub mod rng;
+#[cfg(feature = "shortest-path")]
+pub mod shortest_path;
+#[cfg(feature = "smt")]
+pub mod smt;
+pub mod vec3;
+
+use cancel::CancellationToken;
+pub use input::FromInput;
+use std::io::{self, Read};
+use std::time::{Duration, Instant};
+use std::{env, fmt, fs};
+
+// Answer to one part of a puzzle, as it is entered on the website.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Answer(String);
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+macro_rules! answer_from {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(answer: $t) -> Self {
+                    Self(answer.to_string())
+                }
+            }
+        )*
+    };
+}
+
+answer_from!(usize, isize, u64, i64, u32, i32, u128, i128, String, &str);
+
+// Solution of one day, the input is parsed once for both parts of the puzzle.
+pub trait Solution {
+    type Input;
+
+    // Panics on malformed input, like the binaries do.
+    fn parse(input: &str) -> Self::Input;
+    fn part1(input: &Self::Input) -> Answer;
+    fn part2(input: &Self::Input) -> Answer;
+
+    // Same as `part1`, but gives up with `None` once `cancel` is cancelled. Only days with
+    // long-running searches check the token.
+    fn part1_cancellable(input: &Self::Input, _cancel: &CancellationToken) -> Option<Answer> {
+        Some(Self::part1(input))
+    }
+
+    fn part2_cancellable(input: &Self::Input, _cancel: &CancellationToken) -> Option<Answer> {
+        Some(Self::part2(input))
+    }
+}
+
+pub fn solve<S: Solution>(input: &str) -> (Answer, Answer) {
+    let input = S::parse(input);
+    (S::part1(&input), S::part2(&input))
+}
+
+// Wall-clock time spent in each phase of solving a puzzle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    pub parse: Duration,
+    pub part1: Duration,
+    pub part2: Duration,
+}
+
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.parse + self.part1 + self.part2
+    }
+}
+
+// None if the solution was cancelled before both parts were solved.
+pub fn solve_timed<S: Solution>(
+    input: &str,
+    cancel: &CancellationToken,
+) -> Option<((Answer, Answer), Timings)> {
+    let start = Instant::now();
+    let input = S::parse(input);
+    let parse = start.elapsed();
+
+    let start = Instant::now();
+    let part1 = S::part1_cancellable(&input, cancel)?;
+    let part1_time = start.elapsed();
+
+    let start = Instant::now();
+    let part2 = S::part2_cancellable(&input, cancel)?;
+    let part2_time = start.elapsed();
+
+    Some((
+        (part1, part2),
+        Timings {
+            parse,
+            part1: part1_time,
+            part2: part2_time,
+        },
+    ))
+}
+
+// Value following the flag `name` on the command line.
+fn flag_value(mut args: impl Iterator<Item = String>, name: &str) -> Option<String> {
+    args.find(|arg| arg == name)?;
+    args.next()
+}
+
+// Contents of the file given with `--input <path>` to a day binary, if any.
+pub fn read_input_file() -> Option<String> {
+    let path = flag_value(env::args(), "--input")?;
+    let input = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read input '{}': {}", path, e));
+    Some(input)
+}
+
+// Puzzle input of a day binary: the file given with `--input <path>`, otherwise stdin.
+pub fn read_input() -> String {
+    read_input_file().unwrap_or_else(|| {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input).unwrap();
+        input
+    })
+}
+
+fn selects_part(selection: Option<&str>, part: usize) -> bool {
+    match selection {
+        None | Some("all") => true,
+        Some("1") => part == 1,
+        Some("2") => part == 2,
+        Some(selection) => panic!("Invalid part '{}', expected 1, 2 or all", selection),
+    }
+}
+
+// Whether a day binary should solve the given part, selected with `--part 1|2|all`.
+// Both parts are solved by default.
+pub fn part_selected(part: usize) -> bool {
+    selects_part(flag_value(env::args(), "--part").as_deref(), part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sum;
+
+    impl Solution for Sum {
+        type Input = Vec<i64>;
+
+        fn parse(input: &str) -> Self::Input {
+            input.lines().map(|line| line.parse().unwrap()).collect()
+        }
+
+        fn part1(input: &Self::Input) -> Answer {
+            input.iter().sum::<i64>().into()
+        }
+
+        fn part2(input: &Self::Input) -> Answer {
+            format!("{} numbers", input.len()).into()
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        assert_eq!(
+            solve::<Sum>("1\n-5\n7\n"),
+            (Answer::from(3i64), Answer::from("3 numbers"))
+        );
+        assert_eq!(Answer::from(42usize).to_string(), "42");
+
+        let cancel = CancellationToken::default();
+        let (answers, timings) = solve_timed::<Sum>("1\n-5\n7\n", &cancel).unwrap();
+        assert_eq!(answers, solve::<Sum>("1\n-5\n7\n"));
+        assert_eq!(
+            timings.total(),
+            timings.parse + timings.part1 + timings.part2
+        );
+    }
+
+    #[test]
+    fn test_flag_value() {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert_eq!(
+            flag_value(
+                args("day1 --report --input input.txt").into_iter(),
+                "--input"
+            ),
+            Some("input.txt".to_string())
+        );
+        assert_eq!(
+            flag_value(args("day1 --report").into_iter(), "--input"),
+            None
+        );
+        assert_eq!(
+            flag_value(args("day1 --input").into_iter(), "--input"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_selects_part() {
+        assert!(selects_part(None, 1) && selects_part(None, 2));
+        assert!(selects_part(Some("all"), 1) && selects_part(Some("all"), 2));
+        assert!(selects_part(Some("1"), 1) && !selects_part(Some("1"), 2));
+        assert!(!selects_part(Some("2"), 1) && selects_part(Some("2"), 2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_selects_invalid_part() {
+        selects_part(Some("3"), 1);
+    }
+}
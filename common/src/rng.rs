@@ -0,0 +1,65 @@
+// Seeded xorshift64 generator, good enough for synthetic inputs and random sampling. The same
+// seed always gives the same numbers.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // zero is a fixed point of xorshift
+        Rng(if seed == 0 { 0x9e3779b97f4a7c15 } else { seed })
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // Slightly biased towards small numbers unless n is a power of two, which doesn't matter for
+    // the small n used here.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    // Uniformly distributed in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+
+    // Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(1);
+        assert_eq!(rng.next_u64(), 0x40822041);
+        assert_eq!(
+            Rng::new(0).next_u64(),
+            Rng::new(0x9e3779b97f4a7c15).next_u64()
+        );
+
+        let mut rng = Rng::new(0x2545f4914f6cdd1d);
+        assert!((0..1000).all(|_| rng.below(7) < 7));
+        assert!((0..1000).all(|_| (0.0..1.0).contains(&rng.next_f64())));
+
+        let mut numbers: Vec<_> = (0..100).collect();
+        rng.shuffle(&mut numbers);
+        assert_ne!(numbers, (0..100).collect::<Vec<_>>());
+        numbers.sort_unstable();
+        assert_eq!(numbers, (0..100).collect::<Vec<_>>());
+    }
+}
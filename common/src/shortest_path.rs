@@ -0,0 +1,241 @@
+// A* search over grids where the cost of a step may depend on where it comes from, enabled with
+// the `shortest-path` feature.
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Moves {
+    Orthogonal,
+    Diagonal,
+}
+
+const ORTHOGONAL_DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+const DIAGONAL_DIRECTIONS: [(isize, isize); 8] = [
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+impl Moves {
+    fn directions(&self) -> &'static [(isize, isize)] {
+        match self {
+            Moves::Orthogonal => &ORTHOGONAL_DIRECTIONS,
+            Moves::Diagonal => &DIAGONAL_DIRECTIONS,
+        }
+    }
+}
+
+// A single move from one cell to an adjacent one, `prev` is the cell we came from before
+// reaching `from` (None at the start). Positions are (x, y).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub prev: Option<(usize, usize)>,
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+    pub value: usize,
+}
+
+impl Step {
+    pub fn is_diagonal(&self) -> bool {
+        self.from.0 != self.to.0 && self.from.1 != self.to.1
+    }
+
+    pub fn is_turn(&self) -> bool {
+        self.prev.is_some_and(|prev| {
+            let incoming = delta(prev, self.from);
+            let outgoing = delta(self.from, self.to);
+            incoming != outgoing
+        })
+    }
+}
+
+fn delta(from: (usize, usize), to: (usize, usize)) -> (isize, isize) {
+    (
+        to.0 as isize - from.0 as isize,
+        to.1 as isize - from.1 as isize,
+    )
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+struct SearchState {
+    estimate: usize,
+    cost: usize,
+    state: usize,
+}
+
+impl Ord for SearchState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.state.cmp(&other.state))
+    }
+}
+
+impl PartialOrd for SearchState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// A* search over the cells of a grid of the given width and height, where `value(x, y)` is the
+// value of a cell and entering a cell costs `cost(step)`. Nodes are cell indices, row by row.
+// Since the cost may depend on the incoming direction, a search state is a cell together with
+// the direction it was entered from. `heuristic(node)` must never overestimate the remaining
+// cost to `end` (use `|_| 0` for plain Dijkstra).
+pub fn shortest_path<V, C, H>(
+    (width, height): (usize, usize),
+    value: V,
+    start: usize,
+    end: usize,
+    moves: Moves,
+    cost: C,
+    heuristic: H,
+) -> Option<usize>
+where
+    V: Fn(usize, usize) -> usize,
+    C: Fn(Step) -> usize,
+    H: Fn(usize) -> usize,
+{
+    let directions = moves.directions();
+    // the extra direction is used for the start node, which has not been entered at all
+    let states_per_node = directions.len() + 1;
+    let start_direction = directions.len();
+
+    let mut heap = BinaryHeap::new();
+    let mut total_cost: Vec<usize> = vec![usize::MAX; width * height * states_per_node];
+
+    let start_state = start * states_per_node + start_direction;
+    total_cost[start_state] = 0;
+    heap.push(SearchState {
+        estimate: heuristic(start),
+        cost: 0,
+        state: start_state,
+    });
+
+    while let Some(SearchState {
+        cost: node_cost,
+        state,
+        ..
+    }) = heap.pop()
+    {
+        if node_cost > total_cost[state] {
+            continue;
+        }
+
+        let node = state / states_per_node;
+        if node == end {
+            return Some(node_cost);
+        }
+
+        let from = (node % width, node / width);
+        let prev = directions.get(state % states_per_node).map(|&(dx, dy)| {
+            (
+                from.0.wrapping_add_signed(-dx),
+                from.1.wrapping_add_signed(-dy),
+            )
+        });
+
+        for (direction, &(dx, dy)) in directions.iter().enumerate() {
+            let x = from.0.wrapping_add_signed(dx);
+            let y = from.1.wrapping_add_signed(dy);
+            if x >= width || y >= height {
+                continue;
+            }
+
+            let new_cost = node_cost
+                + cost(Step {
+                    prev,
+                    from,
+                    to: (x, y),
+                    value: value(x, y),
+                });
+            let v = y * width + x;
+            let new_state = v * states_per_node + direction;
+            if new_cost < total_cost[new_state] {
+                total_cost[new_state] = new_cost;
+                heap.push(SearchState {
+                    estimate: new_cost + heuristic(v),
+                    cost: new_cost,
+                    state: new_state,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ones(_x: usize, _y: usize) -> usize {
+        1
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let grid = [[1, 9, 1], [1, 9, 1], [1, 1, 1]];
+        let value = |x: usize, y: usize| grid[y][x];
+        assert_eq!(
+            shortest_path(
+                (3, 3),
+                value,
+                0,
+                2,
+                Moves::Orthogonal,
+                |step| step.value,
+                |_| 0
+            ),
+            Some(6)
+        );
+        assert_eq!(
+            shortest_path(
+                (3, 3),
+                value,
+                0,
+                2,
+                Moves::Diagonal,
+                |step| step.value,
+                |_| 0
+            ),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn test_step_costs() {
+        // every path needs at least one turn
+        let penalized = |penalty| {
+            shortest_path(
+                (3, 3),
+                ones,
+                0,
+                8,
+                Moves::Orthogonal,
+                |step| step.value + if step.is_turn() { penalty } else { 0 },
+                |_| 0,
+            )
+        };
+        assert_eq!(penalized(0), Some(4));
+        assert_eq!(penalized(10), Some(14));
+
+        // costs are in half units, so diagonal moves cost 1.5 times the value
+        let half_units = |step: Step| {
+            if step.is_diagonal() {
+                3 * step.value
+            } else {
+                2 * step.value
+            }
+        };
+        assert_eq!(
+            shortest_path((3, 3), ones, 0, 8, Moves::Diagonal, half_units, |_| 0),
+            Some(6)
+        );
+    }
+}
@@ -0,0 +1,196 @@
+// Builds SMT-LIB scripts for days which hand their constraints to an external solver,
+// enabled with the `smt` feature.
+use std::fmt;
+
+// A term of an SMT-LIB script, rendered as S-expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Term {
+    Symbol(String),
+    Int(i64),
+    App(&'static str, Vec<Term>),
+}
+
+impl Term {
+    pub fn symbol(name: impl Into<String>) -> Term {
+        Term::Symbol(name.into())
+    }
+
+    pub fn int(value: i64) -> Term {
+        Term::Int(value)
+    }
+
+    pub fn add(terms: Vec<Term>) -> Term {
+        Term::App("+", terms)
+    }
+
+    pub fn mul(terms: Vec<Term>) -> Term {
+        Term::App("*", terms)
+    }
+
+    // named after the SMT-LIB function, not an arithmetic operator on terms
+    #[allow(clippy::should_implement_trait)]
+    pub fn div(lhs: Term, rhs: Term) -> Term {
+        Term::App("div", vec![lhs, rhs])
+    }
+
+    pub fn modulo(lhs: Term, rhs: Term) -> Term {
+        Term::App("mod", vec![lhs, rhs])
+    }
+
+    pub fn eq(lhs: Term, rhs: Term) -> Term {
+        Term::App("=", vec![lhs, rhs])
+    }
+
+    // chainable, e.g. (< 0 w 10)
+    pub fn lt(terms: Vec<Term>) -> Term {
+        Term::App("<", terms)
+    }
+
+    pub fn ite(condition: Term, then: Term, otherwise: Term) -> Term {
+        Term::App("ite", vec![condition, then, otherwise])
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Term::Symbol(name) => write!(f, "{}", name),
+            // SMT-LIB has no negative numerals
+            Term::Int(value) if *value < 0 => write!(f, "(- {})", value.unsigned_abs()),
+            Term::Int(value) => write!(f, "{}", value),
+            Term::App(function, args) => {
+                write!(f, "({}", function)?;
+                for arg in args {
+                    write!(f, " {}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+// Collects the commands of an SMT-LIB script.
+#[derive(Debug, Default)]
+pub struct SmtBuilder {
+    script: String,
+}
+
+impl SmtBuilder {
+    pub fn new() -> SmtBuilder {
+        Self::default()
+    }
+
+    fn command(&mut self, command: fmt::Arguments) -> &mut Self {
+        self.script.push_str(&command.to_string());
+        self.script.push('\n');
+        self
+    }
+
+    pub fn comment(&mut self, text: &str) -> &mut Self {
+        self.command(format_args!("; {}", text))
+    }
+
+    // Declares the constant and returns it for use in other terms.
+    pub fn declare_const(&mut self, name: &str, sort: &str) -> Term {
+        self.command(format_args!("(declare-const {} {})", name, sort));
+        Term::symbol(name)
+    }
+
+    pub fn assert(&mut self, term: Term) -> &mut Self {
+        self.command(format_args!("(assert {})", term))
+    }
+
+    pub fn assert_eq(&mut self, lhs: Term, rhs: Term) -> &mut Self {
+        self.assert(Term::eq(lhs, rhs))
+    }
+
+    pub fn maximize(&mut self, term: &Term) -> &mut Self {
+        self.command(format_args!("(maximize {})", term))
+    }
+
+    pub fn minimize(&mut self, term: &Term) -> &mut Self {
+        self.command(format_args!("(minimize {})", term))
+    }
+
+    pub fn push(&mut self) -> &mut Self {
+        self.command(format_args!("(push)"))
+    }
+
+    pub fn pop(&mut self) -> &mut Self {
+        self.command(format_args!("(pop)"))
+    }
+
+    pub fn check_sat(&mut self) -> &mut Self {
+        self.command(format_args!("(check-sat)"))
+    }
+
+    pub fn get_value(&mut self, terms: &[Term]) -> &mut Self {
+        let terms: Vec<String> = terms.iter().map(Term::to_string).collect();
+        self.command(format_args!("(get-value ({}))", terms.join(" ")))
+    }
+}
+
+impl fmt::Display for SmtBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.script)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_terms() {
+        let x = Term::symbol("x");
+        assert_eq!(Term::int(42).to_string(), "42");
+        assert_eq!(Term::int(-2).to_string(), "(- 2)");
+        assert_eq!(
+            Term::add(vec![Term::modulo(x.clone(), Term::int(26)), Term::int(-14)]).to_string(),
+            "(+ (mod x 26) (- 14))"
+        );
+        assert_eq!(
+            Term::ite(
+                Term::eq(x.clone(), Term::int(1)),
+                Term::div(x.clone(), Term::int(26)),
+                Term::mul(vec![Term::int(26), x]),
+            )
+            .to_string(),
+            "(ite (= x 1) (div x 26) (* 26 x))"
+        );
+    }
+
+    #[test]
+    fn test_builder() {
+        let mut smt = SmtBuilder::new();
+        let w = smt.declare_const("w", "Int");
+        smt.comment("digit")
+            .assert(Term::lt(vec![Term::int(0), w.clone(), Term::int(10)]))
+            .push()
+            .maximize(&w)
+            .check_sat()
+            .get_value(std::slice::from_ref(&w))
+            .pop()
+            .push()
+            .minimize(&w)
+            .assert_eq(w, Term::int(3))
+            .pop();
+
+        assert_eq!(
+            smt.to_string(),
+            "(declare-const w Int)
+; digit
+(assert (< 0 w 10))
+(push)
+(maximize w)
+(check-sat)
+(get-value (w))
+(pop)
+(push)
+(minimize w)
+(assert (= w 3))
+(pop)
+"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::ops;
+
+// Integer vector in 3D space, 2D vectors have z = 0.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vec3 {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+}
+
+impl Vec3 {
+    pub const ZERO: Vec3 = Vec3::new(0, 0, 0);
+
+    pub const fn new(x: isize, y: isize, z: isize) -> Vec3 {
+        Self { x, y, z }
+    }
+
+    pub fn dot(self, other: Vec3) -> i128 {
+        self.x as i128 * other.x as i128
+            + self.y as i128 * other.y as i128
+            + self.z as i128 * other.z as i128
+    }
+
+    // Products of two components don't necessarily fit into an isize, hence the wide result.
+    pub fn cross(self, other: Vec3) -> WideVec3 {
+        let (a, b) = (WideVec3::from(self), WideVec3::from(other));
+        WideVec3 {
+            x: a.y * b.z - a.z * b.y,
+            y: a.z * b.x - a.x * b.z,
+            z: a.x * b.y - a.y * b.x,
+        }
+    }
+
+    pub fn manhattan_length(self) -> isize {
+        self.x.abs() + self.y.abs() + self.z.abs()
+    }
+
+    // Greatest common divisor of the components, 0 for the zero vector.
+    pub fn gcd(self) -> isize {
+        gcd(gcd(self.x, self.y), self.z)
+    }
+
+    // Shortest integer vector pointing in the same direction, the zero vector stays zero.
+    pub fn reduced(self) -> Vec3 {
+        match self.gcd() {
+            0 => self,
+            n => Self::new(self.x / n, self.y / n, self.z / n),
+        }
+    }
+
+    pub fn signum(self) -> Vec3 {
+        Self::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+
+    pub fn components(self) -> [isize; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+// Result of vector products, with room for the product of two isize components.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WideVec3 {
+    pub x: i128,
+    pub y: i128,
+    pub z: i128,
+}
+
+impl WideVec3 {
+    pub const ZERO: WideVec3 = WideVec3 { x: 0, y: 0, z: 0 };
+
+    pub fn dot(self, other: WideVec3) -> i128 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn components(self) -> [i128; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+impl From<Vec3> for WideVec3 {
+    fn from(v: Vec3) -> Self {
+        Self {
+            x: v.x as i128,
+            y: v.y as i128,
+            z: v.z as i128,
+        }
+    }
+}
+
+pub fn gcd(a: isize, b: isize) -> isize {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl ops::Add for Vec3 {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self::Output {
+        self.x += other.x;
+        self.y += other.y;
+        self.z += other.z;
+        self
+    }
+}
+
+impl ops::Sub for Vec3 {
+    type Output = Self;
+
+    fn sub(mut self, other: Self) -> Self::Output {
+        self.x -= other.x;
+        self.y -= other.y;
+        self.z -= other.z;
+        self
+    }
+}
+
+impl ops::Neg for Vec3 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl ops::Mul<isize> for Vec3 {
+    type Output = Self;
+
+    fn mul(mut self, factor: isize) -> Self::Output {
+        self.x *= factor;
+        self.y *= factor;
+        self.z *= factor;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vector_math() {
+        let a = Vec3::new(1, 2, 3);
+        let b = Vec3::new(-4, 0, 2);
+        assert_eq!(a + b, Vec3::new(-3, 2, 5));
+        assert_eq!(a - b, Vec3::new(5, 2, 1));
+        assert_eq!(-a, Vec3::new(-1, -2, -3));
+        assert_eq!(b * 3, Vec3::new(-12, 0, 6));
+        assert_eq!(a.dot(b), 2);
+        assert_eq!(a.cross(b), Vec3::new(4, -14, 8).into());
+        assert_eq!(a.cross(b).dot(a.into()), 0);
+        assert_eq!(b.manhattan_length(), 6);
+
+        // the cross product of 2D vectors points along z
+        assert_eq!(
+            Vec3::new(3, 1, 0).cross(Vec3::new(1, 2, 0)),
+            Vec3::new(0, 0, 5).into()
+        );
+
+        // components beyond the square root of isize::MAX
+        let big = 5_000_000_000;
+        assert_eq!(
+            Vec3::new(big, big, 0).cross(Vec3::new(big, -big, 0)),
+            WideVec3 {
+                x: 0,
+                y: 0,
+                z: -2 * 25_000_000_000_000_000_000
+            }
+        );
+    }
+
+    #[test]
+    fn test_reduced() {
+        assert_eq!(Vec3::new(6, -4, 0).gcd(), 2);
+        assert_eq!(Vec3::new(6, -4, 0).reduced(), Vec3::new(3, -2, 0));
+        assert_eq!(Vec3::new(0, 0, -7).reduced(), Vec3::new(0, 0, -1));
+        assert_eq!(Vec3::ZERO.reduced(), Vec3::ZERO);
+        assert_eq!(Vec3::new(5, 0, -3).signum(), Vec3::new(1, 0, -1));
+    }
+}
@@ -0,0 +1,295 @@
+use common::{Answer, Solution};
+use std::str::FromStr;
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<usize>;
+
+    fn parse(input: &str) -> Self::Input {
+        common::input::parse(input)
+    }
+
+    fn part1(depths: &Self::Input) -> Answer {
+        number_of_depth_increases(depths).into()
+    }
+
+    fn part2(depths: &Self::Input) -> Answer {
+        let windowed_depths: Vec<_> = depths.windows(3).map(|w| w.iter().sum()).collect();
+        number_of_depth_increases(&windowed_depths).into()
+    }
+}
+
+pub fn number_of_depth_increases(depths: &[usize]) -> usize {
+    depths.windows(2).filter(|w| w[0] < w[1]).count()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnomalyThreshold {
+    // maximal distance from the mean, in standard deviations
+    ZScore(f64),
+    // maximal difference to the last regular measurement
+    Jump(usize),
+}
+
+impl FromStr for AnomalyThreshold {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<AnomalyThreshold, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid threshold '{}', expected zscore:<f64> or jump:<usize>",
+                input
+            )
+        };
+        match input.split_once(':') {
+            Some(("zscore", z)) => z.parse().map(Self::ZScore).map_err(|_| invalid()),
+            Some(("jump", jump)) => jump.parse().map(Self::Jump).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+// Indices of the measurements exceeding the threshold, in ascending order.
+pub fn detect_anomalies(depths: &[usize], threshold: AnomalyThreshold) -> Vec<usize> {
+    match threshold {
+        AnomalyThreshold::ZScore(z) => {
+            let n = depths.len() as f64;
+            let mean = depths.iter().sum::<usize>() as f64 / n;
+            let variance = depths
+                .iter()
+                .map(|&depth| (depth as f64 - mean).powi(2))
+                .sum::<f64>()
+                / n;
+            let std_dev = variance.sqrt();
+            if std_dev == 0.0 {
+                return Vec::new();
+            }
+            (0..depths.len())
+                .filter(|&i| (depths[i] as f64 - mean).abs() / std_dev > z)
+                .collect()
+        }
+        AnomalyThreshold::Jump(jump) => {
+            // Comparing against the last regular measurement instead of the previous one
+            // flags a single spike only once, not also the measurement following it.
+            let mut anomalies = Vec::new();
+            let mut last_regular = match depths.first() {
+                Some(&depth) => depth,
+                None => return anomalies,
+            };
+            for (i, &depth) in depths.iter().enumerate().skip(1) {
+                if depth.abs_diff(last_regular) > jump {
+                    anomalies.push(i);
+                } else {
+                    last_regular = depth;
+                }
+            }
+            anomalies
+        }
+    }
+}
+
+pub fn without_anomalies(depths: &[usize], anomalies: &[usize]) -> Vec<usize> {
+    depths
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| anomalies.binary_search(i).is_err())
+        .map(|(_, &depth)| depth)
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Increasing,
+    Decreasing,
+}
+
+// Change between the measurement at `index` and the one following it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change {
+    pub index: usize,
+    pub amount: usize,
+}
+
+// Strictly monotonic run of `len` measurements, starting at `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+    pub start: usize,
+    pub len: usize,
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepthReport {
+    // window size and the number of increases of the sliding window sums
+    pub window_increases: Vec<(usize, usize)>,
+    pub largest_increase: Option<Change>,
+    pub largest_decrease: Option<Change>,
+    pub longest_monotonic_run: Option<Run>,
+}
+
+impl DepthReport {
+    // Computes all metrics in a single pass over the depths.
+    pub fn analyze(depths: &[usize], window_sizes: &[usize]) -> DepthReport {
+        let mut window_sizes = window_sizes.to_vec();
+        window_sizes.sort_unstable();
+        window_sizes.dedup();
+
+        let mut report = DepthReport {
+            window_increases: window_sizes.iter().map(|&size| (size, 0)).collect(),
+            largest_increase: None,
+            largest_decrease: None,
+            longest_monotonic_run: None,
+        };
+        let mut current_run: Option<Run> = None;
+
+        for (i, &depth) in depths.iter().enumerate() {
+            // Consecutive window sums only differ in their first and last element,
+            // hence the sum increases iff the element leaving the window is smaller
+            // than the one entering it.
+            for (size, increases) in report.window_increases.iter_mut() {
+                if *size > 0 && depths.get(i + *size).is_some_and(|&next| depth < next) {
+                    *increases += 1;
+                }
+            }
+
+            let next = match depths.get(i + 1) {
+                Some(&next) => next,
+                None => break,
+            };
+
+            let (largest, direction) = if depth < next {
+                (&mut report.largest_increase, Some(Direction::Increasing))
+            } else if depth > next {
+                (&mut report.largest_decrease, Some(Direction::Decreasing))
+            } else {
+                (&mut report.largest_increase, None)
+            };
+            let amount = depth.abs_diff(next);
+            if direction.is_some() && largest.is_none_or(|change| amount > change.amount) {
+                *largest = Some(Change { index: i, amount });
+            }
+
+            current_run = match (current_run, direction) {
+                (Some(run), Some(direction)) if run.direction == direction => Some(Run {
+                    len: run.len + 1,
+                    ..run
+                }),
+                (_, Some(direction)) => Some(Run {
+                    start: i,
+                    len: 2,
+                    direction,
+                }),
+                (_, None) => None,
+            };
+            if let Some(run) = current_run {
+                if report
+                    .longest_monotonic_run
+                    .is_none_or(|longest| run.len > longest.len)
+                {
+                    report.longest_monotonic_run = Some(run);
+                }
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &[usize] = &[199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn test_solution_example() {
+        assert_eq!(
+            common::solve::<Day1>(include_str!("../example.txt")),
+            (Answer::from(7usize), Answer::from(5usize))
+        );
+    }
+
+    #[test]
+    fn test_report_example() {
+        let report = DepthReport::analyze(EXAMPLE, &[3, 1, 2, 20]);
+        assert_eq!(
+            report,
+            DepthReport {
+                window_increases: vec![(1, 7), (2, 5), (3, 5), (20, 0)],
+                largest_increase: Some(Change {
+                    index: 5,
+                    amount: 33
+                }),
+                largest_decrease: Some(Change {
+                    index: 3,
+                    amount: 10
+                }),
+                longest_monotonic_run: Some(Run {
+                    start: 0,
+                    len: 4,
+                    direction: Direction::Increasing
+                }),
+            }
+        );
+
+        for &(size, increases) in &report.window_increases {
+            let windowed_depths: Vec<usize> =
+                EXAMPLE.windows(size).map(|w| w.iter().sum()).collect();
+            assert_eq!(number_of_depth_increases(&windowed_depths), increases);
+        }
+    }
+
+    #[test]
+    fn test_detect_anomalies() {
+        let depths = [100, 102, 101, 900, 103, 104, 2, 105];
+
+        let anomalies = detect_anomalies(&depths, AnomalyThreshold::Jump(50));
+        assert_eq!(anomalies, vec![3, 6]);
+        assert_eq!(
+            without_anomalies(&depths, &anomalies),
+            vec![100, 102, 101, 103, 104, 105]
+        );
+        assert_eq!(
+            number_of_depth_increases(&without_anomalies(&depths, &anomalies)),
+            4
+        );
+
+        assert_eq!(
+            detect_anomalies(&depths, AnomalyThreshold::ZScore(2.0)),
+            vec![3]
+        );
+        assert!(detect_anomalies(EXAMPLE, AnomalyThreshold::ZScore(3.0)).is_empty());
+        assert!(detect_anomalies(&[5, 5, 5], AnomalyThreshold::ZScore(0.0)).is_empty());
+        assert!(detect_anomalies(&[], AnomalyThreshold::Jump(1)).is_empty());
+
+        assert_eq!("zscore:2.5".parse(), Ok(AnomalyThreshold::ZScore(2.5)));
+        assert_eq!("jump:10".parse(), Ok(AnomalyThreshold::Jump(10)));
+        assert!("jump:-1".parse::<AnomalyThreshold>().is_err());
+        assert!("median:3".parse::<AnomalyThreshold>().is_err());
+    }
+
+    #[test]
+    fn test_report_flat_and_decreasing() {
+        let report = DepthReport::analyze(&[5, 5, 4, 3, 3], &[1]);
+        assert_eq!(report.window_increases, vec![(1, 0)]);
+        assert_eq!(report.largest_increase, None);
+        assert_eq!(
+            report.largest_decrease,
+            Some(Change {
+                index: 1,
+                amount: 1
+            })
+        );
+        assert_eq!(
+            report.longest_monotonic_run,
+            Some(Run {
+                start: 1,
+                len: 3,
+                direction: Direction::Decreasing
+            })
+        );
+
+        assert_eq!(DepthReport::analyze(&[], &[1]).longest_monotonic_run, None);
+    }
+}
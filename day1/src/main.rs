@@ -1,25 +1,65 @@
-use std::io::{self, BufRead};
+use common::Solution;
+use day1::*;
+use std::env;
 
 fn main() {
-    let depths: Vec<usize> = io::stdin()
-        .lock()
-        .lines()
-        .filter_map(|line| line.unwrap().parse().ok())
-        .collect();
+    let input = common::read_input();
+    let mut depths = Day1::parse(&input);
 
-    part1(&depths);
-    part2(&depths);
-}
+    if let Some(threshold) = env::args().skip_while(|arg| arg != "--anomalies").nth(1) {
+        let anomalies = detect_anomalies(&depths, threshold.parse().unwrap());
+        println!(
+            "Anomalies: {} (measurements {})",
+            anomalies.len(),
+            anomalies
+                .iter()
+                .map(|i| (i + 1).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if env::args().any(|arg| arg == "--exclude-anomalies") {
+            depths = without_anomalies(&depths, &anomalies);
+        }
+    }
 
-fn part1(depths: &[usize]) {
-    println!("Part 1: {}", number_of_depth_increases(depths));
-}
+    if common::part_selected(1) {
+        println!("Part 1: {}", Day1::part1(&depths));
+    }
+    if common::part_selected(2) {
+        println!("Part 2: {}", Day1::part2(&depths));
+    }
 
-fn part2(depths: &[usize]) {
-    let windowed_depths: Vec<_> = depths.windows(3).map(|w| w.iter().sum()).collect();
-    println!("Part 2: {}", number_of_depth_increases(&windowed_depths));
+    if env::args().any(|arg| arg == "--report") {
+        let mut window_sizes = vec![1, 3];
+        if let Some(sizes) = env::args().skip_while(|arg| arg != "--windows").nth(1) {
+            window_sizes.extend(sizes.split(',').map(|s| s.parse::<usize>().unwrap()));
+        }
+        print_report(&DepthReport::analyze(&depths, &window_sizes));
+    }
 }
 
-fn number_of_depth_increases(depths: &[usize]) -> usize {
-    depths.windows(2).filter(|w| w[0] < w[1]).count()
+fn print_report(report: &DepthReport) {
+    for (size, increases) in &report.window_increases {
+        println!("Increases (window size {}): {}", size, increases);
+    }
+    if let Some(Change { index, amount }) = report.largest_increase {
+        println!("Largest increase: {} (measurement {})", amount, index + 1);
+    }
+    if let Some(Change { index, amount }) = report.largest_decrease {
+        println!("Largest decrease: {} (measurement {})", amount, index + 1);
+    }
+    if let Some(Run {
+        start,
+        len,
+        direction,
+    }) = report.longest_monotonic_run
+    {
+        println!(
+            "Longest monotonic run: {} measurements {:?} (measurement {} to {})",
+            len,
+            direction,
+            start + 1,
+            start + len
+        );
+    }
 }
@@ -0,0 +1,435 @@
+pub mod scores;
+
+use common::{Answer, Solution};
+use scores::ScoreTable;
+use std::fmt;
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<String>;
+
+    fn parse(input: &str) -> Self::Input {
+        input.lines().map(String::from).collect()
+    }
+
+    fn part1(lines: &Self::Input) -> Answer {
+        total_corruption_score(lines, &ScoreTable::default()).into()
+    }
+
+    fn part2(lines: &Self::Input) -> Answer {
+        median_autocompletion_score(lines, &ScoreTable::default())
+            .unwrap()
+            .into()
+    }
+}
+
+pub fn total_corruption_score<S: AsRef<str>>(lines: &[S], scores: &ScoreTable) -> usize {
+    lines
+        .iter()
+        .flat_map(|s| check_syntax(s.as_ref()))
+        .map(|e| score_corruption_error(&e, scores))
+        .sum()
+}
+
+// None if all lines are corrupted.
+pub fn median_autocompletion_score<S: AsRef<str>>(
+    lines: &[S],
+    scores: &ScoreTable,
+) -> Option<usize> {
+    let autocompletions_scores: Vec<usize> = lines
+        .iter()
+        .map(|s| check_syntax(s.as_ref()))
+        .filter(|errors| !contains_corruption_error(errors))
+        .map(|errors| autocompletion_score(&errors, scores))
+        .collect();
+    median(&autocompletions_scores)
+}
+
+pub fn score_corruption_error(syntax_error: &SyntaxError, scores: &ScoreTable) -> usize {
+    match syntax_error.was {
+        Some(was) => scores.corruption_score(was),
+        None => 0,
+    }
+}
+
+pub fn contains_corruption_error(syntax_errors: &[SyntaxError]) -> bool {
+    syntax_errors.iter().any(|e| e.was.is_some())
+}
+
+pub fn autocompletion_score(syntax_errors: &[SyntaxError], scores: &ScoreTable) -> usize {
+    syntax_errors.iter().fold(0, |cost, syntax_error| {
+        cost * scores.completion_base + score_incompletion_error(syntax_error, scores)
+    })
+}
+
+pub fn score_incompletion_error(syntax_error: &SyntaxError, scores: &ScoreTable) -> usize {
+    match syntax_error {
+        SyntaxError {
+            was: None,
+            expected: Some(expected),
+            ..
+        } => scores.completion_score(*expected),
+        _ => 0,
+    }
+}
+
+pub fn median(xs: &[usize]) -> Option<usize> {
+    if xs.is_empty() {
+        return None;
+    }
+
+    let mut xs = xs.to_vec();
+    xs.sort_unstable();
+
+    if xs.len().is_multiple_of(2) {
+        Some((xs[xs.len() / 2 - 1] + xs[xs.len() / 2]) / 2)
+    } else {
+        Some(xs[xs.len() / 2])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub col: usize,
+    pub expected: Option<char>,
+    pub was: Option<char>,
+    pub fix: Option<Fix>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fix {
+    Replace { col: usize, with: char },
+    Insert { col: usize, c: char },
+    Delete { col: usize },
+}
+
+impl Fix {
+    pub fn apply(&self, line: &str) -> String {
+        let mut chars: Vec<char> = line.chars().collect();
+        match *self {
+            Fix::Replace { col, with } => chars[col] = with,
+            Fix::Insert { col, c } => chars.insert(col, c),
+            Fix::Delete { col } => {
+                chars.remove(col);
+            }
+        }
+        chars.into_iter().collect()
+    }
+
+    // Length of the prefix which is guaranteed to be valid after applying the fix.
+    fn fixed_prefix_len(&self) -> usize {
+        match *self {
+            Fix::Replace { col, .. } => col + 1,
+            Fix::Insert { col, .. } => col + 2,
+            Fix::Delete { col } => col,
+        }
+    }
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fix::Replace { col, with } => write!(f, "replace column {} with '{}'", col, with),
+            Fix::Insert { col, c } => write!(f, "insert '{}' at column {}", c, col),
+            Fix::Delete { col } => write!(f, "delete column {}", col),
+        }
+    }
+}
+
+pub fn check_syntax(line: &str) -> Vec<SyntaxError> {
+    let mut checker = SyntaxChecker::default();
+    checker.push_str(line);
+    checker.errors()
+}
+
+pub fn scan_syntax(line: &str) -> Vec<SyntaxError> {
+    let mut checker = SyntaxChecker::default();
+    checker.push_str(line);
+    checker.scan_errors()
+}
+
+// Checks a single line which is fed in chunks, e.g. while it is being typed. Errors and the
+// completion can be queried at any point and refer to the text received so far.
+#[derive(Debug, Default, Clone)]
+pub struct SyntaxChecker {
+    text: String,
+    len: usize,
+    stack: Vec<char>,
+    corruptions: Vec<SyntaxError>,
+}
+
+impl SyntaxChecker {
+    pub fn push_str(&mut self, chunk: &str) {
+        chunk.chars().for_each(|c| self.push(c));
+    }
+
+    pub fn push(&mut self, c: char) {
+        let col = self.len;
+        self.text.push(c);
+        self.len += 1;
+
+        if matches!(c, '(' | '[' | '{' | '<') {
+            self.stack.push(c);
+            return;
+        }
+
+        match self.stack.pop() {
+            Some(open) if opening_bracket(c) == Some(open) => {}
+            open => self.corruptions.push(SyntaxError {
+                col,
+                expected: open.and_then(closing_bracket),
+                was: Some(c),
+                fix: None,
+            }),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_corrupted(&self) -> bool {
+        !self.corruptions.is_empty()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    // Closing brackets which complete the text so far.
+    pub fn completion(&self) -> String {
+        self.stack
+            .iter()
+            .rev()
+            .filter_map(|&c| closing_bracket(c))
+            .collect()
+    }
+
+    // All errors of the text so far, including suggested fixes for corrupted brackets.
+    pub fn errors(&self) -> Vec<SyntaxError> {
+        let mut errors = self.scan_errors();
+        for error in errors.iter_mut() {
+            if error.was.is_some() {
+                error.fix = suggest_fix(&self.text, error);
+            }
+        }
+        errors
+    }
+
+    fn scan_errors(&self) -> Vec<SyntaxError> {
+        let missing = self
+            .stack
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, &c)| SyntaxError {
+                col: self.len + i,
+                expected: closing_bracket(c),
+                was: None,
+                fix: None,
+            });
+        self.corruptions.iter().cloned().chain(missing).collect()
+    }
+}
+
+pub fn closing_bracket(opening: char) -> Option<char> {
+    match opening {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        '<' => Some('>'),
+        _ => None,
+    }
+}
+
+pub fn opening_bracket(closing: char) -> Option<char> {
+    match closing {
+        ')' => Some('('),
+        ']' => Some('['),
+        '}' => Some('{'),
+        '>' => Some('<'),
+        _ => None,
+    }
+}
+
+// Picks the single bracket edit which makes the line valid up to the corrupted position,
+// preferring edits which leave the fewest errors in the rest of the line.
+pub fn suggest_fix(line: &str, error: &SyntaxError) -> Option<Fix> {
+    let col = error.col;
+    let was = error.was?;
+
+    let mut candidates = Vec::with_capacity(3);
+    if let Some(expected) = error.expected {
+        candidates.push(Fix::Replace {
+            col,
+            with: expected,
+        });
+    }
+    candidates.push(Fix::Delete { col });
+    if let Some(c) = error.expected.or_else(|| opening_bracket(was)) {
+        candidates.push(Fix::Insert { col, c });
+    }
+
+    candidates
+        .into_iter()
+        .filter_map(|fix| {
+            let errors = scan_syntax(&fix.apply(line));
+            let corruptions: Vec<usize> = errors
+                .iter()
+                .filter(|e| e.was.is_some())
+                .map(|e| e.col)
+                .collect();
+            if corruptions.iter().any(|&col| col < fix.fixed_prefix_len()) {
+                return None;
+            }
+            Some(((corruptions.len(), errors.len()), fix))
+        })
+        .min_by_key(|(cost, _)| *cost)
+        .map(|(_, fix)| fix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solution_example() {
+        assert_eq!(
+            common::solve::<Day10>(include_str!("../example.txt")),
+            (Answer::from(26397usize), Answer::from(288957usize))
+        );
+    }
+
+    #[test]
+    fn test_alternative_score_table() {
+        let lines = Day10::parse(include_str!("../example.txt"));
+        let scores: ScoreTable =
+            "corruption ) 1\ncorruption ] 1\ncorruption } 1\ncorruption > 1\nbase 10"
+                .parse()
+                .unwrap();
+
+        // every corrupted line has a single corrupted bracket
+        assert_eq!(total_corruption_score(&lines, &scores), 5);
+        // the completion "}}]])})]" read as a decimal number
+        assert_eq!(
+            autocompletion_score(&check_syntax("[({(<(())[]>[[{[]{<()<>>"), &scores),
+            33221312
+        );
+        // which is the median as with the scores of the puzzle
+        assert_eq!(median_autocompletion_score(&lines, &scores), Some(33221312));
+    }
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("()", vec![])]
+    #[case("[]", vec![])]
+    #[case("{}", vec![])]
+    #[case("<>", vec![])]
+    #[case("([])", vec![])]
+    #[case("{()()()}", vec![])]
+    #[case("<([{}])>", vec![])]
+    #[case("[<>({}){}[([])<>]]", vec![])]
+    #[case("(((((((((())))))))))", vec![])]
+    #[case("<(", vec![
+            SyntaxError{col: 2, expected: Some(')'), was: None, fix: None},
+            SyntaxError{col: 3, expected: Some('>'), was: None, fix: None}
+        ])]
+    #[case("(()", vec![SyntaxError{col: 3, expected: Some(')'), was: None, fix: None}])]
+    #[case("())", vec![SyntaxError{col: 2, expected: None, was: Some(')'), fix: Some(Fix::Delete{col: 2})}])]
+    #[case("(]", vec![SyntaxError{col: 1, expected: Some(')'), was: Some(']'), fix: Some(Fix::Replace{col: 1, with: ')'})}])]
+    #[case("{()()()>",vec![SyntaxError{col: 7, expected: Some('}'), was: Some('>'), fix: Some(Fix::Replace{col: 7, with: '}'})}])]
+    #[case("(((()))}", vec![SyntaxError{col: 7, expected: Some(')'), was: Some('}'), fix: Some(Fix::Replace{col: 7, with: ')'})}])]
+    #[case("<([]){()}[{}])", vec![SyntaxError{col: 13, expected: Some('>'), was: Some(')'), fix: Some(Fix::Replace{col: 13, with: '>'})}])]
+    fn test_syntactically_valid(#[case] line: &str, #[case] expected: Vec<SyntaxError>) {
+        assert_eq!(expected, check_syntax(line));
+    }
+
+    #[rstest]
+    #[case("[(]", Fix::Insert { col: 2, c: ')' }, "[()]")]
+    #[case("[(])", Fix::Delete { col: 2 }, "[()")]
+    #[case("{()()()>", Fix::Replace { col: 7, with: '}' }, "{()()()}")]
+    #[case("())", Fix::Delete { col: 2 }, "()")]
+    #[case("<{(>)}>", Fix::Delete { col: 3 }, "<{()}>")]
+    fn test_suggest_fix(#[case] line: &str, #[case] fix: Fix, #[case] fixed: &str) {
+        let error = check_syntax(line)
+            .into_iter()
+            .find(|e| e.was.is_some())
+            .unwrap();
+        assert_eq!(error.fix, Some(fix));
+        assert_eq!(fix.apply(line), fixed);
+    }
+
+    #[test]
+    fn test_fixed_prefix_is_valid() {
+        let lines = [
+            "{([(<{}[<>[]}>{[]{[(<()>",
+            "[[<[([]))<([[{}[[()]]]",
+            "[{[{({}]{}}([{[{{{}}([]",
+            "[<(<(<(<{}))><([]([]()",
+            "<{([([[(<>()){}]>(<<{{",
+        ];
+        for line in &lines {
+            let error = check_syntax(line)
+                .into_iter()
+                .find(|e| e.was.is_some())
+                .unwrap();
+            let fix = error.fix.unwrap();
+            let fixed = fix.apply(line);
+            assert!(scan_syntax(&fixed)
+                .iter()
+                .filter(|e| e.was.is_some())
+                .all(|e| e.col >= fix.fixed_prefix_len()));
+        }
+    }
+
+    #[test]
+    fn test_checker_in_chunks() {
+        let lines = [
+            "[({(<(())[]>[[{[]{<()<>>",
+            "{([(<{}[<>[]}>{[]{[(<()>",
+            "[[<[([]))<([[{}[[()]]]",
+            "<{([{{}}[<[[[<>{}]]]>[]]",
+            "())",
+            "",
+        ];
+        for line in lines {
+            for split in 0..=line.len() {
+                let mut checker = SyntaxChecker::default();
+                checker.push_str(&line[..split]);
+                assert_eq!(checker.errors(), check_syntax(&line[..split]));
+                checker.push_str(&line[split..]);
+                assert_eq!(checker.text(), line);
+                assert_eq!(checker.errors(), check_syntax(line));
+            }
+        }
+    }
+
+    #[test]
+    fn test_checker_completion() {
+        let mut checker = SyntaxChecker::default();
+        assert!(checker.is_complete());
+        checker.push_str("[({(<(())[]>[[{");
+        assert_eq!(checker.completion(), "}]])})]");
+        checker.push_str("[]{<()<>>");
+        assert_eq!(checker.completion(), "}}]])})]");
+        assert!(!checker.is_corrupted());
+
+        let completion = checker.completion();
+        checker.push_str(&completion);
+        assert!(checker.is_complete());
+        assert!(checker.errors().is_empty());
+
+        checker.push(')');
+        assert!(checker.is_corrupted());
+        assert_eq!(
+            checker.errors(),
+            vec![SyntaxError {
+                col: 32,
+                expected: None,
+                was: Some(')'),
+                fix: Some(Fix::Delete { col: 32 })
+            }]
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use super::opening_bracket;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// Scores of the syntax errors per closing bracket, brackets without a score score 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreTable {
+    // score of an unexpected closing bracket
+    pub corruption: HashMap<char, usize>,
+    // score of a missing closing bracket, one digit of the autocompletion score
+    pub completion: HashMap<char, usize>,
+    // base of the autocompletion score
+    pub completion_base: usize,
+}
+
+impl ScoreTable {
+    pub fn corruption_score(&self, bracket: char) -> usize {
+        self.corruption.get(&bracket).copied().unwrap_or(0)
+    }
+
+    pub fn completion_score(&self, bracket: char) -> usize {
+        self.completion.get(&bracket).copied().unwrap_or(0)
+    }
+}
+
+// The scores of the puzzle.
+impl Default for ScoreTable {
+    fn default() -> Self {
+        Self {
+            corruption: HashMap::from([(')', 3), (']', 57), ('}', 1197), ('>', 25137)]),
+            completion: HashMap::from([(')', 1), (']', 2), ('}', 3), ('>', 4)]),
+            completion_base: 5,
+        }
+    }
+}
+
+// One entry per line, overriding the scores of the puzzle, e.g.
+//
+//   # every missing bracket counts the same
+//   completion ) 1
+//   completion ] 1
+//   completion } 1
+//   completion > 1
+//   base 10
+//
+// Empty lines and lines starting with '#' are ignored.
+impl FromStr for ScoreTable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ScoreTable, Self::Err> {
+        let mut table = Self::default();
+        for (row, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let number = |s: &str| {
+                s.parse::<usize>()
+                    .map_err(|_| format!("line {}: invalid score '{}'", row + 1, s))
+            };
+            let bracket = |s: &str| match s.parse::<char>() {
+                Ok(c) if opening_bracket(c).is_some() => Ok(c),
+                _ => Err(format!(
+                    "line {}: expected a closing bracket, found '{}'",
+                    row + 1,
+                    s
+                )),
+            };
+            match fields.as_slice() {
+                ["corruption", c, score] => {
+                    table.corruption.insert(bracket(c)?, number(score)?);
+                }
+                ["completion", c, score] => {
+                    table.completion.insert(bracket(c)?, number(score)?);
+                }
+                ["base", base] => table.completion_base = number(base)?,
+                _ => {
+                    return Err(format!(
+                        "line {}: expected 'corruption <bracket> <score>', \
+                         'completion <bracket> <score>' or 'base <n>'",
+                        row + 1
+                    ))
+                }
+            }
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_score_table() {
+        assert_eq!("".parse(), Ok(ScoreTable::default()));
+
+        let table: ScoreTable =
+            "# flat\ncompletion ) 1\ncompletion ] 1\n\n  corruption > 9\nbase 10\n"
+                .parse()
+                .unwrap();
+        assert_eq!(table.completion_score(']'), 1);
+        assert_eq!(table.completion_score('>'), 4);
+        assert_eq!(table.corruption_score('>'), 9);
+        assert_eq!(table.corruption_score(')'), 3);
+        assert_eq!(table.corruption_score('('), 0);
+        assert_eq!(table.completion_base, 10);
+
+        for invalid in [
+            "corruption ( 3",
+            "corruption ) -3",
+            "completion ) 1 2",
+            "base",
+            "bonus ) 1",
+        ] {
+            assert!(invalid.parse::<ScoreTable>().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use common::rng::Rng;
+use criterion::{criterion_group, criterion_main, Criterion};
+use day11::{FixedOctopuses, Octopuses};
+
+const STEPS: usize = 1000;
+
+fn generate_grid(seed: u64) -> Vec<usize> {
+    let mut rng = Rng::new(seed);
+    (0..100).map(|_| rng.below(10)).collect()
+}
+
+// Whether the fixed-size 10x10 variant is worth keeping.
+fn fixed_vs_dynamic(c: &mut Criterion) {
+    let grid = generate_grid(0x9e3779b97f4a7c15);
+
+    let mut group = c.benchmark_group("1000 steps");
+    group.bench_function("dynamic", |b| {
+        b.iter(|| {
+            let mut octopuses = Octopuses::new(grid.clone());
+            (0..STEPS).map(|_| octopuses.step()).sum::<usize>()
+        })
+    });
+    group.bench_function("fixed", |b| {
+        b.iter(|| {
+            let mut octopuses = FixedOctopuses::<10, 10>::new(&grid);
+            (0..STEPS).map(|_| octopuses.step()).sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, fixed_vs_dynamic);
+criterion_main!(benches);
@@ -0,0 +1,603 @@
+use common::grid::flood_fill;
+use common::{Answer, Solution};
+use std::collections::{HashMap, HashSet};
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Vec<usize>;
+
+    fn parse(input: &str) -> Self::Input {
+        parse_grid(input)
+    }
+
+    fn part1(grid: &Self::Input) -> Answer {
+        total_flashes(grid.clone(), Neighborhood::default(), 100).into()
+    }
+
+    fn part2(grid: &Self::Input) -> Answer {
+        first_synchronized_step(grid.clone(), Neighborhood::default())
+            .unwrap()
+            .into()
+    }
+}
+
+pub fn parse_grid(input: &str) -> Vec<usize> {
+    input
+        .lines()
+        .flat_map(|line| line.chars().map(|c| c.to_digit(10).unwrap() as usize))
+        .collect()
+}
+
+pub fn total_flashes(grid: Vec<usize>, neighborhood: Neighborhood, steps: usize) -> usize {
+    let mut octopuses = simulation(grid, neighborhood);
+    (1..=steps).map(|_| octopuses.step()).sum()
+}
+
+pub const MAX_STEPS: usize = 10_000;
+
+pub fn first_synchronized_step(grid: Vec<usize>, neighborhood: Neighborhood) -> Option<usize> {
+    let mut octopuses = simulation(grid, neighborhood);
+    let count = octopuses.count();
+    // other neighborhoods might never synchronize
+    (1..=MAX_STEPS)
+        .map(|step| (step, octopuses.step()))
+        .filter(|(_, flashes)| *flashes == count)
+        .map(|(step, _)| step)
+        .next()
+}
+
+pub trait Simulation {
+    // Returns the number of flashes during this step.
+    fn step(&mut self) -> usize;
+
+    // Same as step, but returns the (ascending) indices of the octopuses which flashed.
+    fn step_flashes(&mut self) -> Vec<usize>;
+
+    fn count(&self) -> usize;
+
+    fn energies(&self) -> Vec<usize>;
+}
+
+// Endless iterator over the flashes of each step.
+pub struct Steps<'a> {
+    simulation: &'a mut dyn Simulation,
+}
+
+impl Iterator for Steps<'_> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        Some(self.simulation.step_flashes())
+    }
+}
+
+pub fn steps(simulation: &mut dyn Simulation) -> Steps<'_> {
+    Steps { simulation }
+}
+
+// Number of flashes of each octopus during the next `n` steps.
+pub fn flash_counts(simulation: &mut dyn Simulation, n: usize) -> Vec<usize> {
+    let mut counts = vec![0; simulation.count()];
+    for flashes in steps(simulation).take(n) {
+        flashes.into_iter().for_each(|i| counts[i] += 1);
+    }
+    counts
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Periodicity {
+    // first step during which all octopuses flash
+    pub first_synchronized: Option<usize>,
+    // the energies after step `cycle_start` repeat every `period` steps
+    pub cycle_start: usize,
+    pub period: usize,
+}
+
+// The grid only has finitely many states, so it eventually ends up in a cycle. Gives up if the
+// cycle isn't reached within `max_steps`.
+pub fn periodicity(simulation: &mut dyn Simulation, max_steps: usize) -> Option<Periodicity> {
+    let count = simulation.count();
+    let mut seen = HashMap::from([(simulation.energies(), 0)]);
+    let mut first_synchronized = None;
+    for step in 1..=max_steps {
+        if simulation.step() == count && first_synchronized.is_none() {
+            first_synchronized = Some(step);
+        }
+        if let Some(cycle_start) = seen.insert(simulation.energies(), step) {
+            return Some(Periodicity {
+                first_synchronized,
+                cycle_start,
+                period: step - cycle_start,
+            });
+        }
+    }
+    None
+}
+
+pub fn simulation(grid: Vec<usize>, neighborhood: Neighborhood) -> Box<dyn Simulation> {
+    // the fixed-size grid only supports the puzzle's 8-neighbourhood
+    if grid.len() == 10 * 10 && matches!(neighborhood, Neighborhood::Diagonal) {
+        Box::new(FixedOctopuses::<10, 10>::new(&grid))
+    } else {
+        Box::new(Octopuses::new(grid).with_neighborhood(neighborhood))
+    }
+}
+
+// Indices of the cells adjacent to `idx` in a square grid of the given size.
+pub type AdjacentIndices = fn(size: usize, idx: usize) -> Vec<usize>;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub enum Neighborhood {
+    #[default]
+    Diagonal,
+    Orthogonal,
+    // All cells within the given Chebyshev distance.
+    Radius(usize),
+    Custom(AdjacentIndices),
+}
+
+impl Neighborhood {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "diagonal" => Some(Neighborhood::Diagonal),
+            "orthogonal" => Some(Neighborhood::Orthogonal),
+            _ => name
+                .strip_prefix("radius=")
+                .and_then(|radius| radius.parse().ok())
+                .map(Neighborhood::Radius),
+        }
+    }
+
+    pub fn adjacent_indices(&self, size: usize, idx: usize) -> Vec<usize> {
+        match self {
+            Neighborhood::Diagonal => diagonal_adjacent_indices(size, idx),
+            Neighborhood::Orthogonal => orthogonal_adjacent_indices(size, idx),
+            Neighborhood::Radius(radius) => indices_within_radius(size, idx, *radius),
+            Neighborhood::Custom(adjacent_indices) => adjacent_indices(size, idx),
+        }
+    }
+}
+
+pub fn diagonal_adjacent_indices(size: usize, idx: usize) -> Vec<usize> {
+    let row = idx / size;
+    let col = idx % size;
+
+    let mut adjacent = Vec::with_capacity(8);
+    if row > 0 {
+        adjacent.push((row - 1) * size + col); // above
+        if col > 0 {
+            adjacent.push((row - 1) * size + col - 1); // above left
+        }
+        if col < size - 1 {
+            adjacent.push((row - 1) * size + col + 1); // above right
+        }
+    }
+    if row < size - 1 {
+        adjacent.push((row + 1) * size + col); // below
+        if col > 0 {
+            adjacent.push((row + 1) * size + col - 1); // below left
+        }
+        if col < size - 1 {
+            adjacent.push((row + 1) * size + col + 1); // below right
+        }
+    }
+    if col > 0 {
+        // left
+        adjacent.push(row * size + col - 1);
+    }
+    if col < size - 1 {
+        // right
+        adjacent.push(row * size + col + 1);
+    }
+    adjacent
+}
+
+pub fn orthogonal_adjacent_indices(size: usize, idx: usize) -> Vec<usize> {
+    let row = idx / size;
+    let col = idx % size;
+
+    let mut adjacent = Vec::with_capacity(4);
+    if row > 0 {
+        adjacent.push((row - 1) * size + col); // above
+    }
+    if row < size - 1 {
+        adjacent.push((row + 1) * size + col); // below
+    }
+    if col > 0 {
+        adjacent.push(row * size + col - 1); // left
+    }
+    if col < size - 1 {
+        adjacent.push(row * size + col + 1); // right
+    }
+    adjacent
+}
+
+pub fn indices_within_radius(size: usize, idx: usize, radius: usize) -> Vec<usize> {
+    let row = idx / size;
+    let col = idx % size;
+
+    let rows = row.saturating_sub(radius)..=(row + radius).min(size - 1);
+    let cols = col.saturating_sub(radius)..=(col + radius).min(size - 1);
+    rows.flat_map(|r| cols.clone().map(move |c| r * size + c))
+        .filter(|&i| i != idx)
+        .collect()
+}
+
+pub struct Octopuses {
+    grid: Vec<usize>,
+    size: usize,
+    neighborhood: Neighborhood,
+}
+
+impl Octopuses {
+    pub fn new(grid: Vec<usize>) -> Octopuses {
+        let size = (grid.len() as f64).sqrt() as usize;
+        Self {
+            grid,
+            size,
+            neighborhood: Neighborhood::default(),
+        }
+    }
+
+    pub fn with_neighborhood(mut self, neighborhood: Neighborhood) -> Self {
+        self.neighborhood = neighborhood;
+        self
+    }
+
+    pub fn step(&mut self) -> usize {
+        self.increase_energy();
+        let flashed = self.flash_until_fixed_point();
+        self.reset_flashed(&flashed);
+        flashed.len()
+    }
+
+    pub fn step_flashes(&mut self) -> Vec<usize> {
+        self.increase_energy();
+        let flashed = self.flash_until_fixed_point();
+        self.reset_flashed(&flashed);
+        let mut flashed: Vec<_> = flashed.into_iter().collect();
+        flashed.sort_unstable();
+        flashed
+    }
+
+    fn increase_energy(&mut self) {
+        self.grid.iter_mut().for_each(|energy| *energy += 1);
+    }
+
+    // Flashes spread to the adjacent octopuses, which flash in turn once their energy exceeds 9.
+    fn flash_until_fixed_point(&mut self) -> HashSet<usize> {
+        let flashing: Vec<_> = (0..self.grid.len()).filter(|&i| self.grid[i] > 9).collect();
+        let grid = &mut self.grid;
+        flood_fill(
+            flashing,
+            |i| self.neighborhood.adjacent_indices(self.size, i),
+            |_, i| {
+                grid[i] += 1;
+                grid[i] > 9
+            },
+            |_| {},
+        )
+    }
+
+    fn reset_flashed(&mut self, flashed: &HashSet<usize>) {
+        flashed.iter().for_each(|&i| self.grid[i] = 0);
+    }
+}
+
+impl Simulation for Octopuses {
+    fn step(&mut self) -> usize {
+        Octopuses::step(self)
+    }
+
+    fn step_flashes(&mut self) -> Vec<usize> {
+        Octopuses::step_flashes(self)
+    }
+
+    fn count(&self) -> usize {
+        self.grid.len()
+    }
+
+    fn energies(&self) -> Vec<usize> {
+        self.grid.clone()
+    }
+}
+
+pub struct FixedOctopuses<const W: usize, const H: usize> {
+    grid: [[u8; W]; H],
+}
+
+impl<const W: usize, const H: usize> FixedOctopuses<W, H> {
+    pub fn new(grid: &[usize]) -> Self {
+        assert_eq!(grid.len(), W * H, "grid must be of size {}x{}", W, H);
+
+        let mut energies = [[0; W]; H];
+        for (i, &energy) in grid.iter().enumerate() {
+            energies[i / W][i % W] = energy as u8;
+        }
+        Self { grid: energies }
+    }
+
+    pub fn step(&mut self) -> usize {
+        self.flash()
+            .iter()
+            .flatten()
+            .filter(|&&flashed| flashed)
+            .count()
+    }
+
+    pub fn step_flashes(&mut self) -> Vec<usize> {
+        let flashed = self.flash();
+        (0..W * H).filter(|&i| flashed[i / W][i % W]).collect()
+    }
+
+    // Returns which octopuses flashed.
+    fn flash(&mut self) -> [[bool; W]; H] {
+        let mut flashing = Vec::with_capacity(W * H);
+        for (y, row) in self.grid.iter_mut().enumerate() {
+            for (x, energy) in row.iter_mut().enumerate() {
+                *energy += 1;
+                if *energy > 9 {
+                    flashing.push((x, y));
+                }
+            }
+        }
+
+        let mut flashed = [[false; W]; H];
+        while let Some((x, y)) = flashing.pop() {
+            if flashed[y][x] {
+                continue;
+            }
+            flashed[y][x] = true;
+
+            let adjacent_rows = y.saturating_sub(1)..=(y + 1).min(H - 1);
+            let adjacent_cols = x.saturating_sub(1)..=(x + 1).min(W - 1);
+            for ny in adjacent_rows {
+                for nx in adjacent_cols.clone() {
+                    self.grid[ny][nx] += 1;
+                    if self.grid[ny][nx] > 9 && !flashed[ny][nx] {
+                        flashing.push((nx, ny));
+                    }
+                }
+            }
+        }
+
+        for (row, flashed_row) in self.grid.iter_mut().zip(flashed.iter()) {
+            for (energy, &flashed) in row.iter_mut().zip(flashed_row.iter()) {
+                if flashed {
+                    *energy = 0;
+                }
+            }
+        }
+
+        flashed
+    }
+}
+
+impl<const W: usize, const H: usize> Simulation for FixedOctopuses<W, H> {
+    fn step(&mut self) -> usize {
+        FixedOctopuses::step(self)
+    }
+
+    fn step_flashes(&mut self) -> Vec<usize> {
+        FixedOctopuses::step_flashes(self)
+    }
+
+    fn count(&self) -> usize {
+        W * H
+    }
+
+    fn energies(&self) -> Vec<usize> {
+        self.grid
+            .iter()
+            .flatten()
+            .map(|&energy| energy as usize)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526";
+
+    fn example() -> Vec<usize> {
+        EXAMPLE
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|d| d as usize)
+            .collect()
+    }
+
+    #[test]
+    fn test_fixed_matches_dynamic_on_example() {
+        let mut dynamic = Octopuses::new(example());
+        let mut fixed = FixedOctopuses::<10, 10>::new(&example());
+
+        let mut total_flashes = 0;
+        for step in 1..=195 {
+            let flashes = dynamic.step();
+            assert_eq!(flashes, fixed.step(), "step {}", step);
+            total_flashes += flashes;
+            if step == 100 {
+                assert_eq!(total_flashes, 1656);
+            }
+            if step == 195 {
+                // first step during which all octopuses flash
+                assert_eq!(flashes, fixed.count());
+            }
+        }
+    }
+
+    const SMALL_EXAMPLE: &str = "11111
+19991
+19191
+19991
+11111";
+
+    fn parse_grid(input: &str) -> Vec<usize> {
+        input
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|d| d as usize)
+            .collect()
+    }
+
+    #[test]
+    fn test_diagonal_neighborhood_on_small_example() {
+        let mut octopuses = Octopuses::new(parse_grid(SMALL_EXAMPLE));
+        assert_eq!(octopuses.step(), 9);
+        assert_eq!(
+            octopuses.grid,
+            parse_grid(
+                "34543
+                 40004
+                 50005
+                 40004
+                 34543"
+            )
+        );
+    }
+
+    #[test]
+    fn test_orthogonal_neighborhood_on_small_example() {
+        let mut octopuses =
+            Octopuses::new(parse_grid(SMALL_EXAMPLE)).with_neighborhood(Neighborhood::Orthogonal);
+        assert_eq!(octopuses.step(), 8);
+        assert_eq!(
+            octopuses.grid,
+            parse_grid(
+                "23332
+                 30003
+                 30603
+                 30003
+                 23332"
+            )
+        );
+    }
+
+    #[test]
+    fn test_radius_2_neighborhood_on_small_example() {
+        // the center now sees all 8 flashing octopuses, which makes it flash as well
+        let mut octopuses =
+            Octopuses::new(parse_grid(SMALL_EXAMPLE)).with_neighborhood(Neighborhood::Radius(2));
+        assert_eq!(octopuses.step(), 9);
+        assert_eq!(octopuses.grid[0], 2 + 3 + 1);
+        assert_eq!(octopuses.grid[2], 2 + 5 + 1);
+    }
+
+    #[test]
+    fn test_radius_1_matches_diagonal_on_example() {
+        let mut diagonal = Octopuses::new(example());
+        let mut radius = Octopuses::new(example()).with_neighborhood(Neighborhood::Radius(1));
+        for _ in 0..195 {
+            assert_eq!(diagonal.step(), radius.step());
+        }
+        assert_eq!(diagonal.grid, radius.grid);
+    }
+
+    #[test]
+    fn test_custom_neighborhood() {
+        fn no_neighbors(_size: usize, _idx: usize) -> Vec<usize> {
+            Vec::new()
+        }
+
+        let mut octopuses = Octopuses::new(parse_grid(SMALL_EXAMPLE))
+            .with_neighborhood(Neighborhood::Custom(no_neighbors));
+        assert_eq!(octopuses.step(), 8);
+        assert_eq!(octopuses.grid[12], 2);
+    }
+
+    #[test]
+    fn test_parse_neighborhood() {
+        assert!(matches!(
+            Neighborhood::parse("diagonal"),
+            Some(Neighborhood::Diagonal)
+        ));
+        assert!(matches!(
+            Neighborhood::parse("orthogonal"),
+            Some(Neighborhood::Orthogonal)
+        ));
+        assert!(matches!(
+            Neighborhood::parse("radius=2"),
+            Some(Neighborhood::Radius(2))
+        ));
+        assert!(Neighborhood::parse("hexagonal").is_none());
+    }
+
+    #[test]
+    fn test_flash_counts() {
+        for neighborhood in [Neighborhood::Diagonal, Neighborhood::Radius(1)] {
+            // the fixed and the dynamic simulation respectively
+            let mut octopuses = simulation(example(), neighborhood);
+            let counts = flash_counts(octopuses.as_mut(), 100);
+            assert_eq!(counts.iter().sum::<usize>(), 1656);
+            assert!(counts.iter().all(|&count| count >= 100 / 10));
+        }
+
+        let mut octopuses = Octopuses::new(parse_grid(SMALL_EXAMPLE));
+        let counts = flash_counts(&mut octopuses, 1);
+        assert_eq!(counts, parse_grid("00000 01110 01110 01110 00000"));
+    }
+
+    #[test]
+    fn test_step_iterator() {
+        let mut fixed = FixedOctopuses::<10, 10>::new(&example());
+        let mut dynamic = Octopuses::new(example());
+        for (fixed, dynamic) in steps(&mut fixed).zip(steps(&mut dynamic)).take(200) {
+            assert_eq!(fixed, dynamic);
+        }
+    }
+
+    #[test]
+    fn test_periodicity_after_synchronization() {
+        let mut octopuses = simulation(example(), Neighborhood::Diagonal);
+        assert_eq!(
+            periodicity(octopuses.as_mut(), MAX_STEPS),
+            Some(Periodicity {
+                first_synchronized: Some(195),
+                cycle_start: 195,
+                period: 10,
+            })
+        );
+
+        let mut octopuses = simulation(example(), Neighborhood::Diagonal);
+        assert_eq!(periodicity(octopuses.as_mut(), 100), None);
+    }
+
+    #[test]
+    fn test_periodicity_without_synchronization() {
+        // nobody ever influences anybody else, so everyone flashes every 10 steps on their own
+        fn no_neighbors(_size: usize, _idx: usize) -> Vec<usize> {
+            Vec::new()
+        }
+
+        let mut octopuses = Octopuses::new(parse_grid(SMALL_EXAMPLE))
+            .with_neighborhood(Neighborhood::Custom(no_neighbors));
+        assert_eq!(
+            periodicity(&mut octopuses, MAX_STEPS),
+            Some(Periodicity {
+                first_synchronized: None,
+                cycle_start: 0,
+                period: 10,
+            })
+        );
+    }
+
+    #[test]
+    fn test_solution_example() {
+        let (part1, part2) = common::solve::<Day11>(include_str!("../example.txt"));
+        assert_eq!(part1.to_string(), "1656");
+        assert_eq!(part2.to_string(), "195");
+    }
+}
@@ -1,123 +1,56 @@
-use std::collections::HashSet;
-use std::io::{self, BufRead};
+use common::Solution;
+use day11::*;
+use std::env;
 
 fn main() {
-    let grid: Vec<usize> = io::stdin()
-        .lock()
-        .lines()
-        .flat_map(|line| {
-            line.unwrap()
-                .chars()
-                .map(|c| c.to_digit(10).unwrap() as usize)
-                .collect::<Vec<_>>()
-        })
-        .collect();
+    let input = common::read_input();
+    let grid = Day11::parse(&input);
 
-    part1(grid.clone());
-    part2(grid);
-}
-
-fn part1(grid: Vec<usize>) {
-    let mut octopuses = Octopuses::new(grid);
-    let total_flashes: usize = (1..=100).map(|_| octopuses.step()).sum();
-    println!("Part 1: {}", total_flashes);
-}
-
-fn part2(grid: Vec<usize>) {
-    let mut octopuses = Octopuses::new(grid);
-    let first_step_with_simultaneous_flash = (1..)
-        .map(|step| (step, octopuses.step()))
-        .filter(|(_, flashes)| *flashes == 100)
-        .map(|(step, _)| step)
-        .next();
-    println!("Part 2: {:?}", first_step_with_simultaneous_flash);
-}
-
-struct Octopuses {
-    grid: Vec<usize>,
-    size: usize,
-}
+    let neighborhood = env::args()
+        .skip_while(|arg| arg != "--neighborhood")
+        .nth(1)
+        .map(|name| Neighborhood::parse(&name).expect("invalid neighborhood"))
+        .unwrap_or_default();
 
-impl Octopuses {
-    pub fn new(grid: Vec<usize>) -> Octopuses {
-        let size = (grid.len() as f64).sqrt() as usize;
-        Self { grid, size }
+    if common::part_selected(1) {
+        println!("Part 1: {}", total_flashes(grid.clone(), neighborhood, 100));
     }
-
-    pub fn step(&mut self) -> usize {
-        self.increase_energy();
-        let flashed = self.flash_until_fixed_point();
-        self.reset_flashed(&flashed);
-        flashed.len()
+    if common::part_selected(2) {
+        println!(
+            "Part 2: {:?}",
+            first_synchronized_step(grid.clone(), neighborhood)
+        );
     }
 
-    fn increase_energy(&mut self) {
-        self.grid.iter_mut().for_each(|energy| *energy += 1);
-    }
-
-    fn flash_until_fixed_point(&mut self) -> HashSet<usize> {
-        let mut flashed = HashSet::with_capacity(self.size * self.size);
-
-        loop {
-            let new_flashed: Vec<_> = self
-                .grid
-                .iter()
-                .enumerate()
-                .filter_map(|(i, &energy)| {
-                    if energy > 9 && flashed.insert(i) {
-                        Some(i)
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-
-            if new_flashed.is_empty() {
-                break flashed;
-            }
-
-            new_flashed
-                .iter()
-                .flat_map(|&i| Self::diagonal_adjacent_indices(self.size, i))
-                .for_each(|i| self.grid[i] += 1);
-        }
+    if let Some(steps) = env::args().skip_while(|arg| arg != "--analyze").nth(1) {
+        let steps = steps.parse().expect("invalid number of steps");
+        analyze(grid, neighborhood, steps);
     }
+}
 
-    fn reset_flashed(&mut self, flashed: &HashSet<usize>) {
-        flashed.iter().for_each(|&i| self.grid[i] = 0);
+fn analyze(grid: Vec<usize>, neighborhood: Neighborhood, steps: usize) {
+    let mut octopuses = simulation(grid.clone(), neighborhood);
+    let counts = flash_counts(octopuses.as_mut(), steps);
+    let size = (counts.len() as f64).sqrt() as usize;
+    println!("Flashes per octopus during {} steps:", steps);
+    for row in counts.chunks(size) {
+        let row: Vec<_> = row.iter().map(|count| format!("{:4}", count)).collect();
+        println!("{}", row.join(""));
     }
 
-    fn diagonal_adjacent_indices(size: usize, idx: usize) -> Vec<usize> {
-        let row = idx / size;
-        let col = idx % size;
-
-        let mut adjacent = Vec::with_capacity(8);
-        if row > 0 {
-            adjacent.push((row - 1) * size + col); // above
-            if col > 0 {
-                adjacent.push((row - 1) * size + col - 1); // above left
-            }
-            if col < size - 1 {
-                adjacent.push((row - 1) * size + col + 1); // above right
+    let mut octopuses = simulation(grid, neighborhood);
+    match periodicity(octopuses.as_mut(), MAX_STEPS) {
+        Some(Periodicity {
+            first_synchronized,
+            cycle_start,
+            period,
+        }) => {
+            match first_synchronized {
+                Some(step) => println!("First synchronized flash: step {}", step),
+                None => println!("First synchronized flash: never"),
             }
+            println!("Cycle: from step {} with period {}", cycle_start, period);
         }
-        if row < size - 1 {
-            adjacent.push((row + 1) * size + col); // below
-            if col > 0 {
-                adjacent.push((row + 1) * size + col - 1); // below left
-            }
-            if col < size - 1 {
-                adjacent.push((row + 1) * size + col + 1); // below right
-            }
-        }
-        if col > 0 {
-            // left
-            adjacent.push(row * size + col - 1);
-        }
-        if col < size - 1 {
-            // right
-            adjacent.push(row * size + col + 1);
-        }
-        adjacent
+        None => println!("No cycle within {} steps", MAX_STEPS),
     }
 }
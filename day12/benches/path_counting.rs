@@ -0,0 +1,42 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use day12::*;
+
+fn generated_graph(caves: usize) -> Graph {
+    let config = CaveConfig {
+        caves,
+        big_ratio: 0.2,
+        edge_density: 0.25,
+        seed: 0x9e3779b97f4a7c15,
+    };
+    let mut graph = Graph::default();
+    for (from, to) in generate_caves(&config) {
+        graph.insert_edge_undirected(&from, &to);
+    }
+    graph
+}
+
+fn path_counting(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generated caves");
+    group.sample_size(10);
+    for caves in [10, 14, 18, 22] {
+        let graph = generated_graph(caves);
+        // enumeration gets out of hand quickly
+        if caves <= 14 {
+            group.bench_with_input(
+                BenchmarkId::new("enumeration", caves),
+                &graph,
+                |b, graph| b.iter(|| graph.all_paths("start", "end", true).unwrap().len()),
+            );
+        }
+        group.bench_with_input(BenchmarkId::new("bitmask DP", caves), &graph, |b, graph| {
+            b.iter(|| graph.count_paths("start", "end", true).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", caves), &graph, |b, graph| {
+            b.iter(|| graph.count_paths_parallel("start", "end", true).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, path_counting);
+criterion_main!(benches);
@@ -0,0 +1,724 @@
+use common::rng::Rng;
+use common::{Answer, Solution};
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Graph;
+
+    fn parse(input: &str) -> Self::Input {
+        parse_graph(input)
+    }
+
+    fn part1(graph: &Self::Input) -> Answer {
+        graph.count_paths("start", "end", false).unwrap().into()
+    }
+
+    fn part2(graph: &Self::Input) -> Answer {
+        graph.count_paths("start", "end", true).unwrap().into()
+    }
+}
+
+pub fn parse_graph(input: &str) -> Graph {
+    input.lines().fold(Graph::default(), |mut graph, line| {
+        let (from, to) = line.split_once('-').unwrap();
+        graph.insert_edge_undirected(from, to);
+        graph
+    })
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum GraphError {
+    #[error("start node '{0}' not found")]
+    StartNotFound(String),
+    #[error("end node '{0}' not found")]
+    EndNotFound(String),
+    #[error("too many small caves ({0}), at most 64 are supported")]
+    TooManySmallCaves(usize),
+}
+
+// Decides whether the cave with the given label is small, i.e. may only be visited once.
+pub type NodePolicy = fn(&str) -> bool;
+
+pub fn small_if_lowercase(label: &str) -> bool {
+    label.chars().all(|c| c.is_lowercase())
+}
+
+// Also treats labels containing digits or other non-letters as small caves, as long as
+// they don't contain any uppercase letter.
+pub fn small_unless_uppercase(label: &str) -> bool {
+    !label.chars().any(|c| c.is_uppercase())
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Node {
+    label: String,
+    visit_once: bool,
+}
+
+pub type Path<'a> = Vec<&'a str>;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Edge {
+    from: usize,
+    to: usize,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Graph {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_node_policy"))]
+    node_policy: NodePolicy,
+}
+
+#[cfg(feature = "serde")]
+pub fn default_node_policy() -> NodePolicy {
+    small_if_lowercase
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::with_node_policy(small_if_lowercase)
+    }
+}
+
+impl Graph {
+    pub fn with_node_policy(node_policy: NodePolicy) -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            node_policy,
+        }
+    }
+
+    pub fn insert_edge_directed(&mut self, from: &str, to: &str) {
+        let from = self.find_or_insert_node(from);
+        let to = self.find_or_insert_node(to);
+        self.edges.push(Edge { from, to });
+    }
+
+    pub fn insert_edge_undirected(&mut self, from: &str, to: &str) {
+        self.insert_edge_directed(from, to);
+        self.insert_edge_directed(to, from);
+    }
+
+    fn find_or_insert_node(&mut self, label: &str) -> usize {
+        if let Some(index) = self.find_node(label) {
+            index
+        } else {
+            self.insert_node(label)
+        }
+    }
+
+    fn insert_node<S: Into<String>>(&mut self, label: S) -> usize {
+        let label = label.into();
+        let visit_once = (self.node_policy)(&label);
+        let index = self.nodes.len();
+        self.nodes.push(Node { label, visit_once });
+        index
+    }
+
+    fn find_node(&self, label: &str) -> Option<usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .find(|(_, node)| node.label == label)
+            .map(|(i, _)| i)
+    }
+
+    fn all_successors(&self) -> Vec<Vec<usize>> {
+        let mut succ = vec![Vec::new(); self.nodes.len()];
+        for edge in &self.edges {
+            succ[edge.from].push(edge.to);
+        }
+        succ
+    }
+
+    pub fn all_paths<'graph>(
+        &'graph self,
+        start: &str,
+        end: &str,
+        allow_one_small_cave_twice: bool,
+    ) -> Result<Vec<Path<'graph>>, GraphError> {
+        let start = self
+            .find_node(start)
+            .ok_or_else(|| GraphError::StartNotFound(start.to_string()))?;
+        let end = self
+            .find_node(end)
+            .ok_or_else(|| GraphError::EndNotFound(end.to_string()))?;
+
+        let all_succ = self.all_successors();
+
+        #[allow(clippy::too_many_arguments)]
+        fn all_paths_rec<'graph>(
+            nodes: &'graph [Node],
+            all_succ: &[Vec<usize>],
+            current: usize,
+            start: usize,
+            end: usize,
+            allow_one_small_cave_twice: bool,
+            node_count: &mut [usize],
+            path: &mut Vec<usize>,
+            paths: &mut Vec<Path<'graph>>,
+        ) {
+            if current == end {
+                path.push(current);
+                let labeled_path = path.iter().map(|&i| nodes[i].label.as_ref()).collect();
+                path.pop();
+                paths.push(labeled_path);
+                return;
+            }
+
+            if nodes[current].visit_once && node_count[current] > 0 {
+                if !allow_one_small_cave_twice || current == start {
+                    return;
+                }
+
+                let any_small_cave_visited_twice = node_count
+                    .iter()
+                    .enumerate()
+                    .any(|(i, c)| nodes[i].visit_once && *c > 1);
+                if any_small_cave_visited_twice {
+                    return;
+                }
+            }
+
+            path.push(current);
+            node_count[current] += 1;
+
+            for &succ in &all_succ[current] {
+                all_paths_rec(
+                    nodes,
+                    all_succ,
+                    succ,
+                    start,
+                    end,
+                    allow_one_small_cave_twice,
+                    node_count,
+                    path,
+                    paths,
+                );
+            }
+
+            path.pop();
+            node_count[current] -= 1;
+        }
+
+        let mut paths = Vec::with_capacity(100);
+        let mut node_count = vec![0; self.nodes.len()];
+        let mut path = Vec::with_capacity(100);
+
+        all_paths_rec(
+            &self.nodes,
+            &all_succ,
+            start,
+            start,
+            end,
+            allow_one_small_cave_twice,
+            &mut node_count,
+            &mut path,
+            &mut paths,
+        );
+
+        Ok(paths)
+    }
+}
+
+// Counts paths with memoization over (cave, visited small caves, small cave visited twice).
+pub struct PathCounter {
+    all_succ: Vec<Vec<usize>>,
+    // bit of every small cave in the visited set
+    small_bit: Vec<Option<u64>>,
+    start: usize,
+    end: usize,
+    memo: HashMap<(usize, u64, bool), usize>,
+}
+
+impl PathCounter {
+    fn new(graph: &Graph, start: &str, end: &str) -> Result<Self, GraphError> {
+        let start = graph
+            .find_node(start)
+            .ok_or_else(|| GraphError::StartNotFound(start.to_string()))?;
+        let end = graph
+            .find_node(end)
+            .ok_or_else(|| GraphError::EndNotFound(end.to_string()))?;
+
+        let small_caves = graph.nodes.iter().filter(|node| node.visit_once).count();
+        if small_caves > 64 {
+            return Err(GraphError::TooManySmallCaves(small_caves));
+        }
+        let mut next_bit = 0;
+        let small_bit = graph
+            .nodes
+            .iter()
+            .map(|node| {
+                node.visit_once.then(|| {
+                    next_bit += 1;
+                    1 << (next_bit - 1)
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            all_succ: graph.all_successors(),
+            small_bit,
+            start,
+            end,
+            memo: HashMap::new(),
+        })
+    }
+
+    // Visited small caves and whether one may still be visited twice after entering `current`,
+    // unless it can't be entered.
+    fn enter(&self, current: usize, visited: u64, twice_allowed: bool) -> Option<(u64, bool)> {
+        match self.small_bit[current] {
+            Some(bit) if visited & bit != 0 => {
+                if !twice_allowed || current == self.start {
+                    None
+                } else {
+                    Some((visited, false))
+                }
+            }
+            Some(bit) => Some((visited | bit, twice_allowed)),
+            None => Some((visited, twice_allowed)),
+        }
+    }
+
+    // Number of paths to the end which continue with entering `current`.
+    fn count(&mut self, current: usize, visited: u64, twice_allowed: bool) -> usize {
+        if current == self.end {
+            return 1;
+        }
+        let Some((visited, twice_allowed)) = self.enter(current, visited, twice_allowed) else {
+            return 0;
+        };
+
+        let key = (current, visited, twice_allowed);
+        if let Some(&count) = self.memo.get(&key) {
+            return count;
+        }
+
+        let mut count = 0;
+        for i in 0..self.all_succ[current].len() {
+            let succ = self.all_succ[current][i];
+            count += self.count(succ, visited, twice_allowed);
+        }
+        self.memo.insert(key, count);
+        count
+    }
+}
+
+impl Graph {
+    pub fn count_paths(
+        &self,
+        start: &str,
+        end: &str,
+        allow_one_small_cave_twice: bool,
+    ) -> Result<usize, GraphError> {
+        let mut counter = PathCounter::new(self, start, end)?;
+        Ok(counter.count(counter.start, 0, allow_one_small_cave_twice))
+    }
+
+    // Same as `count_paths`, but counts the paths behind every successor of the start in
+    // parallel (each with its own memoization).
+    pub fn count_paths_parallel(
+        &self,
+        start: &str,
+        end: &str,
+        allow_one_small_cave_twice: bool,
+    ) -> Result<usize, GraphError> {
+        let counter = PathCounter::new(self, start, end)?;
+        if counter.start == counter.end {
+            return Ok(1);
+        }
+        let visited = counter.small_bit[counter.start].unwrap_or(0);
+        Ok(counter.all_succ[counter.start]
+            .par_iter()
+            .map(|&succ| {
+                let mut counter = PathCounter::new(self, start, end).unwrap();
+                counter.count(succ, visited, allow_one_small_cave_twice)
+            })
+            .sum())
+    }
+}
+
+// Draws paths uniformly at random, without enumerating them. Every next cave is chosen with
+// probability proportional to the number of paths continuing through it, which are counted
+// (and memoized) on demand.
+pub struct PathSampler<'graph> {
+    graph: &'graph Graph,
+    counter: PathCounter,
+    twice_allowed: bool,
+    path_count: usize,
+    rng: Rng,
+}
+
+impl<'graph> PathSampler<'graph> {
+    pub fn path_count(&self) -> usize {
+        self.path_count
+    }
+
+    // Every path is drawn with probability 1 / path_count, None if there is no path at all.
+    pub fn sample(&mut self) -> Option<Path<'graph>> {
+        if self.path_count == 0 {
+            return None;
+        }
+
+        let (start, end) = (self.counter.start, self.counter.end);
+        let mut current = start;
+        let (mut visited, mut twice_allowed) =
+            self.counter.enter(start, 0, self.twice_allowed).unwrap();
+        let mut path = vec![start];
+        while current != end {
+            let weights: Vec<(usize, usize)> = self.counter.all_succ[current]
+                .clone()
+                .into_iter()
+                .map(|succ| (succ, self.counter.count(succ, visited, twice_allowed)))
+                .collect();
+            let total = weights.iter().map(|&(_, count)| count).sum();
+
+            let mut pick = self.rng.below(total);
+            for (succ, count) in weights {
+                if pick < count {
+                    current = succ;
+                    break;
+                }
+                pick -= count;
+            }
+            if current != end {
+                (visited, twice_allowed) =
+                    self.counter.enter(current, visited, twice_allowed).unwrap();
+            }
+            path.push(current);
+        }
+
+        Some(
+            path.into_iter()
+                .map(|i| self.graph.nodes[i].label.as_ref())
+                .collect(),
+        )
+    }
+}
+
+impl<'graph> Iterator for PathSampler<'graph> {
+    type Item = Path<'graph>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.sample()
+    }
+}
+
+impl Graph {
+    pub fn path_sampler(
+        &self,
+        start: &str,
+        end: &str,
+        allow_one_small_cave_twice: bool,
+        seed: u64,
+    ) -> Result<PathSampler<'_>, GraphError> {
+        let mut counter = PathCounter::new(self, start, end)?;
+        let path_count = counter.count(counter.start, 0, allow_one_small_cave_twice);
+        Ok(PathSampler {
+            graph: self,
+            counter,
+            twice_allowed: allow_one_small_cave_twice,
+            path_count,
+            rng: Rng::new(seed),
+        })
+    }
+}
+
+// Parameters of a synthetic cave system.
+#[derive(Debug, Clone, Copy)]
+pub struct CaveConfig {
+    // number of caves besides start and end
+    pub caves: usize,
+    pub big_ratio: f64,
+    // probability of an edge between any two caves
+    pub edge_density: f64,
+    pub seed: u64,
+}
+
+// Letters only, so that the default node policy applies: "ca", "cb", .. for small and "CA",
+// "CB", .. for big caves.
+pub fn cave_label(mut index: usize, big: bool) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push((b'a' + (index % 26) as u8) as char);
+        index /= 26;
+        if index == 0 {
+            break;
+        }
+    }
+    letters.push('c');
+    let label: String = letters.into_iter().rev().collect();
+    if big {
+        label.to_uppercase()
+    } else {
+        label
+    }
+}
+
+// Generates the edges of a random cave system. Big caves are never connected to each other,
+// otherwise there would be infinitely many paths.
+pub fn generate_caves(config: &CaveConfig) -> Vec<(String, String)> {
+    let mut rng = Rng::new(config.seed);
+
+    let big_caves = (config.caves as f64 * config.big_ratio).round() as usize;
+    let mut labels = vec!["start".to_string(), "end".to_string()];
+    labels.extend((0..big_caves).map(|i| cave_label(i, true)));
+    labels.extend((big_caves..config.caves).map(|i| cave_label(i, false)));
+    let is_big = |label: &str| label.starts_with('C');
+
+    let mut edges = Vec::new();
+    for (i, from) in labels.iter().enumerate() {
+        for to in &labels[i + 1..] {
+            if is_big(from) && is_big(to) {
+                continue;
+            }
+            if rng.chance(config.edge_density) {
+                edges.push((from.clone(), to.clone()));
+            }
+        }
+    }
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn generated_graph(config: &CaveConfig) -> Graph {
+        let mut graph = Graph::default();
+        for (from, to) in generate_caves(config) {
+            graph.insert_edge_undirected(&from, &to);
+        }
+        // isolated start or end caves don't get an edge
+        graph.find_or_insert_node("start");
+        graph.find_or_insert_node("end");
+        graph
+    }
+
+    #[test]
+    fn test_graph_all_path_search() {
+        let mut graph = Graph::default();
+        graph.insert_edge_undirected("start", "A");
+        graph.insert_edge_undirected("start", "b");
+        graph.insert_edge_undirected("A", "c");
+        graph.insert_edge_undirected("A", "b");
+        graph.insert_edge_undirected("b", "d");
+        graph.insert_edge_undirected("A", "end");
+        graph.insert_edge_undirected("b", "end");
+
+        let paths: HashSet<_> = graph
+            .all_paths("start", "end", false)
+            .unwrap()
+            .into_iter()
+            .map(|path| path.join(","))
+            .collect();
+
+        let expected_paths: HashSet<_> = vec![
+            "start,A,b,A,c,A,end",
+            "start,A,b,A,end",
+            "start,A,b,end",
+            "start,A,c,A,b,A,end",
+            "start,A,c,A,b,end",
+            "start,A,c,A,end",
+            "start,A,end",
+            "start,b,A,c,A,end",
+            "start,b,A,end",
+            "start,b,end",
+        ]
+        .into_iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        assert_eq!(paths, expected_paths);
+    }
+
+    #[test]
+    fn test_graph_node_policy() {
+        // with the default policy, "a1" would be a large cave connected to another large cave
+        let mut graph = Graph::with_node_policy(small_unless_uppercase);
+        graph.insert_edge_undirected("start", "X");
+        graph.insert_edge_undirected("X", "a1");
+        graph.insert_edge_undirected("X", "end");
+
+        let paths: HashSet<_> = graph
+            .all_paths("start", "end", false)
+            .unwrap()
+            .into_iter()
+            .map(|path| path.join(","))
+            .collect();
+        let expected_paths: HashSet<_> = vec!["start,X,end", "start,X,a1,X,end"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(paths, expected_paths);
+    }
+
+    #[test]
+    fn test_generate_caves() {
+        let config = CaveConfig {
+            caves: 20,
+            big_ratio: 0.25,
+            edge_density: 0.3,
+            seed: 42,
+        };
+        let edges = generate_caves(&config);
+        assert_eq!(edges, generate_caves(&config));
+        assert!(!edges.is_empty());
+        assert!(edges
+            .iter()
+            .all(|(from, to)| !(from.starts_with('C') && to.starts_with('C'))));
+
+        let caves: HashSet<_> = edges.iter().flat_map(|(from, to)| [from, to]).collect();
+        assert!(caves.len() <= 22);
+        assert!(caves.iter().filter(|label| label.starts_with('C')).count() <= 5);
+
+        assert_eq!(cave_label(0, false), "ca");
+        assert_eq!(cave_label(27, true), "CBB");
+    }
+
+    #[test]
+    fn test_count_paths_matches_enumeration() {
+        for seed in 1..=20 {
+            let config = CaveConfig {
+                caves: 8,
+                big_ratio: 0.25,
+                edge_density: 0.4,
+                seed,
+            };
+            let graph = generated_graph(&config);
+            for twice in [false, true] {
+                let paths = graph.all_paths("start", "end", twice).unwrap().len();
+                assert_eq!(graph.count_paths("start", "end", twice), Ok(paths));
+                assert_eq!(graph.count_paths_parallel("start", "end", twice), Ok(paths));
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_paths_example() {
+        let mut graph = Graph::default();
+        for line in include_str!("../example3.txt").lines() {
+            let (from, to) = line.split_once('-').unwrap();
+            graph.insert_edge_undirected(from, to);
+        }
+        assert_eq!(graph.count_paths("start", "end", false), Ok(226));
+        assert_eq!(graph.count_paths_parallel("start", "end", true), Ok(3509));
+    }
+
+    #[test]
+    fn test_path_sampler_is_uniform() {
+        let graph = parse_graph(include_str!("../example1.txt"));
+        for twice in [false, true] {
+            let paths = graph.all_paths("start", "end", twice).unwrap();
+            let mut sampler = graph.path_sampler("start", "end", twice, 42).unwrap();
+            assert_eq!(sampler.path_count(), paths.len());
+
+            let samples_per_path = 200;
+            let mut frequency: HashMap<Path, usize> = HashMap::new();
+            for path in sampler.by_ref().take(paths.len() * samples_per_path) {
+                *frequency.entry(path).or_insert(0) += 1;
+            }
+            assert_eq!(frequency.len(), paths.len());
+            for path in &paths {
+                let n = frequency[path];
+                assert!(
+                    (samples_per_path / 2..samples_per_path * 3 / 2).contains(&n),
+                    "{:?} sampled {} times",
+                    path,
+                    n
+                );
+            }
+        }
+
+        // the same seed draws the same paths
+        let draw = |seed| -> Vec<Path> {
+            let sampler = graph.path_sampler("start", "end", true, seed).unwrap();
+            sampler.take(20).collect()
+        };
+        assert_eq!(draw(7), draw(7));
+    }
+
+    #[test]
+    fn test_path_sampler_on_generated_caves() {
+        for seed in 1..=20 {
+            let config = CaveConfig {
+                caves: 8,
+                big_ratio: 0.25,
+                edge_density: 0.4,
+                seed,
+            };
+            let graph = generated_graph(&config);
+            let paths: HashSet<Path> = graph
+                .all_paths("start", "end", true)
+                .unwrap()
+                .into_iter()
+                .collect();
+            let mut sampler = graph.path_sampler("start", "end", true, seed).unwrap();
+            assert_eq!(sampler.path_count(), paths.len());
+            if paths.is_empty() {
+                assert_eq!(sampler.sample(), None);
+            }
+            for path in sampler.take(50) {
+                assert!(paths.contains(&path), "{:?}", path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_graph_missing_start_or_end() {
+        let mut graph = Graph::default();
+        graph.insert_edge_undirected("start", "A");
+
+        assert_eq!(
+            graph.all_paths("begin", "A", false),
+            Err(GraphError::StartNotFound("begin".to_string()))
+        );
+        assert_eq!(
+            graph.all_paths("start", "end", false),
+            Err(GraphError::EndNotFound("end".to_string()))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_graph_serde_round_trip() {
+        let mut graph = Graph::default();
+        graph.insert_edge_undirected("start", "A");
+        graph.insert_edge_undirected("A", "end");
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let graph: Graph = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            graph.all_paths("start", "end", false).unwrap(),
+            vec![vec!["start", "A", "end"]]
+        );
+    }
+
+    #[test]
+    fn test_solution_examples() {
+        let (part1, part2) = common::solve::<Day12>(include_str!("../example1.txt"));
+        assert_eq!(
+            (part1.to_string(), part2.to_string()),
+            ("10".into(), "36".into())
+        );
+        let (part1, part2) = common::solve::<Day12>(include_str!("../example3.txt"));
+        assert_eq!(
+            (part1.to_string(), part2.to_string()),
+            ("226".into(), "3509".into())
+        );
+    }
+}
@@ -0,0 +1,728 @@
+use common::rng::Rng;
+use common::{input, Answer, FromInput, Solution};
+use std::{
+    cmp,
+    collections::{hash_set, HashMap, HashSet},
+    fmt,
+    io::{self, Write},
+    iter::FromIterator,
+    str::FromStr,
+};
+use thiserror::Error;
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = (PointSet, Vec<Instruction>);
+
+    fn parse(input: &str) -> Self::Input {
+        parse_input(input).unwrap()
+    }
+
+    fn part1((points, instructions): &Self::Input) -> Answer {
+        points.transform(&instructions[0]).len().into()
+    }
+
+    fn part2((points, instructions): &Self::Input) -> Answer {
+        render_code(&points.transform(instructions.as_slice())).into()
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ParseError {
+    #[error("invalid point format, expected 'x,y'")]
+    InvalidPointFormat,
+    #[error("invalid instruction format")]
+    InvalidInstructionFormat,
+    #[error("invalid number")]
+    InvalidNumber(#[from] std::num::ParseIntError),
+}
+
+// Dots and fold instructions, separated by an empty line.
+pub fn parse_input(input: &str) -> Result<(PointSet, Vec<Instruction>), ParseError> {
+    let (points, instructions) =
+        input::header_and_body(input).ok_or(ParseError::InvalidInstructionFormat)?;
+    let points: Vec<Point> = FromInput::from_input(points)?;
+    let instructions = FromInput::from_input(instructions)?;
+    Ok((points.into_iter().collect(), instructions))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl FromStr for Point {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Point, Self::Err> {
+        let (x, y) = s.split_once(',').ok_or(ParseError::InvalidPointFormat)?;
+        let x = x.parse()?;
+        let y = y.parse()?;
+        Ok(Point { x, y })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    fn extend(&mut self, p: Point) {
+        self.min = Point {
+            x: cmp::min(self.min.x, p.x),
+            y: cmp::min(self.min.y, p.y),
+        };
+        self.max = Point {
+            x: cmp::max(self.max.x, p.x),
+            y: cmp::max(self.max.y, p.y),
+        };
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PointSet {
+    points: HashSet<Point>,
+    bounds: Option<Bounds>,
+}
+
+impl PointSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, p: Point) -> bool {
+        if !self.points.insert(p) {
+            return false;
+        }
+        match &mut self.bounds {
+            Some(bounds) => bounds.extend(p),
+            None => self.bounds = Some(Bounds { min: p, max: p }),
+        }
+        true
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        self.points.contains(p)
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        self.points.iter().copied()
+    }
+
+    pub fn transform<T: Transform + ?Sized>(&self, t: &T) -> PointSet {
+        self.iter().map(|p| t.apply(p)).collect()
+    }
+}
+
+impl FromIterator<Point> for PointSet {
+    fn from_iter<I: IntoIterator<Item = Point>>(iter: I) -> Self {
+        let mut set = PointSet::new();
+        iter.into_iter().for_each(|p| {
+            set.insert(p);
+        });
+        set
+    }
+}
+
+impl<'a> IntoIterator for &'a PointSet {
+    type Item = &'a Point;
+    type IntoIter = hash_set::Iter<'a, Point>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.points.iter()
+    }
+}
+
+// Dots folded onto each other are counted instead of merged, the count of a position is the number
+// of dots stacked there.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DensityMap {
+    counts: HashMap<Point, usize>,
+    bounds: Option<Bounds>,
+}
+
+impl DensityMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, p: Point, count: usize) {
+        if count == 0 {
+            return;
+        }
+        *self.counts.entry(p).or_insert(0) += count;
+        match &mut self.bounds {
+            Some(bounds) => bounds.extend(p),
+            None => self.bounds = Some(Bounds { min: p, max: p }),
+        }
+    }
+
+    pub fn count(&self, p: &Point) -> usize {
+        self.counts.get(p).copied().unwrap_or(0)
+    }
+
+    // Number of distinct positions with at least one dot.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    // Number of dots, folding never changes it.
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    pub fn max_count(&self) -> usize {
+        self.counts.values().copied().max().unwrap_or(0)
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, usize)> + '_ {
+        self.counts.iter().map(|(&p, &count)| (p, count))
+    }
+
+    pub fn transform<T: Transform + ?Sized>(&self, t: &T) -> DensityMap {
+        let mut density = DensityMap::new();
+        for (p, count) in self.iter() {
+            density.add(t.apply(p), count);
+        }
+        density
+    }
+
+    pub fn points(&self) -> PointSet {
+        self.iter().map(|(p, _)| p).collect()
+    }
+}
+
+impl From<&PointSet> for DensityMap {
+    fn from(points: &PointSet) -> Self {
+        let mut density = DensityMap::new();
+        for p in points.iter() {
+            density.add(p, 1);
+        }
+        density
+    }
+}
+
+pub trait Transform {
+    fn apply(&self, p: Point) -> Point;
+
+    fn then<T: Transform>(self, next: T) -> Then<Self, T>
+    where
+        Self: Sized,
+    {
+        Then(self, next)
+    }
+}
+
+pub struct Then<A, B>(A, B);
+
+impl<A: Transform, B: Transform> Transform for Then<A, B> {
+    fn apply(&self, p: Point) -> Point {
+        self.1.apply(self.0.apply(p))
+    }
+}
+
+impl<T: Transform> Transform for [T] {
+    fn apply(&self, p: Point) -> Point {
+        self.iter().fold(p, |p, t| t.apply(p))
+    }
+}
+
+// Folds the bottom half up onto the top half.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FoldAlongY {
+    pub y: isize,
+}
+
+impl Transform for FoldAlongY {
+    fn apply(&self, p: Point) -> Point {
+        if p.y > self.y {
+            Point {
+                y: 2 * self.y - p.y,
+                ..p
+            }
+        } else {
+            p
+        }
+    }
+}
+
+// Folds the right half over onto the left half.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FoldAlongX {
+    pub x: isize,
+}
+
+impl Transform for FoldAlongX {
+    fn apply(&self, p: Point) -> Point {
+        if p.x > self.x {
+            Point {
+                x: 2 * self.x - p.x,
+                ..p
+            }
+        } else {
+            p
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Translate {
+    pub dx: isize,
+    pub dy: isize,
+}
+
+impl Transform for Translate {
+    fn apply(&self, p: Point) -> Point {
+        Point {
+            x: p.x + self.dx,
+            y: p.y + self.dy,
+        }
+    }
+}
+
+// Rotates clockwise around the origin (y is pointing down).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rotate90;
+
+impl Transform for Rotate90 {
+    fn apply(&self, p: Point) -> Point {
+        Point { x: -p.y, y: p.x }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    FoldAlongY(FoldAlongY),
+    FoldAlongX(FoldAlongX),
+}
+
+impl Instruction {
+    // Whether the fold maps `p` onto another point.
+    fn moves(&self, p: Point) -> bool {
+        match self {
+            Self::FoldAlongY(FoldAlongY { y }) => p.y > *y,
+            Self::FoldAlongX(FoldAlongX { x }) => p.x > *x,
+        }
+    }
+}
+
+impl Transform for Instruction {
+    fn apply(&self, p: Point) -> Point {
+        match self {
+            Self::FoldAlongY(fold) => fold.apply(p),
+            Self::FoldAlongX(fold) => fold.apply(p),
+        }
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Instruction, Self::Err> {
+        let (inst, n) = s
+            .split_once('=')
+            .ok_or(ParseError::InvalidInstructionFormat)?;
+        let n = n.parse()?;
+        match inst {
+            "fold along y" => Ok(Instruction::FoldAlongY(FoldAlongY { y: n })),
+            "fold along x" => Ok(Instruction::FoldAlongX(FoldAlongX { x: n })),
+            _ => Err(ParseError::InvalidInstructionFormat),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FoldAlongY(FoldAlongY { y }) => write!(f, "fold along y={}", y),
+            Self::FoldAlongX(FoldAlongX { x }) => write!(f, "fold along x={}", x),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FoldEffect {
+    // the number of points which are mirrored by the fold
+    Folds(usize),
+    // nothing lies beyond the fold line
+    BeyondBounds,
+    // an earlier fold along the same line already folded everything beyond it
+    Repeated,
+}
+
+// Determines the effect of every instruction on the given points. Only the `Folds` ones change
+// the point set, all others are redundant.
+pub fn analyze_folds(points: &PointSet, instructions: &[Instruction]) -> Vec<FoldEffect> {
+    let mut points = points.clone();
+    let mut applied: Vec<Instruction> = Vec::new();
+    instructions
+        .iter()
+        .map(|instruction| {
+            let moved = points.iter().filter(|&p| instruction.moves(p)).count();
+            if moved > 0 {
+                points = points.transform(instruction);
+                applied.push(*instruction);
+                FoldEffect::Folds(moved)
+            } else if applied.contains(instruction) {
+                FoldEffect::Repeated
+            } else {
+                FoldEffect::BeyondBounds
+            }
+        })
+        .collect()
+}
+
+// Equivalent instruction list without redundant folds.
+pub fn normalize_folds(points: &PointSet, instructions: &[Instruction]) -> Vec<Instruction> {
+    instructions
+        .iter()
+        .zip(analyze_folds(points, instructions))
+        .filter(|(_, effect)| matches!(effect, FoldEffect::Folds(_)))
+        .map(|(&instruction, _)| instruction)
+        .collect()
+}
+
+// Generates a point set together with fold instructions which fold it back into the given target.
+// Every unfold mirrors each point randomly (keeping the original, the mirrored one or both)
+// along a line just beyond the current bounds, so that no point ever lies on a fold line.
+pub fn unfold(target: &PointSet, folds: usize, seed: u64) -> (PointSet, Vec<Instruction>) {
+    let mut rng = Rng::new(seed);
+
+    let mut points = target.clone();
+    let mut instructions = Vec::with_capacity(folds);
+    for _ in 0..folds {
+        let max = match points.bounds() {
+            Some(bounds) => bounds.max,
+            None => Point { x: 0, y: 0 },
+        };
+        let instruction = if rng.below(2) == 0 {
+            Instruction::FoldAlongY(FoldAlongY {
+                y: max.y + 1 + rng.below(3) as isize,
+            })
+        } else {
+            Instruction::FoldAlongX(FoldAlongX {
+                x: max.x + 1 + rng.below(3) as isize,
+            })
+        };
+        let mirror = |p: Point| match instruction {
+            Instruction::FoldAlongY(FoldAlongY { y }) => Point {
+                y: 2 * y - p.y,
+                ..p
+            },
+            Instruction::FoldAlongX(FoldAlongX { x }) => Point {
+                x: 2 * x - p.x,
+                ..p
+            },
+        };
+
+        let mut unfolded = PointSet::new();
+        for p in points.iter() {
+            match rng.below(3) {
+                0 => {
+                    unfolded.insert(p);
+                }
+                1 => {
+                    unfolded.insert(mirror(p));
+                }
+                _ => {
+                    unfolded.insert(p);
+                    unfolded.insert(mirror(p));
+                }
+            }
+        }
+        points = unfolded;
+        instructions.push(instruction);
+    }
+
+    // the last unfold has to be folded first
+    instructions.reverse();
+    (points, instructions)
+}
+
+pub fn write_input<W: Write>(
+    mut writer: W,
+    points: &PointSet,
+    instructions: &[Instruction],
+) -> io::Result<()> {
+    for p in points {
+        writeln!(writer, "{},{}", p.x, p.y)?;
+    }
+    writeln!(writer)?;
+    for instruction in instructions {
+        writeln!(writer, "{}", instruction)?;
+    }
+    Ok(())
+}
+
+pub fn render_code(points: &PointSet) -> String {
+    let bounds = match points.bounds() {
+        Some(bounds) => bounds,
+        None => return String::new(),
+    };
+
+    let mut code = String::new();
+    for y in bounds.min.y..=bounds.max.y {
+        for x in bounds.min.x..=bounds.max.x {
+            if points.contains(&Point { x, y }) {
+                code.push('#')
+            } else {
+                code.push('.')
+            }
+        }
+        code.push('\n');
+    }
+    code
+}
+
+// Like `render_code`, but with the number of stacked dots instead of `#`, `+` for 10 or more.
+pub fn render_density(density: &DensityMap) -> String {
+    let bounds = match density.bounds() {
+        Some(bounds) => bounds,
+        None => return String::new(),
+    };
+
+    let mut code = String::new();
+    for y in bounds.min.y..=bounds.max.y {
+        for x in bounds.min.x..=bounds.max.x {
+            match density.count(&Point { x, y }) {
+                0 => code.push('.'),
+                count @ 1..=9 => code.push(char::from_digit(count as u32, 10).unwrap()),
+                _ => code.push('+'),
+            }
+        }
+        code.push('\n');
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> (PointSet, Vec<Instruction>) {
+        let points = [
+            (6, 10),
+            (0, 14),
+            (9, 10),
+            (0, 3),
+            (10, 4),
+            (4, 11),
+            (6, 0),
+            (6, 12),
+            (4, 1),
+            (0, 13),
+            (10, 12),
+            (3, 4),
+            (3, 0),
+            (8, 4),
+            (1, 10),
+            (2, 14),
+            (8, 10),
+            (9, 0),
+        ]
+        .iter()
+        .map(|&(x, y)| Point { x, y })
+        .collect();
+        let instructions = vec![
+            Instruction::FoldAlongY(FoldAlongY { y: 7 }),
+            Instruction::FoldAlongX(FoldAlongX { x: 5 }),
+        ];
+        (points, instructions)
+    }
+
+    #[test]
+    fn test_fold_example() {
+        let (points, instructions) = example();
+
+        let folded = points.transform(&instructions[0]);
+        assert_eq!(folded.len(), 17);
+
+        let folded = points.transform(instructions.as_slice());
+        assert_eq!(folded.len(), 16);
+        assert_eq!(
+            folded.bounds(),
+            Some(Bounds {
+                min: Point { x: 0, y: 0 },
+                max: Point { x: 4, y: 4 },
+            })
+        );
+    }
+
+    #[test]
+    fn test_fold_density_example() {
+        let (points, instructions) = example();
+
+        let density = DensityMap::from(&points).transform(instructions.as_slice());
+        assert_eq!(density.points(), points.transform(instructions.as_slice()));
+        assert_eq!(density.len(), 16);
+        assert_eq!(density.total(), 18);
+        assert_eq!(density.max_count(), 2);
+        assert_eq!(density.count(&Point { x: 1, y: 4 }), 2);
+        assert_eq!(
+            render_density(&density),
+            "11111\n1...1\n1...1\n1...1\n12211\n"
+        );
+
+        let mut stacked = DensityMap::new();
+        stacked.add(Point { x: 0, y: 0 }, 12);
+        stacked.add(Point { x: 1, y: 0 }, 0);
+        assert_eq!(render_density(&stacked), "+\n");
+    }
+
+    #[test]
+    fn test_unfold_folds_back_to_target() {
+        let (points, instructions) = example();
+        let target = points.transform(instructions.as_slice());
+
+        for seed in 1..=50 {
+            let (unfolded, instructions) = unfold(&target, 1 + seed as usize % 8, seed);
+            assert!(unfolded.len() >= target.len());
+
+            // no point may lie on a fold line
+            let mut folded = unfolded;
+            for instruction in &instructions {
+                assert!(folded.iter().all(|p| match instruction {
+                    Instruction::FoldAlongY(FoldAlongY { y }) => p.y != *y,
+                    Instruction::FoldAlongX(FoldAlongX { x }) => p.x != *x,
+                }));
+                folded = folded.transform(instruction);
+            }
+            assert_eq!(folded, target);
+        }
+    }
+
+    #[test]
+    fn test_unfolded_input_round_trip() {
+        let (points, instructions) = example();
+        let target = points.transform(instructions.as_slice());
+        let (unfolded, instructions) = unfold(&target, 20, 0x9e3779b97f4a7c15);
+
+        let mut input = Vec::new();
+        write_input(&mut input, &unfolded, &instructions).unwrap();
+        let input = String::from_utf8(input).unwrap();
+
+        let (points, instructions) = input.split_once("\n\n").unwrap();
+        let points: PointSet = points.lines().map(|s| s.parse().unwrap()).collect();
+        let instructions: Vec<Instruction> =
+            instructions.lines().map(|s| s.parse().unwrap()).collect();
+        assert_eq!(points, unfolded);
+        assert_eq!(points.transform(instructions.as_slice()), target);
+    }
+
+    #[test]
+    fn test_analyze_folds() {
+        let (points, _) = example();
+        let instructions = vec![
+            Instruction::FoldAlongX(FoldAlongX { x: 20 }),
+            Instruction::FoldAlongY(FoldAlongY { y: 7 }),
+            Instruction::FoldAlongY(FoldAlongY { y: 7 }),
+            Instruction::FoldAlongX(FoldAlongX { x: 5 }),
+            Instruction::FoldAlongY(FoldAlongY { y: 10 }),
+            Instruction::FoldAlongY(FoldAlongY { y: 7 }),
+        ];
+        assert_eq!(
+            analyze_folds(&points, &instructions),
+            vec![
+                FoldEffect::BeyondBounds,
+                FoldEffect::Folds(10),
+                FoldEffect::Repeated,
+                FoldEffect::Folds(8),
+                FoldEffect::BeyondBounds,
+                FoldEffect::Repeated,
+            ]
+        );
+
+        let normalized = normalize_folds(&points, &instructions);
+        assert_eq!(normalized, example().1);
+        assert_eq!(
+            points.transform(normalized.as_slice()),
+            points.transform(instructions.as_slice())
+        );
+    }
+
+    #[test]
+    fn test_normalized_folds_yield_same_points() {
+        let (points, instructions) = example();
+        let target = points.transform(instructions.as_slice());
+
+        let mut rng = Rng::new(0x2545f4914f6cdd1d);
+
+        for unfold_seed in 1..=30 {
+            let (points, mut instructions) = unfold(&target, 6, unfold_seed);
+            let needed = instructions.len();
+
+            // sprinkle in repetitions and folds far beyond the points
+            for _ in 0..6 {
+                let i = rng.below(instructions.len() + 1);
+                let instruction = match rng.below(3) {
+                    0 => instructions[i.saturating_sub(1)],
+                    1 => Instruction::FoldAlongX(FoldAlongX { x: 1000 }),
+                    _ => Instruction::FoldAlongY(FoldAlongY { y: 1000 }),
+                };
+                instructions.insert(i, instruction);
+            }
+
+            let normalized = normalize_folds(&points, &instructions);
+            assert_eq!(normalized.len(), needed);
+            assert_eq!(points.transform(normalized.as_slice()), target);
+            assert_eq!(points.transform(instructions.as_slice()), target);
+            assert_eq!(normalize_folds(&points, &normalized), normalized);
+        }
+    }
+
+    #[test]
+    fn test_translate_and_rotate() {
+        let points: PointSet = [Point { x: 0, y: 0 }, Point { x: 2, y: 1 }]
+            .iter()
+            .copied()
+            .collect();
+
+        let transformed = points.transform(&Rotate90.then(Translate { dx: 1, dy: -2 }));
+        let expected: PointSet = [Point { x: 1, y: -2 }, Point { x: 0, y: 0 }]
+            .iter()
+            .copied()
+            .collect();
+        assert_eq!(transformed, expected);
+        assert_eq!(
+            transformed.bounds(),
+            Some(Bounds {
+                min: Point { x: 0, y: -2 },
+                max: Point { x: 1, y: 0 },
+            })
+        );
+    }
+
+    #[test]
+    fn test_solution_example() {
+        let (points, instructions) = parse_input(include_str!("../example.txt")).unwrap();
+        assert_eq!((points, instructions), example());
+
+        let (part1, part2) = common::solve::<Day13>(include_str!("../example.txt"));
+        assert_eq!(part1.to_string(), "17");
+        assert_eq!(part2.to_string(), "#####\n#...#\n#...#\n#...#\n#####\n");
+    }
+}
@@ -1,119 +1,56 @@
-use std::{
-    cmp,
-    collections::HashSet,
-    io::{self, BufRead},
-    str::FromStr,
-};
-use thiserror::Error;
+use common::Solution;
+use day13::*;
+use std::env;
+use std::io;
 
-#[derive(Error, Debug)]
-pub enum ParseError {
-    #[error("invalid point format, expected 'x,y'")]
-    InvalidPointFormat,
-    #[error("invalid instruction format")]
-    InvalidInstructionFormat,
-    #[error("invalid number")]
-    InvalidNumber(#[from] std::num::ParseIntError),
-}
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-struct Point {
-    pub x: usize,
-    pub y: usize,
-}
-
-impl FromStr for Point {
-    type Err = ParseError;
-
-    fn from_str(s: &str) -> Result<Point, Self::Err> {
-        let (x, y) = s.split_once(',').ok_or(ParseError::InvalidPointFormat)?;
-        let x = x.parse()?;
-        let y = y.parse()?;
-        Ok(Point { x, y })
+fn main() {
+    let input = common::read_input();
+    let (points, instructions) = Day13::parse(&input);
+
+    let folded_points = points.transform(instructions.as_slice());
+
+    // re-unfolds the code into a new (larger) input instead of solving the puzzle
+    if let Some(folds) = env::args().skip_while(|arg| arg != "--unfold").nth(1) {
+        let seed = env::args()
+            .skip_while(|arg| arg != "--seed")
+            .nth(1)
+            .map_or(0x2545f4914f6cdd1d, |seed| seed.parse().unwrap());
+        let (points, instructions) = unfold(&folded_points, folds.parse().unwrap(), seed);
+        write_input(io::stdout().lock(), &points, &instructions).unwrap();
+        return;
     }
-}
-
-enum Instruction {
-    FoldHorizontal { y: usize },
-    FoldVertical { x: usize },
-}
 
-impl Instruction {
-    pub fn transform(&self, points: &HashSet<Point>) -> HashSet<Point> {
-        match self {
-            Self::FoldHorizontal { y } => points
-                .iter()
-                .copied()
-                .map(|p| {
-                    if p.y > *y {
-                        let dy = p.y - y;
-                        Point { y: y - dy, ..p }
-                    } else {
-                        p
-                    }
-                })
-                .collect(),
-            Self::FoldVertical { x } => points
-                .iter()
-                .copied()
-                .map(|p| {
-                    if p.x > *x {
-                        let dx = p.x - x;
-                        Point { x: x - dx, ..p }
-                    } else {
-                        p
-                    }
-                })
-                .collect(),
+    if env::args().any(|arg| arg == "--optimize") {
+        for (instruction, effect) in instructions
+            .iter()
+            .zip(analyze_folds(&points, &instructions))
+        {
+            match effect {
+                FoldEffect::Folds(moved) => println!("{} (folds {} points)", instruction, moved),
+                FoldEffect::BeyondBounds => println!("{} (redundant, beyond bounds)", instruction),
+                FoldEffect::Repeated => println!("{} (redundant, repeated)", instruction),
+            }
         }
+        let normalized = normalize_folds(&points, &instructions);
+        println!(
+            "{} of {} instructions needed",
+            normalized.len(),
+            instructions.len()
+        );
+        return;
     }
-}
-
-impl FromStr for Instruction {
-    type Err = ParseError;
 
-    fn from_str(s: &str) -> Result<Instruction, Self::Err> {
-        let (inst, n) = s
-            .split_once('=')
-            .ok_or(ParseError::InvalidInstructionFormat)?;
-        let n = n.parse()?;
-        match inst {
-            "fold along y" => Ok(Instruction::FoldHorizontal { y: n }),
-            "fold along x" => Ok(Instruction::FoldVertical { x: n }),
-            _ => Err(ParseError::InvalidInstructionFormat),
-        }
+    if common::part_selected(1) {
+        println!("Part 1: {}", points.transform(&instructions[0]).len());
     }
-}
-
-fn print_code(points: &HashSet<Point>) {
-    let size = points.iter().fold((0, 0), |(w, h), p| {
-        (cmp::max(w, p.x + 1), cmp::max(h, p.y + 1))
-    });
-
-    for y in 0..size.1 {
-        for x in 0..size.0 {
-            if points.contains(&Point { x, y }) {
-                print!("#")
-            } else {
-                print!(".")
-            }
+    if common::part_selected(2) {
+        println!("Part 2: {}", folded_points.len());
+        // number of dots stacked onto each position instead of the plain code
+        if env::args().any(|arg| arg == "--density") {
+            let density = DensityMap::from(&points).transform(instructions.as_slice());
+            print!("{}", render_density(&density));
+        } else {
+            print!("{}", render_code(&folded_points));
         }
-        println!("");
     }
 }
-
-fn main() {
-    let lines: Vec<String> = io::stdin().lock().lines().map(|s| s.unwrap()).collect();
-    let parts: Vec<_> = lines.split(|line| line.is_empty()).collect();
-    let points: HashSet<Point> = parts[0].iter().map(|s| s.parse().unwrap()).collect();
-    let instructions: Vec<Instruction> = parts[1].iter().map(|s| s.parse().unwrap()).collect();
-
-    println!("Part 1: {}", instructions[0].transform(&points).len());
-
-    let folded_points = instructions
-        .iter()
-        .fold(points, |points, inst| inst.transform(&points));
-    println!("Part 2: {}", folded_points.len());
-
-    print_code(&folded_points);
-}
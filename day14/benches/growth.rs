@@ -0,0 +1,44 @@
+use common::rng::Rng;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use day14::*;
+
+// Template and a rule for every pair over `elements` distinct (mostly non-ASCII) elements.
+fn synthetic_rules(elements: usize, seed: u64) -> (String, Rules) {
+    let mut rng = Rng::new(seed);
+    let alphabet: Vec<char> = (0..elements as u32)
+        .map(|i| char::from_u32(0xC0 + i).unwrap())
+        .collect();
+    let template = (0..20).map(|_| alphabet[rng.below(elements)]).collect();
+    let mut rules = Rules::default();
+    for &a in &alphabet {
+        for &b in &alphabet {
+            let insert = alphabet[rng.below(elements)];
+            rules.insert(format!("{}{}", a, b), insert.to_string());
+        }
+    }
+    (template, rules)
+}
+
+// Whether growing in parallel pays off for large rule sets.
+fn growth(c: &mut Criterion) {
+    let mut group = c.benchmark_group("40 steps");
+    group.sample_size(10);
+    for elements in [26, 100, 150] {
+        let (template, rules) = synthetic_rules(elements, 0x9e3779b97f4a7c15);
+        let pairs = pairs_of_polymer(&template);
+        group.bench_with_input(
+            BenchmarkId::new("sequential", elements),
+            &rules,
+            |b, rules| b.iter(|| grow_polymer(pairs.clone(), rules, 40)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("parallel", elements),
+            &rules,
+            |b, rules| b.iter(|| grow_polymer_parallel(pairs.clone(), rules, 40)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, growth);
+criterion_main!(benches);
@@ -0,0 +1,342 @@
+pub use common::hash::{HashMap, HashState};
+use common::{input, Answer, Solution};
+use rayon::prelude::*;
+use std::cmp;
+
+pub type Rules = HashMap<String, String>;
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = (Vec<String>, Rules);
+
+    fn parse(input: &str) -> Self::Input {
+        parse_input(input)
+    }
+
+    fn part1((templates, rules): &Self::Input) -> Answer {
+        let result = sweep(&templates[0], rules, &[10], Growth::Sequential)[0];
+        (result.max - result.min).into()
+    }
+
+    fn part2((templates, rules): &Self::Input) -> Answer {
+        let result = sweep(&templates[0], rules, &[40], Growth::Sequential)[0];
+        (result.max - result.min).into()
+    }
+}
+
+// Elements may be any characters, so pairs have to be split at char boundaries.
+pub fn split_pair(pair: &str) -> (&str, &str) {
+    let second = pair.char_indices().nth(1).map_or(pair.len(), |(i, _)| i);
+    pair.split_at(second)
+}
+
+pub fn count_elements(
+    first_polymer_element: char,
+    pairs: &HashMap<String, usize>,
+) -> HashMap<char, usize> {
+    pairs.iter().fold(
+        {
+            let mut count = HashMap::default();
+            count.insert(first_polymer_element, 1);
+            count
+        },
+        |mut count, (key, value)| {
+            let second_pair_element = split_pair(key).1.chars().next().unwrap();
+            *count.entry(second_pair_element).or_insert(0) += value;
+            count
+        },
+    )
+}
+
+pub fn min_max_elements(
+    first_polymer_element: char,
+    pairs: &HashMap<String, usize>,
+) -> (usize, usize) {
+    count_elements(first_polymer_element, pairs)
+        .iter()
+        .fold((usize::MAX, usize::MIN), |(min, max), (_, &count)| {
+            (cmp::min(min, count), cmp::max(max, count))
+        })
+}
+
+pub fn pairs_of_polymer(polymer: &str) -> HashMap<String, usize> {
+    let elements: Vec<char> = polymer.chars().collect();
+    let capacity = elements.len().saturating_sub(1);
+    let mut pairs = HashMap::with_capacity_and_hasher(capacity, HashState::default());
+    for pair in elements.windows(2) {
+        *pairs.entry(pair.iter().collect()).or_insert(0) += 1;
+    }
+    pairs
+}
+
+pub fn grow_pair(
+    next_pairs: &mut HashMap<String, usize>,
+    pair: String,
+    count: usize,
+    rules: &Rules,
+) {
+    if let Some(insert) = rules.get(&pair[..]) {
+        let (first, second) = split_pair(&pair);
+
+        let mut pair1 = String::with_capacity(first.len() + insert.len());
+        pair1.push_str(first);
+        pair1.push_str(insert);
+        *next_pairs.entry(pair1).or_insert(0) += count;
+
+        let mut pair2 = String::with_capacity(insert.len() + second.len());
+        pair2.push_str(insert);
+        pair2.push_str(second);
+        *next_pairs.entry(pair2).or_insert(0) += count;
+    } else {
+        *next_pairs.entry(pair).or_insert(0) += count;
+    }
+}
+
+pub fn grow_polymer(
+    initial_pairs: HashMap<String, usize>,
+    rules: &Rules,
+    steps: usize,
+) -> HashMap<String, usize> {
+    (0..steps).fold(initial_pairs, |pairs, _| {
+        let mut next_pairs =
+            HashMap::with_capacity_and_hasher(pairs.len() * 2, HashState::default());
+        for (pair, count) in pairs {
+            grow_pair(&mut next_pairs, pair, count, rules);
+        }
+        next_pairs
+    })
+}
+
+// Same as grow_polymer, but each thread grows its share of the pairs into a map of its own.
+// The per-thread maps are merged at the end of every step. Only pays off for huge rule sets.
+pub fn grow_polymer_parallel(
+    initial_pairs: HashMap<String, usize>,
+    rules: &Rules,
+    steps: usize,
+) -> HashMap<String, usize> {
+    (0..steps).fold(initial_pairs, |pairs, _| {
+        pairs
+            .into_par_iter()
+            .fold(HashMap::default, |mut next_pairs, (pair, count)| {
+                grow_pair(&mut next_pairs, pair, count, rules);
+                next_pairs
+            })
+            .reduce(HashMap::default, |a, b| {
+                // merge the smaller map into the larger one
+                let (mut merged, other) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+                for (pair, count) in other {
+                    *merged.entry(pair).or_insert(0) += count;
+                }
+                merged
+            })
+    })
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Growth {
+    #[default]
+    Sequential,
+    Parallel,
+}
+
+impl Growth {
+    fn grow(
+        self,
+        pairs: HashMap<String, usize>,
+        rules: &Rules,
+        steps: usize,
+    ) -> HashMap<String, usize> {
+        match self {
+            Growth::Sequential => grow_polymer(pairs, rules, steps),
+            Growth::Parallel => grow_polymer_parallel(pairs, rules, steps),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepResult {
+    pub step: usize,
+    pub min: usize,
+    pub max: usize,
+}
+
+// Grows the template step by step, reporting the min/max element counts after each of the given steps.
+pub fn sweep(template: &str, rules: &Rules, steps: &[usize], growth: Growth) -> Vec<StepResult> {
+    let first_polymer_element = template.chars().next().unwrap();
+
+    let mut steps = steps.to_vec();
+    steps.sort_unstable();
+
+    let mut pairs = pairs_of_polymer(template);
+    let mut current_step = 0;
+    steps
+        .into_iter()
+        .map(|step| {
+            pairs = growth.grow(std::mem::take(&mut pairs), rules, step - current_step);
+            current_step = step;
+            let (min, max) = min_max_elements(first_polymer_element, &pairs);
+            StepResult { step, min, max }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchResult {
+    pub template: String,
+    pub rules: String,
+    pub results: Vec<StepResult>,
+}
+
+// Runs every template against every (named) rule set.
+pub fn process_batch(
+    templates: &[String],
+    rule_sets: &[(String, Rules)],
+    steps: &[usize],
+    growth: Growth,
+) -> Vec<BatchResult> {
+    templates
+        .iter()
+        .flat_map(|template| {
+            rule_sets.iter().map(move |(name, rules)| BatchResult {
+                template: template.clone(),
+                rules: name.clone(),
+                results: sweep(template, rules, steps, growth),
+            })
+        })
+        .collect()
+}
+
+pub fn parse_rules(s: &str) -> Rules {
+    s.lines()
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let (pair, insert) = s.split_once(" -> ").unwrap();
+            (pair.to_string(), insert.to_string())
+        })
+        .collect()
+}
+
+// Templates come first (one per line), followed by an empty line and the insertion rules.
+pub fn parse_input(s: &str) -> (Vec<String>, Rules) {
+    let (templates, rules) = input::header_and_body(s).unwrap_or((s, ""));
+    (input::parse(templates), parse_rules(rules))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rng::Rng;
+
+    const EXAMPLE: &str = "NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C";
+
+    #[test]
+    fn test_sweep_example() {
+        let (templates, rules) = parse_input(EXAMPLE);
+        assert_eq!(templates, vec!["NNCB"]);
+
+        for growth in [Growth::Sequential, Growth::Parallel] {
+            let results = sweep(&templates[0], &rules, &[40, 10], growth);
+            assert_eq!(results[0].step, 10);
+            assert_eq!(results[0].max - results[0].min, 1588);
+            assert_eq!(results[1].step, 40);
+            assert_eq!(results[1].max - results[1].min, 2188189693529);
+        }
+    }
+
+    #[test]
+    fn test_process_batch() {
+        let (templates, rules) = parse_input(&format!("NNCB\nCH\n{}", &EXAMPLE[4..]));
+        assert_eq!(templates, vec!["NNCB", "CH"]);
+
+        let rule_sets = vec![
+            (String::from("example"), rules),
+            (String::from("none"), Rules::default()),
+        ];
+        let batch = process_batch(&templates, &rule_sets, &[0, 1], Growth::Sequential);
+        let summary: Vec<_> = batch
+            .iter()
+            .map(|result| {
+                let counts: Vec<_> = result.results.iter().map(|r| (r.min, r.max)).collect();
+                (result.template.as_str(), result.rules.as_str(), counts)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                // NNCB -> NCNBCHB
+                ("NNCB", "example", vec![(1, 2), (1, 2)]),
+                ("NNCB", "none", vec![(1, 2), (1, 2)]),
+                // CH -> CBH
+                ("CH", "example", vec![(1, 1), (1, 1)]),
+                ("CH", "none", vec![(1, 1), (1, 1)]),
+            ]
+        );
+    }
+
+    // Template and a rule for every pair over `elements` distinct (mostly non-ASCII) elements.
+    fn synthetic_rules(elements: usize, seed: u64) -> (String, Rules) {
+        let mut rng = Rng::new(seed);
+        let alphabet: Vec<char> = (0..elements as u32)
+            .map(|i| char::from_u32(0xC0 + i).unwrap())
+            .collect();
+        let template = (0..20).map(|_| alphabet[rng.below(elements)]).collect();
+        let mut rules = Rules::default();
+        for &a in &alphabet {
+            for &b in &alphabet {
+                let insert = alphabet[rng.below(elements)];
+                rules.insert(format!("{}{}", a, b), insert.to_string());
+            }
+        }
+        (template, rules)
+    }
+
+    #[test]
+    fn test_non_ascii_elements() {
+        let rules = parse_rules("αβ -> γ\nγβ -> α");
+        let pairs = grow_polymer(pairs_of_polymer("αβ"), &rules, 2);
+        // αβ -> αγβ -> αγαβ
+        assert_eq!(count_elements('α', &pairs)[&'α'], 2);
+        assert_eq!(min_max_elements('α', &pairs), (1, 2));
+    }
+
+    #[test]
+    fn test_short_templates() {
+        assert!(pairs_of_polymer("").is_empty());
+        assert!(pairs_of_polymer("N").is_empty());
+    }
+
+    #[test]
+    fn test_parallel_growth_agrees() {
+        let (template, rules) = synthetic_rules(100, 0x2545f4914f6cdd1d);
+        let steps = [1, 5, 12];
+        assert_eq!(
+            sweep(&template, &rules, &steps, Growth::Parallel),
+            sweep(&template, &rules, &steps, Growth::Sequential)
+        );
+    }
+
+    #[test]
+    fn test_solution_example() {
+        let (part1, part2) = common::solve::<Day14>(include_str!("../example.txt"));
+        assert_eq!(part1.to_string(), "1588");
+        assert_eq!(part2.to_string(), "2188189693529");
+    }
+}
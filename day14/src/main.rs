@@ -1,85 +1,56 @@
-use std::{
-    cmp,
-    collections::HashMap,
-    io::{self, BufRead},
-};
+use common::Solution;
+use day14::*;
+use std::{env, fs};
 
-fn count_elements(
-    first_polymer_element: char,
-    pairs: &HashMap<String, usize>,
-) -> HashMap<char, usize> {
-    pairs.iter().fold(
-        {
-            let mut count = HashMap::new();
-            count.insert(first_polymer_element, 1);
-            count
-        },
-        |mut count, (key, value)| {
-            let second_pair_element = key[1..].chars().next().unwrap();
-            *count.entry(second_pair_element).or_insert(0) += value;
-            count
-        },
-    )
-}
-
-fn min_max_elements(first_polymer_element: char, pairs: &HashMap<String, usize>) -> (usize, usize) {
-    count_elements(first_polymer_element, pairs)
-        .iter()
-        .fold((usize::MAX, usize::MIN), |(min, max), (_, &count)| {
-            (cmp::min(min, count), cmp::max(max, count))
-        })
-}
+fn main() {
+    let input = common::read_input();
+    let (templates, rules) = Day14::parse(&input);
 
-fn pairs_of_polymer(polymer: &str) -> HashMap<String, usize> {
-    let mut pairs = HashMap::with_capacity(polymer.len() - 1);
-    for i in 0..polymer.len() - 1 {
-        let pair = &polymer[i..i + 2];
-        *pairs.entry(String::from(pair)).or_insert(0) += 1;
+    let mut rule_sets = Vec::new();
+    if !rules.is_empty() {
+        rule_sets.push((String::from("input"), rules));
     }
-    pairs
-}
-
-fn grow_polymer(
-    initial_pairs: HashMap<String, usize>,
-    rules: &HashMap<&str, &str>,
-    steps: usize,
-) -> HashMap<String, usize> {
-    (0..steps).fold(initial_pairs, |pairs, _| {
-        let mut next_pairs = HashMap::with_capacity(pairs.len() * 2);
-        for (pair, count) in pairs {
-            if let Some(insert) = rules.get(&pair[..]) {
-                let mut pair1 = String::with_capacity(insert.len() + 1);
-                pair1.push_str(&pair[..1]);
-                pair1.push_str(insert);
-                *next_pairs.entry(pair1).or_insert(0) += count;
-
-                let mut pair2 = String::with_capacity(insert.len() + 1);
-                pair2.push_str(insert);
-                pair2.push_str(&pair[1..]);
-                *next_pairs.entry(pair2).or_insert(0) += count;
-            } else {
-                *next_pairs.entry(pair).or_insert(0) += count;
-            }
+    let args: Vec<String> = env::args().collect();
+    for pair in args.windows(2) {
+        if pair[0] == "--rules" {
+            // either a plain list of rules or a complete puzzle input
+            let content = fs::read_to_string(&pair[1]).unwrap();
+            let rules = common::input::header_and_body(&content)
+                .map_or(content.as_str(), |(_, rules)| rules);
+            rule_sets.push((pair[1].clone(), parse_rules(rules)));
         }
-        next_pairs
-    })
-}
-
-fn main() {
-    let lines: Vec<String> = io::stdin().lock().lines().map(|s| s.unwrap()).collect();
-
-    let polymer_template = &lines[0];
-    let insertion_rules: HashMap<&str, &str> = lines
-        .iter()
-        .skip(2)
-        .map(|s| s.split_once(" -> ").unwrap())
+    }
+    let part_steps: Vec<(usize, usize)> = [(1, 10), (2, 40)]
+        .into_iter()
+        .filter(|&(part, _)| common::part_selected(part))
         .collect();
+    let steps: Vec<usize> = env::args()
+        .skip_while(|arg| arg != "--steps")
+        .nth(1)
+        .map(|steps| steps.split(',').map(|s| s.parse().unwrap()).collect())
+        .unwrap_or_else(|| part_steps.iter().map(|&(_, steps)| steps).collect());
+
+    let growth = if env::args().any(|arg| arg == "--parallel") {
+        Growth::Parallel
+    } else {
+        Growth::Sequential
+    };
+
+    let batch = process_batch(&templates, &rule_sets, &steps, growth);
+    if let [single] = &batch[..] {
+        let result_steps = single.results.iter().map(|result| result.step);
+        if result_steps.eq(part_steps.iter().map(|&(_, steps)| steps)) {
+            for (&(part, _), result) in part_steps.iter().zip(&single.results) {
+                println!("Part {}: {}", part, result.max - result.min);
+            }
+            return;
+        }
+    }
 
-    let polymer1_pairs = grow_polymer(pairs_of_polymer(polymer_template), &insertion_rules, 10);
-    let (min, max) = min_max_elements(polymer_template.chars().next().unwrap(), &polymer1_pairs);
-    println!("Part 1: {}", max - min);
-
-    let polymer2_pairs = grow_polymer(polymer1_pairs, &insertion_rules, 30);
-    let (min, max) = min_max_elements(polymer_template.chars().next().unwrap(), &polymer2_pairs);
-    println!("Part 2: {}", max - min);
+    for result in batch {
+        println!("{} ({}):", result.template, result.rules);
+        for StepResult { step, min, max } in result.results {
+            println!("  step {}: {} (min {}, max {})", step, max - min, min, max);
+        }
+    }
 }
@@ -0,0 +1,98 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use day15::bidirectional::{bidirectional_search, Landmarks};
+use day15::generator::{generate_map, MapConfig, MapFamily};
+use day15::risk_map::{bucket_queue_search, RiskLookup, RiskMap};
+use day15::*;
+
+const FAMILIES: [MapFamily; 3] = [MapFamily::Random, MapFamily::Ridges, MapFamily::Maze];
+
+fn random_tile(size: usize) -> Vec<Vec<usize>> {
+    generate_map(&MapConfig {
+        family: MapFamily::Random,
+        size,
+        difficulty: 1.0,
+        seed: 0x9e3779b97f4a7c15,
+    })
+}
+
+fn expanded_maps(c: &mut Criterion) {
+    let tile = RiskMap::from_rows(&random_tile(100));
+    let mut group = c.benchmark_group("expanded map");
+    group.sample_size(10);
+    for n in [5, 10] {
+        let map = tile.expand(n);
+        let end = map.width() * map.height() - 1;
+        for solver in Solver::ALL {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{:?}", solver), n),
+                &map,
+                |b, map| b.iter(|| lowest_risk_with(map, 0, end, solver)),
+            );
+        }
+
+        group.bench_with_input(BenchmarkId::new("landmarks", n), &map, |b, map| {
+            b.iter(|| Landmarks::corners(map))
+        });
+        let landmarks = Landmarks::corners(&map);
+        group.bench_with_input(BenchmarkId::new("Alt query", n), &map, |b, map| {
+            b.iter(|| {
+                bidirectional_search(
+                    map,
+                    0,
+                    end,
+                    |node| landmarks.lower_bound(node, end),
+                    |node| landmarks.lower_bound(0, node),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+fn generated_families(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generated map");
+    group.sample_size(10);
+    for family in FAMILIES {
+        for difficulty in [0.0, 0.5, 1.0] {
+            let map = RiskMap::from_rows(&generate_map(&MapConfig {
+                family,
+                size: 300,
+                difficulty,
+                seed: 0x9e3779b97f4a7c15,
+            }));
+            for solver in Solver::ALL {
+                group.bench_with_input(
+                    BenchmarkId::new(
+                        format!("{:?}", solver),
+                        format!("{:?} {}", family, difficulty),
+                    ),
+                    &map,
+                    |b, map| b.iter(|| lowest_total_risk(map, solver)),
+                );
+            }
+        }
+    }
+    group.finish();
+}
+
+// Rows of vectors against the flat framed map.
+fn map_layouts(c: &mut Criterion) {
+    let tile = RiskMap::from_rows(&random_tile(100));
+    let flat = tile.expand(5);
+    let nested: Vec<Vec<usize>> = (0..flat.height())
+        .map(|y| (0..flat.width()).map(|x| flat.risk(x, y)).collect())
+        .collect();
+    let end = flat.width() * flat.height() - 1;
+
+    let mut group = c.benchmark_group("5x expanded map layout");
+    group.sample_size(10);
+    group.bench_function("A* on nested", |b| b.iter(|| lowest_risk(&nested, 0, end)));
+    group.bench_function("A* on flat", |b| b.iter(|| lowest_risk(&flat, 0, end)));
+    group.bench_function("bucket queue on flat", |b| {
+        b.iter(|| bucket_queue_search(&flat, 0, end))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, expanded_maps, generated_families, map_layouts);
+criterion_main!(benches);
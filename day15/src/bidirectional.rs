@@ -0,0 +1,236 @@
+use super::RiskLookup;
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+// Entering a cell costs its value, so the cost of an edge depends on its direction. The
+// backward search walks edges in reverse and pays the value of the cell it comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Forward,
+    Backward,
+}
+
+fn neighbors(node: usize, width: usize, height: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = (node % width, node / width);
+    [
+        (x > 0).then(|| node - 1),
+        (x + 1 < width).then(|| node + 1),
+        (y > 0).then(|| node - width),
+        (y + 1 < height).then(|| node + width),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+struct SearchState {
+    estimate: usize,
+    cost: usize,
+    node: usize,
+}
+
+impl Ord for SearchState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.node.cmp(&other.node))
+    }
+}
+
+impl PartialOrd for SearchState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Lowest total cost from `source` to every cell (Forward), or from every cell to `source`
+// (Backward). Unreachable cells are usize::MAX.
+fn all_costs<M>(map: &M, source: usize, direction: Direction) -> Vec<usize>
+where
+    M: RiskLookup + ?Sized,
+{
+    let width = map.width();
+    let height = map.height();
+    let value = |node: usize| map.node_risk(node);
+
+    let mut cost = vec![usize::MAX; width * height];
+    let mut heap = BinaryHeap::new();
+    cost[source] = 0;
+    heap.push(SearchState {
+        estimate: 0,
+        cost: 0,
+        node: source,
+    });
+
+    while let Some(SearchState { cost: c, node, .. }) = heap.pop() {
+        if c > cost[node] {
+            continue;
+        }
+        for v in neighbors(node, width, height) {
+            let new_cost = c + match direction {
+                Direction::Forward => value(v),
+                Direction::Backward => value(node),
+            };
+            if new_cost < cost[v] {
+                cost[v] = new_cost;
+                heap.push(SearchState {
+                    estimate: new_cost,
+                    cost: new_cost,
+                    node: v,
+                });
+            }
+        }
+    }
+
+    cost
+}
+
+// Exact costs from and to a few landmark cells. By the triangle inequality they give lower
+// bounds for the cost between any two cells (ALT heuristic).
+pub struct Landmarks {
+    width: usize,
+    from: Vec<Vec<usize>>,
+    to: Vec<Vec<usize>>,
+}
+
+impl Landmarks {
+    pub fn new<M>(map: &M, landmarks: &[usize]) -> Landmarks
+    where
+        M: RiskLookup + ?Sized,
+    {
+        Landmarks {
+            width: map.width(),
+            from: landmarks
+                .iter()
+                .map(|&l| all_costs(map, l, Direction::Forward))
+                .collect(),
+            to: landmarks
+                .iter()
+                .map(|&l| all_costs(map, l, Direction::Backward))
+                .collect(),
+        }
+    }
+
+    pub fn corners<M>(map: &M) -> Landmarks
+    where
+        M: RiskLookup + ?Sized,
+    {
+        let width = map.width();
+        let height = map.height();
+        let mut corners = vec![0, width - 1, (height - 1) * width, width * height - 1];
+        corners.dedup();
+        Self::new(map, &corners)
+    }
+
+    // Lower bound for the cost from `u` to `v`, never worse than the Manhattan distance
+    // (every cell costs at least 1).
+    pub fn lower_bound(&self, u: usize, v: usize) -> usize {
+        let (x1, y1) = (u % self.width, u / self.width);
+        let (x2, y2) = (v % self.width, v / self.width);
+        let manhattan = x1.abs_diff(x2) + y1.abs_diff(y2);
+
+        let via_landmarks = self.from.iter().zip(&self.to).map(|(from, to)| {
+            // d(L, v) <= d(L, u) + d(u, v) and d(u, L) <= d(u, v) + d(v, L)
+            let forward = if from[v] == usize::MAX || from[u] == usize::MAX {
+                0
+            } else {
+                from[v].saturating_sub(from[u])
+            };
+            let backward = if to[u] == usize::MAX || to[v] == usize::MAX {
+                0
+            } else {
+                to[u].saturating_sub(to[v])
+            };
+            forward.max(backward)
+        });
+        via_landmarks.fold(manhattan, usize::max)
+    }
+}
+
+// Searches from both ends at once and stops as soon as one of the frontiers can't improve
+// the best path found so far. `to_end(node)` and `from_start(node)` must be consistent lower
+// bounds for the remaining cost of the forward and the backward search respectively.
+pub fn bidirectional_search<M, F, B>(
+    map: &M,
+    start: usize,
+    end: usize,
+    to_end: F,
+    from_start: B,
+) -> Option<usize>
+where
+    M: RiskLookup + ?Sized,
+    F: Fn(usize) -> usize,
+    B: Fn(usize) -> usize,
+{
+    let width = map.width();
+    let height = map.height();
+    let value = |node: usize| map.node_risk(node);
+
+    if start == end {
+        return Some(0);
+    }
+
+    let mut cost = [
+        vec![usize::MAX; width * height],
+        vec![usize::MAX; width * height],
+    ];
+    let mut heaps = [BinaryHeap::new(), BinaryHeap::new()];
+    cost[0][start] = 0;
+    cost[1][end] = 0;
+    heaps[0].push(SearchState {
+        estimate: to_end(start),
+        cost: 0,
+        node: start,
+    });
+    heaps[1].push(SearchState {
+        estimate: from_start(end),
+        cost: 0,
+        node: end,
+    });
+
+    let mut best = usize::MAX;
+    while let (Some(top_forward), Some(top_backward)) = (heaps[0].peek(), heaps[1].peek()) {
+        if top_forward.estimate >= best || top_backward.estimate >= best {
+            break;
+        }
+
+        // expand the side with the smaller frontier
+        let (side, direction) = if heaps[0].len() <= heaps[1].len() {
+            (0, Direction::Forward)
+        } else {
+            (1, Direction::Backward)
+        };
+        let other = 1 - side;
+        let SearchState { cost: c, node, .. } = heaps[side].pop().unwrap();
+        if c > cost[side][node] {
+            continue;
+        }
+
+        for v in neighbors(node, width, height) {
+            let edge = match direction {
+                Direction::Forward => value(v),
+                Direction::Backward => value(node),
+            };
+            let new_cost = c + edge;
+            if new_cost >= cost[side][v] {
+                continue;
+            }
+            cost[side][v] = new_cost;
+            if cost[other][v] != usize::MAX {
+                best = best.min(new_cost + cost[other][v]);
+            }
+            let estimate = new_cost
+                + match direction {
+                    Direction::Forward => to_end(v),
+                    Direction::Backward => from_start(v),
+                };
+            heaps[side].push(SearchState {
+                estimate,
+                cost: new_cost,
+                node: v,
+            });
+        }
+    }
+
+    (best != usize::MAX).then_some(best)
+}
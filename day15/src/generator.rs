@@ -0,0 +1,125 @@
+// Synthetic risk maps for stress testing the solvers, the puzzle input is too easy to tell
+// them apart.
+
+use common::rng::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapFamily {
+    // independent uniform risks
+    Random,
+    // low risk plains fenced in by high risk ridges with a few passes
+    Ridges,
+    // low risk corridors of a maze between high risk walls
+    Maze,
+}
+
+impl MapFamily {
+    pub fn parse(s: &str) -> Option<MapFamily> {
+        match s {
+            "random" => Some(MapFamily::Random),
+            "ridges" => Some(MapFamily::Ridges),
+            "maze" => Some(MapFamily::Maze),
+            _ => None,
+        }
+    }
+}
+
+// The difficulty (0 to 1) is the spread of the risks for random maps, the density of the ridges
+// and the rarity of passes for ridge maps, and the fraction of walls kept for mazes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapConfig {
+    pub family: MapFamily,
+    pub size: usize,
+    pub difficulty: f64,
+    pub seed: u64,
+}
+
+const MIN_RISK: usize = 1;
+const MAX_RISK: usize = 9;
+
+// Generates a square map, the same config always yields the same map.
+pub fn generate_map(config: &MapConfig) -> Vec<Vec<usize>> {
+    let difficulty = config.difficulty.clamp(0.0, 1.0);
+    let mut rng = Rng::new(config.seed);
+    match config.family {
+        MapFamily::Random => random_map(config.size, difficulty, &mut rng),
+        MapFamily::Ridges => ridge_map(config.size, difficulty, &mut rng),
+        MapFamily::Maze => maze_map(config.size, difficulty, &mut rng),
+    }
+}
+
+fn random_map(size: usize, difficulty: f64, rng: &mut Rng) -> Vec<Vec<usize>> {
+    let spread = 1 + ((MAX_RISK - MIN_RISK) as f64 * difficulty).round() as usize;
+    (0..size)
+        .map(|_| (0..size).map(|_| MIN_RISK + rng.below(spread)).collect())
+        .collect()
+}
+
+fn ridge_map(size: usize, difficulty: f64, rng: &mut Rng) -> Vec<Vec<usize>> {
+    let spacing = 3 + ((1.0 - difficulty) * 13.0).round() as usize;
+    let pass_chance = 0.02 + 0.3 * (1.0 - difficulty);
+    (0..size)
+        .map(|y| {
+            (0..size)
+                .map(|x| {
+                    let ridge = x % spacing == spacing - 1 || y % spacing == spacing - 1;
+                    if ridge && !rng.chance(pass_chance) {
+                        MAX_RISK - rng.below(2)
+                    } else {
+                        MIN_RISK + rng.below(3)
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Rooms are the cells with even coordinates, carved out by a randomized depth-first search.
+fn maze_map(size: usize, difficulty: f64, rng: &mut Rng) -> Vec<Vec<usize>> {
+    let mut map = vec![vec![MAX_RISK; size]; size];
+    if size == 0 {
+        return map;
+    }
+
+    let rooms = size.div_ceil(2);
+    let mut visited = vec![vec![false; rooms]; rooms];
+    let mut stack = vec![(0, 0)];
+    visited[0][0] = true;
+    map[0][0] = MIN_RISK;
+    while let Some(&(rx, ry)) = stack.last() {
+        let mut unvisited = Vec::with_capacity(4);
+        if rx > 0 && !visited[ry][rx - 1] {
+            unvisited.push((rx - 1, ry));
+        }
+        if rx + 1 < rooms && !visited[ry][rx + 1] {
+            unvisited.push((rx + 1, ry));
+        }
+        if ry > 0 && !visited[ry - 1][rx] {
+            unvisited.push((rx, ry - 1));
+        }
+        if ry + 1 < rooms && !visited[ry + 1][rx] {
+            unvisited.push((rx, ry + 1));
+        }
+        if unvisited.is_empty() {
+            stack.pop();
+            continue;
+        }
+
+        let (nx, ny) = unvisited[rng.below(unvisited.len())];
+        visited[ny][nx] = true;
+        // the wall between both rooms
+        map[ry + ny][rx + nx] = MIN_RISK;
+        map[2 * ny][2 * nx] = MIN_RISK;
+        stack.push((nx, ny));
+    }
+
+    // knocking out walls opens up loops, which makes the maze easier
+    for row in &mut map {
+        for risk in row {
+            if *risk == MAX_RISK && !rng.chance(difficulty) {
+                *risk = MIN_RISK;
+            }
+        }
+    }
+    map
+}
@@ -0,0 +1,641 @@
+pub mod bidirectional;
+pub mod generator;
+pub mod risk_map;
+
+use bidirectional::{bidirectional_search, Landmarks};
+use common::shortest_path::{shortest_path, Moves};
+use common::{Answer, Solution};
+use risk_map::{bucket_queue_search, RiskLookup, RiskMap};
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input = RiskMap;
+
+    fn parse(input: &str) -> Self::Input {
+        RiskMap::from_rows(&parse_rows(input))
+    }
+
+    fn part1(map: &Self::Input) -> Answer {
+        lowest_total_risk(map, Solver::default()).unwrap().into()
+    }
+
+    fn part2(map: &Self::Input) -> Answer {
+        lowest_total_risk(&map.expand(5), Solver::default())
+            .unwrap()
+            .into()
+    }
+}
+
+pub fn parse_rows(input: &str) -> Vec<Vec<usize>> {
+    input
+        .lines()
+        .map(|line| {
+            line.chars()
+                .map(|c| c.to_digit(10).unwrap() as usize)
+                .collect()
+        })
+        .collect()
+}
+
+// Lowest total risk from the top left to the bottom right corner.
+pub fn lowest_total_risk(map: &RiskMap, solver: Solver) -> Option<usize> {
+    let top_left = 0;
+    let bottom_right = map.width() * map.height() - 1;
+    lowest_risk_with(map, top_left, bottom_right, solver)
+}
+
+pub fn adjacent_nodes(node: usize, width: usize, height: usize) -> Vec<usize> {
+    let x = node % width;
+    let y = node / width;
+
+    let mut nodes = Vec::with_capacity(4);
+    if x > 0 {
+        nodes.push(y * width + (x - 1));
+    }
+    if x < width - 1 {
+        nodes.push(y * width + (x + 1));
+    }
+    if y > 0 {
+        nodes.push((y - 1) * width + x);
+    }
+    if y < height - 1 {
+        nodes.push((y + 1) * width + x);
+    }
+    nodes
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct PathState {
+    risk: usize,
+    node: usize,
+}
+
+impl Ord for PathState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .risk
+            .cmp(&self.risk)
+            .then_with(|| self.node.cmp(&other.node))
+    }
+}
+
+impl PartialOrd for PathState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub fn manhattan_distance(width: usize, n1: usize, n2: usize) -> usize {
+    let (x1, y1) = (n1 % width, n1 / width);
+    let (x2, y2) = (n2 % width, n2 / width);
+    x1.abs_diff(x2) + y1.abs_diff(y2)
+}
+
+pub fn heuristic(width: usize, start: usize, end: usize) -> usize {
+    manhattan_distance(width, start, end)
+}
+
+pub fn lowest_risk<M>(map: &M, start: usize, end: usize) -> Option<usize>
+where
+    M: RiskLookup + ?Sized,
+{
+    let width = map.width();
+    shortest_path(
+        (width, map.height()),
+        |x, y| map.risk(x, y),
+        start,
+        end,
+        Moves::Orthogonal,
+        |step| step.value,
+        |node| heuristic(width, node, end),
+    )
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Solver {
+    Dijkstra,
+    #[default]
+    AStar,
+    // bidirectional A* with Manhattan bounds
+    Bidirectional,
+    // bidirectional A* with landmark bounds from the corners
+    Alt,
+    // Dijkstra with a bucket queue on the flat map
+    Bucket,
+}
+
+impl Solver {
+    pub const ALL: [Solver; 5] = [
+        Solver::Dijkstra,
+        Solver::AStar,
+        Solver::Bidirectional,
+        Solver::Alt,
+        Solver::Bucket,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Solver::Dijkstra => "dijkstra",
+            Solver::AStar => "astar",
+            Solver::Bidirectional => "bidirectional",
+            Solver::Alt => "alt",
+            Solver::Bucket => "bucket",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Solver> {
+        Self::ALL.into_iter().find(|solver| solver.name() == s)
+    }
+}
+
+pub fn lowest_risk_with(map: &RiskMap, start: usize, end: usize, solver: Solver) -> Option<usize> {
+    let width = map.width();
+    match solver {
+        Solver::Dijkstra => shortest_path(
+            (width, map.height()),
+            |x, y| map.risk(x, y),
+            start,
+            end,
+            Moves::Orthogonal,
+            |step| step.value,
+            |_| 0,
+        ),
+        Solver::AStar => lowest_risk(map, start, end),
+        Solver::Bidirectional => bidirectional_search(
+            map,
+            start,
+            end,
+            |node| manhattan_distance(width, node, end),
+            |node| manhattan_distance(width, start, node),
+        ),
+        Solver::Alt => {
+            let landmarks = Landmarks::corners(map);
+            bidirectional_search(
+                map,
+                start,
+                end,
+                |node| landmarks.lower_bound(node, end),
+                |node| landmarks.lower_bound(start, node),
+            )
+        }
+        Solver::Bucket => bucket_queue_search(map, start, end),
+    }
+}
+
+// Keeps the distances of all nodes (from `start`) together with the shortest-path tree,
+// so that changing the risk of a single cell only re-expands the affected nodes.
+pub struct RiskSolver {
+    map: RiskMap,
+    width: usize,
+    height: usize,
+    start: usize,
+    total_risk: Vec<usize>,
+    parent: Vec<Option<usize>>,
+}
+
+impl RiskSolver {
+    pub fn new(map: RiskMap, start: usize) -> RiskSolver {
+        let width = map.width();
+        let height = map.height();
+        let node_count = width * height;
+
+        let mut solver = RiskSolver {
+            map,
+            width,
+            height,
+            start,
+            total_risk: vec![usize::MAX; node_count],
+            parent: vec![None; node_count],
+        };
+        solver.total_risk[start] = 0;
+        solver.expand(vec![start]);
+        solver
+    }
+
+    pub fn lowest_risk(&self, end: usize) -> Option<usize> {
+        match self.total_risk[end] {
+            usize::MAX => None,
+            risk => Some(risk),
+        }
+    }
+
+    pub fn risk(&self, node: usize) -> usize {
+        self.map.node_risk(node)
+    }
+
+    // Returns the number of nodes which had to be re-expanded.
+    pub fn update_risk(&mut self, node: usize, risk: usize) -> usize {
+        let old_risk = self.risk(node);
+        self.map.set(node, risk);
+
+        if node == self.start || risk == old_risk || self.total_risk[node] == usize::MAX {
+            // the risk of the start node is never paid, same for unreachable nodes
+            return 0;
+        }
+
+        if risk < old_risk {
+            // distances can only decrease, so propagate the improvement starting from node
+            self.total_risk[node] -= old_risk - risk;
+            return self.expand(vec![node]);
+        }
+
+        // All nodes whose shortest path goes through node may get more expensive, all
+        // others are unaffected. Invalidate the affected subtree and re-seed it from its
+        // unaffected neighbors.
+        let mut affected = vec![node];
+        let mut i = 0;
+        while i < affected.len() {
+            let u = affected[i];
+            self.total_risk[u] = usize::MAX;
+            for v in adjacent_nodes(u, self.width, self.height) {
+                if self.parent[v] == Some(u) {
+                    affected.push(v);
+                }
+            }
+            i += 1;
+        }
+
+        for &u in &affected {
+            self.parent[u] = None;
+            for v in adjacent_nodes(u, self.width, self.height) {
+                if self.total_risk[v] == usize::MAX {
+                    continue;
+                }
+                let new_risk = self.total_risk[v] + self.risk(u);
+                if new_risk < self.total_risk[u] {
+                    self.total_risk[u] = new_risk;
+                    self.parent[u] = Some(v);
+                }
+            }
+        }
+
+        let seeds = affected
+            .into_iter()
+            .filter(|&u| self.total_risk[u] != usize::MAX)
+            .collect();
+        self.expand(seeds)
+    }
+
+    // Dijkstra starting from the given (already settled) nodes.
+    fn expand(&mut self, seeds: Vec<usize>) -> usize {
+        let mut heap: BinaryHeap<PathState> = seeds
+            .into_iter()
+            .map(|node| PathState {
+                risk: self.total_risk[node],
+                node,
+            })
+            .collect();
+
+        let mut expanded = 0;
+        while let Some(PathState { risk, node }) = heap.pop() {
+            if risk > self.total_risk[node] {
+                continue;
+            }
+            expanded += 1;
+
+            for v in adjacent_nodes(node, self.width, self.height) {
+                let new_risk = risk + self.risk(v);
+                if new_risk < self.total_risk[v] {
+                    self.total_risk[v] = new_risk;
+                    self.parent[v] = Some(node);
+                    heap.push(PathState {
+                        risk: new_risk,
+                        node: v,
+                    });
+                }
+            }
+        }
+        expanded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generator::{MapConfig, MapFamily};
+
+    const EXAMPLE: &str = "1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581";
+
+    // Expansion of the nested layout, kept as reference for RiskMap::expand.
+    fn expand_row(row: &[usize], n: usize, first_tile_row: bool) -> Vec<usize> {
+        let mut full_row = Vec::with_capacity(row.len() * n);
+        if first_tile_row {
+            for &value in row {
+                full_row.push(value);
+            }
+        } else {
+            for &value in row {
+                if value + 1 > 9 {
+                    full_row.push(1);
+                } else {
+                    full_row.push(value + 1);
+                }
+            }
+        }
+        for tile in 1..n {
+            for col in 0..row.len() {
+                let prev_tile_value = full_row[(tile - 1) * row.len() + col];
+                if prev_tile_value + 1 > 9 {
+                    full_row.push(1);
+                } else {
+                    full_row.push(prev_tile_value + 1);
+                }
+            }
+        }
+        full_row
+    }
+
+    fn expand_map(first_tile: &[Vec<usize>], n: usize) -> Vec<Vec<usize>> {
+        let mut full_map = Vec::with_capacity(first_tile.len() * n);
+        for row in first_tile {
+            full_map.push(expand_row(row, n, true));
+        }
+        for tile in 1..n {
+            for i in 0..first_tile.len() {
+                // only the first tile of the previous row, the others are derived from it again
+                let prev_tile_row =
+                    &full_map[(tile - 1) * first_tile.len() + i][..first_tile[i].len()];
+                let expanded_row = expand_row(prev_tile_row, n, false);
+                full_map.push(expanded_row);
+            }
+        }
+        full_map
+    }
+
+    fn example() -> Vec<Vec<usize>> {
+        EXAMPLE
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| c.to_digit(10).unwrap() as usize)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_solver_example() {
+        let map = RiskMap::from_rows(&example());
+        let solver = RiskSolver::new(map.clone(), 0);
+        assert_eq!(solver.lowest_risk(99), Some(40));
+
+        let expanded_map = map.expand(5);
+        let solver = RiskSolver::new(expanded_map, 0);
+        assert_eq!(solver.lowest_risk(2499), Some(315));
+    }
+
+    #[test]
+    fn test_update_risk_matches_full_solve() {
+        let mut map = expand_map(&example(), 5);
+        let node_count = map.len() * map[0].len();
+        let end = node_count - 1;
+        let mut solver = RiskSolver::new(RiskMap::from_rows(&map), 0);
+
+        let mut seed: u64 = 0x853c49e6748fea9b;
+        for _ in 0..200 {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let node = (seed >> 33) as usize % node_count;
+            let risk = 1 + (seed >> 13) as usize % 9;
+
+            map[node / 50][node % 50] = risk;
+            solver.update_risk(node, risk);
+
+            assert_eq!(solver.lowest_risk(end), lowest_risk(&map, 0, end));
+            let full = RiskSolver::new(RiskMap::from_rows(&map), 0);
+            assert_eq!(solver.total_risk, full.total_risk);
+        }
+    }
+
+    #[test]
+    fn test_lowest_risk_example() {
+        let map = example();
+        assert_eq!(lowest_risk(&map, 0, 99), Some(40));
+        assert_eq!(lowest_risk(&expand_map(&map, 5), 0, 2499), Some(315));
+        assert_eq!(
+            lowest_risk(&RiskMap::from_rows(&map).expand(5), 0, 2499),
+            Some(315)
+        );
+    }
+
+    #[test]
+    fn test_expand_layouts_agree() {
+        let tile = generate_map(7, 0x853c49e6748fea9b);
+        assert_eq!(
+            RiskMap::from_rows(&tile).expand(5),
+            RiskMap::from_rows(&expand_map(&tile, 5))
+        );
+    }
+
+    #[test]
+    fn test_shortest_path_with_turn_penalty() {
+        let map = example();
+        let straight = shortest_path(
+            (10, 10),
+            |x, y| map[y][x],
+            0,
+            99,
+            Moves::Orthogonal,
+            |step| step.value + if step.is_turn() { 100 } else { 0 },
+            |_| 0,
+        );
+        // going along the border takes only a single turn
+        let border_risk =
+            map[0][1..].iter().sum::<usize>() + map[1..].iter().map(|row| row[9]).sum::<usize>();
+        let other_border_risk =
+            map[1..].iter().map(|row| row[0]).sum::<usize>() + map[9][1..].iter().sum::<usize>();
+        assert_eq!(straight, Some(border_risk.min(other_border_risk) + 100));
+    }
+
+    // Bellman-Ford style reference for the lowest risk to the bottom right with 8 moves.
+    fn relaxed_king_moves_risk(map: &[Vec<usize>]) -> usize {
+        let size = map.len();
+        let mut risk = vec![vec![usize::MAX; size]; size];
+        risk[0][0] = 0;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for y in 0..size {
+                for x in 0..size {
+                    for ny in y.saturating_sub(1)..=(y + 1).min(size - 1) {
+                        for nx in x.saturating_sub(1)..=(x + 1).min(size - 1) {
+                            if risk[ny][nx] != usize::MAX && risk[ny][nx] + map[y][x] < risk[y][x] {
+                                risk[y][x] = risk[ny][nx] + map[y][x];
+                                changed = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        risk[size - 1][size - 1]
+    }
+
+    #[test]
+    fn test_shortest_path_with_diagonal_moves() {
+        // costs are in half units, so diagonal moves cost 1.5 times the risk
+        let half_units = |step: common::shortest_path::Step| {
+            if step.is_diagonal() {
+                3 * step.value
+            } else {
+                2 * step.value
+            }
+        };
+
+        let map = example();
+        let value = |x: usize, y: usize| map[y][x];
+        let diagonal =
+            shortest_path((10, 10), value, 0, 99, Moves::Diagonal, half_units, |_| 0).unwrap();
+        assert!(diagonal < 2 * 40);
+        assert_eq!(
+            shortest_path(
+                (10, 10),
+                value,
+                0,
+                99,
+                Moves::Diagonal,
+                |step| step.value,
+                |_| 0
+            ),
+            Some(relaxed_king_moves_risk(&map))
+        );
+    }
+
+    const FAMILIES: [MapFamily; 3] = [MapFamily::Random, MapFamily::Ridges, MapFamily::Maze];
+
+    fn generate_map(size: usize, seed: u64) -> Vec<Vec<usize>> {
+        generator::generate_map(&MapConfig {
+            family: MapFamily::Random,
+            size,
+            difficulty: 1.0,
+            seed,
+        })
+    }
+
+    #[test]
+    fn test_solvers_example() {
+        let map = RiskMap::from_rows(&example());
+        let expanded_map = map.expand(5);
+        for solver in Solver::ALL {
+            assert_eq!(
+                lowest_risk_with(&map, 0, 99, solver),
+                Some(40),
+                "{:?}",
+                solver
+            );
+            assert_eq!(
+                lowest_risk_with(&expanded_map, 0, 2499, solver),
+                Some(315),
+                "{:?}",
+                solver
+            );
+        }
+    }
+
+    #[test]
+    fn test_solvers_agree_on_generated_maps() {
+        for seed in 1..10 {
+            let map = RiskMap::from_rows(&generate_map(30, seed));
+            let node_count = 30 * 30;
+            // also between arbitrary cells, in both directions
+            let queries = [
+                (0, node_count - 1),
+                (node_count - 1, 0),
+                (29, 30 * 29),
+                (seed as usize * 37, seed as usize * 91),
+                (17, 17),
+            ];
+            for (start, end) in queries {
+                let expected = lowest_risk_with(&map, start, end, Solver::Dijkstra);
+                for solver in Solver::ALL {
+                    assert_eq!(
+                        lowest_risk_with(&map, start, end, solver),
+                        expected,
+                        "{:?} {} -> {}",
+                        solver,
+                        start,
+                        end
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_families() {
+        for family in FAMILIES {
+            for difficulty in [0.0, 0.5, 1.0] {
+                let config = MapConfig {
+                    family,
+                    size: 25,
+                    difficulty,
+                    seed: 7,
+                };
+                let rows = generator::generate_map(&config);
+                assert_eq!(rows, generator::generate_map(&config));
+                assert_eq!(rows.len(), 25);
+                assert!(rows
+                    .iter()
+                    .all(|row| row.len() == 25 && row.iter().all(|risk| (1..=9).contains(risk))));
+
+                let map = RiskMap::from_rows(&rows);
+                let expected = lowest_total_risk(&map, Solver::Dijkstra);
+                for solver in Solver::ALL {
+                    assert_eq!(
+                        lowest_total_risk(&map, solver),
+                        expected,
+                        "{:?} {:?} {}",
+                        solver,
+                        family,
+                        difficulty
+                    );
+                }
+            }
+        }
+
+        // the easiest maze has no walls left
+        let maze = generator::generate_map(&MapConfig {
+            family: MapFamily::Maze,
+            size: 25,
+            difficulty: 0.0,
+            seed: 7,
+        });
+        assert!(maze.iter().flatten().all(|&risk| risk == 1));
+    }
+
+    #[test]
+    fn test_landmark_bounds_are_admissible() {
+        let map = RiskMap::from_rows(&generate_map(20, 0x2545f4914f6cdd1d));
+        let landmarks = Landmarks::corners(&map);
+        for u in (0..400).step_by(7) {
+            for v in (0..400).step_by(11) {
+                let exact = lowest_risk_with(&map, u, v, Solver::Dijkstra).unwrap();
+                assert!(landmarks.lower_bound(u, v) <= exact);
+            }
+        }
+        // exact towards a landmark
+        assert_eq!(
+            landmarks.lower_bound(0, 399),
+            lowest_risk_with(&map, 0, 399, Solver::Dijkstra).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_solution_example() {
+        let (part1, part2) = common::solve::<Day15>(include_str!("../example.txt"));
+        assert_eq!(part1.to_string(), "40");
+        assert_eq!(part2.to_string(), "315");
+    }
+}
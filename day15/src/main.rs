@@ -1,167 +1,70 @@
-use std::{
-    cmp::Ordering,
-    collections::BinaryHeap,
-    io::{self, BufRead},
-};
+use common::Solution;
+use day15::generator::{generate_map, MapConfig, MapFamily};
+use day15::risk_map::RiskLookup;
+use day15::*;
+use std::env;
 
-fn adjacent_nodes(node: usize, width: usize, height: usize) -> Vec<usize> {
-    let x = node % width;
-    let y = node / width;
-
-    let mut nodes = Vec::with_capacity(4);
-    if x > 1 {
-        nodes.push(y * width + (x - 1));
-    }
-    if x < width - 1 {
-        nodes.push(y * width + (x + 1));
-    }
-    if y > 1 {
-        nodes.push((y - 1) * width + x);
-    }
-    if y < height - 1 {
-        nodes.push((y + 1) * width + x);
-    }
-    nodes
-}
-
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct PathState {
-    risk: usize,
-    node: usize,
-}
-
-impl Ord for PathState {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other
-            .risk
-            .cmp(&self.risk)
-            .then_with(|| self.node.cmp(&other.node))
-    }
-}
-
-impl PartialOrd for PathState {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-fn abs_diff(x: usize, y: usize) -> usize {
-    if x < y {
-        y - x
-    } else {
-        x - y
-    }
-}
-
-fn manhattan_distance(width: usize, n1: usize, n2: usize) -> usize {
-    let (x1, y1) = (n1 % width, n1 / width);
-    let (x2, y2) = (n2 % width, n2 / width);
-    abs_diff(x1, x2) + abs_diff(y1, y2)
-}
-
-fn heuristic(width: usize, start: usize, end: usize) -> usize {
-    manhattan_distance(width, start, end)
-}
-
-// this implements A* search
-fn lowest_risk(map: &[Vec<usize>], start: usize, end: usize) -> Option<usize> {
-    let width = map[0].len();
-    let height = map.len();
-    let node_count = width * height;
-
-    let mut heap = BinaryHeap::new();
-    let mut total_risk: Vec<usize> = vec![usize::MAX; node_count];
-
-    total_risk[start] = 0;
-    heap.push(PathState {
-        risk: 0,
-        node: start,
-    });
-
-    while let Some(PathState { risk, node }) = heap.pop() {
-        if node == end {
-            return Some(risk);
-        }
-
-        for v in adjacent_nodes(node, width, height) {
-            let new_risk = total_risk[node] + map[v / width][v % width];
-            if new_risk < total_risk[v] {
-                heap.push(PathState {
-                    risk: new_risk + heuristic(width, v, end),
-                    node: v,
-                });
-                total_risk[v] = new_risk;
-            }
+fn main() {
+    if let Some(family) = env::args().skip_while(|arg| arg != "--generate").nth(1) {
+        let arg = |name: &str| env::args().skip_while(|arg| arg != name).nth(1);
+        let config = MapConfig {
+            family: MapFamily::parse(&family).expect("unknown map family"),
+            size: arg("--size").map_or(100, |size| size.parse().unwrap()),
+            difficulty: arg("--difficulty").map_or(0.5, |difficulty| difficulty.parse().unwrap()),
+            seed: arg("--seed").map_or(0x2545f4914f6cdd1d, |seed| seed.parse().unwrap()),
+        };
+        for row in generate_map(&config) {
+            let row: String = row.iter().map(|risk| risk.to_string()).collect();
+            println!("{}", row);
         }
+        return;
     }
 
-    None
-}
+    let solver = env::args()
+        .skip_while(|arg| arg != "--solver")
+        .nth(1)
+        .map(|s| Solver::parse(&s).expect("unknown solver"))
+        .unwrap_or_default();
 
-fn expand_row(row: &[usize], n: usize, first_tile_row: bool) -> Vec<usize> {
-    let mut full_row = Vec::with_capacity(row.len() * n);
-    if first_tile_row {
-        for &value in row {
-            full_row.push(value);
-        }
-    } else {
-        for &value in row {
-            if value + 1 > 9 {
-                full_row.push(1);
-            } else {
-                full_row.push(value + 1);
-            }
-        }
-    }
-    for tile in 1..n {
-        for col in 0..row.len() {
-            let prev_tile_value = full_row[(tile - 1) * row.len() + col];
-            if prev_tile_value + 1 > 9 {
-                full_row.push(1);
-            } else {
-                full_row.push(prev_tile_value + 1);
-            }
-        }
-    }
-    full_row
-}
+    let input = common::read_input();
+    let map = Day15::parse(&input);
 
-fn expand_map(first_tile: &[Vec<usize>], n: usize) -> Vec<Vec<usize>> {
-    let mut full_map = Vec::with_capacity(first_tile.len() * n);
-    for i in 0..first_tile.len() {
-        full_map.push(expand_row(&first_tile[i], n, true));
+    if common::part_selected(1) {
+        println!("Part 1: {:?}", lowest_total_risk(&map, solver));
     }
-    for tile in 1..n {
-        for i in 0..first_tile.len() {
-            let prev_tile_row = &full_map[(tile - 1) * first_tile.len() + i];
-            let expanded_row = expand_row(prev_tile_row, n, false);
-            full_map.push(expanded_row);
-        }
+    if common::part_selected(2) {
+        println!("Part 2: {:?}", lowest_total_risk(&map.expand(5), solver));
     }
-    full_map
-}
 
-fn main() {
-    let map: Vec<Vec<usize>> = io::stdin()
-        .lock()
-        .lines()
-        .map(|s| {
-            s.unwrap()
-                .chars()
-                .map(|c| c.to_digit(10).unwrap() as usize)
-                .collect::<Vec<usize>>()
+    // e.g. --update 10,20,1 makes the cell in column 10 and row 20 of the full map cheaper
+    let updates: Vec<(usize, usize, usize)> = env::args()
+        .skip(1)
+        .collect::<Vec<_>>()
+        .windows(2)
+        .filter(|args| args[0] == "--update")
+        .map(|args| {
+            let values: Vec<usize> = args[1].split(',').map(|v| v.parse().unwrap()).collect();
+            match values[..] {
+                [x, y, risk] => (x, y, risk),
+                _ => panic!("Invalid update '{}', expected x,y,risk", args[1]),
+            }
         })
         .collect();
-
-    let top_left = 0;
-    let bottom_right = map[0].len() * map.len() - 1;
-    println!("Part 1: {:?}", lowest_risk(&map, top_left, bottom_right));
-
-    let exanded_map = expand_map(&map, 5);
-    let top_left = 0;
-    let bottom_right = exanded_map[0].len() * exanded_map.len() - 1;
-    println!(
-        "Part 2: {:?}",
-        lowest_risk(&exanded_map, top_left, bottom_right)
-    );
+    if !updates.is_empty() {
+        let full_map = map.expand(5);
+        let (width, height) = (full_map.width(), full_map.height());
+        let mut risk_solver = RiskSolver::new(full_map, 0);
+        for (x, y, risk) in updates {
+            assert!(x < width && y < height, "({}, {}) is not on the map", x, y);
+            let expanded = risk_solver.update_risk(y * width + x, risk);
+            println!(
+                "Risk of ({}, {}) set to {}: {:?} ({} nodes re-expanded)",
+                x,
+                y,
+                risk,
+                risk_solver.lowest_risk(width * height - 1),
+                expanded
+            );
+        }
+    }
 }
@@ -0,0 +1,212 @@
+pub trait RiskLookup {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    // risk of the cell at (x, y), which must be inside the map
+    fn risk(&self, x: usize, y: usize) -> usize;
+
+    fn node_risk(&self, node: usize) -> usize {
+        self.risk(node % self.width(), node / self.width())
+    }
+}
+
+impl RiskLookup for [Vec<usize>] {
+    fn width(&self) -> usize {
+        self[0].len()
+    }
+
+    fn height(&self) -> usize {
+        self.len()
+    }
+
+    fn risk(&self, x: usize, y: usize) -> usize {
+        self[y][x]
+    }
+}
+
+impl RiskLookup for Vec<Vec<usize>> {
+    fn width(&self) -> usize {
+        self[..].width()
+    }
+
+    fn height(&self) -> usize {
+        self[..].height()
+    }
+
+    fn risk(&self, x: usize, y: usize) -> usize {
+        self[y][x]
+    }
+}
+
+// Flat row-major storage with a frame of one cell around the map, so that every cell of the
+// map has four neighbors in memory. Frame cells have risk 0, map cells are in 1..=9.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiskMap {
+    cells: Vec<u8>,
+    width: usize,
+    height: usize,
+    stride: usize,
+}
+
+impl RiskMap {
+    pub fn from_rows(rows: &[Vec<usize>]) -> RiskMap {
+        let mut map = RiskMap::framed(rows.width(), rows.height());
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &risk) in row.iter().enumerate() {
+                map.set(y * map.width + x, risk);
+            }
+        }
+        map
+    }
+
+    fn framed(width: usize, height: usize) -> RiskMap {
+        let stride = width + 2;
+        RiskMap {
+            cells: vec![0; stride * (height + 2)],
+            width,
+            height,
+            stride,
+        }
+    }
+
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    // All cells including the frame.
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+
+    // Index into `cells()` of the given node (y * width + x).
+    pub fn cell_index(&self, node: usize) -> usize {
+        let (x, y) = (node % self.width, node / self.width);
+        (y + 1) * self.stride + x + 1
+    }
+
+    pub fn set(&mut self, node: usize, risk: usize) {
+        assert!((1..=9).contains(&risk), "Risk {} out of range", risk);
+        let index = self.cell_index(node);
+        self.cells[index] = risk as u8;
+    }
+
+    // The full map made of n x n tiles, each one more risky than the one above or left of it.
+    pub fn expand(&self, n: usize) -> RiskMap {
+        let mut full_map = RiskMap::framed(self.width * n, self.height * n);
+        for y in 0..full_map.height {
+            for x in 0..full_map.width {
+                let tile = x / self.width + y / self.height;
+                let risk = self.risk(x % self.width, y % self.height);
+                full_map.set(y * full_map.width + x, (risk - 1 + tile) % 9 + 1);
+            }
+        }
+        full_map
+    }
+}
+
+impl RiskLookup for RiskMap {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn risk(&self, x: usize, y: usize) -> usize {
+        self.cells[(y + 1) * self.stride + x + 1] as usize
+    }
+}
+
+// Dijkstra with a bucket queue (Dial's algorithm). Entering a cell costs at most 9, hence all
+// queued costs are within 9 of the current one and 10 buckets indexed by cost modulo 10
+// suffice.
+pub fn bucket_queue_search(map: &RiskMap, start: usize, end: usize) -> Option<usize> {
+    let cells = map.cells();
+    let stride = map.stride();
+    let start = map.cell_index(start);
+    let end = map.cell_index(end);
+
+    let mut cost = vec![u32::MAX; cells.len()];
+    let mut buckets: [Vec<usize>; 10] = Default::default();
+    cost[start] = 0;
+    buckets[0].push(start);
+
+    let mut queued = 1;
+    let mut current = 0;
+    while queued > 0 {
+        while let Some(cell) = buckets[current as usize % 10].pop() {
+            queued -= 1;
+            if cost[cell] != current {
+                // improved after it was queued
+                continue;
+            }
+            if cell == end {
+                return Some(current as usize);
+            }
+
+            // Relax all four neighbors as one batch. Thanks to the frame they all exist, frame
+            // cells are masked out by their risk of 0 instead of branching on the border.
+            let neighbors = [cell - 1, cell + 1, cell - stride, cell + stride];
+            let risks = neighbors.map(|v| cells[v] as u32);
+            let candidates = risks.map(|risk| current + risk);
+            let improved =
+                [0, 1, 2, 3].map(|i| (risks[i] != 0) & (candidates[i] < cost[neighbors[i]]));
+            for i in 0..4 {
+                if improved[i] {
+                    cost[neighbors[i]] = candidates[i];
+                    buckets[candidates[i] as usize % 10].push(neighbors[i]);
+                    queued += 1;
+                }
+            }
+        }
+        current += 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let map = RiskMap::from_rows(&[vec![1, 2, 3], vec![4, 5, 6]]);
+        assert_eq!(map.stride(), 5);
+        assert_eq!(
+            map.cells(),
+            &[0, 0, 0, 0, 0, 0, 1, 2, 3, 0, 0, 4, 5, 6, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(map.node_risk(4), 5);
+        assert_eq!(map.cells()[map.cell_index(4)], 5);
+    }
+
+    #[test]
+    fn test_expand() {
+        let map = RiskMap::from_rows(&[vec![8]]).expand(5);
+        let rows: Vec<Vec<usize>> = (0..5)
+            .map(|y| (0..5).map(|x| map.risk(x, y)).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec![8, 9, 1, 2, 3],
+                vec![9, 1, 2, 3, 4],
+                vec![1, 2, 3, 4, 5],
+                vec![2, 3, 4, 5, 6],
+                vec![3, 4, 5, 6, 7],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bucket_queue_search() {
+        let map = RiskMap::from_rows(&[vec![1, 9, 1], vec![1, 9, 1], vec![1, 1, 1]]);
+        assert_eq!(bucket_queue_search(&map, 0, 2), Some(6));
+        assert_eq!(bucket_queue_search(&map, 2, 0), Some(6));
+        assert_eq!(bucket_queue_search(&map, 4, 4), Some(0));
+        // single column, no horizontal neighbors at all
+        let map = RiskMap::from_rows(&[vec![3], vec![4], vec![5]]);
+        assert_eq!(bucket_queue_search(&map, 0, 2), Some(9));
+    }
+}
@@ -0,0 +1,1035 @@
+use common::{Answer, Solution};
+use limits::{ResourceLimitExceeded, ResourceLimits};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+use thiserror::Error;
+
+pub mod limits;
+pub mod repl;
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Packet;
+
+    fn parse(input: &str) -> Self::Input {
+        decode_transmission(input.trim()).unwrap()
+    }
+
+    fn part1(packet: &Self::Input) -> Answer {
+        sum_of_packet_version(packet).into()
+    }
+
+    fn part2(packet: &Self::Input) -> Answer {
+        packet.eval().into()
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Packet {
+    pub header: Header,
+    pub payload: Payload,
+    // where the packet was found in the transmission, None for constructed packets
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub span: Option<BitSpan>,
+}
+
+// The span is not part of the packet's identity, re-encoding may move packets around.
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.header == other.header && self.payload == other.payload
+    }
+}
+
+impl Packet {
+    pub fn literal(value: usize) -> Packet {
+        Packet {
+            header: Header {
+                version: 0,
+                type_id: 4,
+            },
+            payload: Payload::Literal(value),
+            span: None,
+        }
+    }
+
+    pub fn operator(operator: Operator) -> Packet {
+        Packet {
+            header: Header {
+                version: 0,
+                type_id: operator.type_id(),
+            },
+            payload: Payload::Operator(operator),
+            span: None,
+        }
+    }
+
+    // The raw bits of this packet including all of its sub-packets.
+    pub fn raw_bits<'a>(&self, transmission: BitSlice<'a>) -> Option<BitSlice<'a>> {
+        let span = self.span?;
+        transmission.get(span.offset..span.offset + span.len)
+    }
+
+    pub fn eval(&self) -> usize {
+        self.eval_bounded(&ResourceLimits::unlimited()).unwrap()
+    }
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.payload {
+            Payload::Literal(value) => write!(f, "{}", value),
+            Payload::Operator(op) => write!(f, "{}", op),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Operator {
+    Sum(Vec<Packet>),
+    Product(Vec<Packet>),
+    Minimum(Vec<Packet>),
+    Maximum(Vec<Packet>),
+    GreaterThan {
+        left: Box<Packet>,
+        right: Box<Packet>,
+    },
+    LessThan {
+        left: Box<Packet>,
+        right: Box<Packet>,
+    },
+    EqualTo {
+        left: Box<Packet>,
+        right: Box<Packet>,
+    },
+}
+
+impl Operator {
+    fn type_id(&self) -> usize {
+        match self {
+            Operator::Sum(..) => 0,
+            Operator::Product(..) => 1,
+            Operator::Minimum(..) => 2,
+            Operator::Maximum(..) => 3,
+            Operator::GreaterThan { .. } => 5,
+            Operator::LessThan { .. } => 6,
+            Operator::EqualTo { .. } => 7,
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let write_infix = |f: &mut fmt::Formatter<'_>, op: &str, operands: &[Packet]| {
+            write!(f, "(")?;
+            for (i, operand) in operands.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {} ", op)?;
+                }
+                write!(f, "{}", operand)?;
+            }
+            write!(f, ")")
+        };
+        let write_call = |f: &mut fmt::Formatter<'_>, name: &str, operands: &[Packet]| {
+            write!(f, "{}(", name)?;
+            for (i, operand) in operands.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", operand)?;
+            }
+            write!(f, ")")
+        };
+
+        match self {
+            Operator::Sum(operands) => write_infix(f, "+", operands),
+            Operator::Product(operands) => write_infix(f, "*", operands),
+            Operator::Minimum(operands) => write_call(f, "min", operands),
+            Operator::Maximum(operands) => write_call(f, "max", operands),
+            Operator::GreaterThan { left, right } => write!(f, "({} > {})", left, right),
+            Operator::LessThan { left, right } => write!(f, "({} < {})", left, right),
+            Operator::EqualTo { left, right } => write!(f, "({} == {})", left, right),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Payload {
+    Literal(usize),
+    Operator(Operator),
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Header {
+    pub version: usize,
+    pub type_id: usize,
+}
+
+pub type BitSlice<'a> = &'a [bool];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitSpan {
+    pub offset: usize,
+    pub len: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PacketTypeStats {
+    pub packets: usize,
+    // bits of the packets themselves, excluding their sub-packets
+    pub bits: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TransmissionStats {
+    pub packets: usize,
+    pub packet_bits: usize,
+    pub per_type: BTreeMap<usize, PacketTypeStats>,
+    pub padding_bits: usize,
+    pub non_zero_padding: bool,
+}
+
+impl TransmissionStats {
+    fn record_packet(&mut self, type_id: usize, bits: usize) {
+        self.packets += 1;
+        self.packet_bits += bits;
+        let type_stats = self.per_type.entry(type_id).or_default();
+        type_stats.packets += 1;
+        type_stats.bits += bits;
+    }
+}
+
+pub fn packet_type_name(type_id: usize) -> &'static str {
+    match type_id {
+        0 => "sum",
+        1 => "product",
+        2 => "minimum",
+        3 => "maximum",
+        4 => "literal",
+        5 => "greater than",
+        6 => "less than",
+        7 => "equal to",
+        _ => "unknown",
+    }
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum MalformedReason {
+    #[error("invalid hex digit '{0}'")]
+    InvalidHexDigit(char),
+    #[error("the transmission ends within a packet")]
+    UnexpectedEnd,
+    #[error("expected {expected} operands but got {actual}")]
+    OperandCount { expected: usize, actual: usize },
+    #[error("operator without operands")]
+    NoOperands,
+    #[error("sub-packets of {length} bits exceed the remaining {remaining} bits")]
+    LengthOutOfRange { length: usize, remaining: usize },
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    // `offset` is the bit at which the malformed packet starts
+    #[error("Malformed transmission at bit {offset}: {reason}")]
+    Malformed {
+        offset: usize,
+        reason: MalformedReason,
+    },
+    #[error(transparent)]
+    LimitExceeded(#[from] ResourceLimitExceeded),
+}
+
+// State while decoding a transmission. Once an error has occurred, parsing stops as if the
+// transmission had ended.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    stats: TransmissionStats,
+    limits: ResourceLimits,
+    depth: usize,
+    error: Option<DecodeError>,
+}
+
+impl Decoder {
+    fn new(limits: ResourceLimits) -> Decoder {
+        Decoder {
+            limits,
+            ..Decoder::default()
+        }
+    }
+
+    // To be called once the header of a packet has been read.
+    fn check_limits(&mut self) -> Option<()> {
+        // packets are recorded once they are complete, hence the open ones have to be added
+        let result = self
+            .limits
+            .check_packets(self.stats.packets + self.depth)
+            .and_then(|_| self.limits.check_depth(self.depth));
+        match result {
+            Ok(()) => Some(()),
+            Err(exceeded) => self.fail(exceeded.into()),
+        }
+    }
+
+    // Records the error and stops parsing.
+    fn fail<T>(&mut self, error: DecodeError) -> Option<T> {
+        self.error.get_or_insert(error);
+        None
+    }
+
+    fn malformed<T>(&mut self, offset: usize, reason: MalformedReason) -> Option<T> {
+        self.fail(DecodeError::Malformed { offset, reason })
+    }
+}
+
+pub fn read_bits(bits: BitSlice, n: usize) -> Option<(BitSlice, usize)> {
+    if bits.len() < n {
+        return None;
+    }
+
+    let value = bits[..n]
+        .iter()
+        .enumerate()
+        .fold(0, |value, (i, &bit)| value | (bit as usize) << (n - i - 1));
+    Some((&bits[n..], value))
+}
+
+pub fn parse_header(bits: BitSlice) -> Option<(BitSlice, Header)> {
+    let (bits, version) = read_bits(bits, 3)?;
+    let (bits, type_id) = read_bits(bits, 3)?;
+    Some((bits, Header { version, type_id }))
+}
+
+pub fn parse_literal(bits: BitSlice) -> Option<(BitSlice, usize)> {
+    let mut next_bits = bits;
+    let mut value = 0;
+    loop {
+        let (bits, prefix) = read_bits(next_bits, 1)?;
+        let (bits, group) = read_bits(bits, 4)?;
+
+        value = value << 4 | group;
+        next_bits = bits;
+
+        if prefix == 0 {
+            break Some((next_bits, value));
+        }
+    }
+}
+
+// `bits` follow the header of the operator packet, which starts at `offset` within the
+// transmission.
+pub fn parse_operands<'a>(
+    bits: BitSlice<'a>,
+    offset: usize,
+    decoder: &mut Decoder,
+) -> Option<(BitSlice<'a>, Vec<Packet>)> {
+    let (bits, length_type_id) = read_bits(bits, 1)?;
+    let operands = match length_type_id {
+        0 => {
+            let (bits, byte_length_of_packets) = read_bits(bits, 15)?;
+            if byte_length_of_packets > bits.len() {
+                let reason = MalformedReason::LengthOutOfRange {
+                    length: byte_length_of_packets,
+                    remaining: bits.len(),
+                };
+                return decoder.malformed(offset, reason);
+            }
+            let (sub_packets, bits) = bits.split_at(byte_length_of_packets);
+            let packets = read_packets_until_end(sub_packets, offset + 22, decoder);
+            Some((bits, packets))
+        }
+        1 => {
+            let (bits, number_of_packets) = read_bits(bits, 11)?;
+            read_packets_exactly(bits, offset + 18, number_of_packets, decoder)
+        }
+        _ => unreachable!(),
+    };
+    // the operands are incomplete
+    if decoder.error.is_some() {
+        return None;
+    }
+    operands
+}
+
+pub fn read_packets_until_end(bits: BitSlice, offset: usize, decoder: &mut Decoder) -> Vec<Packet> {
+    let mut next_bits = bits;
+    let mut packets = Vec::new();
+    while !next_bits.is_empty() {
+        match parse_packet(next_bits, offset + (bits.len() - next_bits.len()), decoder) {
+            Some((bits, packet)) => {
+                packets.push(packet);
+                next_bits = bits;
+            }
+            None => break,
+        }
+    }
+    packets
+}
+
+pub fn read_packets_exactly<'a>(
+    bits: BitSlice<'a>,
+    offset: usize,
+    n: usize,
+    decoder: &mut Decoder,
+) -> Option<(BitSlice<'a>, Vec<Packet>)> {
+    let mut next_bits = bits;
+    // the count comes from the transmission, don't trust it for the allocation
+    let mut packets = Vec::with_capacity(n.min(decoder.limits.max_packets));
+    for _ in 0..n {
+        let (bits, packet) =
+            parse_packet(next_bits, offset + (bits.len() - next_bits.len()), decoder)?;
+        packets.push(packet);
+        next_bits = bits;
+    }
+    Some((next_bits, packets))
+}
+
+pub fn parse_packet<'a>(
+    bits: BitSlice<'a>,
+    offset: usize,
+    decoder: &mut Decoder,
+) -> Option<(BitSlice<'a>, Packet)> {
+    if decoder.error.is_some() {
+        return None;
+    }
+    decoder.depth += 1;
+    let result = parse_packet_contents(bits, offset, decoder);
+    decoder.depth -= 1;
+    // without a recorded error, the bits ran out within this packet
+    if result.is_none() && decoder.error.is_none() {
+        return decoder.malformed(offset, MalformedReason::UnexpectedEnd);
+    }
+    result
+}
+
+pub fn parse_packet_contents<'a>(
+    bits: BitSlice<'a>,
+    offset: usize,
+    decoder: &mut Decoder,
+) -> Option<(BitSlice<'a>, Packet)> {
+    let packet_start = bits;
+    let sub_packet_bits_before = decoder.stats.packet_bits;
+
+    let (bits, header) = parse_header(bits)?;
+    decoder.check_limits()?;
+    let (bits, payload) = match header {
+        Header { type_id: 4, .. } => {
+            let (bits, value) = parse_literal(bits)?;
+            (bits, Payload::Literal(value))
+        }
+        Header {
+            type_id: op @ 0..=3,
+            ..
+        } => {
+            let (bits, operands) = parse_operands(bits, offset, decoder)?;
+            if operands.is_empty() {
+                return decoder.malformed(offset, MalformedReason::NoOperands);
+            }
+            let operator = match op {
+                0 => Operator::Sum(operands),
+                1 => Operator::Product(operands),
+                2 => Operator::Minimum(operands),
+                3 => Operator::Maximum(operands),
+                _ => unreachable!(),
+            };
+            (bits, Payload::Operator(operator))
+        }
+        Header {
+            type_id: op @ 5..=7,
+            ..
+        } => {
+            let (bits, mut operands) = parse_operands(bits, offset, decoder)?;
+            if operands.len() != 2 {
+                let reason = MalformedReason::OperandCount {
+                    expected: 2,
+                    actual: operands.len(),
+                };
+                return decoder.malformed(offset, reason);
+            }
+            let right = Box::new(operands.pop()?);
+            let left = Box::new(operands.pop()?);
+            let operator = match op {
+                5 => Operator::GreaterThan { left, right },
+                6 => Operator::LessThan { left, right },
+                7 => Operator::EqualTo { left, right },
+                _ => unreachable!(),
+            };
+            (bits, Payload::Operator(operator))
+        }
+        // the type ID has 3 bits, all of them are handled above
+        _ => unreachable!(),
+    };
+
+    let sub_packet_bits = decoder.stats.packet_bits - sub_packet_bits_before;
+    let packet_bits = packet_start.len() - bits.len();
+    decoder
+        .stats
+        .record_packet(header.type_id, packet_bits - sub_packet_bits);
+
+    let span = BitSpan {
+        offset,
+        len: packet_bits,
+    };
+    Some((
+        bits,
+        Packet {
+            header,
+            payload,
+            span: Some(span),
+        },
+    ))
+}
+
+pub fn hex_string_to_bits(s: &str) -> Vec<bool> {
+    s.chars()
+        .flat_map(|c| match c {
+            '0' => vec![false, false, false, false],
+            '1' => vec![false, false, false, true],
+            '2' => vec![false, false, true, false],
+            '3' => vec![false, false, true, true],
+            '4' => vec![false, true, false, false],
+            '5' => vec![false, true, false, true],
+            '6' => vec![false, true, true, false],
+            '7' => vec![false, true, true, true],
+            '8' => vec![true, false, false, false],
+            '9' => vec![true, false, false, true],
+            'A' => vec![true, false, true, false],
+            'B' => vec![true, false, true, true],
+            'C' => vec![true, true, false, false],
+            'D' => vec![true, true, false, true],
+            'E' => vec![true, true, true, false],
+            'F' => vec![true, true, true, true],
+            _ => panic!("Unexpected hex char"),
+        })
+        .collect()
+}
+
+pub fn write_bits(bits: &mut Vec<bool>, value: usize, n: usize) {
+    bits.extend((0..n).rev().map(|i| (value >> i) & 1 == 1));
+}
+
+pub fn encode_literal(bits: &mut Vec<bool>, value: usize) {
+    let groups = ((usize::BITS - value.leading_zeros()) as usize)
+        .div_ceil(4)
+        .max(1);
+    for group in (0..groups).rev() {
+        write_bits(bits, (group > 0) as usize, 1);
+        write_bits(bits, (value >> (group * 4)) & 0xF, 4);
+    }
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeError {
+    #[error("{operands} operands take {length} bits, more than a 15-bit length can hold")]
+    LengthOverflow { operands: usize, length: usize },
+}
+
+pub fn encode_operands(bits: &mut Vec<bool>, operands: &[&Packet]) -> Result<(), EncodeError> {
+    // prefer the packet count, fall back to the bit length for more than 2047 operands
+    if operands.len() < 1 << 11 {
+        write_bits(bits, 1, 1);
+        write_bits(bits, operands.len(), 11);
+        for operand in operands {
+            encode_packet(operand, bits)?;
+        }
+    } else {
+        let mut sub_packets = Vec::new();
+        for operand in operands {
+            encode_packet(operand, &mut sub_packets)?;
+        }
+        if sub_packets.len() >= 1 << 15 {
+            return Err(EncodeError::LengthOverflow {
+                operands: operands.len(),
+                length: sub_packets.len(),
+            });
+        }
+        write_bits(bits, 0, 1);
+        write_bits(bits, sub_packets.len(), 15);
+        bits.extend(sub_packets);
+    }
+    Ok(())
+}
+
+pub fn encode_packet(packet: &Packet, bits: &mut Vec<bool>) -> Result<(), EncodeError> {
+    write_bits(bits, packet.header.version, 3);
+    write_bits(bits, packet.header.type_id, 3);
+    match &packet.payload {
+        Payload::Literal(value) => {
+            encode_literal(bits, *value);
+            Ok(())
+        }
+        Payload::Operator(
+            Operator::Sum(ops)
+            | Operator::Product(ops)
+            | Operator::Minimum(ops)
+            | Operator::Maximum(ops),
+        ) => encode_operands(bits, &ops.iter().collect::<Vec<_>>()),
+        Payload::Operator(
+            Operator::GreaterThan { left, right }
+            | Operator::LessThan { left, right }
+            | Operator::EqualTo { left, right },
+        ) => encode_operands(bits, &[left, right]),
+    }
+}
+
+pub fn bits_to_hex_string(bits: &[bool]) -> String {
+    bits.chunks(4)
+        .map(|chunk| {
+            let nibble = (0..4).fold(0, |nibble, i| {
+                nibble << 1 | chunk.get(i).copied().unwrap_or(false) as u32
+            });
+            char::from_digit(nibble, 16).unwrap().to_ascii_uppercase()
+        })
+        .collect()
+}
+
+pub fn encode_transmission(packet: &Packet) -> Result<String, EncodeError> {
+    let mut bits = Vec::new();
+    encode_packet(packet, &mut bits)?;
+    Ok(bits_to_hex_string(&bits))
+}
+
+pub fn decode_transmission(transmission: &str) -> Option<Packet> {
+    decode_transmission_with_stats(transmission).map(|(packet, _)| packet)
+}
+
+pub fn decode_transmission_with_stats(transmission: &str) -> Option<(Packet, TransmissionStats)> {
+    decode_transmission_with_limits(transmission, &ResourceLimits::unlimited()).ok()
+}
+
+pub fn decode_transmission_with_limits(
+    transmission: &str,
+    limits: &ResourceLimits,
+) -> Result<(Packet, TransmissionStats), DecodeError> {
+    if let Some((i, c)) = transmission
+        .chars()
+        .enumerate()
+        .find(|&(_, c)| !matches!(c, '0'..='9' | 'A'..='F'))
+    {
+        return Err(DecodeError::Malformed {
+            offset: i * 4,
+            reason: MalformedReason::InvalidHexDigit(c),
+        });
+    }
+    let bits = hex_string_to_bits(transmission);
+    let mut decoder = Decoder::new(*limits);
+    let (padding, packet) = match parse_packet(&bits, 0, &mut decoder) {
+        Some(decoded) => decoded,
+        // parsing only stops after recording an error
+        None => return Err(decoder.error.unwrap()),
+    };
+
+    let mut stats = decoder.stats;
+    stats.padding_bits = padding.len();
+    stats.non_zero_padding = padding.iter().any(|&bit| bit);
+    Ok((packet, stats))
+}
+
+pub fn sum_of_packet_version(packet: &Packet) -> usize {
+    let sub_packet_version_sum = match &packet.payload {
+        Payload::Literal(..) => 0,
+        Payload::Operator(
+            Operator::Sum(ops)
+            | Operator::Product(ops)
+            | Operator::Minimum(ops)
+            | Operator::Maximum(ops),
+        ) => ops.iter().map(sum_of_packet_version).sum(),
+        Payload::Operator(
+            Operator::GreaterThan { left, right }
+            | Operator::LessThan { left, right }
+            | Operator::EqualTo { left, right },
+        ) => sum_of_packet_version(left) + sum_of_packet_version(right),
+    };
+    packet.header.version + sub_packet_version_sum
+}
+
+pub fn bits_to_string(bits: BitSlice) -> String {
+    bits.iter()
+        .map(|&bit| if bit { '1' } else { '0' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const INSTANCE: &str = include_str!("../instance.txt");
+
+    #[test]
+    fn test_decode_literal_packet() {
+        assert_eq!(
+            decode_transmission("D2FE28"),
+            Some(Packet {
+                header: Header {
+                    version: 6,
+                    type_id: 4,
+                },
+                payload: Payload::Literal(2021),
+                span: None,
+            })
+        )
+    }
+
+    #[test]
+    fn test_decode_operator_packet() {
+        assert_eq!(
+            decode_transmission("38006F45291200"),
+            Some(Packet {
+                header: Header {
+                    version: 1,
+                    type_id: 6,
+                },
+                payload: Payload::Operator(Operator::LessThan {
+                    left: Box::new(Packet {
+                        header: Header {
+                            version: 6,
+                            type_id: 4,
+                        },
+                        payload: Payload::Literal(10),
+                        span: None,
+                    }),
+                    right: Box::new(Packet {
+                        header: Header {
+                            version: 2,
+                            type_id: 4,
+                        },
+                        payload: Payload::Literal(20),
+                        span: None,
+                    })
+                }),
+                span: None,
+            })
+        )
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dump_json() {
+        let packet = decode_transmission("38006F45291200").unwrap();
+        assert_eq!(
+            serde_json::to_value(&packet).unwrap(),
+            serde_json::json!({
+                "header": { "version": 1, "type_id": 6 },
+                "payload": {
+                    "operator": {
+                        "less_than": {
+                            "left": {
+                                "header": { "version": 6, "type_id": 4 },
+                                "payload": { "literal": 10 }
+                            },
+                            "right": {
+                                "header": { "version": 2, "type_id": 4 },
+                                "payload": { "literal": 20 }
+                            }
+                        }
+                    }
+                }
+            })
+        );
+
+        let json = serde_json::to_string(&packet).unwrap();
+        assert_eq!(serde_json::from_str::<Packet>(&json).unwrap(), packet);
+    }
+
+    #[rstest]
+    #[case("C200B40A82", "(1 + 2)")]
+    #[case("04005AC33890", "(6 * 9)")]
+    #[case("880086C3E88112", "min(7, 8, 9)")]
+    #[case("D8005AC2A8F0", "(5 < 15)")]
+    #[case("9C0141080250320F1802104A08", "((1 + 3) == (2 * 2))")]
+    fn test_display_expression(#[case] transmission: &str, #[case] expected_expr: &str) {
+        let packet = decode_transmission(transmission).unwrap();
+        assert_eq!(packet.to_string(), expected_expr);
+    }
+
+    #[rstest]
+    #[case("8A004A801A8002F478", 16)]
+    #[case("620080001611562C8802118E34", 12)]
+    #[case("C0015000016115A2E0802F182340", 23)]
+    #[case("A0016C880162017C3686B18A3D4780", 31)]
+    fn test_sum_of_packet_version(#[case] transmission: &str, #[case] expected_sum: usize) {
+        let packet = decode_transmission(transmission).unwrap();
+        dbg!(&packet);
+        assert_eq!(sum_of_packet_version(&packet), expected_sum);
+    }
+
+    #[rstest]
+    #[case("C200B40A82", 3)]
+    #[case("04005AC33890", 54)]
+    #[case("880086C3E88112", 7)]
+    #[case("CE00C43D881120", 9)]
+    #[case("D8005AC2A8F0", 1)]
+    #[case("F600BC2D8F", 0)]
+    #[case("9C005AC2F8F0", 0)]
+    #[case("9C0141080250320F1802104A08", 1)]
+    fn test_eval(#[case] transmission: &str, #[case] expected_result: usize) {
+        let packet = decode_transmission(transmission).unwrap();
+        dbg!(&packet);
+        assert_eq!(packet.eval(), expected_result);
+    }
+
+    #[test]
+    fn test_encode_literal() {
+        let mut packet = Packet::literal(2021);
+        packet.header.version = 6;
+        assert_eq!(encode_transmission(&packet).unwrap(), "D2FE28");
+        assert_eq!(encode_transmission(&Packet::literal(0)).unwrap(), "100");
+    }
+
+    #[test]
+    fn test_encode_operator() {
+        let packet = Packet::operator(Operator::LessThan {
+            left: Box::new(Packet::literal(10)),
+            right: Box::new(Packet::literal(20)),
+        });
+        // "38006F45291200" with version 0 and the packet count as length type
+        let transmission = encode_transmission(&packet).unwrap();
+        assert_eq!(transmission, "1A0084509120");
+        assert_eq!(decode_transmission(&transmission).unwrap(), packet);
+    }
+
+    #[test]
+    fn test_encode_many_operands() {
+        let packet = Packet::operator(Operator::Sum(
+            (0..2100).map(|i| Packet::literal(i % 16)).collect(),
+        ));
+        let decoded = decode_transmission(&encode_transmission(&packet).unwrap()).unwrap();
+        assert_eq!(decoded, packet);
+        assert_eq!(decoded.eval(), (0..2100).map(|i| i % 16).sum::<usize>());
+
+        // 2048 literals of 46 bits each don't fit into a 15-bit length
+        let packet = Packet::operator(Operator::Sum(
+            (0..2048).map(|_| Packet::literal(1 << 30)).collect(),
+        ));
+        assert_eq!(
+            encode_transmission(&packet),
+            Err(EncodeError::LengthOverflow {
+                operands: 2048,
+                length: 2048 * 46
+            })
+        );
+    }
+
+    #[rstest]
+    #[case("38006F45291200")]
+    #[case("EE00D40C823060")]
+    #[case("9C0141080250320F1802104A08")]
+    #[case("A0016C880162017C3686B18A3D4780")]
+    #[case(INSTANCE.trim())]
+    fn test_encode_round_trip(#[case] transmission: &str) {
+        let packet = decode_transmission(transmission).unwrap();
+        let encoded = encode_transmission(&packet).unwrap();
+        assert_eq!(decode_transmission(&encoded).unwrap(), packet);
+    }
+
+    #[test]
+    fn test_stats_operator_packet() {
+        let (_, stats) = decode_transmission_with_stats("38006F45291200").unwrap();
+        assert_eq!(stats.packets, 3);
+        assert_eq!(stats.packet_bits, 49);
+        assert_eq!(
+            stats.per_type,
+            BTreeMap::from([
+                (
+                    4,
+                    PacketTypeStats {
+                        packets: 2,
+                        bits: 27
+                    }
+                ),
+                (
+                    6,
+                    PacketTypeStats {
+                        packets: 1,
+                        bits: 22
+                    }
+                ),
+            ])
+        );
+        assert_eq!(stats.padding_bits, 7);
+        assert!(!stats.non_zero_padding);
+    }
+
+    #[test]
+    fn test_stats_non_zero_padding() {
+        let (packet, stats) = decode_transmission_with_stats("D2FE29").unwrap();
+        assert_eq!(packet.eval(), 2021);
+        assert_eq!(stats.packets, 1);
+        assert_eq!(stats.padding_bits, 3);
+        assert!(stats.non_zero_padding);
+    }
+
+    #[rstest]
+    #[case("8A004A801A8002F478", 4)]
+    #[case("620080001611562C8802118E34", 7)]
+    #[case("C0015000016115A2E0802F182340", 7)]
+    #[case("A0016C880162017C3686B18A3D4780", 8)]
+    fn test_stats_cover_whole_transmission(#[case] transmission: &str, #[case] packets: usize) {
+        let (_, stats) = decode_transmission_with_stats(transmission).unwrap();
+        assert_eq!(stats.packets, packets);
+        assert_eq!(
+            stats.per_type.values().map(|s| s.bits).sum::<usize>(),
+            stats.packet_bits
+        );
+        assert_eq!(
+            stats.packet_bits + stats.padding_bits,
+            transmission.len() * 4
+        );
+    }
+
+    #[test]
+    fn test_spans() {
+        let packet = decode_transmission("38006F45291200").unwrap();
+        assert_eq!(packet.span, Some(BitSpan { offset: 0, len: 49 }));
+        let Payload::Operator(Operator::LessThan { left, right }) = &packet.payload else {
+            panic!("Expected less than operator");
+        };
+        assert_eq!(
+            left.span,
+            Some(BitSpan {
+                offset: 22,
+                len: 11
+            })
+        );
+        assert_eq!(
+            right.span,
+            Some(BitSpan {
+                offset: 33,
+                len: 16
+            })
+        );
+        assert_eq!(Packet::literal(1).span, None);
+    }
+
+    #[rstest]
+    #[case("8A004A801A8002F478")]
+    #[case("620080001611562C8802118E34")]
+    #[case("C0015000016115A2E0802F182340")]
+    #[case(INSTANCE.trim())]
+    fn test_raw_bits_of_subtrees(#[case] transmission: &str) {
+        // every subtree decodes on its own to the same packet
+        fn check(packet: &Packet, transmission: BitSlice) {
+            let raw_bits = packet.raw_bits(transmission).unwrap();
+            let mut decoder = Decoder::default();
+            let (rest, decoded) = parse_packet(raw_bits, 0, &mut decoder).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(&decoded, packet);
+            match &packet.payload {
+                Payload::Literal(..) => {}
+                Payload::Operator(
+                    Operator::Sum(ops)
+                    | Operator::Product(ops)
+                    | Operator::Minimum(ops)
+                    | Operator::Maximum(ops),
+                ) => ops.iter().for_each(|op| check(op, transmission)),
+                Payload::Operator(
+                    Operator::GreaterThan { left, right }
+                    | Operator::LessThan { left, right }
+                    | Operator::EqualTo { left, right },
+                ) => {
+                    check(left, transmission);
+                    check(right, transmission);
+                }
+            }
+        }
+
+        let packet = decode_transmission(transmission).unwrap();
+        check(&packet, &hex_string_to_bits(transmission));
+    }
+
+    #[test]
+    fn test_error_location() {
+        // sum of a literal and a greater than operator with 3 operands
+        let mut bits = Vec::new();
+        write_bits(&mut bits, 0, 6);
+        write_bits(&mut bits, 1, 1);
+        write_bits(&mut bits, 2, 11);
+        encode_packet(&Packet::literal(1), &mut bits).unwrap();
+        write_bits(&mut bits, 5, 6);
+        write_bits(&mut bits, 1, 1);
+        write_bits(&mut bits, 3, 11);
+        for value in 0..3 {
+            encode_packet(&Packet::literal(value), &mut bits).unwrap();
+        }
+        let error = decode_transmission_with_limits(
+            &bits_to_hex_string(&bits),
+            &ResourceLimits::unlimited(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            DecodeError::Malformed {
+                offset: 29,
+                reason: MalformedReason::OperandCount {
+                    expected: 2,
+                    actual: 3
+                }
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Malformed transmission at bit 29: expected 2 operands but got 3"
+        );
+        assert!(matches!(
+            decode_transmission_with_limits("D2FE", &ResourceLimits::unlimited()),
+            Err(DecodeError::Malformed {
+                offset: 0,
+                reason: MalformedReason::UnexpectedEnd
+            })
+        ));
+    }
+
+    #[rstest]
+    // maximum of three literals at bits 18, 29 and 40, cut within or right before the third one
+    #[case("EE00D40C823", 40)]
+    #[case("EE00D40C82", 40)]
+    // the maximum itself ends within its 11-bit packet count
+    #[case("EE00", 0)]
+    fn test_unexpected_end_location(#[case] transmission: &str, #[case] offset: usize) {
+        assert_eq!(
+            decode_transmission_with_limits(transmission, &ResourceLimits::unlimited()).err(),
+            Some(DecodeError::Malformed {
+                offset,
+                reason: MalformedReason::UnexpectedEnd
+            })
+        );
+    }
+
+    #[rstest]
+    // less than with sub-packets of 16383 bits, but only 10 bits left
+    #[case("38FFFF00", 0, MalformedReason::LengthOutOfRange { length: 16383, remaining: 10 })]
+    // minimum without operands
+    #[case("08000000", 0, MalformedReason::NoOperands)]
+    #[case("D2FX28", 12, MalformedReason::InvalidHexDigit('X'))]
+    fn test_malformed_transmission(
+        #[case] transmission: &str,
+        #[case] offset: usize,
+        #[case] reason: MalformedReason,
+    ) {
+        assert_eq!(
+            decode_transmission_with_limits(transmission, &ResourceLimits::untrusted()).err(),
+            Some(DecodeError::Malformed { offset, reason })
+        );
+    }
+
+    #[test]
+    fn test_solution() {
+        let (part1, _) = common::solve::<Day16>("8A004A801A8002F478\n");
+        assert_eq!(part1.to_string(), "16");
+        let (_, part2) = common::solve::<Day16>("9C0141080250320F1802104A08\n");
+        assert_eq!(part2.to_string(), "1");
+    }
+}
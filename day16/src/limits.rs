@@ -0,0 +1,216 @@
+use super::{Operator, Packet, Payload};
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceLimitExceeded {
+    #[error("Transmission contains more than {0} packets")]
+    Packets(usize),
+    #[error("Packets are nested deeper than {0} levels")]
+    Depth(usize),
+    #[error("Evaluation takes more than {0} operations")]
+    Operations(usize),
+    #[error("Evaluation overflows")]
+    Overflow,
+}
+
+// Bounds for decoding and evaluating untrusted transmissions. The root packet is at depth 1 and
+// evaluating a packet (without its operands) is one operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub max_packets: usize,
+    pub max_depth: usize,
+    pub max_operations: usize,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+impl ResourceLimits {
+    pub fn unlimited() -> Self {
+        Self {
+            max_packets: usize::MAX,
+            max_depth: usize::MAX,
+            max_operations: usize::MAX,
+        }
+    }
+
+    // Plenty for the puzzle transmissions, which have a few hundred packets nested about ten
+    // levels deep.
+    pub fn untrusted() -> Self {
+        Self {
+            max_packets: 10_000,
+            max_depth: 64,
+            max_operations: 100_000,
+        }
+    }
+
+    pub fn check_packets(&self, packets: usize) -> Result<(), ResourceLimitExceeded> {
+        if packets > self.max_packets {
+            return Err(ResourceLimitExceeded::Packets(self.max_packets));
+        }
+        Ok(())
+    }
+
+    pub fn check_depth(&self, depth: usize) -> Result<(), ResourceLimitExceeded> {
+        if depth > self.max_depth {
+            return Err(ResourceLimitExceeded::Depth(self.max_depth));
+        }
+        Ok(())
+    }
+
+    pub fn check_operations(&self, operations: usize) -> Result<(), ResourceLimitExceeded> {
+        if operations > self.max_operations {
+            return Err(ResourceLimitExceeded::Operations(self.max_operations));
+        }
+        Ok(())
+    }
+}
+
+impl Packet {
+    // Same as `eval`, but gives up as soon as one of the limits is exceeded.
+    pub fn eval_bounded(&self, limits: &ResourceLimits) -> Result<usize, ResourceLimitExceeded> {
+        let mut operations = 0;
+        self.eval_counted(limits, 1, &mut operations)
+    }
+
+    fn eval_counted(
+        &self,
+        limits: &ResourceLimits,
+        depth: usize,
+        operations: &mut usize,
+    ) -> Result<usize, ResourceLimitExceeded> {
+        use Operator::*;
+
+        limits.check_depth(depth)?;
+        *operations += 1;
+        limits.check_operations(*operations)?;
+
+        let mut eval_all = |operands: &[Packet]| -> Result<Vec<usize>, ResourceLimitExceeded> {
+            operands
+                .iter()
+                .map(|p| p.eval_counted(limits, depth + 1, operations))
+                .collect()
+        };
+
+        let value = match &self.payload {
+            Payload::Literal(value) => *value,
+            Payload::Operator(Sum(operands)) => eval_all(operands)?
+                .into_iter()
+                .try_fold(0, usize::checked_add)
+                .ok_or(ResourceLimitExceeded::Overflow)?,
+            Payload::Operator(Product(operands)) => eval_all(operands)?
+                .into_iter()
+                .try_fold(1, usize::checked_mul)
+                .ok_or(ResourceLimitExceeded::Overflow)?,
+            Payload::Operator(Minimum(operands)) => eval_all(operands)?.into_iter().min().unwrap(),
+            Payload::Operator(Maximum(operands)) => eval_all(operands)?.into_iter().max().unwrap(),
+            Payload::Operator(
+                GreaterThan { left, right } | LessThan { left, right } | EqualTo { left, right },
+            ) => {
+                let left = left.eval_counted(limits, depth + 1, operations)?;
+                let right = right.eval_counted(limits, depth + 1, operations)?;
+                let result = match &self.payload {
+                    Payload::Operator(GreaterThan { .. }) => left > right,
+                    Payload::Operator(LessThan { .. }) => left < right,
+                    _ => left == right,
+                };
+                result as usize
+            }
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decode_transmission, decode_transmission_with_limits, DecodeError, MalformedReason,
+    };
+
+    // (1 + 3) == (2 * 2), 7 packets nested 3 levels deep
+    const EXAMPLE: &str = "9C0141080250320F1802104A08";
+
+    fn limits(max_packets: usize, max_depth: usize, max_operations: usize) -> ResourceLimits {
+        ResourceLimits {
+            max_packets,
+            max_depth,
+            max_operations,
+        }
+    }
+
+    #[test]
+    fn test_decode_within_limits() {
+        let (packet, stats) = decode_transmission_with_limits(EXAMPLE, &limits(7, 3, 0)).unwrap();
+        assert_eq!(stats.packets, 7);
+        assert_eq!(packet.eval_bounded(&limits(0, 3, 7)), Ok(1));
+        assert_eq!(packet.eval_bounded(&ResourceLimits::untrusted()), Ok(1));
+    }
+
+    #[test]
+    fn test_decode_limits_exceeded() {
+        assert_eq!(
+            decode_transmission_with_limits(EXAMPLE, &limits(6, 3, 0)).err(),
+            Some(DecodeError::LimitExceeded(ResourceLimitExceeded::Packets(
+                6
+            )))
+        );
+        assert_eq!(
+            decode_transmission_with_limits(EXAMPLE, &limits(7, 2, 0)).err(),
+            Some(DecodeError::LimitExceeded(ResourceLimitExceeded::Depth(2)))
+        );
+        assert_eq!(
+            decode_transmission_with_limits("D2FE", &ResourceLimits::default()).err(),
+            Some(DecodeError::Malformed {
+                offset: 0,
+                reason: MalformedReason::UnexpectedEnd
+            })
+        );
+    }
+
+    #[test]
+    fn test_eval_limits_exceeded() {
+        let packet = decode_transmission(EXAMPLE).unwrap();
+        assert_eq!(
+            packet.eval_bounded(&limits(0, 3, 6)),
+            Err(ResourceLimitExceeded::Operations(6))
+        );
+        assert_eq!(
+            packet.eval_bounded(&limits(0, 2, 7)),
+            Err(ResourceLimitExceeded::Depth(2))
+        );
+
+        // constructed packets bypass the decoder limits
+        let deep = (0..1000).fold(Packet::literal(1), |packet, _| {
+            Packet::operator(Operator::Sum(vec![packet]))
+        });
+        assert_eq!(
+            deep.eval_bounded(&ResourceLimits::untrusted()),
+            Err(ResourceLimitExceeded::Depth(64))
+        );
+        let wide = Packet::operator(Operator::Sum((0..10).map(Packet::literal).collect()));
+        assert_eq!(wide.eval_bounded(&limits(0, 2, 11)), Ok(45));
+        assert_eq!(
+            wide.eval_bounded(&limits(0, 2, 10)),
+            Err(ResourceLimitExceeded::Operations(10))
+        );
+    }
+
+    #[test]
+    fn test_eval_overflow() {
+        let big = || Packet::literal(1 << 32);
+        let product = Packet::operator(Operator::Product(vec![big(), big(), big()]));
+        assert_eq!(
+            product.eval_bounded(&ResourceLimits::untrusted()),
+            Err(ResourceLimitExceeded::Overflow)
+        );
+        let sum = Packet::operator(Operator::Sum(vec![Packet::literal(usize::MAX), big()]));
+        assert_eq!(
+            sum.eval_bounded(&ResourceLimits::untrusted()),
+            Err(ResourceLimitExceeded::Overflow)
+        );
+    }
+}
@@ -0,0 +1,339 @@
+use super::{decode_transmission_with_limits, encode_transmission, Operator, Packet};
+use crate::limits::{ResourceLimitExceeded, ResourceLimits};
+use std::{
+    io::{self, BufRead, Write},
+    iter::Peekable,
+    str::Chars,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ExprError {
+    #[error("Unexpected end of expression")]
+    UnexpectedEnd,
+    #[error("Unexpected character '{0}'")]
+    UnexpectedChar(char),
+    #[error("Invalid number '{0}'")]
+    InvalidNumber(String),
+    #[error("Unknown operator '{0}', expected one of sum, product, min, max, gt, lt, eq")]
+    UnknownOperator(String),
+    #[error("Operator '{name}' expects {expected} operands, got {actual}")]
+    WrongArity {
+        name: String,
+        expected: &'static str,
+        actual: usize,
+    },
+    #[error(transparent)]
+    LimitExceeded(#[from] ResourceLimitExceeded),
+}
+
+struct ExprParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    // nesting of the expression being parsed, the root is at depth 1
+    depth: usize,
+    // expressions parsed so far, each one becomes a packet
+    packets: usize,
+    limits: ResourceLimits,
+}
+
+impl<'a> ExprParser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ExprError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(ExprError::UnexpectedChar(c)),
+            None => Err(ExprError::UnexpectedEnd),
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(c) = self.chars.next_if(|&c| predicate(c)) {
+            taken.push(c);
+        }
+        taken
+    }
+
+    fn parse_expr(&mut self) -> Result<Packet, ExprError> {
+        self.depth += 1;
+        self.packets += 1;
+        self.limits.check_depth(self.depth)?;
+        self.limits.check_packets(self.packets)?;
+        let expr = self.parse_nested_expr();
+        self.depth -= 1;
+        expr
+    }
+
+    // expr := number | name '(' expr (',' expr)* ')'
+    fn parse_nested_expr(&mut self) -> Result<Packet, ExprError> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            None => Err(ExprError::UnexpectedEnd),
+            Some(c) if c.is_ascii_digit() => {
+                let digits = self.take_while(|c| c.is_ascii_alphanumeric());
+                let value = digits
+                    .parse()
+                    .map_err(|_| ExprError::InvalidNumber(digits))?;
+                Ok(Packet::literal(value))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let name = self.take_while(|c| c.is_ascii_alphanumeric());
+                let operands = self.parse_operands()?;
+                make_operator(name, operands).map(Packet::operator)
+            }
+            Some(&c) => Err(ExprError::UnexpectedChar(c)),
+        }
+    }
+
+    fn parse_operands(&mut self) -> Result<Vec<Packet>, ExprError> {
+        self.expect('(')?;
+        let mut operands = vec![self.parse_expr()?];
+        loop {
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => operands.push(self.parse_expr()?),
+                Some(')') => break Ok(operands),
+                Some(c) => break Err(ExprError::UnexpectedChar(c)),
+                None => break Err(ExprError::UnexpectedEnd),
+            }
+        }
+    }
+}
+
+fn make_operator(name: String, mut operands: Vec<Packet>) -> Result<Operator, ExprError> {
+    let comparison = matches!(name.as_str(), "gt" | "lt" | "eq");
+    if comparison && operands.len() != 2 {
+        return Err(ExprError::WrongArity {
+            name,
+            expected: "exactly 2",
+            actual: operands.len(),
+        });
+    }
+
+    let operator = match name.as_str() {
+        "sum" => Operator::Sum(operands),
+        "product" => Operator::Product(operands),
+        "min" => Operator::Minimum(operands),
+        "max" => Operator::Maximum(operands),
+        _ if comparison => {
+            let right = Box::new(operands.pop().unwrap());
+            let left = Box::new(operands.pop().unwrap());
+            match name.as_str() {
+                "gt" => Operator::GreaterThan { left, right },
+                "lt" => Operator::LessThan { left, right },
+                _ => Operator::EqualTo { left, right },
+            }
+        }
+        _ => return Err(ExprError::UnknownOperator(name)),
+    };
+    Ok(operator)
+}
+
+// Expressions nested deeper than the depth limit or with more packets than the packet limit are
+// rejected.
+pub fn parse_expression(expr: &str, limits: &ResourceLimits) -> Result<Packet, ExprError> {
+    let mut parser = ExprParser {
+        chars: expr.chars().peekable(),
+        depth: 0,
+        packets: 0,
+        limits: *limits,
+    };
+    let packet = parser.parse_expr()?;
+    parser.skip_whitespace();
+    match parser.chars.next() {
+        Some(c) => Err(ExprError::UnexpectedChar(c)),
+        None => Ok(packet),
+    }
+}
+
+const HELP: &str = "Enter expressions like sum(1, product(2, 3)) or one of the commands:
+  :hex           toggle printing the hex encoding of each expression
+  :decode <hex>  decode a transmission and print its expression
+  :help          show this help
+  :quit          leave the REPL";
+
+fn write_evaluated<W: Write>(
+    packet: &Packet,
+    limits: &ResourceLimits,
+    output: &mut W,
+) -> io::Result<()> {
+    match packet.eval_bounded(limits) {
+        Ok(value) => writeln!(output, "{} = {}", packet, value),
+        Err(e) => writeln!(output, "{} = error: {}", packet, e),
+    }
+}
+
+fn eval_line<W: Write>(line: &str, show_hex: &mut bool, output: &mut W) -> io::Result<()> {
+    // everything entered is untrusted
+    let limits = ResourceLimits::untrusted();
+    match line.split_once(' ').unwrap_or((line, "")) {
+        ("", _) => {}
+        (":help", _) => writeln!(output, "{}", HELP)?,
+        (":hex", _) => {
+            *show_hex = !*show_hex;
+            writeln!(
+                output,
+                "hex output {}",
+                if *show_hex { "on" } else { "off" }
+            )?;
+        }
+        (":decode", transmission) => {
+            let transmission = transmission.trim().to_ascii_uppercase();
+            match decode_transmission_with_limits(&transmission, &limits) {
+                Ok((packet, _)) => write_evaluated(&packet, &limits, output)?,
+                Err(e) => writeln!(output, "error: {}", e)?,
+            }
+        }
+        _ => match parse_expression(line, &limits) {
+            Ok(packet) => {
+                write_evaluated(&packet, &limits, output)?;
+                if *show_hex {
+                    match encode_transmission(&packet) {
+                        Ok(hex) => writeln!(output, "{}", hex)?,
+                        Err(e) => writeln!(output, "error: {}", e)?,
+                    }
+                }
+            }
+            Err(e) => writeln!(output, "error: {}", e)?,
+        },
+    }
+    Ok(())
+}
+
+pub fn run<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    let mut show_hex = false;
+    write!(output, "> ")?;
+    output.flush()?;
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line == ":quit" {
+            break;
+        }
+        eval_line(line, &mut show_hex, &mut output)?;
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(expr: &str) -> Result<Packet, ExprError> {
+        parse_expression(expr, &ResourceLimits::untrusted())
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        let packet = parse("sum(1, product(2,3))").unwrap();
+        assert_eq!(packet.to_string(), "(1 + (2 * 3))");
+        assert_eq!(packet.eval(), 7);
+
+        let packet = parse(" eq( max(1, 5 ,3), min(9,5) ) ").unwrap();
+        assert_eq!(packet.to_string(), "(max(1, 5, 3) == min(9, 5))");
+        assert_eq!(packet.eval(), 1);
+
+        assert_eq!(parse("lt(2, 1)").unwrap().eval(), 0);
+        assert_eq!(parse("42").unwrap(), Packet::literal(42));
+    }
+
+    #[test]
+    fn test_parse_expression_errors() {
+        assert_eq!(parse(""), Err(ExprError::UnexpectedEnd));
+        assert_eq!(parse("sum(1, 2"), Err(ExprError::UnexpectedEnd));
+        assert_eq!(parse("sum()"), Err(ExprError::UnexpectedChar(')')));
+        assert_eq!(parse("1 2"), Err(ExprError::UnexpectedChar('2')));
+        assert_eq!(
+            parse("12ab"),
+            Err(ExprError::InvalidNumber("12ab".to_string()))
+        );
+        assert_eq!(
+            parse("div(4, 2)"),
+            Err(ExprError::UnknownOperator("div".to_string()))
+        );
+        assert_eq!(
+            parse("gt(1, 2, 3)"),
+            Err(ExprError::WrongArity {
+                name: "gt".to_string(),
+                expected: "exactly 2",
+                actual: 3
+            })
+        );
+
+        // too deep to parse, no matter whether it is complete
+        assert_eq!(
+            parse(&"sum(".repeat(200_000)),
+            Err(ExprError::LimitExceeded(ResourceLimitExceeded::Depth(64)))
+        );
+        let nested = |depth: usize| format!("{}1{}", "sum(".repeat(depth), ")".repeat(depth));
+        assert!(parse(&nested(63)).is_ok());
+        assert!(parse(&nested(64)).is_err());
+
+        // too many packets, even though each one is small
+        let flat = |packets: usize| format!("sum({})", vec!["1"; packets - 1].join(","));
+        assert!(parse(&flat(10_000)).is_ok());
+        assert_eq!(
+            parse(&flat(10_001)),
+            Err(ExprError::LimitExceeded(ResourceLimitExceeded::Packets(
+                10_000
+            )))
+        );
+    }
+
+    #[test]
+    fn test_hex_length_overflow() {
+        let line = format!("sum({})", vec!["1073741824"; 2048].join(", "));
+        let mut output = Vec::new();
+        eval_line(&line, &mut true, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with(
+            "= 2199023255552\nerror: 2048 operands take 94208 bits, more than a 15-bit length can hold\n"
+        ));
+    }
+
+    #[test]
+    fn test_session() {
+        let input = "sum(1, product(2, 3))
+:hex
+lt(5, 15)
+min(1,
+:decode 9c0141080250320f1802104a08
+:decode XYZ
+:decode 1600C40081104
+:decode 38FFFF00
+:quit
+42
+";
+        let mut output = Vec::new();
+        run(input.as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let expected_hex = encode_transmission(
+            &parse_expression("lt(5, 15)", &ResourceLimits::default()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "> (1 + (2 * 3)) = 7
+> hex output on
+> (5 < 15) = 1
+{}
+> error: Unexpected end of expression
+> ((1 + 3) == (2 * 2)) = 1
+> error: Malformed transmission at bit 0: invalid hex digit 'X'
+> error: Malformed transmission at bit 0: expected 2 operands but got 3
+> error: Malformed transmission at bit 0: sub-packets of 16383 bits exceed the remaining 10 bits
+> ",
+                expected_hex
+            )
+        );
+    }
+}
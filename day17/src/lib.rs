@@ -0,0 +1,463 @@
+use common::{Answer, Solution};
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = Area;
+
+    fn parse(input: &str) -> Self::Input {
+        parse_target_area(input)
+    }
+
+    fn part1(target: &Self::Input) -> Answer {
+        best_initial_velocity(target).unwrap().0.into()
+    }
+
+    fn part2(target: &Self::Input) -> Answer {
+        count_initital_velocities_in_range(target).into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Acceleration {
+    pub horizontal: isize,
+    pub vertical: isize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Velocity {
+    pub horizontal: isize,
+    pub vertical: isize,
+}
+
+impl Velocity {
+    pub fn accelerate(mut self, accel: Acceleration) -> Velocity {
+        self.horizontal += accel.horizontal;
+        self.vertical += accel.vertical;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Position {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Position {
+    pub fn step(mut self, vel: Velocity) -> Position {
+        self.x += vel.horizontal;
+        self.y += vel.vertical;
+        self
+    }
+
+    pub fn is_below(&self, other: &Position) -> bool {
+        self.y < other.y
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Area {
+    pub top_left: Position,
+    pub bottom_right: Position,
+}
+
+impl Area {
+    pub fn contains(&self, pos: &Position) -> bool {
+        pos.x >= self.top_left.x
+            && pos.x <= self.bottom_right.x
+            && pos.y >= self.bottom_right.y
+            && pos.y <= self.top_left.y
+    }
+}
+
+// Parses "target area: x=x1..x2, y=y1..y2".
+pub fn parse_target_area(s: &str) -> Area {
+    let range = |s: &str| -> (isize, isize) {
+        let (from, to) = s[2..].split_once("..").unwrap();
+        (from.parse().unwrap(), to.parse().unwrap())
+    };
+    let (x, y) = s
+        .trim()
+        .strip_prefix("target area: ")
+        .unwrap()
+        .split_once(", ")
+        .unwrap();
+    let (x1, x2) = range(x);
+    let (y1, y2) = range(y);
+    Area {
+        top_left: Position {
+            x: x1.min(x2),
+            y: y1.max(y2),
+        },
+        bottom_right: Position {
+            x: x1.max(x2),
+            y: y1.min(y2),
+        },
+    }
+}
+
+pub fn reaches_target_with_max_height(
+    init_pos: Position,
+    init_vel: Velocity,
+    target: &Area,
+) -> Option<(isize, Position)> {
+    let mut pos = init_pos;
+    let mut vel = init_vel;
+    let mut max_height = pos.y;
+
+    loop {
+        if target.contains(&pos) {
+            break Some((max_height, pos));
+        }
+        if pos.is_below(&target.bottom_right) {
+            break None;
+        }
+
+        let accel = match vel.horizontal.cmp(&0) {
+            std::cmp::Ordering::Equal => Acceleration {
+                horizontal: 0,
+                vertical: -1,
+            },
+            std::cmp::Ordering::Greater => Acceleration {
+                horizontal: -1,
+                vertical: -1,
+            },
+            std::cmp::Ordering::Less => Acceleration {
+                horizontal: 1,
+                vertical: -1,
+            },
+        };
+
+        pos = pos.step(vel);
+        vel = vel.accelerate(accel);
+
+        if pos.y > max_height {
+            max_height = pos.y;
+        }
+    }
+}
+
+pub fn find_best_initital_velocity(target: &Area) -> Option<(isize, Velocity)> {
+    let mut best = None;
+
+    for dx in -100..100 {
+        for dy in -100..100 {
+            let vel = Velocity {
+                horizontal: dx,
+                vertical: dy,
+            };
+            if let Some((height, _)) =
+                reaches_target_with_max_height(Position::default(), vel, target)
+            {
+                best = match best {
+                    Some((best_height, _)) if height > best_height => Some((height, vel)),
+                    None => Some((height, vel)),
+                    _ => best,
+                };
+            }
+        }
+    }
+
+    best
+}
+
+// If the target lies below the launch position, the highest trajectory goes up with
+// vertical velocity v, comes back to y = 0 with velocity -(v + 1) and hits the bottom row of
+// the target in the next step. That is v = -ymin - 1 and a max height of ymin * (ymin + 1) / 2.
+// This only works if some horizontal velocity stalls within the target, which is verified by
+// simulating the candidate trajectories.
+pub fn max_height_closed_form(target: &Area) -> Option<(isize, Velocity)> {
+    if target.top_left.y >= 0 {
+        return None;
+    }
+
+    let ymin = target.bottom_right.y;
+    let vertical = -ymin - 1;
+    let stall_x = |dx: isize| dx.signum() * dx.abs() * (dx.abs() + 1) / 2;
+
+    let min_dx = target.top_left.x.min(0);
+    let max_dx = target.bottom_right.x.max(0);
+    (min_dx..=max_dx)
+        .filter(|&dx| (target.top_left.x..=target.bottom_right.x).contains(&stall_x(dx)))
+        .map(|dx| Velocity {
+            horizontal: dx,
+            vertical,
+        })
+        .find_map(|vel| {
+            let (height, _) = reaches_target_with_max_height(Position::default(), vel, target)?;
+            debug_assert_eq!(height, ymin * (ymin + 1) / 2);
+            Some((height, vel))
+        })
+}
+
+pub fn best_initial_velocity(target: &Area) -> Option<(isize, Velocity)> {
+    max_height_closed_form(target).or_else(|| find_best_initital_velocity(target))
+}
+
+pub fn count_initital_velocities_in_range(target: &Area) -> usize {
+    let mut count = 0;
+
+    for dx in -500..500 {
+        for dy in -500..500 {
+            let vel = Velocity {
+                horizontal: dx,
+                vertical: dy,
+            };
+            if reaches_target_with_max_height(Position::default(), vel, target).is_some() {
+                count += 1;
+            }
+        }
+    }
+
+    count
+}
+
+// Outcome of launching with any of the velocities within +-uncertainty of the nominal one,
+// all of them being equally likely.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LaunchAnalysis {
+    pub launches: usize,
+    pub hits: usize,
+    // of the launches which hit the target
+    pub expected_max_height: Option<f64>,
+}
+
+impl LaunchAnalysis {
+    pub fn hit_fraction(&self) -> f64 {
+        self.hits as f64 / self.launches as f64
+    }
+}
+
+pub fn analyze_launch(
+    target: &Area,
+    nominal: Velocity,
+    uncertainty: (usize, usize),
+) -> LaunchAnalysis {
+    let (dx, dy) = (uncertainty.0 as isize, uncertainty.1 as isize);
+
+    let mut launches = 0;
+    let mut hits = 0;
+    let mut height_sum = 0;
+    for horizontal in nominal.horizontal - dx..=nominal.horizontal + dx {
+        for vertical in nominal.vertical - dy..=nominal.vertical + dy {
+            let vel = Velocity {
+                horizontal,
+                vertical,
+            };
+            launches += 1;
+            if let Some((height, _)) =
+                reaches_target_with_max_height(Position::default(), vel, target)
+            {
+                hits += 1;
+                height_sum += height;
+            }
+        }
+    }
+
+    LaunchAnalysis {
+        launches,
+        hits,
+        expected_max_height: (hits > 0).then(|| height_sum as f64 / hits as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance() {
+        let target = Day17::parse(include_str!("../instance.txt"));
+        assert_eq!(
+            find_best_initital_velocity(&target),
+            Some((
+                4753,
+                Velocity {
+                    horizontal: 17,
+                    vertical: 97
+                }
+            ))
+        );
+        assert_eq!(count_initital_velocities_in_range(&target), 1546);
+    }
+
+    #[test]
+    fn test_example() {
+        let target = Area {
+            top_left: Position { x: 20, y: -5 },
+            bottom_right: Position { x: 30, y: -10 },
+        };
+        assert_eq!(
+            find_best_initital_velocity(&target),
+            Some((
+                45,
+                Velocity {
+                    horizontal: 6,
+                    vertical: 9
+                }
+            ))
+        );
+        assert_eq!(count_initital_velocities_in_range(&target), 112);
+    }
+
+    #[test]
+    fn test_reaches_target_with_max_height1() {
+        let target = Area {
+            top_left: Position { x: 20, y: -5 },
+            bottom_right: Position { x: 30, y: -10 },
+        };
+        let result = reaches_target_with_max_height(
+            Position::default(),
+            Velocity {
+                horizontal: 7,
+                vertical: 2,
+            },
+            &target,
+        );
+        assert_eq!(result, Some((3, Position { x: 28, y: -7 })));
+    }
+
+    #[test]
+    fn test_reaches_target_with_max_height2() {
+        let target = Area {
+            top_left: Position { x: 20, y: -5 },
+            bottom_right: Position { x: 30, y: -10 },
+        };
+        let result = reaches_target_with_max_height(
+            Position::default(),
+            Velocity {
+                horizontal: 6,
+                vertical: 3,
+            },
+            &target,
+        );
+        assert_eq!(result, Some((6, Position { x: 21, y: -9 })));
+    }
+
+    #[test]
+    fn test_reaches_target_with_max_height3() {
+        let target = Area {
+            top_left: Position { x: 20, y: -5 },
+            bottom_right: Position { x: 30, y: -10 },
+        };
+        let result = reaches_target_with_max_height(
+            Position::default(),
+            Velocity {
+                horizontal: 9,
+                vertical: 0,
+            },
+            &target,
+        );
+        assert_eq!(result, Some((0, Position { x: 30, y: -6 })));
+    }
+
+    #[test]
+    fn test_reaches_target_with_max_height4() {
+        let target = Area {
+            top_left: Position { x: 20, y: -5 },
+            bottom_right: Position { x: 30, y: -10 },
+        };
+        let result = reaches_target_with_max_height(
+            Position::default(),
+            Velocity {
+                horizontal: 17,
+                vertical: -4,
+            },
+            &target,
+        );
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_closed_form_matches_simulation() {
+        let instance = Area {
+            top_left: Position { x: 137, y: -73 },
+            bottom_right: Position { x: 171, y: -98 },
+        };
+        let example = Area {
+            top_left: Position { x: 20, y: -5 },
+            bottom_right: Position { x: 30, y: -10 },
+        };
+        for target in [instance, example] {
+            let closed_form = max_height_closed_form(&target);
+            assert!(closed_form.is_some());
+            assert_eq!(closed_form, find_best_initital_velocity(&target));
+        }
+    }
+
+    #[test]
+    fn test_analyze_launch() {
+        let target = Area {
+            top_left: Position { x: 20, y: -5 },
+            bottom_right: Position { x: 30, y: -10 },
+        };
+        let best = Velocity {
+            horizontal: 6,
+            vertical: 9,
+        };
+        assert_eq!(
+            analyze_launch(&target, best, (0, 0)),
+            LaunchAnalysis {
+                launches: 1,
+                hits: 1,
+                expected_max_height: Some(45.0),
+            }
+        );
+
+        // launching faster upwards overshoots, slower to the right stalls in front of the target
+        let analysis = analyze_launch(&target, best, (1, 1));
+        assert_eq!(analysis.launches, 9);
+        assert_eq!(analysis.hits, 4);
+        assert!((analysis.hit_fraction() - 4.0 / 9.0).abs() < 1e-9);
+        // (6, 9) and (7, 9) reach 45, (6, 8) and (7, 8) reach 36
+        assert_eq!(analysis.expected_max_height, Some(40.5));
+
+        let miss = Velocity {
+            horizontal: 17,
+            vertical: -4,
+        };
+        let analysis = analyze_launch(&target, miss, (0, 0));
+        assert_eq!(analysis.hits, 0);
+        assert_eq!(analysis.hit_fraction(), 0.0);
+        assert_eq!(analysis.expected_max_height, None);
+
+        // a box covering all velocities which hit
+        let center = Velocity {
+            horizontal: 15,
+            vertical: 0,
+        };
+        let analysis = analyze_launch(&target, center, (20, 20));
+        assert_eq!(analysis.launches, 41 * 41);
+        assert_eq!(analysis.hits, 112);
+    }
+
+    #[test]
+    fn test_closed_form_falls_back_to_simulation() {
+        // target above the launch position
+        let target = Area {
+            top_left: Position { x: 20, y: 10 },
+            bottom_right: Position { x: 30, y: 5 },
+        };
+        assert_eq!(max_height_closed_form(&target), None);
+        assert_eq!(
+            best_initial_velocity(&target),
+            find_best_initital_velocity(&target)
+        );
+
+        // no horizontal velocity stalls within the target
+        let target = Area {
+            top_left: Position { x: 11, y: -5 },
+            bottom_right: Position { x: 14, y: -10 },
+        };
+        assert_eq!(max_height_closed_form(&target), None);
+        assert!(best_initial_velocity(&target).is_some());
+    }
+
+    #[test]
+    fn test_solution() {
+        let (part1, part2) = common::solve::<Day17>(include_str!("../example.txt"));
+        assert_eq!(part1.to_string(), "45");
+        assert_eq!(part2.to_string(), "112");
+    }
+}
@@ -1,254 +1,48 @@
-#[derive(Debug, Clone, Copy, Default, PartialEq)]
-struct Acceleration {
-    pub horizontal: isize,
-    pub vertical: isize,
-}
-
-#[derive(Debug, Clone, Copy, Default, PartialEq)]
-struct Velocity {
-    pub horizontal: isize,
-    pub vertical: isize,
-}
-
-impl Velocity {
-    pub fn accelerate(mut self, accel: Acceleration) -> Velocity {
-        self.horizontal += accel.horizontal;
-        self.vertical += accel.vertical;
-        self
-    }
-}
-
-#[derive(Debug, Clone, Copy, Default, PartialEq)]
-struct Position {
-    pub x: isize,
-    pub y: isize,
-}
-
-impl Position {
-    pub fn step(mut self, vel: Velocity) -> Position {
-        self.x += vel.horizontal;
-        self.y += vel.vertical;
-        self
-    }
-
-    pub fn is_below(&self, other: &Position) -> bool {
-        self.y < other.y
-    }
-}
-
-struct Area {
-    pub top_left: Position,
-    pub bottom_right: Position,
-}
-
-impl Area {
-    pub fn contains(&self, pos: &Position) -> bool {
-        pos.x >= self.top_left.x
-            && pos.x <= self.bottom_right.x
-            && pos.y >= self.bottom_right.y
-            && pos.y <= self.top_left.y
-    }
-}
-
-fn reaches_target_with_max_height(
-    init_pos: Position,
-    init_vel: Velocity,
-    target: &Area,
-) -> Option<(isize, Position)> {
-    let mut pos = init_pos;
-    let mut vel = init_vel;
-    let mut max_height = pos.y;
-
-    loop {
-        if target.contains(&pos) {
-            break Some((max_height, pos));
-        }
-        if pos.is_below(&target.bottom_right) {
-            break None;
-        }
-
-        let accel = match vel.horizontal.cmp(&0) {
-            std::cmp::Ordering::Equal => Acceleration {
-                horizontal: 0,
-                vertical: -1,
-            },
-            std::cmp::Ordering::Greater => Acceleration {
-                horizontal: -1,
-                vertical: -1,
-            },
-            std::cmp::Ordering::Less => Acceleration {
-                horizontal: 1,
-                vertical: -1,
-            },
-        };
-
-        pos = pos.step(vel);
-        vel = vel.accelerate(accel);
-
-        if pos.y > max_height {
-            max_height = pos.y;
-        }
-    }
-}
-
-fn find_best_initital_velocity(target: &Area) -> Option<(isize, Velocity)> {
-    let mut best = None;
-
-    for dx in -100..100 {
-        for dy in -100..100 {
-            let vel = Velocity {
-                horizontal: dx,
-                vertical: dy,
-            };
-            if let Some((height, _)) =
-                reaches_target_with_max_height(Position::default(), vel, target)
-            {
-                best = match best {
-                    Some((best_height, _)) if height > best_height => Some((height, vel)),
-                    None => Some((height, vel)),
-                    _ => best,
-                };
-            }
-        }
-    }
-
-    best
-}
-
-fn count_initital_velocities_in_range(target: &Area) -> usize {
-    let mut count = 0;
-
-    for dx in -500..500 {
-        for dy in -500..500 {
-            let vel = Velocity {
-                horizontal: dx,
-                vertical: dy,
-            };
-            if reaches_target_with_max_height(Position::default(), vel, target).is_some() {
-                count += 1;
-            }
-        }
-    }
-
-    count
-}
+use common::Solution;
+use day17::*;
+use std::env;
 
 fn main() {
-    let target = Area {
-        top_left: Position { x: 137, y: -73 },
-        bottom_right: Position { x: 171, y: -98 },
-    };
-    println!("Part 1: {:?}", find_best_initital_velocity(&target));
-    println!("Part 2: {}", count_initital_velocities_in_range(&target));
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_instance() {
-        let target = Area {
-            top_left: Position { x: 137, y: -73 },
-            bottom_right: Position { x: 171, y: -98 },
-        };
-        assert_eq!(
-            find_best_initital_velocity(&target),
-            Some((
-                4753,
-                Velocity {
-                    horizontal: 17,
-                    vertical: 97
-                }
-            ))
-        );
-        assert_eq!(count_initital_velocities_in_range(&target), 1546);
-    }
-
-    #[test]
-    fn test_example() {
-        let target = Area {
-            top_left: Position { x: 20, y: -5 },
-            bottom_right: Position { x: 30, y: -10 },
+    let target = Day17::parse(&common::read_input());
+
+    // --launch dx,dy [--uncertainty k | kx,ky]
+    let arg = |name: &str| env::args().skip_while(|arg| arg != name).nth(1);
+    if let Some(launch) = arg("--launch") {
+        let (horizontal, vertical) = parse_pair(&launch);
+        let nominal = Velocity {
+            horizontal,
+            vertical,
         };
-        assert_eq!(
-            find_best_initital_velocity(&target),
-            Some((
-                45,
-                Velocity {
-                    horizontal: 6,
-                    vertical: 9
-                }
-            ))
+        let (kx, ky) = arg("--uncertainty").map_or((0, 0), |k| parse_pair(&k));
+        let uncertainty = (kx.unsigned_abs(), ky.unsigned_abs());
+
+        let analysis = analyze_launch(&target, nominal, uncertainty);
+        println!(
+            "{} of {} launches hit the target ({:.1}%)",
+            analysis.hits,
+            analysis.launches,
+            100.0 * analysis.hit_fraction()
         );
-        assert_eq!(count_initital_velocities_in_range(&target), 112);
+        match analysis.expected_max_height {
+            Some(height) => println!("Expected max height: {:.2}", height),
+            None => println!("Expected max height: -"),
+        }
+        return;
     }
-
-    #[test]
-    fn test_reaches_target_with_max_height1() {
-        let target = Area {
-            top_left: Position { x: 20, y: -5 },
-            bottom_right: Position { x: 30, y: -10 },
-        };
-        let result = reaches_target_with_max_height(
-            Position::default(),
-            Velocity {
-                horizontal: 7,
-                vertical: 2,
-            },
-            &target,
-        );
-        assert_eq!(result, Some((3, Position { x: 28, y: -7 })));
+    if common::part_selected(1) {
+        println!("Part 1: {:?}", best_initial_velocity(&target));
     }
-
-    #[test]
-    fn test_reaches_target_with_max_height2() {
-        let target = Area {
-            top_left: Position { x: 20, y: -5 },
-            bottom_right: Position { x: 30, y: -10 },
-        };
-        let result = reaches_target_with_max_height(
-            Position::default(),
-            Velocity {
-                horizontal: 6,
-                vertical: 3,
-            },
-            &target,
-        );
-        assert_eq!(result, Some((6, Position { x: 21, y: -9 })));
-    }
-
-    #[test]
-    fn test_reaches_target_with_max_height3() {
-        let target = Area {
-            top_left: Position { x: 20, y: -5 },
-            bottom_right: Position { x: 30, y: -10 },
-        };
-        let result = reaches_target_with_max_height(
-            Position::default(),
-            Velocity {
-                horizontal: 9,
-                vertical: 0,
-            },
-            &target,
-        );
-        assert_eq!(result, Some((0, Position { x: 30, y: -6 })));
+    if common::part_selected(2) {
+        println!("Part 2: {}", count_initital_velocities_in_range(&target));
     }
+}
 
-    #[test]
-    fn test_reaches_target_with_max_height4() {
-        let target = Area {
-            top_left: Position { x: 20, y: -5 },
-            bottom_right: Position { x: 30, y: -10 },
-        };
-        let result = reaches_target_with_max_height(
-            Position::default(),
-            Velocity {
-                horizontal: 17,
-                vertical: -4,
-            },
-            &target,
-        );
-        assert_eq!(result, None);
+fn parse_pair(s: &str) -> (isize, isize) {
+    match s.split_once(',') {
+        Some((a, b)) => (a.parse().unwrap(), b.parse().unwrap()),
+        None => {
+            let a = s.parse().unwrap();
+            (a, a)
+        }
     }
 }
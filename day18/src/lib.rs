@@ -0,0 +1,914 @@
+use common::{Answer, Solution};
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp,
+    fmt::{self, Write},
+    iter, mem, ops,
+    str::{self, FromStr},
+};
+use thiserror::Error;
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = Vec<SnailfishNumber>;
+
+    fn parse(input: &str) -> Self::Input {
+        input.lines().map(|line| line.parse().unwrap()).collect()
+    }
+
+    fn part1(numbers: &Self::Input) -> Answer {
+        let sum: SnailfishNumber = numbers.iter().sum::<Option<_>>().unwrap();
+        sum.magnitude().unwrap().into()
+    }
+
+    fn part2(numbers: &Self::Input) -> Answer {
+        max_pairwise_magnitude(numbers).unwrap().into()
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ParseError {
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+    #[error("unexpected character '{0}'")]
+    UnexpectedCharacter(char),
+    #[error("unexpected trailing input '{0}'")]
+    TrailingInput(String),
+    #[error("invalid number")]
+    InvalidNumber(#[from] std::num::ParseIntError),
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("magnitude overflows")]
+pub struct MagnitudeOverflow;
+
+// Integer types magnitudes can be computed in.
+pub trait Magnitude: Sized {
+    fn from_regular(n: usize) -> Option<Self>;
+    // 3 * lhs + 2 * rhs, None on overflow
+    fn combine(lhs: Self, rhs: Self) -> Option<Self>;
+}
+
+impl Magnitude for usize {
+    fn from_regular(n: usize) -> Option<Self> {
+        Some(n)
+    }
+
+    fn combine(lhs: Self, rhs: Self) -> Option<Self> {
+        lhs.checked_mul(3)?.checked_add(rhs.checked_mul(2)?)
+    }
+}
+
+impl Magnitude for u64 {
+    fn from_regular(n: usize) -> Option<Self> {
+        n.try_into().ok()
+    }
+
+    fn combine(lhs: Self, rhs: Self) -> Option<Self> {
+        lhs.checked_mul(3)?.checked_add(rhs.checked_mul(2)?)
+    }
+}
+
+impl Magnitude for u128 {
+    fn from_regular(n: usize) -> Option<Self> {
+        n.try_into().ok()
+    }
+
+    fn combine(lhs: Self, rhs: Self) -> Option<Self> {
+        lhs.checked_mul(3)?.checked_add(rhs.checked_mul(2)?)
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl Magnitude for BigUint {
+    fn from_regular(n: usize) -> Option<Self> {
+        Some(BigUint::from(n))
+    }
+
+    fn combine(lhs: Self, rhs: Self) -> Option<Self> {
+        Some(lhs * 3u32 + rhs * 2u32)
+    }
+}
+
+// Serialized in the puzzle notation, e.g. [[1,2],3]. Deserialized numbers are taken as-is, without reduction.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(untagged))]
+pub enum SnailfishNumber {
+    Regular(usize),
+    Pair(Box<Self>, Box<Self>),
+}
+
+// Pair which exploded, the carried values are still to be added to the neighbouring regular numbers.
+struct Explosion {
+    left: usize,
+    right: usize,
+    carry_left: Option<usize>,
+    carry_right: Option<usize>,
+}
+
+// Side taken in a pair, a path of sides leads from the outermost pair to a nested number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReductionStep {
+    Exploded {
+        path: Vec<Side>,
+        left: usize,
+        right: usize,
+    },
+    Split {
+        path: Vec<Side>,
+        value: usize,
+    },
+}
+
+impl fmt::Display for ReductionStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let write_path = |f: &mut fmt::Formatter<'_>, path: &[Side]| {
+            path.iter().try_for_each(|side| match side {
+                Side::Left => write!(f, "L"),
+                Side::Right => write!(f, "R"),
+            })
+        };
+        match self {
+            Self::Exploded { path, left, right } => {
+                write!(f, "explode [{},{}] at ", left, right)?;
+                write_path(f, path)
+            }
+            Self::Split { path, value } => {
+                write!(f, "split {} at ", value)?;
+                write_path(f, path)
+            }
+        }
+    }
+}
+
+// Iterator over the steps of reducing a number, the number can be inspected after every step.
+pub struct Reduction {
+    number: SnailfishNumber,
+}
+
+impl Reduction {
+    pub fn number(&self) -> &SnailfishNumber {
+        &self.number
+    }
+
+    pub fn into_number(self) -> SnailfishNumber {
+        self.number
+    }
+}
+
+impl Iterator for Reduction {
+    type Item = ReductionStep;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.number.reduce_step()
+    }
+}
+
+impl SnailfishNumber {
+    pub fn regular(n: usize) -> SnailfishNumber {
+        Self::Regular(n)
+    }
+
+    pub fn pair(lhs: SnailfishNumber, rhs: SnailfishNumber) -> SnailfishNumber {
+        Self::Pair(Box::new(lhs), Box::new(rhs))
+    }
+
+    fn is_regular(&self) -> bool {
+        matches!(self, Self::Regular(_))
+    }
+
+    fn regular_value(&self) -> Option<usize> {
+        match self {
+            Self::Regular(n) => Some(*n),
+            Self::Pair(..) => None,
+        }
+    }
+
+    fn add_to_leftmost_regular(&mut self, n: usize) {
+        match self {
+            Self::Regular(m) => *m += n,
+            Self::Pair(lhs, _) => lhs.add_to_leftmost_regular(n),
+        }
+    }
+
+    fn add_to_rightmost_regular(&mut self, n: usize) {
+        match self {
+            Self::Regular(m) => *m += n,
+            Self::Pair(_, rhs) => rhs.add_to_rightmost_regular(n),
+        }
+    }
+
+    // On an explosion, `path` is left pointing to the exploded pair.
+    fn explode(&mut self, path: &mut Vec<Side>) -> Option<Explosion> {
+        match self {
+            Self::Regular(_) => None,
+            Self::Pair(lhs, rhs)
+                if path.len() >= EXPLODE_DEPTH && lhs.is_regular() && rhs.is_regular() =>
+            {
+                let left = lhs.regular_value()?;
+                let right = rhs.regular_value()?;
+                *self = Self::regular(0);
+                Some(Explosion {
+                    left,
+                    right,
+                    carry_left: Some(left),
+                    carry_right: Some(right),
+                })
+            }
+            Self::Pair(lhs, rhs) => {
+                path.push(Side::Left);
+                if let Some(mut explosion) = lhs.explode(path) {
+                    if let Some(n) = explosion.carry_right.take() {
+                        rhs.add_to_leftmost_regular(n);
+                    }
+                    return Some(explosion);
+                }
+
+                *path.last_mut().unwrap() = Side::Right;
+                if let Some(mut explosion) = rhs.explode(path) {
+                    if let Some(n) = explosion.carry_left.take() {
+                        lhs.add_to_rightmost_regular(n);
+                    }
+                    return Some(explosion);
+                }
+
+                path.pop();
+                None
+            }
+        }
+    }
+
+    // On a split, `path` is left pointing to the split regular number. Returns its value.
+    fn split(&mut self, path: &mut Vec<Side>) -> Option<usize> {
+        match self {
+            Self::Regular(n) if *n >= SPLIT_THRESHOLD => {
+                let value = *n;
+                let lhs = value / 2;
+                let rhs = value - lhs;
+                *self = Self::pair(Self::regular(lhs), Self::regular(rhs));
+                Some(value)
+            }
+            Self::Regular(_) => None,
+            Self::Pair(lhs, rhs) => {
+                path.push(Side::Left);
+                if let Some(value) = lhs.split(path) {
+                    return Some(value);
+                }
+
+                *path.last_mut().unwrap() = Side::Right;
+                if let Some(value) = rhs.split(path) {
+                    return Some(value);
+                }
+
+                path.pop();
+                None
+            }
+        }
+    }
+
+    fn reduce_step(&mut self) -> Option<ReductionStep> {
+        let mut path = Vec::new();
+        if let Some(Explosion { left, right, .. }) = self.explode(&mut path) {
+            return Some(ReductionStep::Exploded { path, left, right });
+        }
+        let value = self.split(&mut path)?;
+        Some(ReductionStep::Split { path, value })
+    }
+
+    fn reduce(&mut self) {
+        while self.reduce_step().is_some() {}
+    }
+
+    // Reduces the number one explosion or split at a time.
+    pub fn reduction(self) -> Reduction {
+        Reduction { number: self }
+    }
+
+    fn parse_regular(
+        mut chars: str::Chars<'_>,
+    ) -> Result<(str::Chars<'_>, SnailfishNumber), ParseError> {
+        let s = chars.as_str();
+        while chars.clone().next().is_some_and(|c| c.is_ascii_digit()) {
+            chars.next();
+        }
+        let n = &s[..s.len() - chars.as_str().len()];
+        Ok((chars, SnailfishNumber::regular(n.parse()?)))
+    }
+
+    fn expect(mut chars: str::Chars<'_>, expected: char) -> Result<str::Chars<'_>, ParseError> {
+        match chars.next() {
+            Some(c) if c == expected => Ok(chars),
+            Some(c) => Err(ParseError::UnexpectedCharacter(c)),
+            None => Err(ParseError::UnexpectedEnd),
+        }
+    }
+
+    fn parse_pair(chars: str::Chars<'_>) -> Result<(str::Chars<'_>, SnailfishNumber), ParseError> {
+        let chars = Self::expect(chars, '[')?;
+        let (chars, lhs) = Self::parse_number(chars)?;
+        let chars = Self::expect(chars, ',')?;
+        let (chars, rhs) = Self::parse_number(chars)?;
+        let chars = Self::expect(chars, ']')?;
+        Ok((chars, SnailfishNumber::pair(lhs, rhs)))
+    }
+
+    fn parse_number(
+        chars: str::Chars<'_>,
+    ) -> Result<(str::Chars<'_>, SnailfishNumber), ParseError> {
+        match chars.clone().next() {
+            Some('[') => Self::parse_pair(chars),
+            Some(c) if c.is_ascii_digit() => Self::parse_regular(chars),
+            Some(c) => Err(ParseError::UnexpectedCharacter(c)),
+            None => Err(ParseError::UnexpectedEnd),
+        }
+    }
+
+    // Parses the number as is, without reducing it.
+    pub fn parse_unreduced(s: &str) -> Result<SnailfishNumber, ParseError> {
+        let (chars, n) = Self::parse_number(s.trim().chars())?;
+        if !chars.as_str().is_empty() {
+            return Err(ParseError::TrailingInput(chars.as_str().to_owned()));
+        }
+        Ok(n)
+    }
+
+    // Magnitudes grow by a factor of up to 5 per level, so deep unreduced numbers easily
+    // exceed 64 bits. Reduced numbers stay below 9 * 5^4.
+    pub fn magnitude_as<T: Magnitude>(&self) -> Result<T, MagnitudeOverflow> {
+        match self {
+            Self::Regular(n) => T::from_regular(*n).ok_or(MagnitudeOverflow),
+            Self::Pair(lhs, rhs) => {
+                T::combine(lhs.magnitude_as()?, rhs.magnitude_as()?).ok_or(MagnitudeOverflow)
+            }
+        }
+    }
+
+    pub fn magnitude(&self) -> Result<usize, MagnitudeOverflow> {
+        self.magnitude_as()
+    }
+}
+
+// Pairs nested inside four pairs explode, regular numbers of 10 or more split.
+pub const EXPLODE_DEPTH: usize = 4;
+pub const SPLIT_THRESHOLD: usize = 10;
+
+impl SnailfishNumber {
+    // Renders the number as an indented tree, marking explosion and split candidates.
+    pub fn render_tree(&self) -> String {
+        let mut out = String::new();
+        self.write_tree(&mut out, 0).unwrap();
+        out
+    }
+
+    fn write_tree(&self, out: &mut String, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        match self {
+            Self::Regular(n) if *n >= SPLIT_THRESHOLD => writeln!(out, "{}{} (split)", indent, n),
+            Self::Regular(n) => writeln!(out, "{}{}", indent, n),
+            Self::Pair(lhs, rhs) => {
+                if depth >= EXPLODE_DEPTH {
+                    writeln!(out, "{}[] (explode)", indent)?;
+                } else {
+                    writeln!(out, "{}[]", indent)?;
+                }
+                lhs.write_tree(out, depth + 1)?;
+                rhs.write_tree(out, depth + 1)
+            }
+        }
+    }
+
+    // Renders the number as a Graphviz digraph, explosion candidates are filled orange,
+    // split candidates light blue.
+    pub fn render_dot(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "digraph snailfish {{").unwrap();
+        self.write_dot(&mut out, 0, &mut 0).unwrap();
+        writeln!(out, "}}").unwrap();
+        out
+    }
+
+    fn write_dot(&self, out: &mut String, depth: usize, next_id: &mut usize) -> fmt::Result {
+        let id = *next_id;
+        *next_id += 1;
+
+        match self {
+            Self::Regular(n) => {
+                let highlight = if *n >= SPLIT_THRESHOLD {
+                    ", style=filled, fillcolor=lightblue"
+                } else {
+                    ""
+                };
+                writeln!(out, "  n{} [shape=box, label=\"{}\"{}];", id, n, highlight)
+            }
+            Self::Pair(lhs, rhs) => {
+                let highlight = if depth >= EXPLODE_DEPTH {
+                    ", style=filled, fillcolor=orange"
+                } else {
+                    ""
+                };
+                writeln!(out, "  n{} [shape=point{}];", id, highlight)?;
+                for child in [lhs, rhs] {
+                    writeln!(out, "  n{} -> n{};", id, next_id)?;
+                    child.write_dot(out, depth + 1, next_id)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FromStr for SnailfishNumber {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<SnailfishNumber, Self::Err> {
+        let mut n = Self::parse_unreduced(s)?;
+        n.reduce();
+        Ok(n)
+    }
+}
+
+impl ops::Add for SnailfishNumber {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        let mut n = Self::pair(self, other);
+        n.reduce();
+        n
+    }
+}
+
+impl ops::Add<&SnailfishNumber> for SnailfishNumber {
+    type Output = SnailfishNumber;
+
+    fn add(self, other: &SnailfishNumber) -> Self::Output {
+        self + other.clone()
+    }
+}
+
+impl ops::Add<SnailfishNumber> for &SnailfishNumber {
+    type Output = SnailfishNumber;
+
+    fn add(self, other: SnailfishNumber) -> Self::Output {
+        self.clone() + other
+    }
+}
+
+impl ops::Add<&SnailfishNumber> for &SnailfishNumber {
+    type Output = SnailfishNumber;
+
+    fn add(self, other: &SnailfishNumber) -> Self::Output {
+        self.clone() + other.clone()
+    }
+}
+
+impl ops::AddAssign for SnailfishNumber {
+    fn add_assign(&mut self, other: Self) {
+        let lhs = mem::replace(self, Self::regular(0));
+        *self = lhs + other;
+    }
+}
+
+impl ops::AddAssign<&SnailfishNumber> for SnailfishNumber {
+    fn add_assign(&mut self, other: &SnailfishNumber) {
+        let lhs = mem::replace(self, Self::regular(0));
+        *self = lhs + other;
+    }
+}
+
+// There is no neutral element for the addition of snailfish numbers, hence the sum of
+// no numbers is `None`.
+impl iter::Sum<SnailfishNumber> for Option<SnailfishNumber> {
+    fn sum<I: Iterator<Item = SnailfishNumber>>(iter: I) -> Self {
+        iter.reduce(|lhs, rhs| lhs + rhs)
+    }
+}
+
+impl<'a> iter::Sum<&'a SnailfishNumber> for Option<SnailfishNumber> {
+    fn sum<I: Iterator<Item = &'a SnailfishNumber>>(mut iter: I) -> Self {
+        let first = iter.next()?.clone();
+        Some(iter.fold(first, |lhs, rhs| lhs + rhs))
+    }
+}
+
+impl fmt::Display for SnailfishNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Regular(n) => write!(f, "{}", n),
+            Self::Pair(n1, n2) => write!(f, "[{},{}]", n1, n2),
+        }
+    }
+}
+
+pub fn max_pairwise_magnitude(numbers: &[SnailfishNumber]) -> Option<usize> {
+    if numbers.is_empty() {
+        return None;
+    }
+
+    let mut max_magnitude = 0;
+
+    // addition of snailfish number is not commutative -> need to consider all pairs!
+    for n1 in numbers {
+        for n2 in numbers {
+            let sum = n1 + n2;
+            // the sum is reduced, hence its magnitude is small
+            max_magnitude = cmp::max(max_magnitude, sum.magnitude().unwrap());
+        }
+    }
+
+    Some(max_magnitude)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rng::Rng;
+    use rstest::rstest;
+
+    fn parse(s: &str) -> SnailfishNumber {
+        s.parse().unwrap()
+    }
+
+    #[rstest]
+    #[case("1", SnailfishNumber::regular(1))]
+    #[case(
+        "[1,2]",
+        SnailfishNumber::pair(SnailfishNumber::regular(1), SnailfishNumber::regular(2))
+    )]
+    #[case(
+        "[[1,2],3]",
+        SnailfishNumber::pair(
+            SnailfishNumber::pair(SnailfishNumber::regular(1), SnailfishNumber::regular(2)),
+            SnailfishNumber::regular(3)
+        )
+    )]
+    #[case(
+        "[[1,9],[8,5]]",
+        SnailfishNumber::pair(
+            SnailfishNumber::pair(SnailfishNumber::regular(1), SnailfishNumber::regular(9)),
+            SnailfishNumber::pair(SnailfishNumber::regular(8), SnailfishNumber::regular(5))
+        )
+    )]
+    fn test_parse(#[case] s: &str, #[case] expected: SnailfishNumber) {
+        assert_eq!(parse(s), expected);
+    }
+
+    #[test]
+    fn test_parse_unreduced() {
+        let raw = SnailfishNumber::parse_unreduced("[[[[[9,8],1],2],3],4]").unwrap();
+        assert_eq!(raw.to_string(), "[[[[[9,8],1],2],3],4]");
+        assert_eq!(
+            parse("[[[[[9,8],1],2],3],4]").to_string(),
+            "[[[[0,9],2],3],4]"
+        );
+    }
+
+    #[rstest]
+    #[case("", ParseError::UnexpectedEnd)]
+    #[case("[1,2", ParseError::UnexpectedEnd)]
+    #[case("[1;2]", ParseError::UnexpectedCharacter(';'))]
+    #[case("[a,2]", ParseError::UnexpectedCharacter('a'))]
+    #[case("[1,2]]", ParseError::TrailingInput("]".to_owned()))]
+    fn test_parse_errors(#[case] s: &str, #[case] expected: ParseError) {
+        assert_eq!(s.parse::<SnailfishNumber>(), Err(expected));
+    }
+
+    #[test]
+    fn test_add_assign_and_sum() {
+        let numbers: Vec<_> = ["[1,1]", "[2,2]", "[3,3]", "[4,4]", "[5,5]", "[6,6]"]
+            .iter()
+            .map(|s| parse(s))
+            .collect();
+        let expected = parse("[[[[5,0],[7,4]],[5,5]],[6,6]]");
+
+        let mut sum = numbers[0].clone();
+        for n in &numbers[1..] {
+            sum += n;
+        }
+        assert_eq!(sum, expected);
+
+        assert_eq!(numbers.iter().sum::<Option<_>>(), Some(expected.clone()));
+        assert_eq!(numbers.into_iter().sum::<Option<_>>(), Some(expected));
+        assert_eq!(
+            Vec::<SnailfishNumber>::new().into_iter().sum::<Option<_>>(),
+            None
+        );
+    }
+
+    #[rstest]
+    #[case("[1,2]", "[[3,4],5]", "[[1,2],[[3,4],5]]")]
+    #[case(
+        "[[[[4,3],4],4],[7,[[8,4],9]]]",
+        "[1,1]",
+        "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"
+    )]
+    #[case(
+        "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]",
+        "[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]",
+        "[[[[4,0],[5,4]],[[7,7],[6,0]]],[[8,[7,7]],[[7,9],[5,0]]]]"
+    )]
+    fn test_addition(#[case] lhs: &str, #[case] rhs: &str, #[case] expected: &str) {
+        let result = parse(lhs) + parse(rhs);
+        assert_eq!(result, parse(expected));
+    }
+
+    #[rstest]
+    #[case("[[[[[9,8],1],2],3],4]", "[[[[0,9],2],3],4]")]
+    #[case("[7,[6,[5,[4,[3,2]]]]]", "[7,[6,[5,[7,0]]]]")]
+    #[case("[[6,[5,[4,[3,2]]]],1]", "[[6,[5,[7,0]]],3]")]
+    #[case(
+        "[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]",
+        "[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]"
+    )]
+    #[case("[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]", "[[3,[2,[8,0]]],[9,[5,[7,0]]]]")]
+    #[case(
+        "[[[[4,0],[5,4]],[[7,7],[6,0]]],[[8,[7,7]],[[7,9],[0,10]]]]",
+        "[[[[4,0],[5,4]],[[7,7],[6,0]]],[[8,[7,7]],[[7,9],[5,0]]]]"
+    )]
+    fn test_reduction(#[case] given: &str, #[case] expected: &str) {
+        assert_eq!(parse(given), parse(expected));
+    }
+
+    #[rstest]
+    #[case("[9,1]", 29)]
+    #[case("[1,9]", 21)]
+    #[case("[[9,1],[1,9]]", 129)]
+    #[case("[[1,2],[[3,4],5]]", 143)]
+    #[case("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]", 1384)]
+    #[case("[[[[1,1],[2,2]],[3,3]],[4,4]]", 445)]
+    #[case("[[[[3,0],[5,3]],[4,4]],[5,5]]", 791)]
+    #[case("[[[[5,0],[7,4]],[5,5]],[6,6]]", 1137)]
+    #[case("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]", 3488)]
+    fn test_magnitude(#[case] given: &str, #[case] expected: usize) {
+        assert_eq!(parse(given).magnitude(), Ok(expected));
+    }
+
+    #[test]
+    fn test_sum_example() {
+        let numbers = vec![
+            "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]",
+            "[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]",
+            "[[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]",
+            "[[[[2,4],7],[6,[0,5]]],[[[6,8],[2,8]],[[2,1],[4,5]]]]",
+            "[7,[5,[[3,8],[1,4]]]]",
+            "[[2,[2,2]],[8,[8,1]]]",
+            "[2,9]",
+            "[1,[[[9,3],9],[[9,0],[0,7]]]]",
+            "[[[5,[7,4]],7],1]",
+            "[[[[4,2],2],6],[8,7]]",
+        ];
+        let sum = numbers
+            .into_iter()
+            .map(parse)
+            .reduce(|lhs, rhs| {
+                println!("{} + {}", lhs, rhs);
+                let result = lhs + rhs;
+                println!("= {}", result);
+                result
+            })
+            .unwrap();
+        assert_eq!(
+            sum,
+            parse("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]")
+        );
+    }
+
+    #[test]
+    fn test_sum_and_magnitude_example() {
+        let numbers = vec![
+            "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]",
+            "[[[5,[2,8]],4],[5,[[9,9],0]]]",
+            "[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]",
+            "[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]",
+            "[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]",
+            "[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]",
+            "[[[[5,4],[7,7]],8],[[8,3],8]]",
+            "[[9,3],[[9,9],[6,[4,9]]]]",
+            "[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]",
+            "[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]",
+        ];
+        let sum = numbers
+            .into_iter()
+            .map(parse)
+            .reduce(|lhs, rhs| {
+                println!("{} + {}", lhs, rhs);
+                let result = lhs + rhs;
+                println!("= {}", result);
+                result
+            })
+            .unwrap();
+        assert_eq!(
+            sum,
+            parse("[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]")
+        );
+        assert_eq!(sum.magnitude(), Ok(4140));
+    }
+
+    // Unreduced number nested up to `depth` levels, mostly along one side to get deep.
+    fn random_number(rng: &mut Rng, depth: usize) -> SnailfishNumber {
+        if depth == 0 || rng.below(8) == 0 {
+            let n = match rng.below(4) {
+                0 => usize::MAX - rng.below(16),
+                _ => rng.below(100),
+            };
+            return SnailfishNumber::regular(n);
+        }
+        let shallow = random_number(rng, depth.min(2) - 1);
+        let deep = random_number(rng, depth - 1);
+        if rng.below(2) == 0 {
+            SnailfishNumber::pair(deep, shallow)
+        } else {
+            SnailfishNumber::pair(shallow, deep)
+        }
+    }
+
+    #[test]
+    fn test_magnitude_overflow() {
+        let mut deep = SnailfishNumber::regular(1);
+        for _ in 0..40 {
+            deep = SnailfishNumber::pair(deep, SnailfishNumber::regular(0));
+        }
+        // 3^40 fits into 64 bits, 3^41 doesn't
+        assert_eq!(deep.magnitude_as::<u64>(), Ok(3u64.pow(40)));
+        let deeper = SnailfishNumber::pair(deep, SnailfishNumber::regular(0));
+        assert_eq!(deeper.magnitude_as::<u64>(), Err(MagnitudeOverflow));
+        assert_eq!(deeper.magnitude_as::<u128>(), Ok(3u128.pow(41)));
+    }
+
+    #[test]
+    fn test_magnitude_properties() {
+        let mut rng = Rng::new(0x9e3779b97f4a7c15);
+        for _ in 0..500 {
+            let depth = 1 + rng.below(90);
+            let number = random_number(&mut rng, depth);
+            let wide = number.magnitude_as::<u128>();
+            let narrow = number.magnitude_as::<u64>();
+            match &wide {
+                // the narrow type overflows exactly if the result doesn't fit into it
+                Ok(wide) => assert_eq!(narrow.ok(), u64::try_from(*wide).ok()),
+                Err(_) => assert_eq!(narrow, Err(MagnitudeOverflow)),
+            }
+
+            #[cfg(feature = "bigint")]
+            {
+                let big = number.magnitude_as::<BigUint>().unwrap();
+                assert_eq!(wide.ok(), u128::try_from(&big).ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_pairwise_magnitude() {
+        let numbers: Vec<_> = vec![
+            "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]",
+            "[[[5,[2,8]],4],[5,[[9,9],0]]]",
+            "[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]",
+            "[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]",
+            "[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]",
+            "[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]",
+            "[[[[5,4],[7,7]],8],[[8,3],8]]",
+            "[[9,3],[[9,9],[6,[4,9]]]]",
+            "[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]",
+            "[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]",
+        ]
+        .into_iter()
+        .map(parse)
+        .collect();
+        assert_eq!(max_pairwise_magnitude(&numbers), Some(3993));
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let number = SnailfishNumber::parse_unreduced("[[[[[9,8],1],2],3],12]").unwrap();
+        assert_eq!(
+            number.render_tree(),
+            "[]
+  []
+    []
+      []
+        [] (explode)
+          9
+          8
+        1
+      2
+    3
+  12 (split)
+"
+        );
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let number = SnailfishNumber::parse_unreduced("[[1,[2,[3,[4,[5,6]]]]],15]").unwrap();
+        let dot = number.render_dot();
+        assert!(dot.starts_with("digraph snailfish {\n"));
+        assert!(dot.ends_with("}\n"));
+        // [4,[5,6]] and [5,6] are both nested inside four pairs
+        assert_eq!(dot.matches("fillcolor=orange").count(), 2);
+        assert_eq!(dot.matches("fillcolor=lightblue").count(), 1);
+        assert!(dot.contains("[shape=box, label=\"15\", style=filled, fillcolor=lightblue];"));
+        // 7 regular numbers and 6 pairs, connected by 12 edges
+        assert_eq!(dot.matches("shape=box").count(), 7);
+        assert_eq!(dot.matches("shape=point").count(), 6);
+        assert_eq!(dot.matches(" -> ").count(), 12);
+
+        let number = SnailfishNumber::parse_unreduced("[1,2]").unwrap();
+        assert_eq!(
+            number.render_dot(),
+            "digraph snailfish {
+  n0 [shape=point];
+  n0 -> n1;
+  n1 [shape=box, label=\"1\"];
+  n0 -> n2;
+  n2 [shape=box, label=\"2\"];
+}
+"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let n = parse("[[1,2],[[3,4],5]]");
+        let json = serde_json::to_string(&n).unwrap();
+        assert_eq!(json, "[[1,2],[[3,4],5]]");
+        assert_eq!(serde_json::from_str::<SnailfishNumber>(&json).unwrap(), n);
+    }
+
+    #[test]
+    fn test_reduction_trace() {
+        use Side::*;
+
+        let number = parse("[[[[4,3],4],4],[7,[[8,4],9]]]") + parse("[1,1]");
+        assert_eq!(number, parse("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"));
+
+        let unreduced = SnailfishNumber::parse_unreduced("[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]");
+        let mut reduction = unreduced.unwrap().reduction();
+        let mut trace = Vec::new();
+        while let Some(step) = reduction.next() {
+            trace.push((step, reduction.number().to_string()));
+        }
+        assert_eq!(
+            trace,
+            vec![
+                (
+                    ReductionStep::Exploded {
+                        path: vec![Left, Left, Left, Left],
+                        left: 4,
+                        right: 3,
+                    },
+                    "[[[[0,7],4],[7,[[8,4],9]]],[1,1]]".to_string()
+                ),
+                (
+                    ReductionStep::Exploded {
+                        path: vec![Left, Right, Right, Left],
+                        left: 8,
+                        right: 4,
+                    },
+                    "[[[[0,7],4],[15,[0,13]]],[1,1]]".to_string()
+                ),
+                (
+                    ReductionStep::Split {
+                        path: vec![Left, Right, Left],
+                        value: 15,
+                    },
+                    "[[[[0,7],4],[[7,8],[0,13]]],[1,1]]".to_string()
+                ),
+                (
+                    ReductionStep::Split {
+                        path: vec![Left, Right, Right, Right],
+                        value: 13,
+                    },
+                    "[[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]".to_string()
+                ),
+                (
+                    ReductionStep::Exploded {
+                        path: vec![Left, Right, Right, Right],
+                        left: 6,
+                        right: 7,
+                    },
+                    "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]".to_string()
+                ),
+            ]
+        );
+        assert_eq!(reduction.into_number(), number);
+        assert_eq!(trace[2].0.to_string(), "split 15 at LRL");
+        assert_eq!(trace[4].0.to_string(), "explode [6,7] at LRRR");
+    }
+
+    #[test]
+    fn test_solution_example() {
+        let (part1, part2) = common::solve::<Day18>(include_str!("../example.txt"));
+        assert_eq!(part1.to_string(), "4140");
+        assert_eq!(part2.to_string(), "3993");
+    }
+}
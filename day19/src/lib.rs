@@ -0,0 +1,1589 @@
+pub use common::cancel::CancellationToken;
+pub use common::hash::{HashMap, HashSet, HashState};
+use common::{input, Answer, FromInput, Solution};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BTreeMap,
+    fmt, ops,
+    str::FromStr,
+};
+use thiserror::Error;
+use tracing::{debug, debug_span, trace};
+
+// Number of beacons two scanners have to share to be considered overlapping.
+pub const MIN_OVERLAP: usize = 12;
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input = Vec<Map>;
+
+    fn parse(input: &str) -> Self::Input {
+        parse_scanners(input)
+    }
+
+    fn part1(scanners: &Self::Input) -> Answer {
+        Self::part1_cancellable(scanners, &CancellationToken::default()).unwrap()
+    }
+
+    fn part2(scanners: &Self::Input) -> Answer {
+        Self::part2_cancellable(scanners, &CancellationToken::default()).unwrap()
+    }
+
+    fn part1_cancellable(scanners: &Self::Input, cancel: &CancellationToken) -> Option<Answer> {
+        let answer = match largest_component(scanners, cancel)? {
+            Ok(component) => component.map.beacons_count().into(),
+            Err(assembly) => no_map(&assembly),
+        };
+        Some(answer)
+    }
+
+    fn part2_cancellable(scanners: &Self::Input, cancel: &CancellationToken) -> Option<Answer> {
+        let answer = match largest_component(scanners, cancel)? {
+            Ok(component) => component.max_scanner_distance().into(),
+            Err(assembly) => no_map(&assembly),
+        };
+        Some(answer)
+    }
+}
+
+// The whole assembly if no two scanners overlap, None if the assembly was cancelled.
+fn largest_component(
+    scanners: &[Map],
+    cancel: &CancellationToken,
+) -> Option<Result<Component, Assembly>> {
+    let mut assembly = compute_map(scanners, cancel);
+    if cancel.is_cancelled() {
+        return None;
+    }
+    if assembly.components.is_empty() {
+        return Some(Err(assembly));
+    }
+    Some(Ok(assembly.components.swap_remove(0)))
+}
+
+fn no_map(assembly: &Assembly) -> Answer {
+    format!("No two scanners overlap (orphans: {:?})", assembly.orphans).into()
+}
+
+#[derive(Error, Debug)]
+pub enum ParseError {
+    #[error("invalid position format, expected 'x,y,z'")]
+    InvalidPositionFormat,
+    #[error("invalid number")]
+    InvalidNumber(#[from] std::num::ParseIntError),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Position3d {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+}
+
+impl Position3d {
+    pub fn manhattan_distance(self, other: Position3d) -> isize {
+        let d = self - other;
+        d.x.abs() + d.y.abs() + d.z.abs()
+    }
+}
+
+impl ops::Add for Position3d {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self::Output {
+        self.x += other.x;
+        self.y += other.y;
+        self.z += other.z;
+        self
+    }
+}
+
+impl ops::Sub for Position3d {
+    type Output = Self;
+
+    fn sub(mut self, other: Self) -> Self::Output {
+        self.x -= other.x;
+        self.y -= other.y;
+        self.z -= other.z;
+        self
+    }
+}
+
+impl FromStr for Position3d {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Position3d, Self::Err> {
+        let mut parts = s.split(',');
+        let x = parts
+            .next()
+            .ok_or(ParseError::InvalidPositionFormat)?
+            .parse()?;
+        let y = parts
+            .next()
+            .ok_or(ParseError::InvalidPositionFormat)?
+            .parse()?;
+        let z = parts
+            .next()
+            .ok_or(ParseError::InvalidPositionFormat)?
+            .parse()?;
+        Ok(Self { x, y, z })
+    }
+}
+
+impl fmt::Display for Position3d {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Distance3d {
+    // Stores the distance in each dimension sorted in ascending order.
+    // Sorting has the advantage that the equality and hashing is orientation invariant.
+    // Storing individual distances instead of the euclidean distance has the advantage
+    // that we can avoid hashing of floating point numbers.
+    dists_sorted: [isize; 3],
+}
+
+impl Distance3d {
+    pub fn new(d1: isize, d2: isize, d3: isize) -> Distance3d {
+        let mut dists = [d1, d2, d3];
+        dists.sort();
+        Self {
+            dists_sorted: dists,
+        }
+    }
+
+    pub fn between(a: &Position3d, b: &Position3d) -> Distance3d {
+        Self::new((a.x - b.x).abs(), (a.y - b.y).abs(), (a.z - b.z).abs())
+    }
+
+    pub fn euclid(&self) -> f64 {
+        self.dists_sorted
+            .iter()
+            .map(|x| (x * x) as f64)
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.dists_sorted.iter().all(|&x| x == 0)
+    }
+}
+
+impl fmt::Debug for Distance3d {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Distance3d")
+            .field("euclid", &self.euclid())
+            .finish()
+    }
+}
+
+// each beacon to all other beacons
+pub fn compute_all_distances(positions: &[Position3d]) -> Vec<Vec<Distance3d>> {
+    positions
+        .iter()
+        .map(|a| {
+            positions
+                .iter()
+                .map(|b| Distance3d::between(a, b))
+                .collect()
+        })
+        .collect()
+}
+
+// allows to look up beacon indices by distance
+pub fn compute_distance_lookup_table(
+    all_distances: &[Vec<Distance3d>],
+) -> HashMap<Distance3d, Vec<(usize, usize)>> {
+    let mut lookup_table: HashMap<_, Vec<_>> = HashMap::default();
+    for (i, distances) in all_distances.iter().enumerate() {
+        for (j, dist) in distances.iter().enumerate() {
+            if dist.is_zero() {
+                continue;
+            }
+            lookup_table.entry(*dist).or_default().push((i, j));
+        }
+    }
+    lookup_table
+}
+
+// Sorted multiset of the distances between all pairs of beacons. Scanners which share at
+// least 12 beacons share at least the C(12, 2) = 66 distances between them.
+pub fn compute_fingerprint(all_distances: &[Vec<Distance3d>]) -> Vec<Distance3d> {
+    let mut fingerprint: Vec<_> = all_distances
+        .iter()
+        .enumerate()
+        .flat_map(|(i, distances)| distances[i + 1..].iter().copied())
+        .collect();
+    fingerprint.sort_unstable();
+    fingerprint
+}
+
+// Size of the intersection of two fingerprints (as multisets).
+pub fn shared_distances(fingerprint1: &[Distance3d], fingerprint2: &[Distance3d]) -> usize {
+    let (mut i, mut j) = (0, 0);
+    let mut shared = 0;
+    while i < fingerprint1.len() && j < fingerprint2.len() {
+        match fingerprint1[i].cmp(&fingerprint2[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    shared
+}
+
+pub fn min_shared_distances(min_overlap: usize) -> usize {
+    min_overlap * min_overlap.saturating_sub(1) / 2
+}
+
+#[derive(Debug, Default)]
+pub struct Map {
+    pub positions: Vec<Position3d>,
+    pub all_distances: Vec<Vec<Distance3d>>,
+    pub distance_to_beacons: HashMap<Distance3d, Vec<(usize, usize)>>,
+    pub fingerprint: Vec<Distance3d>,
+}
+
+impl Map {
+    pub fn new(positions: Vec<Position3d>) -> Map {
+        let all_distances = compute_all_distances(&positions);
+        let distance_to_beacons = compute_distance_lookup_table(&all_distances);
+        let fingerprint = compute_fingerprint(&all_distances);
+        Self {
+            positions,
+            all_distances,
+            distance_to_beacons,
+            fingerprint,
+        }
+    }
+
+    pub fn insert_beacons(&mut self, positions: &[Position3d]) {
+        // TODO orientation and offset
+        self.positions.extend_from_slice(positions);
+        self.positions.sort_unstable();
+        self.positions.dedup();
+
+        self.all_distances = compute_all_distances(&self.positions);
+        self.distance_to_beacons = compute_distance_lookup_table(&self.all_distances);
+        self.fingerprint = compute_fingerprint(&self.all_distances);
+    }
+
+    pub fn beacons_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    // 2D scanner, all beacons lie in the z = 0 plane.
+    pub fn is_planar(&self) -> bool {
+        self.positions.iter().all(|pos| pos.z == 0)
+    }
+
+    // Sorted beacon positions, which don't depend on the order the beacons were reported in.
+    pub fn canonical_beacons(&self) -> Vec<Position3d> {
+        let mut positions = self.positions.clone();
+        positions.sort_unstable();
+        positions
+    }
+
+    // Pairs of distinct beacons at most `epsilon` apart (manhattan distance). Those are likely
+    // the same beacon, seen by two scanners whose alignment is slightly off.
+    pub fn near_duplicates(&self, epsilon: isize) -> Vec<(Position3d, Position3d)> {
+        let positions = self.canonical_beacons();
+        let mut pairs = Vec::new();
+        // sorted by x first, so only the following beacons within epsilon along x qualify
+        for (i, &a) in positions.iter().enumerate() {
+            for &b in positions[i + 1..]
+                .iter()
+                .take_while(|b| b.x - a.x <= epsilon)
+            {
+                if a != b && a.manhattan_distance(b) <= epsilon {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        pairs
+    }
+}
+
+pub fn subsets_of_length<T: Copy>(length: usize, s: &[T]) -> Vec<Vec<T>> {
+    // TODO if copy trait is absent should return references
+    (0..2usize.pow(s.len() as u32))
+        .map(|i| {
+            s.iter()
+                .enumerate()
+                .filter(|&(t, _)| (i >> t) % 2 == 1)
+                .map(|(_, element)| element)
+                .copied()
+                .collect()
+        })
+        .filter(|s: &Vec<T>| s.len() == length)
+        .collect()
+}
+
+pub fn possible_matching_beacons(
+    distance_to_beacons1: &HashMap<Distance3d, Vec<(usize, usize)>>,
+    distance_to_beacons2: &HashMap<Distance3d, Vec<(usize, usize)>>,
+) -> (Vec<usize>, Vec<usize>) {
+    let mut possible_beacons1 = HashSet::default();
+    let mut possible_beacons2 = HashSet::default();
+
+    for (dist1, pairs1) in distance_to_beacons1 {
+        if let Some(pairs2) = distance_to_beacons2.get(dist1) {
+            for &(s1, t1) in pairs1 {
+                possible_beacons1.insert(s1);
+                possible_beacons1.insert(t1);
+            }
+            for &(s2, t2) in pairs2 {
+                possible_beacons2.insert(s2);
+                possible_beacons2.insert(t2);
+            }
+        }
+    }
+
+    (
+        possible_beacons1.into_iter().collect(),
+        possible_beacons2.into_iter().collect(),
+    )
+}
+
+pub fn beacons_with_distances_sorted(
+    all_distances: &[Vec<Distance3d>],
+    beacons: &[usize],
+) -> (Vec<Vec<Distance3d>>, Vec<usize>) {
+    let dinstances_and_beacons: BTreeMap<_, _> = beacons
+        .iter()
+        .map(|&a| {
+            let mut distances = beacons
+                .iter()
+                .map(|&b| all_distances[a][b])
+                .collect::<Vec<_>>();
+            distances.sort_unstable();
+            (distances, a)
+        })
+        .collect();
+    let distances: Vec<_> = dinstances_and_beacons.keys().cloned().collect();
+    let beacons: Vec<_> = dinstances_and_beacons.values().copied().collect();
+    (distances, beacons)
+}
+
+#[derive(Debug, Clone)]
+pub struct Warp {
+    sel: [usize; 3],
+    mul: [isize; 3],
+    ofs: Position3d,
+}
+
+impl Warp {
+    pub fn new(sel: [usize; 3], mul: [isize; 3]) -> Warp {
+        Self {
+            sel,
+            mul,
+            ofs: Position3d::default(),
+        }
+    }
+
+    pub fn warp(&self, pos: Position3d) -> Position3d {
+        let c = [pos.x, pos.y, pos.z];
+        let warped_pos = Position3d {
+            x: c[self.sel[0]] * self.mul[0],
+            y: c[self.sel[1]] * self.mul[1],
+            z: c[self.sel[2]] * self.mul[2],
+        };
+        warped_pos + self.ofs
+    }
+
+    pub fn with_offset(self, ofs: Position3d) -> Warp {
+        Self { ofs, ..self }
+    }
+
+    pub fn second_to_first(p1: Position3d, p2: Position3d) -> Warp {
+        let mut sel = [0, 1, 2];
+        let mut mul = [1; 3];
+
+        if p1.x.abs() == p2.x.abs() {
+            sel[0] = 0;
+            if p1.x != p2.x {
+                mul[0] = -1;
+            }
+        } else if p1.x.abs() == p2.y.abs() {
+            sel[0] = 1;
+            if p1.x != p2.y {
+                mul[0] = -1;
+            }
+        } else if p1.x.abs() == p2.z.abs() {
+            sel[0] = 2;
+            if p1.x != p2.z {
+                mul[0] = -1;
+            }
+        }
+
+        if p1.y.abs() == p2.y.abs() {
+            sel[1] = 1;
+            if p1.y != p2.y {
+                mul[1] = -1;
+            }
+        } else if p1.y.abs() == p2.x.abs() {
+            sel[1] = 0;
+            if p1.y != p2.x {
+                mul[1] = -1;
+            }
+        } else if p1.y.abs() == p2.z.abs() {
+            sel[1] = 2;
+            if p1.y != p2.z {
+                mul[1] = -1;
+            }
+        }
+
+        if p1.z.abs() == p2.z.abs() {
+            sel[2] = 2;
+            if p1.z != p2.z {
+                mul[2] = -1;
+            }
+        } else if p1.z.abs() == p2.x.abs() {
+            sel[2] = 0;
+            if p1.z != p2.x {
+                mul[2] = -1;
+            }
+        } else if p1.z.abs() == p2.y.abs() {
+            sel[2] = 1;
+            if p1.z != p2.y {
+                mul[2] = -1;
+            }
+        }
+
+        Warp::new(sel, mul)
+    }
+}
+
+impl Default for Warp {
+    fn default() -> Warp {
+        Self::new([0, 1, 2], [1; 3])
+    }
+}
+
+// The 8 rotations keeping the z = 0 plane in place: 4 rotations around the z axis, each
+// optionally combined with flipping the scanner upside down, which mirrors the plane.
+pub fn planar_orientations() -> Vec<Warp> {
+    let mut orientations = Vec::with_capacity(8);
+    for swap in [false, true] {
+        for mul_x in [1, -1] {
+            for mul_y in [1, -1] {
+                let sel = if swap { [1, 0, 2] } else { [0, 1, 2] };
+                // z is negated exactly if the xy part is a reflection, hence a proper rotation
+                let det = if swap { -1 } else { 1 } * mul_x * mul_y;
+                orientations.push(Warp::new(sel, [mul_x, mul_y, det]));
+            }
+        }
+    }
+    orientations
+}
+
+// Tries all given orientations of the second scanner and looks for an offset under which at
+// least `min_overlap` beacons coincide. Each pair of beacons votes for the offset aligning them.
+pub fn align_by_orientations(
+    scanner1: &Map,
+    scanner2: &Map,
+    orientations: &[Warp],
+    min_overlap: usize,
+) -> Option<(Position3d, Warp)> {
+    for orientation in orientations {
+        let mut votes: BTreeMap<Position3d, usize> = BTreeMap::new();
+        for &pos2 in &scanner2.positions {
+            let warped = orientation.warp(pos2);
+            for &pos1 in &scanner1.positions {
+                *votes.entry(pos1 - warped).or_default() += 1;
+            }
+        }
+        let best = votes
+            .into_iter()
+            .filter(|&(_, count)| count >= min_overlap)
+            .max_by_key(|&(offset, count)| (count, Reverse(offset)));
+        if let Some((offset, _)) = best {
+            return Some((offset, orientation.clone().with_offset(offset)));
+        }
+    }
+    None
+}
+
+// Whether the rotation of an offset can be told from the offset alone, which needs three
+// different non-zero absolute coordinates.
+fn has_distinct_axes(p: Position3d) -> bool {
+    let (x, y, z) = (p.x.abs(), p.y.abs(), p.z.abs());
+    x != 0 && y != 0 && z != 0 && x != y && y != z && x != z
+}
+
+pub fn compute_relative_position_and_orientation_between(
+    scanner1: &Map,
+    scanner2: &Map,
+    min_overlap: usize,
+) -> Option<(Position3d, Warp)> {
+    // 2D mode, only rotations within the plane (and flips of it) are possible
+    if scanner1.is_planar() && scanner2.is_planar() {
+        return align_by_orientations(scanner1, scanner2, &planar_orientations(), min_overlap);
+    }
+
+    let (beacons1, beacons2) =
+        possible_matching_beacons(&scanner1.distance_to_beacons, &scanner2.distance_to_beacons);
+
+    if beacons1.len() < min_overlap || beacons2.len() < min_overlap {
+        return None;
+    }
+
+    // FIXME compute possible tranformation matrix between beacons
+    // Should map to same origin
+    // REMOVE subset
+
+    let beacons1_subsets = subsets_of_length(min_overlap, &beacons1);
+    let beacons2_subsets = subsets_of_length(min_overlap, &beacons2);
+
+    let distances_beacons1: Vec<_> = beacons1_subsets
+        .into_iter()
+        .map(|subset| beacons_with_distances_sorted(&scanner1.all_distances, &subset))
+        .collect();
+
+    let distances_beacons2: Vec<_> = beacons2_subsets
+        .into_iter()
+        .map(|subset| beacons_with_distances_sorted(&scanner2.all_distances, &subset))
+        .collect();
+
+    for (distances1, beacons1) in &distances_beacons1 {
+        for (distances2, beacons2) in &distances_beacons2 {
+            if distances1 == distances2 {
+                trace!(
+                    beacons1 = ?beacons1.iter().map(|&i| scanner1.positions[i]).collect::<Vec<_>>(),
+                    beacons2 = ?beacons2.iter().map(|&i| scanner2.positions[i]).collect::<Vec<_>>(),
+                    "found overlap"
+                );
+
+                // the sorted subsets list corresponding beacons at the same index
+                let offset = |scanner: &Map, beacons: &[usize], (i, j): (usize, usize)| {
+                    scanner.positions[beacons[i]] - scanner.positions[beacons[j]]
+                };
+                let pairs = (0..beacons1.len())
+                    .flat_map(|i| (0..beacons1.len()).map(move |j| (i, j)))
+                    .filter(|&(i, j)| i != j);
+                let aligned = pairs
+                    .map(|pair| {
+                        (
+                            offset(scanner1, beacons1, pair),
+                            offset(scanner2, beacons2, pair),
+                        )
+                    })
+                    .filter(|&(p1, _)| has_distinct_axes(p1))
+                    .map(|(p1, p2)| (p1, p2, Warp::second_to_first(p1, p2)))
+                    .find(|(p1, p2, warp)| warp.warp(*p2) == *p1);
+                let Some((p1, p2, warp)) = aligned else {
+                    continue;
+                };
+                trace!(%p1, %p2, ?warp, "aligned beacon pair");
+
+                let beacon1 = beacons1[0];
+                let beacon1_pos = scanner1.positions[beacon1];
+
+                let beacon2 = beacons2[0];
+                let beacon2_pos = warp.warp(scanner2.positions[beacon2]);
+
+                let scanner2_pos = beacon1_pos - beacon2_pos;
+                return Some((scanner2_pos, warp.with_offset(scanner2_pos)));
+            }
+        }
+    }
+
+    None
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CandidatePair {
+    pub scanners: (usize, usize),
+    pub shared_distances: usize,
+}
+
+// All pairs of scanners which may overlap, most shared distances first.
+pub fn rank_candidate_pairs(scanners: &[Map], min_overlap: usize) -> Vec<CandidatePair> {
+    let mut candidates = Vec::new();
+    for i in 0..scanners.len() {
+        for j in i + 1..scanners.len() {
+            let shared = shared_distances(&scanners[i].fingerprint, &scanners[j].fingerprint);
+            if shared >= min_shared_distances(min_overlap) {
+                candidates.push(CandidatePair {
+                    scanners: (i, j),
+                    shared_distances: shared,
+                });
+            }
+        }
+    }
+    candidates.sort_by_key(|candidate| (Reverse(candidate.shared_distances), candidate.scanners));
+    candidates
+}
+
+// How the beacons of a scanner were merged into the map of its component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergeStats {
+    pub scanner: usize,
+    // beacons which were already on the map
+    pub matched: usize,
+    // beacons which were new to the map
+    pub added: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Component {
+    pub map: Map,
+    // scanner indices and their positions, relative to the first scanner of the component
+    pub scanners: Vec<(usize, Position3d)>,
+    // in the order the scanners were merged, same as `scanners`
+    pub merges: Vec<MergeStats>,
+}
+
+impl Component {
+    pub fn scanner_positions(&self) -> impl Iterator<Item = Position3d> + '_ {
+        self.scanners.iter().map(|&(_, pos)| pos)
+    }
+
+    // Largest manhattan distance between any two scanners of the component.
+    pub fn max_scanner_distance(&self) -> isize {
+        self.scanner_positions()
+            .flat_map(|p1| {
+                self.scanner_positions()
+                    .map(move |p2| p1.manhattan_distance(p2))
+            })
+            .max()
+            .unwrap()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Assembly {
+    // components with at least two scanners, largest first
+    pub components: Vec<Component>,
+    // scanners which don't overlap with any other scanner
+    pub orphans: Vec<usize>,
+    // scanners left over when the assembly was cancelled
+    pub unassembled: Vec<usize>,
+}
+
+impl Assembly {
+    pub fn is_connected(&self) -> bool {
+        self.components.len() == 1 && self.orphans.is_empty() && self.unassembled.is_empty()
+    }
+}
+
+// Grows a component from the seed scanner until none of the open scanners overlaps with it anymore.
+// Only open scanners sharing enough distances with the component are aligned, best candidates first.
+// Stops early, with the scanners aligned so far, once cancelled.
+pub fn assemble_component(
+    scanners: &[Map],
+    seed: usize,
+    open: &mut Vec<usize>,
+    cancel: &CancellationToken,
+) -> Component {
+    let _span = debug_span!("component", seed).entered();
+    let mut component = Component::default();
+    component.map.insert_beacons(&scanners[seed].positions);
+    component.scanners.push((seed, Position3d::default()));
+    component.merges.push(MergeStats {
+        scanner: seed,
+        matched: 0,
+        added: component.map.beacons_count(),
+    });
+
+    while !cancel.is_cancelled() {
+        let mut candidates: Vec<_> = open
+            .iter()
+            .enumerate()
+            .map(|(i, &index)| {
+                let shared =
+                    shared_distances(&component.map.fingerprint, &scanners[index].fingerprint);
+                (i, shared)
+            })
+            .filter(|&(_, shared)| shared >= min_shared_distances(MIN_OVERLAP))
+            .collect();
+        candidates.sort_by_key(|&(i, shared)| (Reverse(shared), i));
+
+        let Some((i, scanner_pos, warp)) = candidates.into_iter().find_map(|(i, _)| {
+            compute_relative_position_and_orientation_between(
+                &component.map,
+                &scanners[open[i]],
+                MIN_OVERLAP,
+            )
+            .map(|(scanner_pos, warp)| (i, scanner_pos, warp))
+        }) else {
+            break;
+        };
+
+        let scanner_index = open.remove(i);
+        let beacons: Vec<_> = scanners[scanner_index]
+            .positions
+            .iter()
+            .map(|&p| warp.warp(p))
+            .collect();
+        // inserting keeps the positions of the map sorted
+        let matched = beacons
+            .iter()
+            .filter(|p| component.map.positions.binary_search(p).is_ok())
+            .count();
+        let beacons_before = component.map.beacons_count();
+        component.map.insert_beacons(&beacons);
+        component.scanners.push((scanner_index, scanner_pos));
+        let merge = MergeStats {
+            scanner: scanner_index,
+            matched,
+            added: component.map.beacons_count() - beacons_before,
+        };
+        debug!(
+            scanner = scanner_index,
+            position = %scanner_pos,
+            matched,
+            added = merge.added,
+            "merged scanner"
+        );
+        component.merges.push(merge);
+    }
+
+    component
+}
+
+// Scanners are processed in the order of their canonical beacons, so the assembled maps (and
+// the scanner each component's coordinate system is relative to) don't depend on the order
+// of the input. A cancelled assembly contains the components found so far, the scanners not
+// processed yet are reported as unassembled.
+pub fn compute_map(scanners: &[Map], cancel: &CancellationToken) -> Assembly {
+    let mut assembly = Assembly::default();
+
+    let mut open: Vec<_> = (0..scanners.len()).collect();
+    open.sort_by_cached_key(|&i| (scanners[i].canonical_beacons(), i));
+    while !open.is_empty() && !cancel.is_cancelled() {
+        let seed = open.remove(0);
+        let component = assemble_component(scanners, seed, &mut open, cancel);
+        if component.scanners.len() > 1 {
+            assembly.components.push(component);
+        } else if cancel.is_cancelled() {
+            // the seed may still overlap with one of the open scanners
+            open.push(seed);
+        } else {
+            debug!(scanner = seed, "orphan");
+            assembly.orphans.push(seed);
+        }
+    }
+    open.sort_unstable();
+    assembly.unassembled = open;
+
+    // stable sort, components of equal size stay in canonical order
+    assembly
+        .components
+        .sort_by_key(|component| Reverse(component.scanners.len()));
+    assembly.orphans.sort_unstable();
+
+    assembly
+}
+
+// One report per scanner, separated by blank lines.
+pub fn parse_scanners(s: &str) -> Vec<Map> {
+    input::blocks(s).into_iter().map(input::parse).collect()
+}
+
+// The header line of a scanner report, followed by the relative positions of its beacons.
+impl FromInput for Map {
+    type Err = ParseError;
+
+    fn from_input(s: &str) -> Result<Map, Self::Err> {
+        // the scanners are numbered in order, so the header carries no information
+        let beacons = match s.split_once('\n') {
+            Some((header, beacons)) if header.starts_with("--- scanner ") => beacons,
+            _ => s,
+        };
+        Ok(Map::new(FromInput::from_input(beacons)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rng::Rng;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    #[test]
+    fn test_compute_relative_position_and_orientation_between_2d_example_s2() {
+        let positions1 = vec![
+            Position3d { x: 0, y: 2, z: 0 },
+            Position3d { x: 4, y: 1, z: 0 },
+            Position3d { x: 3, y: 3, z: 0 },
+        ];
+        let scanner1 = Map::new(positions1);
+
+        let positions2 = vec![
+            Position3d { x: -1, y: -1, z: 0 },
+            Position3d { x: -5, y: 0, z: 0 },
+            Position3d { x: -2, y: 1, z: 0 },
+        ];
+        let scanner2 = Map::new(positions2);
+
+        let (rel_pos, _) =
+            compute_relative_position_and_orientation_between(&scanner1, &scanner2, 3).unwrap();
+        assert_eq!(rel_pos, Position3d { x: 5, y: 2, z: 0 });
+    }
+
+    #[test]
+    fn test_compute_relative_position_and_orientation_between_2d_example_s3() {
+        let positions1 = vec![
+            Position3d { x: 0, y: 2, z: 0 },
+            Position3d { x: 4, y: 1, z: 0 },
+            Position3d { x: 3, y: 3, z: 0 },
+        ];
+        let scanner1 = Map::new(positions1);
+
+        let positions2 = vec![
+            Position3d { x: -5, y: 1, z: 0 },
+            Position3d { x: -4, y: 5, z: 0 },
+            Position3d { x: -3, y: 2, z: 0 },
+        ];
+        let scanner2 = Map::new(positions2);
+
+        let (rel_pos, _) =
+            compute_relative_position_and_orientation_between(&scanner1, &scanner2, 3).unwrap();
+        assert_eq!(rel_pos, Position3d { x: 5, y: 6, z: 0 });
+    }
+
+    #[test]
+    fn test_compute_relative_position_and_orientation_between_2d_example_s4() {
+        let positions1 = vec![
+            Position3d { x: 0, y: 2, z: 0 },
+            Position3d { x: 4, y: 1, z: 0 },
+            Position3d { x: 3, y: 3, z: 0 },
+        ];
+        let scanner1 = Map::new(positions1);
+
+        let positions2 = vec![
+            Position3d { x: -3, y: 3, z: 0 },
+            Position3d { x: 1, y: 2, z: 0 },
+            Position3d { x: -2, y: 1, z: 0 },
+        ];
+        let scanner2 = Map::new(positions2);
+
+        let (rel_pos, _) =
+            compute_relative_position_and_orientation_between(&scanner1, &scanner2, 3).unwrap();
+        assert_eq!(rel_pos, Position3d { x: 1, y: 4, z: 0 });
+    }
+
+    #[test]
+    fn test_compute_relative_position_and_orientation_between_2d_example_s5() {
+        let positions1 = vec![
+            Position3d { x: 0, y: 2, z: 0 },
+            Position3d { x: 4, y: 1, z: 0 },
+            Position3d { x: 3, y: 3, z: 0 },
+        ];
+        let scanner1 = Map::new(positions1);
+
+        let positions2 = vec![
+            Position3d { x: 3, y: 5, z: 0 },
+            Position3d { x: 2, y: 1, z: 0 },
+            Position3d { x: 1, y: 4, z: 0 },
+        ];
+        let scanner2 = Map::new(positions2);
+
+        let (rel_pos, _) =
+            compute_relative_position_and_orientation_between(&scanner1, &scanner2, 3).unwrap();
+        assert_eq!(rel_pos, Position3d { x: -1, y: 4, z: 0 });
+    }
+
+    #[test]
+    fn test_planar_orientations() {
+        let orientations = planar_orientations();
+        assert_eq!(orientations.len(), 8);
+
+        let pos = Position3d { x: 1, y: 2, z: 0 };
+        let mut images: Vec<_> = orientations.iter().map(|o| o.warp(pos)).collect();
+        images.sort_unstable();
+        images.dedup();
+        assert_eq!(images.len(), 8);
+        assert!(images.iter().all(|image| image.z == 0));
+
+        // proper rotations, the z axis is flipped exactly for the mirroring ones
+        let up = Position3d { x: 0, y: 0, z: 1 };
+        let mirrored = orientations.iter().filter(|o| o.warp(up).z == -1).count();
+        assert_eq!(mirrored, 4);
+    }
+
+    #[test]
+    fn test_planar_mode_is_detected() {
+        let planar = Map::new(vec![
+            Position3d { x: 1, y: 2, z: 0 },
+            Position3d { x: -3, y: 4, z: 0 },
+        ]);
+        let spatial = Map::new(vec![
+            Position3d { x: 1, y: 2, z: 0 },
+            Position3d { x: -3, y: 4, z: 5 },
+        ]);
+        assert!(planar.is_planar());
+        assert!(!spatial.is_planar());
+    }
+
+    #[test]
+    fn test_planar_alignment_in_all_orientations() {
+        let mut rng = Rng::new(0x9e3779b97f4a7c15);
+        let mut next = move || rng.below(2000) as isize - 1000;
+        let beacons: Vec<_> = (0..6)
+            .map(|_| Position3d {
+                x: next(),
+                y: next(),
+                z: 0,
+            })
+            .collect();
+        let scanner1 = Map::new(beacons.clone());
+
+        let offset = Position3d {
+            x: 17,
+            y: -42,
+            z: 0,
+        };
+        for orientation in planar_orientations() {
+            // the scanner sees the beacons relative to its position, in the inverse orientation
+            let probe = Position3d { x: 1, y: 2, z: 3 };
+            let inverse = planar_orientations()
+                .into_iter()
+                .find(|inverse| orientation.warp(inverse.warp(probe)) == probe)
+                .unwrap();
+            let seen: Vec<_> = beacons
+                .iter()
+                .map(|&pos| inverse.warp(pos - offset))
+                .collect();
+            let scanner2 = Map::new(seen.clone());
+
+            let (scanner_pos, warp) =
+                compute_relative_position_and_orientation_between(&scanner1, &scanner2, 6).unwrap();
+            assert_eq!(scanner_pos, offset);
+            let mut aligned: Vec<_> = seen.iter().map(|&p| warp.warp(p)).collect();
+            aligned.sort_unstable();
+            let mut expected = beacons.clone();
+            expected.sort_unstable();
+            assert_eq!(aligned, expected);
+        }
+    }
+
+    #[test]
+    fn test_planar_scanners_without_overlap() {
+        let scanner1 = Map::new(vec![
+            Position3d { x: 0, y: 0, z: 0 },
+            Position3d { x: 1, y: 0, z: 0 },
+            Position3d { x: 0, y: 5, z: 0 },
+        ]);
+        let scanner2 = Map::new(vec![
+            Position3d { x: 0, y: 0, z: 0 },
+            Position3d { x: 2, y: 0, z: 0 },
+            Position3d { x: 0, y: 7, z: 0 },
+        ]);
+        assert!(
+            compute_relative_position_and_orientation_between(&scanner1, &scanner2, 3).is_none()
+        );
+        // two beacons can always be aligned
+        assert!(
+            compute_relative_position_and_orientation_between(&scanner1, &scanner2, 1).is_some()
+        );
+    }
+
+    #[test]
+    fn test_compute_relative_position_and_orientation_between_3d_example_scanner0_scanner1() {
+        let positions1 = vec![
+            "404,-588,-901",
+            "528,-643,409",
+            "-838,591,734",
+            "390,-675,-793",
+            "-537,-823,-458",
+            "-485,-357,347",
+            "-345,-311,381",
+            "-661,-816,-575",
+            "-876,649,763",
+            "-618,-824,-621",
+            "553,345,-567",
+            "474,580,667",
+            "-447,-329,318",
+            "-584,868,-557",
+            "544,-627,-890",
+            "564,392,-477",
+            "455,729,728",
+            "-892,524,684",
+            "-689,845,-530",
+            "423,-701,434",
+            "7,-33,-71",
+            "630,319,-379",
+            "443,580,662",
+            "-789,900,-551",
+            "459,-707,401",
+        ];
+        let positions1 = positions1
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let scanner1 = Map::new(positions1);
+
+        let positions2 = vec![
+            "686,422,578",
+            "605,423,415",
+            "515,917,-361",
+            "-336,658,858",
+            "95,138,22",
+            "-476,619,847",
+            "-340,-569,-846",
+            "567,-361,727",
+            "-460,603,-452",
+            "669,-402,600",
+            "729,430,532",
+            "-500,-761,534",
+            "-322,571,750",
+            "-466,-666,-811",
+            "-429,-592,574",
+            "-355,545,-477",
+            "703,-491,-529",
+            "-328,-685,520",
+            "413,935,-424",
+            "-391,539,-444",
+            "586,-435,557",
+            "-364,-763,-893",
+            "807,-499,-711",
+            "755,-354,-619",
+            "553,889,-390",
+        ];
+        let positions2 = positions2
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let scanner2 = Map::new(positions2);
+
+        let (rel_pos, _) =
+            compute_relative_position_and_orientation_between(&scanner1, &scanner2, 12).unwrap();
+        assert_eq!(
+            rel_pos,
+            Position3d {
+                x: 68,
+                y: -1246,
+                z: -43,
+            }
+        );
+    }
+
+    #[test]
+    fn test_compute_relative_position_and_orientation_between_3d_example_scanner1_scanner4() {
+        let positions1 = vec![
+            "686,422,578",
+            "605,423,415",
+            "515,917,-361",
+            "-336,658,858",
+            "95,138,22",
+            "-476,619,847",
+            "-340,-569,-846",
+            "567,-361,727",
+            "-460,603,-452",
+            "669,-402,600",
+            "729,430,532",
+            "-500,-761,534",
+            "-322,571,750",
+            "-466,-666,-811",
+            "-429,-592,574",
+            "-355,545,-477",
+            "703,-491,-529",
+            "-328,-685,520",
+            "413,935,-424",
+            "-391,539,-444",
+            "586,-435,557",
+            "-364,-763,-893",
+            "807,-499,-711",
+            "755,-354,-619",
+            "553,889,-390",
+        ];
+        let positions1 = positions1
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let scanner1 = Map::new(positions1);
+
+        let positions2 = vec![
+            "727,592,562",
+            "-293,-554,779",
+            "441,611,-461",
+            "-714,465,-776",
+            "-743,427,-804",
+            "-660,-479,-426",
+            "832,-632,460",
+            "927,-485,-438",
+            "408,393,-506",
+            "466,436,-512",
+            "110,16,151",
+            "-258,-428,682",
+            "-393,719,612",
+            "-211,-452,876",
+            "808,-476,-593",
+            "-575,615,604",
+            "-485,667,467",
+            "-680,325,-822",
+            "-627,-443,-432",
+            "872,-547,-609",
+            "833,512,582",
+            "807,604,487",
+            "839,-516,451",
+            "891,-625,532",
+            "-652,-548,-490",
+            "30,-46,-14",
+        ];
+        let positions2 = positions2
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let scanner2 = Map::new(positions2);
+
+        let (rel_pos, _) =
+            compute_relative_position_and_orientation_between(&scanner1, &scanner2, 12).unwrap();
+        // the puzzle gives -20,-1133,1061 relative to scanner 0, scanner 1 is at 68,-1246,-43 and
+        // facing the opposite x and z direction
+        assert_eq!(
+            rel_pos,
+            Position3d {
+                x: 88,
+                y: 113,
+                z: -1104,
+            }
+        );
+    }
+
+    #[test]
+    fn test_compute_all_distances() {
+        let positions = [
+            Position3d { x: 0, y: 2, z: 0 },
+            Position3d { x: 4, y: 1, z: 0 },
+            Position3d { x: 3, y: 3, z: 0 },
+        ];
+
+        let distances = compute_all_distances(&positions);
+        assert_eq!(
+            distances,
+            vec![
+                vec![
+                    Distance3d::between(&positions[0], &positions[0]),
+                    Distance3d::between(&positions[0], &positions[1]),
+                    Distance3d::between(&positions[0], &positions[2])
+                ],
+                vec![
+                    Distance3d::between(&positions[1], &positions[0]),
+                    Distance3d::between(&positions[1], &positions[1]),
+                    Distance3d::between(&positions[1], &positions[2])
+                ],
+                vec![
+                    Distance3d::between(&positions[2], &positions[0]),
+                    Distance3d::between(&positions[2], &positions[1]),
+                    Distance3d::between(&positions[2], &positions[2])
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compute_distance_lookup_table() {
+        let positions = [
+            Position3d { x: 0, y: 2, z: 0 },
+            Position3d { x: 4, y: 1, z: 0 },
+            Position3d { x: 8, y: 0, z: 0 },
+        ];
+
+        let distances = compute_all_distances(&positions);
+        let dist_lookup_table = compute_distance_lookup_table(&distances);
+        assert_eq!(
+            dist_lookup_table,
+            vec![
+                (
+                    Distance3d::new(4, 1, 0),
+                    vec![(0, 1), (1, 0), (1, 2), (2, 1)]
+                ),
+                (Distance3d::new(8, 2, 0), vec![(0, 2), (2, 0)]),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn test_distance_3d_orientation_invariance() {
+        let dist1 = Distance3d::between(
+            &Position3d { x: 2, y: 5, z: -1 },
+            &Position3d { x: 8, y: -1, z: 4 },
+        );
+        let dist2 = Distance3d::between(
+            &Position3d { x: 8, y: -1, z: 4 },
+            &Position3d { x: 2, y: 5, z: -1 },
+        );
+        let dist3 = Distance3d::between(
+            &Position3d { x: 4, y: 2, z: -1 },
+            &Position3d { x: -1, y: 8, z: 5 },
+        );
+
+        assert_eq!(dist1, dist2);
+        assert_eq!(dist2, dist3);
+
+        assert_eq!(calculate_hash(&dist1), calculate_hash(&dist2));
+        assert_eq!(calculate_hash(&dist2), calculate_hash(&dist3));
+    }
+
+    fn calculate_hash<T: Hash>(t: &T) -> u64 {
+        let mut s = DefaultHasher::new();
+        t.hash(&mut s);
+        s.finish()
+    }
+
+    #[test]
+    fn test_subsets_of_length() {
+        let v = vec![1, 2, 3];
+        assert_eq!(subsets_of_length(1, &v), [[1], [2], [3]]);
+        assert_eq!(subsets_of_length(2, &v), [[1, 2], [1, 3], [2, 3]]);
+        assert_eq!(subsets_of_length(3, &v), [[1, 2, 3]]);
+    }
+
+    const EXAMPLE_SCANNER0: &str = "404,-588,-901
+528,-643,409
+-838,591,734
+390,-675,-793
+-537,-823,-458
+-485,-357,347
+-345,-311,381
+-661,-816,-575
+-876,649,763
+-618,-824,-621
+553,345,-567
+474,580,667
+-447,-329,318
+-584,868,-557
+544,-627,-890
+564,392,-477
+455,729,728
+-892,524,684
+-689,845,-530
+423,-701,434
+7,-33,-71
+630,319,-379
+443,580,662
+-789,900,-551
+459,-707,401";
+
+    const EXAMPLE_SCANNER1: &str = "686,422,578
+605,423,415
+515,917,-361
+-336,658,858
+95,138,22
+-476,619,847
+-340,-569,-846
+567,-361,727
+-460,603,-452
+669,-402,600
+729,430,532
+-500,-761,534
+-322,571,750
+-466,-666,-811
+-429,-592,574
+-355,545,-477
+703,-491,-529
+-328,-685,520
+413,935,-424
+-391,539,-444
+586,-435,557
+-364,-763,-893
+807,-499,-711
+755,-354,-619
+553,889,-390";
+
+    const EXAMPLE_SCANNER4: &str = "727,592,562
+-293,-554,779
+441,611,-461
+-714,465,-776
+-743,427,-804
+-660,-479,-426
+832,-632,460
+927,-485,-438
+408,393,-506
+466,436,-512
+110,16,151
+-258,-428,682
+-393,719,612
+-211,-452,876
+808,-476,-593
+-575,615,604
+-485,667,467
+-680,325,-822
+-627,-443,-432
+872,-547,-609
+833,512,582
+807,604,487
+839,-516,451
+891,-625,532
+-652,-548,-490
+30,-46,-14";
+
+    #[test]
+    fn test_compute_map_cancelled() {
+        let example = |s: &str| Map::new(s.lines().map(|s| s.parse().unwrap()).collect());
+        let scanners = vec![example(EXAMPLE_SCANNER0), example(EXAMPLE_SCANNER1)];
+
+        let cancel = CancellationToken::default();
+        cancel.cancel();
+        let assembly = compute_map(&scanners, &cancel);
+        assert!(assembly.components.is_empty());
+        assert!(assembly.orphans.is_empty());
+        assert_eq!(assembly.unassembled, vec![0, 1]);
+        assert!(!assembly.is_connected());
+        assert_eq!(Day19::part1_cancellable(&scanners, &cancel), None);
+    }
+
+    #[test]
+    fn test_compute_map_disconnected() {
+        // synthetic scanners far away from the example ones
+        let mut rng = Rng::new(0x2545f4914f6cdd1d);
+        let mut next = move || rng.below(1000) as isize - 500;
+        let mut random_positions = |n: usize| -> Vec<Position3d> {
+            (0..n)
+                .map(|_| Position3d {
+                    x: next(),
+                    y: next(),
+                    z: next(),
+                })
+                .collect()
+        };
+
+        let island = random_positions(14);
+        let offset = Position3d {
+            x: 13,
+            y: -170,
+            z: 42,
+        };
+        let island_shifted: Vec<_> = island.iter().map(|&p| p + offset).collect();
+        let orphan = random_positions(20);
+
+        let example = |s: &str| Map::new(s.lines().map(|s| s.parse().unwrap()).collect());
+        let scanners = vec![
+            example(EXAMPLE_SCANNER0),
+            example(EXAMPLE_SCANNER1),
+            Map::new(island),
+            example(EXAMPLE_SCANNER4),
+            Map::new(orphan),
+            Map::new(island_shifted),
+        ];
+
+        let assembly = compute_map(&scanners, &CancellationToken::default());
+        assert!(!assembly.is_connected());
+        assert_eq!(assembly.orphans, vec![4]);
+        assert_eq!(assembly.components.len(), 2);
+
+        let mut scanners = assembly.components[0].scanners.clone();
+        scanners.sort_unstable();
+        assert_eq!(
+            scanners,
+            vec![
+                (0, Position3d::default()),
+                (
+                    1,
+                    Position3d {
+                        x: 68,
+                        y: -1246,
+                        z: -43,
+                    }
+                ),
+                (
+                    3,
+                    Position3d {
+                        x: -20,
+                        y: -1133,
+                        z: 1061,
+                    }
+                ),
+            ]
+        );
+
+        assert_eq!(
+            assembly.components[1].scanners,
+            vec![
+                (2, Position3d::default()),
+                (
+                    5,
+                    Position3d {
+                        x: -13,
+                        y: 170,
+                        z: -42,
+                    }
+                ),
+            ]
+        );
+        assert_eq!(assembly.components[1].map.beacons_count(), 14);
+    }
+
+    #[test]
+    fn test_fingerprint_ranking() {
+        let example = |s: &str| Map::new(s.lines().map(|s| s.parse().unwrap()).collect());
+        let scanners = vec![
+            example(EXAMPLE_SCANNER0),
+            example(EXAMPLE_SCANNER1),
+            example(EXAMPLE_SCANNER4),
+        ];
+        assert_eq!(scanners[0].fingerprint.len(), 25 * 24 / 2);
+        assert_eq!(
+            shared_distances(&scanners[0].fingerprint, &scanners[0].fingerprint),
+            scanners[0].fingerprint.len()
+        );
+        assert_eq!(min_shared_distances(MIN_OVERLAP), 66);
+
+        // scanner 4 only overlaps with scanner 1
+        assert_eq!(
+            rank_candidate_pairs(&scanners, MIN_OVERLAP),
+            vec![
+                CandidatePair {
+                    scanners: (0, 1),
+                    shared_distances: 66,
+                },
+                CandidatePair {
+                    scanners: (1, 2),
+                    shared_distances: 66,
+                },
+            ]
+        );
+        assert!(shared_distances(&scanners[0].fingerprint, &scanners[2].fingerprint) < 66);
+    }
+
+    #[test]
+    fn test_compute_map_independent_of_input_order() {
+        let example =
+            |s: &str| -> Vec<Position3d> { s.lines().map(|s| s.parse().unwrap()).collect() };
+        let scanners = [
+            example(EXAMPLE_SCANNER0),
+            example(EXAMPLE_SCANNER1),
+            example(EXAMPLE_SCANNER4),
+        ];
+        let maps: Vec<_> = scanners.iter().map(|s| Map::new(s.clone())).collect();
+        let expected = compute_map(&maps, &CancellationToken::default());
+        assert!(expected.is_connected());
+
+        for permutation in [[1, 0, 2], [2, 1, 0], [1, 2, 0]] {
+            // also report the beacons in reverse order
+            let shuffled: Vec<_> = permutation
+                .iter()
+                .map(|&i| Map::new(scanners[i].iter().rev().copied().collect()))
+                .collect();
+            let assembly = compute_map(&shuffled, &CancellationToken::default());
+            assert!(assembly.is_connected());
+
+            let component = &assembly.components[0];
+            assert_eq!(
+                component.map.positions,
+                expected.components[0].map.positions
+            );
+            let scanners: Vec<_> = component
+                .scanners
+                .iter()
+                .map(|&(i, pos)| (permutation[i], pos))
+                .collect();
+            assert_eq!(scanners, expected.components[0].scanners);
+        }
+    }
+
+    #[test]
+    fn test_merge_stats() {
+        let example = |s: &str| Map::new(s.lines().map(|s| s.parse().unwrap()).collect());
+        let scanners = vec![
+            example(EXAMPLE_SCANNER0),
+            example(EXAMPLE_SCANNER1),
+            example(EXAMPLE_SCANNER4),
+        ];
+        let assembly = compute_map(&scanners, &CancellationToken::default());
+        let component = &assembly.components[0];
+
+        let merged: Vec<_> = component.merges.iter().map(|m| m.scanner).collect();
+        let aligned: Vec<_> = component.scanners.iter().map(|&(i, _)| i).collect();
+        assert_eq!(merged, aligned);
+
+        assert_eq!(component.merges[0].matched, 0);
+        assert!(component.merges[1..]
+            .iter()
+            .all(|merge| merge.matched >= MIN_OVERLAP));
+        for merge in &component.merges {
+            assert_eq!(
+                merge.matched + merge.added,
+                scanners[merge.scanner].beacons_count()
+            );
+        }
+        assert_eq!(
+            component.merges.iter().map(|m| m.added).sum::<usize>(),
+            component.map.beacons_count()
+        );
+
+        assert!(component.map.near_duplicates(10).is_empty());
+    }
+
+    #[test]
+    fn test_near_duplicates() {
+        let pos = |x, y, z| Position3d { x, y, z };
+        let map = Map::new(vec![
+            pos(0, 0, 0),
+            pos(100, 0, 0),
+            pos(1, 1, 0),
+            pos(-500, 20, 7),
+            pos(101, -1, 1),
+            pos(104, 0, 0),
+        ]);
+        assert_eq!(map.near_duplicates(2), vec![(pos(0, 0, 0), pos(1, 1, 0))]);
+        assert_eq!(
+            map.near_duplicates(3),
+            vec![
+                (pos(0, 0, 0), pos(1, 1, 0)),
+                (pos(100, 0, 0), pos(101, -1, 1)),
+            ]
+        );
+        assert_eq!(map.near_duplicates(4).len(), 3);
+        assert_eq!(map.near_duplicates(5).len(), 4);
+        assert!(map.near_duplicates(1).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_position_serde_round_trip() {
+        let pos = Position3d {
+            x: 68,
+            y: -1246,
+            z: -43,
+        };
+        let json = serde_json::to_string(&pos).unwrap();
+        assert_eq!(json, r#"{"x":68,"y":-1246,"z":-43}"#);
+        assert_eq!(serde_json::from_str::<Position3d>(&json).unwrap(), pos);
+    }
+
+    #[test]
+    fn test_solution_example() {
+        let (part1, part2) = common::solve::<Day19>(include_str!("../example.txt"));
+        assert_eq!(part1.to_string(), "79");
+        assert_eq!(part2.to_string(), "3621");
+    }
+
+    #[test]
+    fn test_has_distinct_axes() {
+        let p = |x, y, z| Position3d { x, y, z };
+        assert!(has_distinct_axes(p(1, -2, 3)));
+        // the stored input has an offset with equal x and z like this one
+        assert!(!has_distinct_axes(p(-4, 7, -4)));
+        assert!(!has_distinct_axes(p(3, -3, 1)));
+        assert!(!has_distinct_axes(p(0, 2, 5)));
+    }
+
+    #[test]
+    fn test_solution_without_overlap() {
+        let input = format!("--- scanner 0 ---\n{}\n", EXAMPLE_SCANNER0);
+        let (part1, part2) = common::solve::<Day19>(&input);
+        assert_eq!(part1.to_string(), "No two scanners overlap (orphans: [0])");
+        assert_eq!(part2, part1);
+    }
+}
@@ -0,0 +1,36 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use day2::*;
+
+// 3 million commands
+fn repeated_course() -> Vec<Command> {
+    let lines: Vec<String> = "repeat 1000 {\nrepeat 1000 {\ndown 1\nforward 2\nup 1\n}\n}"
+        .lines()
+        .map(str::to_owned)
+        .collect();
+    parse_lines(&lines).unwrap()
+}
+
+fn executors(c: &mut Criterion) {
+    let course = repeated_course();
+    let extended: Vec<_> = course
+        .iter()
+        .cloned()
+        .map(ExtendedCommand::Classic)
+        .collect();
+
+    let mut group = c.benchmark_group("repeated course");
+    group.sample_size(10);
+    group.bench_function("part1", |b| {
+        b.iter(|| execute_course_part1(Position::default(), black_box(&course)))
+    });
+    group.bench_function("part2", |b| {
+        b.iter(|| execute_course_part2(PositionWithAim::default(), black_box(&course)))
+    });
+    group.bench_function("3d", |b| {
+        b.iter(|| execute_course_3d(Position3D::default(), black_box(&extended)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, executors);
+criterion_main!(benches);
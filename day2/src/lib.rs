@@ -0,0 +1,530 @@
+use common::{Answer, Solution};
+use std::{
+    fmt,
+    io::{self, Write},
+    str::FromStr,
+};
+use thiserror::Error;
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Vec<Command>;
+
+    fn parse(input: &str) -> Self::Input {
+        let lines: Vec<String> = input.lines().map(String::from).collect();
+        parse_lines(&lines).unwrap()
+    }
+
+    fn part1(course: &Self::Input) -> Answer {
+        let final_pos = execute_course_part1(Position::default(), course);
+        (final_pos.horizontal * final_pos.depth).into()
+    }
+
+    fn part2(course: &Self::Input) -> Answer {
+        let final_pos = execute_course_part2(PositionWithAim::default(), course);
+        (final_pos.horizontal * final_pos.depth).into()
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ParseError {
+    #[error("invalid format, expected '<command> <steps>' but was '{0}'")]
+    InvalidFormat(String),
+    #[error("invalid number")]
+    InvalidNumber(#[from] std::num::ParseIntError),
+    #[error("invalid command '{0}'")]
+    InvalidCommand(String),
+    #[error("invalid repeat, expected 'repeat <count> {{' but was '{0}'")]
+    InvalidRepeat(String),
+    #[error("'}}' without matching repeat")]
+    UnmatchedBlockEnd,
+    #[error("{0} repeat block(s) not closed")]
+    UnclosedBlock(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Forward(i64),
+    Down(i64),
+    Up(i64),
+}
+
+impl FromStr for Command {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Command, Self::Err> {
+        if let Some((cmd, x)) = input.split_once(' ') {
+            let x: i64 = x.trim().parse()?;
+            match cmd {
+                "forward" => Ok(Self::Forward(x)),
+                "down" => Ok(Self::Down(x)),
+                "up" => Ok(Self::Up(x)),
+                _ => Err(ParseError::InvalidCommand(cmd.to_owned())),
+            }
+        } else {
+            Err(ParseError::InvalidFormat(input.to_owned()))
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Forward(x) => write!(f, "forward {}", x),
+            Self::Down(x) => write!(f, "down {}", x),
+            Self::Up(x) => write!(f, "up {}", x),
+        }
+    }
+}
+
+// Command set of the 3D variant, a superset of the classic commands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExtendedCommand {
+    Classic(Command),
+    // quarter turns
+    Left(i64),
+    Right(i64),
+    // immediate depth changes, independent of the aim
+    Climb(i64),
+    Dive(i64),
+}
+
+impl FromStr for ExtendedCommand {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<ExtendedCommand, Self::Err> {
+        if let Some((cmd, x)) = input.split_once(' ') {
+            let extended = match cmd {
+                "left" => Self::Left,
+                "right" => Self::Right,
+                "climb" => Self::Climb,
+                "dive" => Self::Dive,
+                _ => return input.parse().map(Self::Classic),
+            };
+            Ok(extended(x.trim().parse()?))
+        } else {
+            Err(ParseError::InvalidFormat(input.to_owned()))
+        }
+    }
+}
+
+impl fmt::Display for ExtendedCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Classic(cmd) => write!(f, "{}", cmd),
+            Self::Left(x) => write!(f, "left {}", x),
+            Self::Right(x) => write!(f, "right {}", x),
+            Self::Climb(x) => write!(f, "climb {}", x),
+            Self::Dive(x) => write!(f, "dive {}", x),
+        }
+    }
+}
+
+pub fn write_course<T: fmt::Display, W: Write>(course: &[T], mut writer: W) -> io::Result<()> {
+    for cmd in course {
+        writeln!(writer, "{}", cmd)?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct Position {
+    pub horizontal: i64,
+    pub depth: i64,
+}
+
+pub fn execute_course_part1(initial_pos: Position, course: &[Command]) -> Position {
+    course.iter().fold(initial_pos, |pos, cmd| match cmd {
+        Command::Forward(x) => Position {
+            horizontal: pos.horizontal + x,
+            ..pos
+        },
+        Command::Down(x) => Position {
+            depth: pos.depth + x,
+            ..pos
+        },
+        Command::Up(x) => Position {
+            depth: pos.depth - x,
+            ..pos
+        },
+    })
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct PositionWithAim {
+    pub horizontal: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+pub fn execute_course_part2(initial_pos: PositionWithAim, course: &[Command]) -> PositionWithAim {
+    course.iter().fold(initial_pos, |pos, cmd| match cmd {
+        Command::Forward(x) => PositionWithAim {
+            horizontal: pos.horizontal + x,
+            depth: pos.depth + pos.aim * x,
+            ..pos
+        },
+        Command::Down(x) => PositionWithAim {
+            aim: pos.aim + x,
+            ..pos
+        },
+        Command::Up(x) => PositionWithAim {
+            aim: pos.aim - x,
+            ..pos
+        },
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heading {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Heading {
+    const CLOCKWISE: [Heading; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    fn turn(self, quarter_turns: i64) -> Heading {
+        let index = Self::CLOCKWISE.iter().position(|&h| h == self).unwrap() as i64;
+        Self::CLOCKWISE[(index + quarter_turns).rem_euclid(4) as usize]
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Position3D {
+    pub north: i64,
+    pub east: i64,
+    pub depth: i64,
+    pub aim: i64,
+    pub heading: Heading,
+}
+
+impl Default for Position3D {
+    fn default() -> Self {
+        // facing east, so the classic commands behave as in part 2
+        Self {
+            north: 0,
+            east: 0,
+            depth: 0,
+            aim: 0,
+            heading: Heading::East,
+        }
+    }
+}
+
+impl fmt::Display for Position3D {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "north {}, east {}, depth {}, aim {}, heading {:?}",
+            self.north, self.east, self.depth, self.aim, self.heading
+        )
+    }
+}
+
+pub fn execute_course_3d(initial_pos: Position3D, course: &[ExtendedCommand]) -> Position3D {
+    course.iter().fold(initial_pos, |pos, cmd| match cmd {
+        ExtendedCommand::Classic(Command::Forward(x)) => {
+            let (north, east) = match pos.heading {
+                Heading::North => (pos.north + x, pos.east),
+                Heading::East => (pos.north, pos.east + x),
+                Heading::South => (pos.north - x, pos.east),
+                Heading::West => (pos.north, pos.east - x),
+            };
+            Position3D {
+                north,
+                east,
+                depth: pos.depth + pos.aim * x,
+                ..pos
+            }
+        }
+        ExtendedCommand::Classic(Command::Down(x)) => Position3D {
+            aim: pos.aim + x,
+            ..pos
+        },
+        ExtendedCommand::Classic(Command::Up(x)) => Position3D {
+            aim: pos.aim - x,
+            ..pos
+        },
+        ExtendedCommand::Left(x) => Position3D {
+            heading: pos.heading.turn(-x),
+            ..pos
+        },
+        ExtendedCommand::Right(x) => Position3D {
+            heading: pos.heading.turn(*x),
+            ..pos
+        },
+        ExtendedCommand::Climb(x) => Position3D {
+            depth: pos.depth - x,
+            ..pos
+        },
+        ExtendedCommand::Dive(x) => Position3D {
+            depth: pos.depth + x,
+            ..pos
+        },
+    })
+}
+
+// Parses the course into a flat command list. Blocks of the form
+//
+//   repeat <count> {
+//   ...
+//   }
+//
+// can be nested and are expanded while parsing.
+pub fn parse_lines<T>(lines: &[String]) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr<Err = ParseError> + Clone,
+{
+    let mut course = Vec::new();
+    // repeat count and start of the block in the course
+    let mut blocks: Vec<(usize, usize)> = Vec::new();
+
+    for line in lines {
+        let line = line.trim();
+        if let Some(repeat) = line.strip_prefix("repeat ") {
+            let count = repeat
+                .strip_suffix('{')
+                .ok_or_else(|| ParseError::InvalidRepeat(line.to_owned()))?;
+            blocks.push((count.trim().parse()?, course.len()));
+        } else if line == "}" {
+            let (count, start) = blocks.pop().ok_or(ParseError::UnmatchedBlockEnd)?;
+            if count == 0 {
+                course.truncate(start);
+            } else {
+                let end = course.len();
+                for _ in 1..count {
+                    course.extend_from_within(start..end);
+                }
+            }
+        } else {
+            course.push(line.parse()?);
+        }
+    }
+
+    if blocks.is_empty() {
+        Ok(course)
+    } else {
+        Err(ParseError::UnclosedBlock(blocks.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rng::Rng;
+
+    #[test]
+    fn test_solution_example() {
+        assert_eq!(
+            common::solve::<Day2>(include_str!("../example.txt")),
+            (Answer::from(150i64), Answer::from(900i64))
+        );
+    }
+
+    fn generate_course(len: usize, seed: u64) -> Vec<Command> {
+        let mut rng = Rng::new(seed);
+        (0..len)
+            .map(|_| {
+                let x = rng.below(2_000_000) as i64 - 1_000_000;
+                match rng.below(3) {
+                    0 => Command::Forward(x),
+                    1 => Command::Down(x),
+                    _ => Command::Up(x),
+                }
+            })
+            .collect()
+    }
+
+    fn parse_course(s: &str) -> Vec<Command> {
+        s.lines().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_course_round_trip() {
+        for seed in 1..=100 {
+            let course = generate_course(seed as usize % 50, seed);
+
+            let mut written = Vec::new();
+            write_course(&course, &mut written).unwrap();
+            let written = String::from_utf8(written).unwrap();
+            assert_eq!(parse_course(&written), course);
+
+            // writing is a fixpoint
+            let mut rewritten = Vec::new();
+            write_course(&parse_course(&written), &mut rewritten).unwrap();
+            assert_eq!(String::from_utf8(rewritten).unwrap(), written);
+        }
+    }
+
+    #[test]
+    fn test_normalize_course() {
+        let course = parse_course("forward  5\ndown 05 \nup -3");
+        let mut written = Vec::new();
+        write_course(&course, &mut written).unwrap();
+        assert_eq!(
+            String::from_utf8(written).unwrap(),
+            "forward 5\ndown 5\nup -3\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_extended_commands() {
+        let course: Vec<ExtendedCommand> = "forward 5\nleft 1\nright -2\nclimb 3\ndive 4\nup 2"
+            .lines()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        assert_eq!(
+            course,
+            vec![
+                ExtendedCommand::Classic(Command::Forward(5)),
+                ExtendedCommand::Left(1),
+                ExtendedCommand::Right(-2),
+                ExtendedCommand::Climb(3),
+                ExtendedCommand::Dive(4),
+                ExtendedCommand::Classic(Command::Up(2)),
+            ]
+        );
+
+        let mut written = Vec::new();
+        write_course(&course, &mut written).unwrap();
+        assert_eq!(
+            String::from_utf8(written).unwrap(),
+            "forward 5\nleft 1\nright -2\nclimb 3\ndive 4\nup 2\n"
+        );
+
+        assert!(matches!(
+            "sideways 3".parse::<ExtendedCommand>(),
+            Err(ParseError::InvalidCommand(cmd)) if cmd == "sideways"
+        ));
+        assert!(matches!(
+            "left".parse::<ExtendedCommand>(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+    }
+
+    fn lines(s: &str) -> Vec<String> {
+        s.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_parse_repeat_blocks() {
+        use Command::*;
+        let course: Vec<Command> = parse_lines(&lines(
+            "down 1
+repeat 2 {
+  forward 3
+  repeat 3 {
+    up 2
+  }
+}
+repeat 0 {
+  down 7
+}
+forward 1",
+        ))
+        .unwrap();
+        assert_eq!(
+            course,
+            vec![
+                Down(1),
+                Forward(3),
+                Up(2),
+                Up(2),
+                Up(2),
+                Forward(3),
+                Up(2),
+                Up(2),
+                Up(2),
+                Forward(1),
+            ]
+        );
+
+        let course: Vec<ExtendedCommand> =
+            parse_lines(&lines("repeat 4 {\nright 1\nforward 2\n}")).unwrap();
+        let pos = execute_course_3d(Position3D::default(), &course);
+        assert_eq!((pos.north, pos.east, pos.heading), (0, 0, Heading::East));
+    }
+
+    #[test]
+    fn test_parse_repeat_block_errors() {
+        assert!(matches!(
+            parse_lines::<Command>(&lines("repeat 2\nforward 1\n}")),
+            Err(ParseError::InvalidRepeat(line)) if line == "repeat 2"
+        ));
+        assert!(matches!(
+            parse_lines::<Command>(&lines("repeat -2 {\n}")),
+            Err(ParseError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            parse_lines::<Command>(&lines("forward 1\n}")),
+            Err(ParseError::UnmatchedBlockEnd)
+        ));
+        assert!(matches!(
+            parse_lines::<Command>(&lines("repeat 2 {\nrepeat 3 {\nforward 1\n}")),
+            Err(ParseError::UnclosedBlock(1))
+        ));
+    }
+
+    #[test]
+    fn test_execute_course_3d() {
+        use Command::*;
+        // the classic commands alone behave as in part 2
+        let classic = vec![Forward(5), Down(5), Forward(8), Up(3), Down(8), Forward(2)];
+        let course: Vec<_> = classic.into_iter().map(ExtendedCommand::Classic).collect();
+        let pos = execute_course_3d(Position3D::default(), &course);
+        assert_eq!((pos.east, pos.depth, pos.aim), (15, 60, 10));
+
+        let course = vec![
+            ExtendedCommand::Left(1),
+            ExtendedCommand::Classic(Forward(4)),
+            ExtendedCommand::Right(3),
+            ExtendedCommand::Classic(Forward(2)),
+            ExtendedCommand::Dive(7),
+            ExtendedCommand::Climb(2),
+            ExtendedCommand::Right(-6),
+        ];
+        assert_eq!(
+            execute_course_3d(Position3D::default(), &course),
+            Position3D {
+                north: 4,
+                east: -2,
+                depth: 5,
+                aim: 0,
+                heading: Heading::East,
+            }
+        );
+    }
+
+    #[test]
+    fn test_execute_course_part1() {
+        use Command::*;
+        let course = vec![Forward(5), Down(5), Forward(8), Up(3), Down(8), Forward(2)];
+        let pos = execute_course_part1(Position::default(), &course);
+        assert_eq!(
+            pos,
+            Position {
+                horizontal: 15,
+                depth: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn test_execute_course_part2() {
+        use Command::*;
+        let course = vec![Forward(5), Down(5), Forward(8), Up(3), Down(8), Forward(2)];
+        let pos = execute_course_part2(PositionWithAim::default(), &course);
+        assert_eq!(
+            pos,
+            PositionWithAim {
+                horizontal: 15,
+                depth: 60,
+                aim: 10,
+            }
+        );
+    }
+}
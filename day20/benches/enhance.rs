@@ -0,0 +1,41 @@
+use common::rng::Rng;
+use criterion::{criterion_group, criterion_main, Criterion};
+use day20::*;
+
+fn generate(len: usize, rng: &mut Rng) -> Vec<bool> {
+    (0..len).map(|_| rng.next_u64() & 1 == 0).collect()
+}
+
+fn generate_image(size: usize, rng: &mut Rng) -> Image {
+    let mut img = Image::default();
+    for (i, lit) in generate(size * size, rng).into_iter().enumerate() {
+        if lit {
+            img.lit_pixel((i % size) as isize, (i / size) as isize);
+        }
+    }
+    img
+}
+
+// Tiled enhancement against the serial one on a large image.
+fn tiled_vs_serial(c: &mut Criterion) {
+    let mut rng = Rng::new(0x9e3779b97f4a7c15);
+    let setting = generate(512, &mut rng);
+    let image = generate_image(400, &mut rng);
+
+    let mut group = c.benchmark_group("50 enhancements of 400x400");
+    group.sample_size(10);
+    group.bench_function("serial", |b| {
+        b.iter(|| (0..50).fold(image.clone(), |img, _| img.enhance(&setting)))
+    });
+    group.bench_function("tiled", |b| {
+        b.iter(|| {
+            (0..50).fold(image.clone(), |img, _| {
+                img.enhance_tiled(&setting, TILE_SIZE)
+            })
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, tiled_vs_serial);
+criterion_main!(benches);
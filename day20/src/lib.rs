@@ -0,0 +1,567 @@
+use common::{input, Answer, Solution};
+use std::{cmp, collections::BTreeSet, fmt};
+
+use rayon::prelude::*;
+use tracing::{debug, debug_span, trace};
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = (Vec<bool>, Image);
+
+    fn parse(input: &str) -> Self::Input {
+        parse_input(input)
+    }
+
+    fn part1((setting, image): &Self::Input) -> Answer {
+        let (image, _) = enhance_until_stable(image.clone(), setting, 2, |_, _| {});
+        image.lit_pixel_count().into()
+    }
+
+    fn part2((setting, image): &Self::Input) -> Answer {
+        let (image, _) = enhance_until_stable(image.clone(), setting, 50, |_, _| {});
+        image.lit_pixel_count().into()
+    }
+}
+
+// The image enhancement algorithm, followed by an empty line and the input image.
+pub fn parse_input(s: &str) -> (Vec<bool>, Image) {
+    let (setting, image) = input::header_and_body(s).unwrap();
+    (parse_setting(setting.trim()), parse_image(image))
+}
+
+pub fn parse_setting(s: &str) -> Vec<bool> {
+    s.chars().map(|c| c == '#').collect()
+}
+
+pub fn parse_image(s: &str) -> Image {
+    let mut img = Image::default();
+    for (y, line) in s.lines().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            if c == '#' {
+                img.lit_pixel(x as isize, y as isize);
+            }
+        }
+    }
+    img
+}
+
+pub const TILE_SIZE: usize = 64;
+
+// Inclusive rectangle of pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    top_left: (isize, isize),
+    bottom_right: (isize, isize),
+}
+
+impl Rect {
+    pub fn new(top_left: (isize, isize), bottom_right: (isize, isize)) -> Rect {
+        Self {
+            top_left,
+            bottom_right,
+        }
+    }
+
+    pub fn padded(&self, padding: isize) -> Rect {
+        Self::new(
+            (self.top_left.0 - padding, self.top_left.1 - padding),
+            (self.bottom_right.0 + padding, self.bottom_right.1 + padding),
+        )
+    }
+
+    pub fn width(&self) -> usize {
+        (self.bottom_right.0 - self.top_left.0 + 1).max(0) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.bottom_right.1 - self.top_left.1 + 1).max(0) as usize
+    }
+
+    pub fn area(&self) -> usize {
+        self.width() * self.height()
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct Image {
+    pixels: BTreeSet<(isize, isize)>,
+    top_left: (isize, isize),
+    bottom_right: (isize, isize),
+    pub negative: bool,
+}
+
+impl Image {
+    pub fn negative() -> Image {
+        Self {
+            negative: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_lit(&self, x: isize, y: isize) -> bool {
+        self.pixels.contains(&(x, y)) ^ self.negative
+    }
+
+    fn mark_pixel(&mut self, x: isize, y: isize) {
+        if self.pixels.insert((x, y)) {
+            self.top_left = (cmp::min(x, self.top_left.0), cmp::min(y, self.top_left.1));
+            self.bottom_right = (
+                cmp::max(x, self.bottom_right.0),
+                cmp::max(y, self.bottom_right.1),
+            );
+        }
+    }
+
+    pub fn lit_pixel(&mut self, x: isize, y: isize) {
+        if self.negative {
+            return;
+        }
+        self.mark_pixel(x, y);
+    }
+
+    pub fn darken_pixel(&mut self, x: isize, y: isize) {
+        if !self.negative {
+            return;
+        }
+        self.mark_pixel(x, y);
+    }
+
+    pub fn lit_pixel_count(&self) -> usize {
+        if self.negative {
+            usize::MAX
+        } else {
+            self.pixels.len()
+        }
+    }
+
+    // Number of lit pixels within `rect`, which is finite even for a negative image.
+    pub fn lit_in(&self, rect: &Rect) -> usize {
+        if rect.area() == 0 {
+            return 0;
+        }
+        // pixels are ordered by x first, so the range still contains other rows
+        let marked = self
+            .pixels
+            .range(rect.top_left..=rect.bottom_right)
+            .filter(|&&(_, y)| y >= rect.top_left.1 && y <= rect.bottom_right.1)
+            .count();
+        if self.negative {
+            rect.area() - marked
+        } else {
+            marked
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        Rect::new(self.top_left, self.bottom_right)
+    }
+
+    fn square_of_pixels(&self, x: isize, y: isize) -> [bool; 9] {
+        let mut square = [false; 9];
+        for col in 0..3 {
+            for row in 0..3 {
+                let index = row * 3 + col;
+                square[index] = self.is_lit(x - 1 + col as isize, y - 1 + row as isize);
+            }
+        }
+        square
+    }
+
+    fn top_left_with_padding(&self, padding: isize) -> (isize, isize) {
+        (self.top_left.0 - padding, self.top_left.1 - padding)
+    }
+
+    fn bottom_right_with_padding(&self, padding: isize) -> (isize, isize) {
+        (self.bottom_right.0 + padding, self.bottom_right.1 + padding)
+    }
+
+    fn enhanced_background(&self, setting: &[bool]) -> Image {
+        // a lit background consists of squares of lit pixels only
+        let background = if self.negative { 511 } else { 0 };
+        if setting[background] {
+            // If first enhancement is #, then this would result in infinitely many #.
+            // Therefore we negate the image to keep track of .
+            Image::negative()
+        } else {
+            Image::default()
+        }
+    }
+
+    pub fn enhance(&self, setting: &[bool]) -> Image {
+        let mut result = self.enhanced_background(setting);
+
+        let top_left = self.top_left_with_padding(2);
+        let bottom_right = self.bottom_right_with_padding(2);
+
+        for y in top_left.1..=bottom_right.1 {
+            for x in top_left.0..=bottom_right.0 {
+                let pixels = self.square_of_pixels(x, y);
+                let number = pixels
+                    .iter()
+                    .fold(0, |number, &p| (number << 1) | (p as usize));
+                trace!(x, y, number, "enhanced pixel");
+                if setting[number] {
+                    result.lit_pixel(x, y);
+                } else {
+                    result.darken_pixel(x, y);
+                }
+            }
+        }
+
+        result
+    }
+
+    // Same as `enhance`, but splits the image into tiles of `tile_size` x `tile_size` pixels
+    // which are enhanced in parallel.
+    pub fn enhance_tiled(&self, setting: &[bool], tile_size: usize) -> Image {
+        let mut result = self.enhanced_background(setting);
+
+        let top_left = self.top_left_with_padding(2);
+        let bottom_right = self.bottom_right_with_padding(2);
+
+        let tiles: Vec<_> = (top_left.1..=bottom_right.1)
+            .step_by(tile_size)
+            .flat_map(|y| {
+                (top_left.0..=bottom_right.0)
+                    .step_by(tile_size)
+                    .map(move |x| (x, y))
+            })
+            .collect();
+
+        let marked_pixels: Vec<_> = tiles
+            .par_iter()
+            .map(|&(x, y)| {
+                let tile_bottom_right = (
+                    cmp::min(x + tile_size as isize - 1, bottom_right.0),
+                    cmp::min(y + tile_size as isize - 1, bottom_right.1),
+                );
+                self.enhance_tile(setting, result.negative, (x, y), tile_bottom_right)
+            })
+            .collect();
+
+        for (x, y) in marked_pixels.into_iter().flatten() {
+            result.mark_pixel(x, y);
+        }
+
+        result
+    }
+
+    // Enhances the given rectangle based on a dense copy of it, including a 1-pixel halo.
+    // Returns the pixels which have to be marked in an image with the given negative flag.
+    fn enhance_tile(
+        &self,
+        setting: &[bool],
+        negative: bool,
+        top_left: (isize, isize),
+        bottom_right: (isize, isize),
+    ) -> Vec<(isize, isize)> {
+        let halo_top_left = (top_left.0 - 1, top_left.1 - 1);
+        let halo_bottom_right = (bottom_right.0 + 1, bottom_right.1 + 1);
+        let width = (halo_bottom_right.0 - halo_top_left.0 + 1) as usize;
+        let height = (halo_bottom_right.1 - halo_top_left.1 + 1) as usize;
+
+        let mut halo = vec![self.negative; width * height];
+        for &(x, y) in self.pixels.range(halo_top_left..=halo_bottom_right) {
+            if y >= halo_top_left.1 && y <= halo_bottom_right.1 {
+                let row = (y - halo_top_left.1) as usize;
+                let col = (x - halo_top_left.0) as usize;
+                halo[row * width + col] = !self.negative;
+            }
+        }
+
+        let mut marked_pixels = Vec::new();
+        for row in 1..height - 1 {
+            for col in 1..width - 1 {
+                let mut number = 0;
+                for r in row - 1..=row + 1 {
+                    for c in col - 1..=col + 1 {
+                        number = (number << 1) | (halo[r * width + c] as usize);
+                    }
+                }
+                if setting[number] ^ negative {
+                    marked_pixels.push((
+                        halo_top_left.0 + col as isize,
+                        halo_top_left.1 + row as isize,
+                    ));
+                }
+            }
+        }
+        marked_pixels
+    }
+}
+
+// Images are equal if they light the same pixels, the bounds follow from the marked pixels.
+impl PartialEq for Image {
+    fn eq(&self, other: &Image) -> bool {
+        self.negative == other.negative && self.pixels == other.pixels
+    }
+}
+
+impl fmt::Display for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f)?;
+
+        let top_left = self.top_left_with_padding(2);
+        let bottom_right = self.bottom_right_with_padding(2);
+
+        for x in top_left.0..=bottom_right.0 {
+            write!(f, "{}", if x == 0 { "|" } else { " " })?;
+        }
+        writeln!(f)?;
+
+        for y in top_left.1..=bottom_right.1 {
+            write!(f, "{}", if y == 0 { "-" } else { " " })?;
+            for x in top_left.0..=bottom_right.0 {
+                write!(f, "{}", if self.is_lit(x, y) { "#" } else { "." })?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+// What the enhancement does to the infinite background, which only depends on the settings for
+// a square of dark (index 0) and a square of lit pixels (index 511).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    StaysDark,
+    Blinks,
+    // lit after the first step and stays lit
+    Converges,
+}
+
+impl Background {
+    pub fn classify(setting: &[bool]) -> Background {
+        match (setting[0], setting[511]) {
+            (false, _) => Background::StaysDark,
+            (true, false) => Background::Blinks,
+            (true, true) => Background::Converges,
+        }
+    }
+}
+
+impl fmt::Display for Background {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Background::StaysDark => write!(f, "stays dark"),
+            Background::Blinks => write!(f, "blinks"),
+            Background::Converges => write!(f, "converges to lit"),
+        }
+    }
+}
+
+// Enhances the image up to `steps` times and stops early at a fixed point, i.e. as soon as a
+// step doesn't change the image anymore. Returns the final image and the number of the last
+// step which changed it, if a fixed point has been reached.
+pub fn enhance_until_stable<F>(
+    mut image: Image,
+    setting: &[bool],
+    steps: usize,
+    mut after_step: F,
+) -> (Image, Option<usize>)
+where
+    F: FnMut(usize, &Image),
+{
+    for step in 1..=steps {
+        let _span = debug_span!("enhance", step).entered();
+        let next = image.enhance_tiled(setting, TILE_SIZE);
+        // a negative image keeps track of its dark pixels
+        debug!(
+            pixels = next.pixels.len(),
+            negative = next.negative,
+            "enhanced image"
+        );
+        after_step(step, &next);
+        if next == image {
+            return (image, Some(step - 1));
+        }
+        image = next;
+    }
+    (image, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rng::Rng;
+
+    const EXAMPLE_SETTING: &str = "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#";
+
+    const EXAMPLE_IMAGE: &str = "#..#.
+#....
+##..#
+..#..
+..###";
+
+    fn generate(len: usize, seed: u64) -> Vec<bool> {
+        let mut rng = Rng::new(seed);
+        (0..len).map(|_| rng.next_u64() & 1 == 0).collect()
+    }
+
+    fn generate_image(size: usize, seed: u64) -> Image {
+        let mut img = Image::default();
+        for (i, lit) in generate(size * size, seed).into_iter().enumerate() {
+            if lit {
+                img.lit_pixel((i % size) as isize, (i / size) as isize);
+            }
+        }
+        img
+    }
+
+    fn assert_same_image(a: &Image, b: &Image) {
+        assert_eq!(a.negative, b.negative);
+        assert_eq!(a.pixels, b.pixels);
+    }
+
+    #[test]
+    fn test_enhance_example() {
+        let setting = parse_setting(EXAMPLE_SETTING);
+        let image = parse_image(EXAMPLE_IMAGE);
+
+        let serial = (0..2).fold(image.clone(), |img, _| img.enhance(&setting));
+        let tiled = (0..2).fold(image.clone(), |img, _| img.enhance_tiled(&setting, 4));
+        assert_eq!(serial.lit_pixel_count(), 35);
+        assert_same_image(&serial, &tiled);
+
+        let tiled = (0..50).fold(image, |img, _| img.enhance_tiled(&setting, 16));
+        assert_eq!(tiled.lit_pixel_count(), 3351);
+    }
+
+    #[test]
+    fn test_enhance_tiled_matches_serial() {
+        // flips the infinite background with every step
+        let mut setting = generate(512, 0x2545f4914f6cdd1d);
+        setting[0] = true;
+        setting[511] = false;
+
+        let image = generate_image(40, 0x9e3779b97f4a7c15);
+        for tile_size in [1, 7, TILE_SIZE] {
+            let mut serial = image.clone();
+            let mut tiled = image.clone();
+            for _ in 0..6 {
+                serial = serial.enhance(&setting);
+                tiled = tiled.enhance_tiled(&setting, tile_size);
+                assert_same_image(&serial, &tiled);
+            }
+        }
+    }
+
+    fn lit_in_brute_force(image: &Image, rect: &Rect) -> usize {
+        (rect.top_left.1..=rect.bottom_right.1)
+            .flat_map(|y| (rect.top_left.0..=rect.bottom_right.0).map(move |x| (x, y)))
+            .filter(|&(x, y)| image.is_lit(x, y))
+            .count()
+    }
+
+    #[test]
+    fn test_lit_in() {
+        let image = parse_image(EXAMPLE_IMAGE);
+        assert_eq!(image.bounds(), Rect::new((0, 0), (4, 4)));
+        assert_eq!(image.lit_in(&image.bounds()), 10);
+        assert_eq!(image.lit_in(&image.bounds().padded(10)), 10);
+        assert_eq!(image.lit_in(&Rect::new((0, 0), (1, 1))), 2);
+        assert_eq!(image.lit_in(&Rect::new((2, 4), (4, 4))), 3);
+        assert_eq!(image.lit_in(&Rect::new((1, 1), (0, 0))), 0);
+
+        let mut negative = Image::negative();
+        negative.darken_pixel(0, 0);
+        negative.darken_pixel(5, -3);
+        assert_eq!(negative.lit_pixel_count(), usize::MAX);
+        assert_eq!(negative.lit_in(&Rect::new((0, 0), (2, 1))), 5);
+        assert_eq!(
+            negative.lit_in(&Rect::new((-10, -10), (10, 10))),
+            21 * 21 - 2
+        );
+    }
+
+    #[test]
+    fn test_lit_in_blinking_background() {
+        let mut setting = generate(512, 0x2545f4914f6cdd1d);
+        setting[0] = true;
+        setting[511] = false;
+
+        let mut image = generate_image(20, 0x9e3779b97f4a7c15);
+        let initial_bounds = image.bounds();
+        for step in 1..=5 {
+            image = image.enhance(&setting);
+            assert_eq!(image.negative, step & 1 == 1);
+            for padding in [0, step, step + 3] {
+                let window = initial_bounds.padded(padding);
+                assert_eq!(image.lit_in(&window), lit_in_brute_force(&image, &window));
+            }
+            // outside of the influenced window only the background remains
+            let window = initial_bounds.padded(step + 3);
+            let influenced = initial_bounds.padded(step);
+            assert_eq!(
+                image.lit_in(&window) - image.lit_in(&influenced),
+                if image.negative {
+                    window.area() - influenced.area()
+                } else {
+                    0
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_classify_background() {
+        let mut setting = parse_setting(EXAMPLE_SETTING);
+        assert_eq!(Background::classify(&setting), Background::StaysDark);
+        setting[0] = true;
+        setting[511] = false;
+        assert_eq!(Background::classify(&setting), Background::Blinks);
+        setting[511] = true;
+        assert_eq!(Background::classify(&setting), Background::Converges);
+
+        let image = generate_image(10, 0x9e3779b97f4a7c15);
+        let enhanced = image.enhance(&setting).enhance(&setting);
+        assert!(enhanced.negative);
+        assert!(enhanced.is_lit(-100, 100));
+    }
+
+    #[test]
+    fn test_enhance_until_stable() {
+        let image = parse_image(EXAMPLE_IMAGE);
+
+        // keeps the center pixel
+        let identity: Vec<bool> = (0..512).map(|n| n & 16 != 0).collect();
+        let (stable, since) = enhance_until_stable(image.clone(), &identity, 50, |_, _| {});
+        assert_eq!(since, Some(0));
+        assert_eq!(stable, image);
+
+        let dark = vec![false; 512];
+        let mut steps = 0;
+        let (stable, since) = enhance_until_stable(image.clone(), &dark, 50, |step, _| {
+            steps = step;
+        });
+        assert_eq!(since, Some(1));
+        assert_eq!(steps, 2);
+        assert_eq!(stable.lit_pixel_count(), 0);
+
+        let lit = vec![true; 512];
+        let (stable, since) = enhance_until_stable(image.clone(), &lit, 50, |_, _| {});
+        assert_eq!(since, Some(1));
+        assert!(stable.negative);
+        assert_eq!(stable.lit_in(&Rect::new((-5, -5), (5, 5))), 121);
+
+        // a blinking background never repeats the previous image
+        let mut blinking = dark;
+        blinking[0] = true;
+        let (_, since) = enhance_until_stable(image.clone(), &blinking, 10, |_, _| {});
+        assert_eq!(since, None);
+
+        let setting = parse_setting(EXAMPLE_SETTING);
+        let (enhanced, since) = enhance_until_stable(image, &setting, 2, |_, _| {});
+        assert_eq!(since, None);
+        assert_eq!(enhanced.lit_pixel_count(), 35);
+    }
+
+    #[test]
+    fn test_solution_example() {
+        let (part1, part2) = common::solve::<Day20>(include_str!("../example.txt"));
+        assert_eq!(part1.to_string(), "35");
+        assert_eq!(part2.to_string(), "3351");
+    }
+}
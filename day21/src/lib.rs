@@ -0,0 +1,735 @@
+use common::{Answer, Solution};
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+pub const BOARD_SIZE: usize = 10;
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input = (Player, Player);
+
+    fn parse(input: &str) -> Self::Input {
+        parse_starting_positions(input)
+    }
+
+    fn part1(&(player1, player2): &Self::Input) -> Answer {
+        let mut dice = DeterministicDice::new(100);
+        let GameResult { loser, .. } = play_game(&mut dice, player1, player2, 1000);
+        (loser.total_score() * dice.roll_count).into()
+    }
+
+    fn part2(&(player1, player2): &Self::Input) -> Answer {
+        let (player1_wins, player2_wins) = play_dirac_game(player1, player2, 21);
+        cmp::max(player1_wins, player2_wins).into()
+    }
+}
+
+// Parses "Player N starting position: P" for both players.
+pub fn parse_starting_positions(input: &str) -> (Player, Player) {
+    let mut players = input.lines().map(|line| {
+        let (_, pos) = line.rsplit_once(": ").unwrap();
+        Player::new(pos.trim().parse().unwrap())
+    });
+    (players.next().unwrap(), players.next().unwrap())
+}
+
+pub trait Roll {
+    fn roll(&mut self) -> usize;
+}
+
+pub struct DeterministicDice {
+    next: usize,
+    limit: usize,
+    roll_count: usize,
+}
+
+impl DeterministicDice {
+    pub fn new(limit: usize) -> DeterministicDice {
+        Self {
+            next: 1,
+            limit,
+            roll_count: 0,
+        }
+    }
+}
+
+impl Roll for DeterministicDice {
+    fn roll(&mut self) -> usize {
+        let result = self.next;
+        self.next += 1;
+        if self.next > self.limit {
+            self.next = 1;
+        }
+        self.roll_count += 1;
+        result
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Player {
+    pos: usize,
+    total_score: usize,
+}
+
+impl Player {
+    pub fn new(pos: usize) -> Player {
+        Self {
+            pos: Self::cirular_board_position(pos),
+            total_score: 0,
+        }
+    }
+
+    pub fn play<Dice: Roll>(&mut self, dice: &mut Dice) {
+        let n: usize = (0..3).map(|_| dice.roll()).sum();
+        self.moves(n);
+    }
+
+    pub fn moves(&mut self, n: usize) {
+        self.pos = Self::cirular_board_position(self.pos + n);
+        self.total_score += self.pos;
+    }
+
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn total_score(&self) -> usize {
+        self.total_score
+    }
+
+    fn cirular_board_position(pos: usize) -> usize {
+        (pos - 1) % BOARD_SIZE + 1
+    }
+}
+
+pub struct GameResult {
+    winner: Player,
+    loser: Player,
+    // 1 or 2
+    winning_player: usize,
+}
+
+pub fn play_game<Dice: Roll>(
+    dice: &mut Dice,
+    mut player1: Player,
+    mut player2: Player,
+    winning_score: usize,
+) -> GameResult {
+    loop {
+        player1.play(dice);
+        if player1.total_score() >= winning_score {
+            break GameResult {
+                winner: player1,
+                loser: player2,
+                winning_player: 1,
+            };
+        }
+
+        player2.play(dice);
+        if player2.total_score() >= winning_score {
+            break GameResult {
+                winner: player2,
+                loser: player1,
+                winning_player: 2,
+            };
+        }
+    }
+}
+
+pub fn quantum() -> Vec<usize> {
+    let mut q = Vec::new();
+    for i in 1..=3 {
+        for j in 1..=3 {
+            for k in 1..=3 {
+                q.push(i + j + k);
+            }
+        }
+    }
+    q
+}
+
+pub type Memoization = HashMap<(Player, Player, usize), (usize, usize)>;
+
+pub fn play_dirac_game(player1: Player, player2: Player, winning_score: usize) -> (usize, usize) {
+    let mut memoization = Memoization::new();
+    play_dirac_game_from(&mut memoization, player1, player2, winning_score)
+}
+
+// Wins of the player to move and the other player, over all universes starting in this state.
+pub fn play_dirac_game_from(
+    memoization: &mut Memoization,
+    player1: Player,
+    player2: Player,
+    winning_score: usize,
+) -> (usize, usize) {
+    let mut total_player1_wins = 0;
+    let mut total_player2_wins = 0;
+
+    for dice in quantum() {
+        let (player1_wins, player2_wins) =
+            play_dirac_game_rec(memoization, player1, player2, dice, winning_score);
+        total_player1_wins += player1_wins;
+        total_player2_wins += player2_wins;
+    }
+
+    (total_player1_wins, total_player2_wins)
+}
+
+pub fn play_dirac_game_rec(
+    memoization: &mut Memoization,
+    mut player: Player,
+    other: Player,
+    dice: usize,
+    winning_score: usize,
+) -> (usize, usize) {
+    if let Some(&(player_wins, other_wins)) = memoization.get(&(player, other, dice)) {
+        return (player_wins, other_wins);
+    }
+
+    let initial_player = player; // memoize the unmodified player
+
+    player.moves(dice);
+    if player.total_score() >= winning_score {
+        return (1, 0);
+    }
+
+    let mut total_player_wins = 0;
+    let mut total_other_wins = 0;
+
+    for dice in quantum() {
+        let (other_wins, player_wins) =
+            play_dirac_game_rec(memoization, other, player, dice, winning_score);
+        total_player_wins += player_wins;
+        total_other_wins += other_wins;
+    }
+
+    memoization.insert(
+        (initial_player, other, dice),
+        (total_player_wins, total_other_wins),
+    );
+
+    (total_player_wins, total_other_wins)
+}
+
+// Distinct sums of the three Dirac dice rolls and the number of universes they occur in.
+pub fn dice_sums() -> Vec<(usize, usize)> {
+    let mut sums: BTreeMap<usize, usize> = BTreeMap::new();
+    for dice in quantum() {
+        *sums.entry(dice).or_default() += 1;
+    }
+    sums.into_iter().collect()
+}
+
+// Outcome of the Dirac game over all universes, every universe counted once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DiracOutcome {
+    // wins of the player to move and of the other player
+    pub wins: (usize, usize),
+    // number of universes in which the game ends after the given number of turns (index),
+    // one turn being three rolls of a single player
+    pub lengths: Vec<usize>,
+}
+
+impl DiracOutcome {
+    pub fn universes(&self) -> usize {
+        self.lengths.iter().sum()
+    }
+
+    // Sum of the game lengths over all universes, the exact expected number of turns is this
+    // divided by the number of universes.
+    pub fn total_turns(&self) -> usize {
+        self.lengths
+            .iter()
+            .enumerate()
+            .map(|(turns, universes)| turns * universes)
+            .sum()
+    }
+
+    pub fn expected_turns(&self) -> f64 {
+        self.total_turns() as f64 / self.universes() as f64
+    }
+}
+
+pub fn add_game_length(lengths: &mut Vec<usize>, turns: usize, universes: usize) {
+    if lengths.len() <= turns {
+        lengths.resize(turns + 1, 0);
+    }
+    lengths[turns] += universes;
+}
+
+pub type OutcomeMemoization = HashMap<(Player, Player), DiracOutcome>;
+
+pub fn dirac_game_outcome(player1: Player, player2: Player, winning_score: usize) -> DiracOutcome {
+    let mut memoization = OutcomeMemoization::new();
+    dirac_game_outcome_rec(&mut memoization, player1, player2, winning_score)
+}
+
+// Same recursion as `play_dirac_game_rec`, but per sum of the dice weighted by the number of
+// universes it occurs in, and counting the turns until the game ends.
+pub fn dirac_game_outcome_rec(
+    memoization: &mut OutcomeMemoization,
+    player: Player,
+    other: Player,
+    winning_score: usize,
+) -> DiracOutcome {
+    if let Some(outcome) = memoization.get(&(player, other)) {
+        return outcome.clone();
+    }
+
+    let mut outcome = DiracOutcome::default();
+
+    for (dice_sum, multiplicity) in dice_sums() {
+        let mut moved = player;
+        moved.moves(dice_sum);
+        if moved.total_score() >= winning_score {
+            outcome.wins.0 += multiplicity;
+            add_game_length(&mut outcome.lengths, 1, multiplicity);
+            continue;
+        }
+
+        let next = dirac_game_outcome_rec(memoization, other, moved, winning_score);
+        outcome.wins.0 += multiplicity * next.wins.1;
+        outcome.wins.1 += multiplicity * next.wins.0;
+        for (turns, universes) in next.lengths.into_iter().enumerate() {
+            add_game_length(&mut outcome.lengths, turns + 1, multiplicity * universes);
+        }
+    }
+
+    memoization.insert((player, other), outcome.clone());
+    outcome
+}
+
+// Outcome of both game variants for one pair of starting positions.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SweepEntry {
+    start: (usize, usize),
+    deterministic_winner: usize,
+    deterministic_scores: (usize, usize),
+    deterministic_rolls: usize,
+    dirac_wins: (usize, usize),
+}
+
+impl SweepEntry {
+    fn dirac_winner(&self) -> usize {
+        if self.dirac_wins.0 >= self.dirac_wins.1 {
+            1
+        } else {
+            2
+        }
+    }
+}
+
+// Plays both variants for all starting positions, ordered by player 1's and then player 2's
+// starting position.
+pub fn sweep_starting_positions(winning_score: usize) -> Vec<SweepEntry> {
+    let starts: Vec<_> = (1..=BOARD_SIZE)
+        .flat_map(|p1| (1..=BOARD_SIZE).map(move |p2| (p1, p2)))
+        .collect();
+
+    starts
+        .into_par_iter()
+        .map(|(p1, p2)| sweep_entry(p1, p2, winning_score))
+        .collect()
+}
+
+pub fn sweep_entry(p1: usize, p2: usize, winning_score: usize) -> SweepEntry {
+    let (player1, player2) = (Player::new(p1), Player::new(p2));
+
+    let mut dice = DeterministicDice::new(100);
+    let result = play_game(&mut dice, player1, player2, 1000);
+    let deterministic_scores = match result.winning_player {
+        1 => (result.winner.total_score(), result.loser.total_score()),
+        _ => (result.loser.total_score(), result.winner.total_score()),
+    };
+
+    SweepEntry {
+        start: (p1, p2),
+        deterministic_winner: result.winning_player,
+        deterministic_scores,
+        deterministic_rolls: dice.roll_count,
+        dirac_wins: play_dirac_game(player1, player2, winning_score),
+    }
+}
+
+pub fn sweep_to_table(sweep: &[SweepEntry]) -> String {
+    let mut out = String::new();
+    let mut write_matrix = |title: &str, cell: &dyn Fn(&SweepEntry) -> String| {
+        writeln!(out, "{}", title).unwrap();
+        write!(out, "P1\\P2").unwrap();
+        for p2 in 1..=BOARD_SIZE {
+            write!(out, " {:>4}", p2).unwrap();
+        }
+        writeln!(out).unwrap();
+        for row in sweep.chunks(BOARD_SIZE) {
+            write!(out, "{:>5}", row[0].start.0).unwrap();
+            for entry in row {
+                write!(out, " {:>4}", cell(entry)).unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        writeln!(out).unwrap();
+    };
+
+    write_matrix("Deterministic dice winner", &|entry| {
+        format!("P{}", entry.deterministic_winner)
+    });
+    write_matrix("Deterministic dice rolls", &|entry| {
+        entry.deterministic_rolls.to_string()
+    });
+    write_matrix("Dirac dice winner", &|entry| {
+        format!("P{}", entry.dirac_winner())
+    });
+    writeln!(out, "start  deterministic scores  Dirac wins").unwrap();
+    for entry in sweep {
+        writeln!(
+            out,
+            "{:>2},{:<2}  {:>9} / {:<9}  {} / {}",
+            entry.start.0,
+            entry.start.1,
+            entry.deterministic_scores.0,
+            entry.deterministic_scores.1,
+            entry.dirac_wins.0,
+            entry.dirac_wins.1
+        )
+        .unwrap();
+    }
+    out
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct GameTreeNode {
+    player1: Player,
+    player2: Player,
+    // 1 or 2
+    to_move: usize,
+    winner: Option<usize>,
+    // (player 1, player 2) wins over all universes reachable from this state
+    wins: (usize, usize),
+    // empty for won states and states beyond the depth limit
+    branches: Vec<GameTreeBranch>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct GameTreeBranch {
+    dice_sum: usize,
+    multiplicity: usize,
+    node: GameTreeNode,
+}
+
+// Expands the game tree up to the given number of turns, the win counts of each node come
+// from the memoized computation.
+pub fn dirac_game_tree(
+    player1: Player,
+    player2: Player,
+    winning_score: usize,
+    depth: usize,
+) -> GameTreeNode {
+    let mut memoization = Memoization::new();
+    build_game_tree_node(
+        &mut memoization,
+        (player1, player2),
+        1,
+        winning_score,
+        depth,
+    )
+}
+
+pub fn build_game_tree_node(
+    memoization: &mut Memoization,
+    players: (Player, Player),
+    to_move: usize,
+    winning_score: usize,
+    depth: usize,
+) -> GameTreeNode {
+    let (player1, player2) = players;
+    let (player, other) = if to_move == 1 {
+        (player1, player2)
+    } else {
+        (player2, player1)
+    };
+
+    let (player_wins, other_wins) = play_dirac_game_from(memoization, player, other, winning_score);
+    let wins = if to_move == 1 {
+        (player_wins, other_wins)
+    } else {
+        (other_wins, player_wins)
+    };
+
+    let mut branches = Vec::new();
+    if depth > 0 {
+        for (dice_sum, multiplicity) in dice_sums() {
+            let mut moved = player;
+            moved.moves(dice_sum);
+            let players = if to_move == 1 {
+                (moved, player2)
+            } else {
+                (player1, moved)
+            };
+
+            let node = if moved.total_score() >= winning_score {
+                GameTreeNode {
+                    player1: players.0,
+                    player2: players.1,
+                    to_move: 3 - to_move,
+                    winner: Some(to_move),
+                    wins: if to_move == 1 { (1, 0) } else { (0, 1) },
+                    branches: Vec::new(),
+                }
+            } else {
+                build_game_tree_node(memoization, players, 3 - to_move, winning_score, depth - 1)
+            };
+            branches.push(GameTreeBranch {
+                dice_sum,
+                multiplicity,
+                node,
+            });
+        }
+    }
+
+    GameTreeNode {
+        player1,
+        player2,
+        to_move,
+        winner: None,
+        wins,
+        branches,
+    }
+}
+
+pub fn game_tree_to_dot(root: &GameTreeNode) -> String {
+    fn write_node(out: &mut String, node: &GameTreeNode, next_id: &mut usize) {
+        let id = *next_id;
+        *next_id += 1;
+
+        let style = match node.winner {
+            Some(_) => ", style=filled, fillcolor=lightgreen",
+            None => "",
+        };
+        writeln!(
+            out,
+            "  n{} [label=\"P1 at {} with {}\\nP2 at {} with {}\\nwins {} / {}\"{}];",
+            id,
+            node.player1.position(),
+            node.player1.total_score(),
+            node.player2.position(),
+            node.player2.total_score(),
+            node.wins.0,
+            node.wins.1,
+            style
+        )
+        .unwrap();
+
+        for branch in &node.branches {
+            writeln!(
+                out,
+                "  n{} -> n{} [label=\"P{} rolls {} (x{})\"];",
+                id, next_id, node.to_move, branch.dice_sum, branch.multiplicity
+            )
+            .unwrap();
+            write_node(out, &branch.node, next_id);
+        }
+    }
+
+    let mut out = String::new();
+    writeln!(out, "digraph dirac {{").unwrap();
+    write_node(&mut out, root, &mut 0);
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dice_sums() {
+        assert_eq!(
+            dice_sums(),
+            vec![(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)]
+        );
+    }
+
+    #[test]
+    fn test_dirac_game_outcome() {
+        let outcome = dirac_game_outcome(Player::new(4), Player::new(8), 21);
+        assert_eq!(outcome.wins, (444356092776315, 341960390180808));
+        assert_eq!(outcome.universes(), outcome.wins.0 + outcome.wins.1);
+        // every turn scores at least 1, hence nobody gets more than 21 turns
+        assert!(outcome.lengths.len() <= 2 * 21 + 1);
+
+        // the first turn always wins
+        let outcome = dirac_game_outcome(Player::new(4), Player::new(8), 1);
+        assert_eq!(outcome.wins, (27, 0));
+        assert_eq!(outcome.lengths, vec![0, 27]);
+        assert_eq!(outcome.expected_turns(), 1.0);
+    }
+
+    #[test]
+    fn test_dirac_game_lengths_by_brute_force() {
+        fn count_lengths(player: Player, other: Player, turns: usize, lengths: &mut Vec<usize>) {
+            for dice in quantum() {
+                let mut moved = player;
+                moved.moves(dice);
+                if moved.total_score() >= 8 {
+                    add_game_length(lengths, turns, 1);
+                } else {
+                    count_lengths(other, moved, turns + 1, lengths);
+                }
+            }
+        }
+
+        let mut expected = Vec::new();
+        count_lengths(Player::new(4), Player::new(8), 1, &mut expected);
+        let outcome = dirac_game_outcome(Player::new(4), Player::new(8), 8);
+        assert_eq!(outcome.lengths, expected);
+        assert_eq!(
+            outcome.wins,
+            play_dirac_game(Player::new(4), Player::new(8), 8)
+        );
+    }
+
+    #[test]
+    fn test_play_game() {
+        let mut dice = DeterministicDice::new(100);
+        let result = play_game(&mut dice, Player::new(4), Player::new(8), 1000);
+        assert_eq!(result.winning_player, 1);
+        assert_eq!(result.winner.total_score(), 1000);
+        assert_eq!(result.loser.total_score() * dice.roll_count, 739785);
+    }
+
+    // The game used to check player 1's score after player 2's move, so player 2 never won and
+    // the game went on until player 1 reached the winning score.
+    #[test]
+    fn test_play_game_player2_wins() {
+        let mut dice = DeterministicDice::new(100);
+        let result = play_game(&mut dice, Player::new(1), Player::new(5), 1000);
+        assert_eq!(result.winning_player, 2);
+        assert_eq!(result.winner.total_score(), 1005);
+        assert_eq!(result.loser.total_score(), 465);
+        assert_eq!(dice.roll_count, 930);
+    }
+
+    #[test]
+    fn test_sweep_entry() {
+        let example = sweep_entry(4, 8, 21);
+        assert_eq!(example.start, (4, 8));
+        assert_eq!(example.deterministic_winner, 1);
+        assert_eq!(example.deterministic_scores, (1000, 745));
+        assert_eq!(example.deterministic_rolls, 993);
+        assert_eq!(example.dirac_wins, (444356092776315, 341960390180808));
+        assert_eq!(example.dirac_winner(), 1);
+    }
+
+    #[test]
+    fn test_sweep_starting_positions() {
+        // low winning score to keep the 100 Dirac games fast
+        let sweep = sweep_starting_positions(10);
+        assert_eq!(sweep.len(), 100);
+        for (i, entry) in sweep.iter().enumerate() {
+            assert_eq!(
+                entry,
+                &sweep_entry(i / BOARD_SIZE + 1, i % BOARD_SIZE + 1, 10)
+            );
+            let (winner, loser) = match entry.deterministic_winner {
+                1 => entry.deterministic_scores,
+                _ => (entry.deterministic_scores.1, entry.deterministic_scores.0),
+            };
+            assert!(winner >= 1000 && loser < 1000);
+        }
+
+        let table = sweep_to_table(&sweep);
+        assert!(table.starts_with("Deterministic dice winner\nP1\\P2    1    2"));
+        let (wins1, wins2) = sweep[3 * BOARD_SIZE + 7].dirac_wins;
+        assert!(table.contains(&format!(
+            "\n 4,8        1000 / 745        {} / {}\n",
+            wins1, wins2
+        )));
+    }
+
+    #[test]
+    fn test_game_tree_root_matches_dirac_game() {
+        let tree = dirac_game_tree(Player::new(4), Player::new(8), 21, 1);
+        assert_eq!(tree.wins, (444356092776315, 341960390180808));
+        assert_eq!(tree.branches.len(), 7);
+        assert!(tree
+            .branches
+            .iter()
+            .all(|branch| branch.node.branches.is_empty() && branch.node.to_move == 2));
+    }
+
+    #[test]
+    fn test_game_tree_wins_add_up() {
+        fn check(node: &GameTreeNode) {
+            if node.branches.is_empty() {
+                return;
+            }
+            let wins = node.branches.iter().fold((0, 0), |wins, branch| {
+                (
+                    wins.0 + branch.multiplicity * branch.node.wins.0,
+                    wins.1 + branch.multiplicity * branch.node.wins.1,
+                )
+            });
+            assert_eq!(wins, node.wins);
+            node.branches.iter().for_each(|branch| check(&branch.node));
+        }
+
+        // low winning score to get won states within the first few turns
+        let tree = dirac_game_tree(Player::new(4), Player::new(8), 10, 3);
+        assert_eq!(
+            tree.wins,
+            play_dirac_game(Player::new(4), Player::new(8), 10)
+        );
+        check(&tree);
+
+        // player 1 moves from 4 to 10 and wins right away
+        let winning_branch = &tree.branches[3];
+        assert_eq!(winning_branch.dice_sum, 6);
+        assert_eq!(winning_branch.node.winner, Some(1));
+        assert_eq!(winning_branch.node.wins, (1, 0));
+        assert!(winning_branch.node.branches.is_empty());
+    }
+
+    #[test]
+    fn test_game_tree_to_dot() {
+        let tree = dirac_game_tree(Player::new(4), Player::new(8), 21, 1);
+        let dot = game_tree_to_dot(&tree);
+        assert!(dot.starts_with("digraph dirac {\n"));
+        assert_eq!(dot.matches(" -> ").count(), 7);
+        assert!(dot.contains("n0 -> n1 [label=\"P1 rolls 3 (x1)\"];"));
+        assert!(dot.contains("n1 [label=\"P1 at 7 with 7\\nP2 at 8 with 0\\nwins "));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_game_tree_to_json() {
+        let tree = dirac_game_tree(Player::new(4), Player::new(8), 21, 1);
+        let json = serde_json::to_value(&tree).unwrap();
+        assert_eq!(json["player1"]["pos"], 4);
+        assert_eq!(json["to_move"], 1);
+        assert_eq!(json["branches"][3]["dice_sum"], 6);
+        assert_eq!(json["branches"][3]["multiplicity"], 7);
+        assert_eq!(json["branches"][3]["node"]["player1"]["total_score"], 10);
+    }
+
+    #[test]
+    fn test_solution() {
+        assert_eq!(
+            Day21::parse(include_str!("../instance.txt")),
+            (Player::new(8), Player::new(10))
+        );
+
+        let (part1, part2) = common::solve::<Day21>(include_str!("../example.txt"));
+        assert_eq!(part1.to_string(), "739785");
+        assert_eq!(part2.to_string(), "444356092776315");
+    }
+}
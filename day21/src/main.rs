@@ -1,184 +1,73 @@
+use common::Solution;
+use day21::*;
 use std::cmp;
-use std::collections::HashMap;
+use std::env;
 
-const BOARD_SIZE: usize = 10;
-
-trait Roll {
-    fn roll(&mut self) -> usize;
-}
-
-struct DeterministicDice {
-    next: usize,
-    limit: usize,
-    roll_count: usize,
-}
-
-impl DeterministicDice {
-    pub fn new(limit: usize) -> DeterministicDice {
-        Self {
-            next: 1,
-            limit,
-            roll_count: 0,
-        }
-    }
-}
-
-impl Roll for DeterministicDice {
-    fn roll(&mut self) -> usize {
-        let result = self.next;
-        self.next += 1;
-        if self.next > self.limit {
-            self.next = 1;
+fn main() {
+    let (player1, player2) = Day21::parse(&common::read_input());
+
+    let winning_score = env::args()
+        .skip_while(|arg| arg != "--winning-score")
+        .nth(1)
+        .map_or(21, |score| score.parse().unwrap());
+
+    if let Some(format) = env::args().skip_while(|arg| arg != "--tree").nth(1) {
+        let depth = env::args()
+            .skip_while(|arg| arg != "--depth")
+            .nth(1)
+            .map_or(2, |depth| depth.parse().unwrap());
+        let tree = dirac_game_tree(player1, player2, winning_score, depth);
+        match format.as_str() {
+            "dot" => print!("{}", game_tree_to_dot(&tree)),
+            #[cfg(feature = "serde")]
+            "json" => println!("{}", serde_json::to_string_pretty(&tree).unwrap()),
+            #[cfg(not(feature = "serde"))]
+            "json" => panic!("JSON output needs the serde feature"),
+            _ => panic!("Unknown tree format '{}', expected 'dot' or 'json'", format),
         }
-        self.roll_count += 1;
-        result
+        return;
     }
-}
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-struct Player {
-    pos: usize,
-    total_score: usize,
-}
 
-impl Player {
-    pub fn new(pos: usize) -> Player {
-        Self {
-            pos: Self::cirular_board_position(pos),
-            total_score: 0,
+    if let Some(format) = env::args().skip_while(|arg| arg != "--sweep").nth(1) {
+        let sweep = sweep_starting_positions(winning_score);
+        match format.as_str() {
+            "table" => print!("{}", sweep_to_table(&sweep)),
+            #[cfg(feature = "serde")]
+            "json" => println!("{}", serde_json::to_string_pretty(&sweep).unwrap()),
+            #[cfg(not(feature = "serde"))]
+            "json" => panic!("JSON output needs the serde feature"),
+            _ => panic!(
+                "Unknown sweep format '{}', expected 'table' or 'json'",
+                format
+            ),
         }
+        return;
     }
 
-    pub fn play<Dice: Roll>(&mut self, dice: &mut Dice) {
-        let n: usize = (0..3).map(|_| dice.roll()).sum();
-        self.moves(n);
+    if common::part_selected(1) {
+        println!("Part 1: {}", Day21::part1(&(player1, player2)));
     }
 
-    pub fn moves(&mut self, n: usize) {
-        self.pos = Self::cirular_board_position(self.pos + n);
-        self.total_score += self.pos;
+    if common::part_selected(2) {
+        let (player1_wins, player2_wins) = play_dirac_game(player1, player2, winning_score);
+        println!("Part 2: {}", cmp::max(player1_wins, player2_wins));
     }
 
-    pub fn position(&self) -> usize {
-        self.pos
-    }
-
-    pub fn total_score(&self) -> usize {
-        self.total_score
-    }
-
-    fn cirular_board_position(pos: usize) -> usize {
-        (pos - 1) % BOARD_SIZE + 1
-    }
-}
-
-struct GameResult {
-    winner: Player,
-    loser: Player,
-}
-
-fn play_game<Dice: Roll>(
-    dice: &mut Dice,
-    mut player1: Player,
-    mut player2: Player,
-    winning_score: usize,
-) -> GameResult {
-    loop {
-        player1.play(dice);
-        if player1.total_score() >= winning_score {
-            break GameResult {
-                winner: player1,
-                loser: player2,
-            };
-        }
-
-        player2.play(dice);
-        if player1.total_score() >= winning_score {
-            break GameResult {
-                winner: player2,
-                loser: player1,
-            };
-        }
+    if env::args().any(|arg| arg == "--lengths") {
+        print_game_lengths(&dirac_game_outcome(player1, player2, winning_score));
     }
 }
 
-fn quantum() -> Vec<usize> {
-    let mut q = Vec::new();
-    for i in 1..=3 {
-        for j in 1..=3 {
-            for k in 1..=3 {
-                q.push(i + j + k);
-            }
+fn print_game_lengths(outcome: &DiracOutcome) {
+    println!(
+        "Expected game length: {:.4} turns ({} / {})",
+        outcome.expected_turns(),
+        outcome.total_turns(),
+        outcome.universes()
+    );
+    for (turns, universes) in outcome.lengths.iter().enumerate() {
+        if *universes > 0 {
+            println!("{:>3} turns: {}", turns, universes);
         }
     }
-    q
-}
-
-fn play_dirac_game(player1: Player, player2: Player, winning_score: usize) -> (usize, usize) {
-    let mut memoization: HashMap<(Player, Player, usize), (usize, usize)> = HashMap::new();
-
-    let mut total_player1_wins = 0;
-    let mut total_player2_wins = 0;
-
-    for dice in quantum() {
-        let (player1_wins, player2_wins) =
-            play_dirac_game_rec(&mut memoization, player1, player2, dice, winning_score);
-        total_player1_wins += player1_wins;
-        total_player2_wins += player2_wins;
-    }
-
-    (total_player1_wins, total_player2_wins)
-}
-
-fn play_dirac_game_rec(
-    memoization: &mut HashMap<(Player, Player, usize), (usize, usize)>,
-    mut player: Player,
-    other: Player,
-    dice: usize,
-    winning_score: usize,
-) -> (usize, usize) {
-    if let Some(&(player_wins, other_wins)) = memoization.get(&(player, other, dice)) {
-        return (player_wins, other_wins);
-    }
-
-    let initial_player = player; // memoize the unmodified player
-
-    player.moves(dice);
-    if player.total_score() >= winning_score {
-        return (1, 0);
-    }
-
-    let mut total_player_wins = 0;
-    let mut total_other_wins = 0;
-
-    for dice in quantum() {
-        let (other_wins, player_wins) =
-            play_dirac_game_rec(memoization, other, player, dice, winning_score);
-        total_player_wins += player_wins;
-        total_other_wins += other_wins;
-    }
-
-    memoization.insert(
-        (initial_player, other, dice),
-        (total_player_wins, total_other_wins),
-    );
-
-    (total_player_wins, total_other_wins)
-}
-
-fn main() {
-    // Example
-    //let player1 = Player::new(4);
-    //let player2 = Player::new(8);
-
-    // Puzzle input
-    let player1 = Player::new(8);
-    let player2 = Player::new(10);
-
-    let mut dice = DeterministicDice::new(100);
-    let GameResult { loser, .. } = play_game(&mut dice, player1, player2, 1000);
-    println!("Part 1: {}", loser.total_score() * dice.roll_count);
-
-    let (player1_wins, player2_wins) = play_dirac_game(player1, player2, 21);
-    println!("Part 2: {}", cmp::max(player1_wins, player2_wins));
 }
@@ -0,0 +1,1012 @@
+pub use common::cancel::CancellationToken;
+use common::{Answer, Solution};
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use thiserror::Error;
+use tracing::{debug, trace};
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Input = Vec<Step>;
+
+    fn parse(input: &str) -> Self::Input {
+        parse_steps(input, false)
+    }
+
+    fn part1(steps: &Self::Input) -> Answer {
+        Self::part1_cancellable(steps, &CancellationToken::default()).unwrap()
+    }
+
+    fn part2(steps: &Self::Input) -> Answer {
+        Self::part2_cancellable(steps, &CancellationToken::default()).unwrap()
+    }
+
+    fn part1_cancellable(steps: &Self::Input, cancel: &CancellationToken) -> Option<Answer> {
+        let part1_steps = steps.iter().filter(|step| !step.ignore_part1());
+        active_cell_count(part1_steps, cancel).map(Answer::from)
+    }
+
+    fn part2_cancellable(steps: &Self::Input, cancel: &CancellationToken) -> Option<Answer> {
+        active_cell_count(steps, cancel).map(Answer::from)
+    }
+}
+
+fn active_cell_count<'a, I>(steps: I, cancel: &CancellationToken) -> Option<i128>
+where
+    I: IntoIterator<Item = &'a Step>,
+{
+    let cube = execute_steps(steps, cancel).ok()?;
+    Some(cube.active_cell_count().unwrap())
+}
+
+// One step per line, lines which aren't valid steps are skipped.
+pub fn parse_steps(input: &str, extended: bool) -> Vec<Step> {
+    input
+        .lines()
+        .filter_map(|s| {
+            if extended {
+                parse_extended_step(s).ok()
+            } else {
+                s.parse().ok().map(|step| vec![step])
+            }
+        })
+        .flatten()
+        .collect()
+}
+
+#[derive(Error, Debug)]
+pub enum ParseError {
+    #[error("invalid cuboid format, expected 'x=10..12,y=10..12,z=10..12'")]
+    InvalidCuboidFormat,
+    #[error("invalid step format")]
+    InvalidStepFormat,
+    #[error("invalid number")]
+    InvalidNumber(#[from] std::num::ParseIntError),
+    #[error("upper bound {0} is out of range")]
+    UpperBoundOutOfRange(i32),
+    #[error(
+        "invalid shape format, expected 'sphere x=0,y=0,z=0,r=5' or 'cylinder x=0,y=0,z=-5..5,r=5'"
+    )]
+    InvalidShapeFormat,
+    #[error("shape exceeds the coordinate range")]
+    ShapeOutOfRange,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum VolumeError {
+    #[error("volume of cuboid {0} overflows")]
+    CuboidOverflow(String),
+    #[error("total volume overflows")]
+    TotalOverflow,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum GeometryError {
+    #[error("cuboid #{0} is empty")]
+    EmptyCuboid(usize),
+    #[error("cuboids #{0} and #{1} overlap")]
+    Overlap(usize, usize),
+    #[error("region {region} has {actual} active cells, expected {expected}")]
+    CellCountMismatch {
+        region: String,
+        expected: i128,
+        actual: i128,
+    },
+    #[error(transparent)]
+    Volume(#[from] VolumeError),
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pos3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Pos3 {
+    pub fn with_x(mut self, x: i32) -> Pos3 {
+        self.x = x;
+        self
+    }
+
+    pub fn with_y(mut self, y: i32) -> Pos3 {
+        self.y = y;
+        self
+    }
+
+    pub fn with_z(mut self, z: i32) -> Pos3 {
+        self.z = z;
+        self
+    }
+}
+
+impl std::fmt::Display for Pos3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}/{}/{}]", self.x, self.y, self.z)
+    }
+}
+
+#[derive(Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cube {
+    cubioids: Vec<Cuboid>,
+}
+
+impl Cube {
+    pub fn active_cell_count(&self) -> Result<i128, VolumeError> {
+        total_cells(&self.cubioids)
+    }
+
+    pub fn union(&mut self, cuboid: Cuboid) {
+        self.cut(&cuboid);
+        self.cubioids.push(cuboid);
+    }
+
+    pub fn cut(&mut self, cuboid: &Cuboid) {
+        self.cubioids = self.cubioids.iter().flat_map(|c| c.cut(cuboid)).collect();
+    }
+
+    // Active cells within the cuboid become inactive and vice versa.
+    pub fn toggle(&mut self, cuboid: &Cuboid) {
+        if cuboid.is_empty() {
+            return;
+        }
+        let active: Vec<Cuboid> = self
+            .cubioids
+            .iter()
+            .filter_map(|c| c.intersection(cuboid))
+            .collect();
+        self.cut(cuboid);
+
+        let mut inactive = vec![cuboid.clone()];
+        for c in &active {
+            inactive = inactive.iter().flat_map(|piece| piece.cut(c)).collect();
+        }
+        self.cubioids.extend(inactive);
+    }
+
+    pub fn clipped_cell_count(&self, region: &Cuboid) -> Result<i128, VolumeError> {
+        let clipped: Vec<Cuboid> = self
+            .cubioids
+            .iter()
+            .filter_map(|c| c.intersection(region))
+            .collect();
+        total_cells(&clipped)
+    }
+
+    // Checks that all cuboids are non-empty and pairwise disjoint.
+    pub fn validate(&self) -> Result<(), GeometryError> {
+        if let Some(i) = self.cubioids.iter().position(Cuboid::is_empty) {
+            return Err(GeometryError::EmptyCuboid(i));
+        }
+
+        // sweep along x, only cuboids with overlapping x ranges need to be compared
+        let mut order: Vec<usize> = (0..self.cubioids.len()).collect();
+        order.sort_unstable_by_key(|&i| self.cubioids[i].pos1.x);
+        for (k, &i) in order.iter().enumerate() {
+            let cuboid = &self.cubioids[i];
+            for &j in order[k + 1..]
+                .iter()
+                .take_while(|&&j| self.cubioids[j].pos1.x < cuboid.pos2.x)
+            {
+                if cuboid.overlaps(&self.cubioids[j]) {
+                    return Err(GeometryError::Overlap(i.min(j), i.max(j)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Cube {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.active_cell_count() {
+            Ok(cells) => writeln!(f, "total cells: {}", cells)?,
+            Err(e) => writeln!(f, "total cells: {}", e)?,
+        }
+        for (i, cuboid) in self.cubioids.iter().enumerate() {
+            match cuboid.cells() {
+                Ok(cells) => writeln!(f, "{}: {} [cells: {}]", i, cuboid, cells)?,
+                Err(e) => writeln!(f, "{}: {} [{}]", i, cuboid, e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cuboid {
+    pos1: Pos3,
+    pos2: Pos3,
+}
+
+impl Cuboid {
+    pub fn new(pos1: Pos3, pos2: Pos3) -> Cuboid {
+        Self { pos1, pos2 }
+    }
+
+    pub fn cut(&self, other: &Cuboid) -> Vec<Cuboid> {
+        if !self.overlaps(other) {
+            return vec![self.clone()];
+        }
+        if self.fully_covered_by(other) {
+            return vec![];
+        }
+
+        let xs = {
+            let mut xs = Vec::new();
+            if self.pos1.x < other.pos1.x {
+                assert!(other.pos1.x <= self.pos2.x);
+                xs.push(other.pos1.x);
+            }
+            if other.pos2.x < self.pos2.x {
+                assert!(self.pos1.x <= other.pos2.x);
+                xs.push(other.pos2.x);
+            }
+            xs
+        };
+
+        let ys = {
+            let mut ys = Vec::new();
+            if self.pos1.y < other.pos1.y {
+                assert!(other.pos1.y <= self.pos2.y);
+                ys.push(other.pos1.y);
+            }
+            if other.pos2.y < self.pos2.y {
+                assert!(self.pos1.y <= other.pos2.y);
+                ys.push(other.pos2.y);
+            }
+            ys
+        };
+
+        let zs = {
+            let mut zs = Vec::new();
+            if self.pos1.z < other.pos1.z {
+                assert!(other.pos1.z <= self.pos2.z);
+                zs.push(other.pos1.z);
+            }
+            if other.pos2.z < self.pos2.z {
+                assert!(self.pos1.z <= other.pos2.z);
+                zs.push(other.pos2.z);
+            }
+            zs
+        };
+
+        let pieces: Vec<Cuboid> = self
+            .split_x(xs)
+            .iter()
+            .flat_map(|c| c.split_y(ys.clone()))
+            .flat_map(|c| c.split_z(zs.clone()))
+            .filter(|c| !c.fully_covered_by(other))
+            .collect();
+
+        // As long as the pieces of every cut are non-empty, disjoint and outside of the cut
+        // cuboid, the cuboids of a cube stay pairwise disjoint after every step.
+        debug_assert!(pieces.iter().all(|c| !c.is_empty()));
+        debug_assert!(pieces.iter().all(|c| !c.overlaps(other)));
+        debug_assert!(pieces
+            .iter()
+            .enumerate()
+            .all(|(i, c)| pieces[i + 1..].iter().all(|d| !c.overlaps(d))));
+        debug_assert_eq!(
+            total_cells(&pieces),
+            self.cells().and_then(|cells| {
+                let cut = self.intersection(other).map_or(Ok(0), |c| c.cells())?;
+                Ok(cells - cut)
+            })
+        );
+
+        pieces
+    }
+
+    fn split_x(&self, mut xs: Vec<i32>) -> Vec<Cuboid> {
+        xs.insert(0, self.pos1.x);
+        xs.push(self.pos2.x);
+        xs.windows(2)
+            .map(|x| Cuboid::new(self.pos1.with_x(x[0]), self.pos2.with_x(x[1])))
+            .collect()
+    }
+
+    fn split_y(&self, mut ys: Vec<i32>) -> Vec<Cuboid> {
+        ys.insert(0, self.pos1.y);
+        ys.push(self.pos2.y);
+        ys.windows(2)
+            .map(|y| Cuboid::new(self.pos1.with_y(y[0]), self.pos2.with_y(y[1])))
+            .collect()
+    }
+
+    fn split_z(&self, mut zs: Vec<i32>) -> Vec<Cuboid> {
+        zs.insert(0, self.pos1.z);
+        zs.push(self.pos2.z);
+        zs.windows(2)
+            .map(|z| Cuboid::new(self.pos1.with_z(z[0]), self.pos2.with_z(z[1])))
+            .collect()
+    }
+
+    fn fully_covered_by(&self, other: &Cuboid) -> bool {
+        other.pos1.x <= self.pos1.x
+            && self.pos2.x <= other.pos2.x
+            && other.pos1.y <= self.pos1.y
+            && self.pos2.y <= other.pos2.y
+            && other.pos1.z <= self.pos1.z
+            && self.pos2.z <= other.pos2.z
+    }
+
+    // pos2 is exclusive, so cuboids which merely touch don't overlap
+    fn overlaps(&self, other: &Cuboid) -> bool {
+        !(other.pos1.x >= self.pos2.x
+            || other.pos1.y >= self.pos2.y
+            || other.pos1.z >= self.pos2.z
+            || other.pos2.x <= self.pos1.x
+            || other.pos2.y <= self.pos1.y
+            || other.pos2.z <= self.pos1.z)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos2.x <= self.pos1.x || self.pos2.y <= self.pos1.y || self.pos2.z <= self.pos1.z
+    }
+
+    pub fn contains(&self, pos: Pos3) -> bool {
+        (self.pos1.x..self.pos2.x).contains(&pos.x)
+            && (self.pos1.y..self.pos2.y).contains(&pos.y)
+            && (self.pos1.z..self.pos2.z).contains(&pos.z)
+    }
+
+    pub fn intersection(&self, other: &Cuboid) -> Option<Cuboid> {
+        if !self.overlaps(other) {
+            return None;
+        }
+        Some(Cuboid::new(
+            Pos3 {
+                x: self.pos1.x.max(other.pos1.x),
+                y: self.pos1.y.max(other.pos1.y),
+                z: self.pos1.z.max(other.pos1.z),
+            },
+            Pos3 {
+                x: self.pos2.x.min(other.pos2.x),
+                y: self.pos2.y.min(other.pos2.y),
+                z: self.pos2.z.min(other.pos2.z),
+            },
+        ))
+    }
+
+    // The extents of i32 coordinates need at most 33 bits, so the volume fits into an i128,
+    // but the arithmetic is checked nevertheless.
+    pub fn cells(&self) -> Result<i128, VolumeError> {
+        let extent = |a: i32, b: i32| (i128::from(b) - i128::from(a)).abs();
+        extent(self.pos1.x, self.pos2.x)
+            .checked_mul(extent(self.pos1.y, self.pos2.y))
+            .and_then(|cells| cells.checked_mul(extent(self.pos1.z, self.pos2.z)))
+            .ok_or_else(|| VolumeError::CuboidOverflow(self.to_string()))
+    }
+}
+
+pub fn total_cells(cuboids: &[Cuboid]) -> Result<i128, VolumeError> {
+    cuboids.iter().try_fold(0i128, |total, cuboid| {
+        total
+            .checked_add(cuboid.cells()?)
+            .ok_or(VolumeError::TotalOverflow)
+    })
+}
+
+impl std::fmt::Display for Cuboid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "x={}..{},y={}..{},z={}..{}",
+            self.pos1.x, self.pos2.x, self.pos1.y, self.pos2.y, self.pos1.z, self.pos2.z
+        )
+    }
+}
+
+impl FromStr for Cuboid {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Cuboid, Self::Err> {
+        let re = Regex::new(
+            r"^x=(?P<x1>-?\d+)\.\.(?P<x2>-?\d+),y=(?P<y1>-?\d+)\.\.(?P<y2>-?\d+),z=(?P<z1>-?\d+)\.\.(?P<z2>-?\d+)$",
+        )
+        .unwrap();
+        let caps = re.captures(s).ok_or(ParseError::InvalidCuboidFormat)?;
+
+        let p1 = Pos3 {
+            x: caps
+                .name("x1")
+                .ok_or(ParseError::InvalidCuboidFormat)?
+                .as_str()
+                .parse()?,
+            y: caps
+                .name("y1")
+                .ok_or(ParseError::InvalidCuboidFormat)?
+                .as_str()
+                .parse()?,
+            z: caps
+                .name("z1")
+                .ok_or(ParseError::InvalidCuboidFormat)?
+                .as_str()
+                .parse()?,
+        };
+
+        let p2 = Pos3 {
+            x: caps
+                .name("x2")
+                .ok_or(ParseError::InvalidCuboidFormat)?
+                .as_str()
+                .parse()?,
+            y: caps
+                .name("y2")
+                .ok_or(ParseError::InvalidCuboidFormat)?
+                .as_str()
+                .parse()?,
+            z: caps
+                .name("z2")
+                .ok_or(ParseError::InvalidCuboidFormat)?
+                .as_str()
+                .parse()?,
+        };
+        // exclude
+        let exclusive = |bound: i32| {
+            bound
+                .checked_add(1)
+                .ok_or(ParseError::UpperBoundOutOfRange(bound))
+        };
+        let p2 = Pos3 {
+            x: exclusive(p2.x)?,
+            y: exclusive(p2.y)?,
+            z: exclusive(p2.z)?,
+        };
+
+        Ok(Self::new(p1, p2))
+    }
+}
+
+pub enum Step {
+    On(Cuboid),
+    Off(Cuboid),
+    // extended mode only
+    Toggle(Cuboid),
+}
+
+impl Step {
+    pub fn execute(&self, mut cube: Cube) -> Cube {
+        match self {
+            Self::On(cuboid) => cube.union(cuboid.clone()),
+            Self::Off(cuboid) => cube.cut(cuboid),
+            Self::Toggle(cuboid) => cube.toggle(cuboid),
+        };
+        trace!(%cube, "executed step");
+        cube
+    }
+
+    // State of a cell within the cuboid after the step, given its state before.
+    pub fn apply(&self, active: bool) -> bool {
+        match self {
+            Self::On(_) => true,
+            Self::Off(_) => false,
+            Self::Toggle(_) => !active,
+        }
+    }
+
+    pub fn cuboid(&self) -> &Cuboid {
+        match self {
+            Self::On(cuboid) | Self::Off(cuboid) | Self::Toggle(cuboid) => cuboid,
+        }
+    }
+
+    pub fn ignore_part1(&self) -> bool {
+        let cuboid = self.cuboid();
+        cuboid.pos1.x < -50
+            || cuboid.pos1.y < -50
+            || cuboid.pos1.z < -50
+            || cuboid.pos1.x > 50
+            || cuboid.pos1.y > 50
+            || cuboid.pos1.z > 50
+    }
+}
+
+impl FromStr for Step {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Step, Self::Err> {
+        let (cmd, cuboid) = s.split_once(' ').ok_or(ParseError::InvalidStepFormat)?;
+        let cuboid = cuboid.parse()?;
+        match cmd {
+            "on" => Ok(Self::On(cuboid)),
+            "off" => Ok(Self::Off(cuboid)),
+            _ => Err(ParseError::InvalidStepFormat),
+        }
+    }
+}
+
+// Shapes of the extended mode, cylinders have their axis along z.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Sphere {
+        center: Pos3,
+        radius: i32,
+    },
+    Cylinder {
+        x: i32,
+        y: i32,
+        z1: i32,
+        z2: i32,
+        radius: i32,
+    },
+}
+
+impl Shape {
+    // Disjoint cuboids covering exactly the cells whose center lies within the shape. Every
+    // slice along x is split into runs of columns with the same z extent.
+    pub fn voxelize(&self) -> Result<Vec<Cuboid>, ParseError> {
+        let (cx, cy, radius) = match *self {
+            Shape::Sphere { center, radius } => (center.x, center.y, radius),
+            Shape::Cylinder { x, y, radius, .. } => (x, y, radius),
+        };
+        let (cx, cy, r) = (i64::from(cx), i64::from(cy), i64::from(radius));
+        // inclusive z extent of the column with the given squared distance from the axis
+        let z_extent = |d2: i64| match *self {
+            Shape::Sphere { center, .. } => {
+                let h = (r * r - d2).isqrt();
+                (i64::from(center.z) - h, i64::from(center.z) + h)
+            }
+            Shape::Cylinder { z1, z2, .. } => (i64::from(z1), i64::from(z2)),
+        };
+        let coord = |v: i64| i32::try_from(v).map_err(|_| ParseError::ShapeOutOfRange);
+
+        let mut cuboids = Vec::new();
+        for x in cx - r..=cx + r {
+            let dx2 = (x - cx).pow(2);
+            let mut run: Option<(i64, (i64, i64))> = None;
+            for y in cy - r..=cy + r + 1 {
+                let d2 = dx2 + (y - cy).pow(2);
+                let extent = (y <= cy + r && d2 <= r * r).then(|| z_extent(d2));
+                if run.map(|(_, extent)| extent) == extent {
+                    continue;
+                }
+                if let Some((y1, (z1, z2))) = run {
+                    if z1 <= z2 {
+                        cuboids.push(Cuboid::new(
+                            Pos3 {
+                                x: coord(x)?,
+                                y: coord(y1)?,
+                                z: coord(z1)?,
+                            },
+                            Pos3 {
+                                x: coord(x + 1)?,
+                                y: coord(y)?,
+                                z: coord(z2 + 1)?,
+                            },
+                        ));
+                    }
+                }
+                run = extent.map(|extent| (y, extent));
+            }
+        }
+        Ok(cuboids)
+    }
+}
+
+impl FromStr for Shape {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Shape, Self::Err> {
+        let sphere = Regex::new(r"^sphere x=(-?\d+),y=(-?\d+),z=(-?\d+),r=(\d+)$").unwrap();
+        let cylinder =
+            Regex::new(r"^cylinder x=(-?\d+),y=(-?\d+),z=(-?\d+)\.\.(-?\d+),r=(\d+)$").unwrap();
+
+        if let Some(caps) = sphere.captures(s) {
+            Ok(Shape::Sphere {
+                center: Pos3 {
+                    x: caps[1].parse()?,
+                    y: caps[2].parse()?,
+                    z: caps[3].parse()?,
+                },
+                radius: caps[4].parse()?,
+            })
+        } else if let Some(caps) = cylinder.captures(s) {
+            Ok(Shape::Cylinder {
+                x: caps[1].parse()?,
+                y: caps[2].parse()?,
+                z1: caps[3].parse()?,
+                z2: caps[4].parse()?,
+                radius: caps[5].parse()?,
+            })
+        } else {
+            Err(ParseError::InvalidShapeFormat)
+        }
+    }
+}
+
+// Parses a step of the extended mode, which adds 'toggle' and spheres/cylinders to the classic
+// grammar:
+//
+//   toggle x=10..12,y=10..12,z=10..12
+//   on sphere x=0,y=0,z=0,r=5
+//   off cylinder x=0,y=0,z=-5..5,r=5
+//
+// Shapes are voxelized into one step per cuboid.
+pub fn parse_extended_step(s: &str) -> Result<Vec<Step>, ParseError> {
+    let (cmd, region) = s.split_once(' ').ok_or(ParseError::InvalidStepFormat)?;
+    let step = match cmd {
+        "on" => Step::On,
+        "off" => Step::Off,
+        "toggle" => Step::Toggle,
+        _ => return Err(ParseError::InvalidStepFormat),
+    };
+    let cuboids = if region.starts_with(|c: char| c.is_ascii_alphabetic() && c != 'x') {
+        region.parse::<Shape>()?.voxelize()?
+    } else {
+        vec![region.parse()?]
+    };
+    Ok(cuboids.into_iter().map(step).collect())
+}
+
+// Progress of a cancelled reboot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled {
+    pub executed_steps: usize,
+    pub cuboids: usize,
+}
+
+// Executes the steps one after another, checking for cancellation in between.
+pub fn execute_steps<'a, I>(steps: I, cancel: &CancellationToken) -> Result<Cube, Cancelled>
+where
+    I: IntoIterator<Item = &'a Step>,
+{
+    let mut cube = Cube::default();
+    for (executed_steps, step) in steps.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            return Err(Cancelled {
+                executed_steps,
+                cuboids: cube.cubioids.len(),
+            });
+        }
+        cube = step.execute(cube);
+        debug!(
+            step = executed_steps + 1,
+            cuboids = cube.cubioids.len(),
+            "executed step"
+        );
+    }
+    Ok(cube)
+}
+
+// Counts the active cells within the region voxel by voxel, applying all steps covering a voxel
+// in order.
+pub fn brute_force_cell_count(steps: &[Step], region: &Cuboid) -> i128 {
+    let steps: Vec<&Step> = steps
+        .iter()
+        .filter(|step| step.cuboid().overlaps(region))
+        .collect();
+
+    let mut count = 0;
+    for x in region.pos1.x..region.pos2.x {
+        for y in region.pos1.y..region.pos2.y {
+            for z in region.pos1.z..region.pos2.z {
+                let pos = Pos3 { x, y, z };
+                let active = steps
+                    .iter()
+                    .filter(|step| step.cuboid().contains(pos))
+                    .fold(false, |active, step| step.apply(active));
+                if active {
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
+
+// Small regions around the corners of (some of) the steps, which is where off-by-one errors
+// show up, plus the initialization region of part 1.
+pub fn self_check_regions(steps: &[Step]) -> Vec<Cuboid> {
+    const MAX_REGIONS: usize = 32;
+    const RADIUS: i32 = 4;
+
+    let around = |pos: Pos3| {
+        Cuboid::new(
+            Pos3 {
+                x: pos.x - RADIUS,
+                y: pos.y - RADIUS,
+                z: pos.z - RADIUS,
+            },
+            Pos3 {
+                x: pos.x + RADIUS,
+                y: pos.y + RADIUS,
+                z: pos.z + RADIUS,
+            },
+        )
+    };
+
+    let mut regions = vec![Cuboid::new(
+        Pos3 {
+            x: -50,
+            y: -50,
+            z: -50,
+        },
+        Pos3 {
+            x: 51,
+            y: 51,
+            z: 51,
+        },
+    )];
+    let step_by = (2 * steps.len()).div_ceil(MAX_REGIONS).max(1);
+    regions.extend(
+        steps
+            .iter()
+            .step_by(step_by)
+            .flat_map(|step| [around(step.cuboid().pos1), around(step.cuboid().pos2)]),
+    );
+    regions
+}
+
+// Runs all steps, validating the cube after each of them, and compares the active cells
+// within the self-check regions against a brute-force count. Returns the number of checked regions.
+pub fn self_check(steps: &[Step]) -> Result<usize, GeometryError> {
+    let mut cube = Cube::default();
+    for step in steps {
+        cube = step.execute(cube);
+        cube.validate()?;
+    }
+
+    let regions = self_check_regions(steps);
+    for region in &regions {
+        let expected = brute_force_cell_count(steps, region);
+        let actual = cube.clipped_cell_count(region)?;
+        if actual != expected {
+            return Err(GeometryError::CellCountMismatch {
+                region: region.to_string(),
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(regions.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "on x=10..12,y=10..12,z=10..12
+on x=11..13,y=11..13,z=11..13
+off x=9..11,y=9..11,z=9..11
+on x=10..10,y=10..10,z=10..10";
+
+    fn parse_steps(s: &str) -> Vec<Step> {
+        s.lines().map(|line| line.parse().unwrap()).collect()
+    }
+
+    fn cuboid(s: &str) -> Cuboid {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_example() {
+        let steps = parse_steps(EXAMPLE);
+        let cube = steps
+            .iter()
+            .fold(Cube::default(), |cube, step| step.execute(cube));
+        assert_eq!(cube.active_cell_count(), Ok(39));
+        assert_eq!(cube.validate(), Ok(()));
+        assert_eq!(self_check(&steps), Ok(9));
+    }
+
+    #[test]
+    fn test_execute_steps_cancellation() {
+        let steps = parse_steps(EXAMPLE);
+        let cube = execute_steps(&steps, &CancellationToken::default()).unwrap();
+        assert_eq!(cube.active_cell_count(), Ok(39));
+
+        let cancel = CancellationToken::default();
+        cancel.cancel();
+        assert_eq!(
+            execute_steps(&steps, &cancel).err(),
+            Some(Cancelled {
+                executed_steps: 0,
+                cuboids: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_touching_cuboids_dont_overlap() {
+        let a = cuboid("x=0..1,y=0..1,z=0..1");
+        let b = cuboid("x=2..3,y=0..1,z=0..1");
+        assert!(!a.overlaps(&b));
+        assert!(a.intersection(&b).is_none());
+
+        let pieces = a.cut(&b);
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(pieces[0].cells(), Ok(8));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut cube = Cube::default();
+        cube.cubioids.push(cuboid("x=0..1,y=0..1,z=0..1"));
+        cube.cubioids.push(cuboid("x=2..3,y=0..1,z=0..1"));
+        assert_eq!(cube.validate(), Ok(()));
+
+        cube.cubioids.push(cuboid("x=1..2,y=1..1,z=1..1"));
+        assert_eq!(cube.validate(), Err(GeometryError::Overlap(0, 2)));
+
+        cube.cubioids
+            .push(Cuboid::new(Pos3::default(), Pos3::default().with_x(1)));
+        assert_eq!(cube.validate(), Err(GeometryError::EmptyCuboid(3)));
+    }
+
+    #[test]
+    fn test_clipped_cell_count_matches_brute_force() {
+        let steps = parse_steps(EXAMPLE);
+        let cube = steps
+            .iter()
+            .fold(Cube::default(), |cube, step| step.execute(cube));
+        let region = cuboid("x=11..12,y=9..13,z=10..11");
+        assert_eq!(
+            cube.clipped_cell_count(&region),
+            Ok(brute_force_cell_count(&steps, &region))
+        );
+    }
+
+    #[test]
+    fn test_near_overflow_volumes() {
+        const MIN: i32 = i32::MIN;
+        const MAX: i32 = i32::MAX - 1;
+        let extent = (1i128 << 32) - 1;
+        let everything = cuboid(&format!(
+            "x={}..{},y={}..{},z={}..{}",
+            MIN, MAX, MIN, MAX, MIN, MAX
+        ));
+        assert_eq!(everything.cells(), Ok(extent.pow(3)));
+
+        let mut cube = Cube::default();
+        cube.union(everything.clone());
+        cube.union(cuboid(&format!("x={}..{},y=0..0,z=0..0", MIN, MAX)));
+        assert_eq!(cube.active_cell_count(), Ok(extent.pow(3)));
+
+        cube.cut(&cuboid(&format!("x={}..{},y=0..0,z=0..0", MAX - 9, MAX)));
+        assert_eq!(cube.active_cell_count(), Ok(extent.pow(3) - 10));
+        assert_eq!(cube.validate(), Ok(()));
+
+        cube.cut(&cuboid(&format!(
+            "x={}..{},y={}..{},z=0..0",
+            MIN, MAX, MIN, MAX
+        )));
+        assert_eq!(cube.active_cell_count(), Ok(extent.pow(3) - extent.pow(2)));
+
+        let clipped =
+            cube.clipped_cell_count(&cuboid(&format!("x={}..{},y=-1..0,z=-1..0", MIN, MAX)));
+        // z=0 has been cut away
+        assert_eq!(clipped, Ok(2 * extent));
+    }
+
+    fn parse_extended_steps(s: &str) -> Vec<Step> {
+        s.lines()
+            .flat_map(|line| parse_extended_step(line).unwrap())
+            .collect()
+    }
+
+    fn lattice_points(shape: &Shape) -> i128 {
+        let cube = shape
+            .voxelize()
+            .unwrap()
+            .into_iter()
+            .fold(Cube::default(), |cube, cuboid| {
+                Step::On(cuboid).execute(cube)
+            });
+        assert_eq!(cube.validate(), Ok(()));
+        cube.active_cell_count().unwrap()
+    }
+
+    #[test]
+    fn test_toggle() {
+        let steps = parse_extended_steps(
+            "toggle x=0..2,y=0..2,z=0..2
+toggle x=1..3,y=0..2,z=0..2
+on x=2..2,y=2..2,z=2..2
+toggle x=2..2,y=1..2,z=2..2",
+        );
+        let cube = execute_steps(&steps, &CancellationToken::default()).unwrap();
+        // 27 + 27 - 2 * 18 cells at x=0 and x=3, plus the one cell turned on which is then
+        // swapped for its inactive neighbor
+        assert_eq!(cube.active_cell_count(), Ok(19));
+        assert_eq!(cube.validate(), Ok(()));
+        assert_eq!(self_check(&steps), Ok(9));
+    }
+
+    #[test]
+    fn test_voxelize_shapes() {
+        let sphere = |radius| Shape::Sphere {
+            center: Pos3 { x: 3, y: -4, z: 5 },
+            radius,
+        };
+        assert_eq!(lattice_points(&sphere(0)), 1);
+        assert_eq!(lattice_points(&sphere(1)), 7);
+        assert_eq!(lattice_points(&sphere(2)), 33);
+        assert_eq!(lattice_points(&sphere(10)), 4169);
+
+        let cylinder = Shape::Cylinder {
+            x: 0,
+            y: 0,
+            z1: -1,
+            z2: 1,
+            radius: 1,
+        };
+        assert_eq!(lattice_points(&cylinder), 15);
+
+        let too_large = Shape::Sphere {
+            center: Pos3 {
+                x: i32::MAX,
+                y: 0,
+                z: 0,
+            },
+            radius: 1,
+        };
+        assert!(matches!(
+            too_large.voxelize(),
+            Err(ParseError::ShapeOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn test_extended_shapes_match_brute_force() {
+        let steps = parse_extended_steps(
+            "on sphere x=0,y=0,z=0,r=6
+off cylinder x=2,y=1,z=-10..10,r=3
+toggle sphere x=-3,y=-3,z=2,r=4
+toggle x=-2..2,y=-2..2,z=-2..2",
+        );
+        let cube = execute_steps(&steps, &CancellationToken::default()).unwrap();
+        assert_eq!(cube.validate(), Ok(()));
+        let region = cuboid("x=-8..8,y=-8..8,z=-11..11");
+        assert_eq!(
+            cube.clipped_cell_count(&region),
+            Ok(brute_force_cell_count(&steps, &region))
+        );
+    }
+
+    #[test]
+    fn test_extended_grammar_is_opt_in() {
+        for step in [
+            "toggle x=0..1,y=0..1,z=0..1",
+            "on sphere x=0,y=0,z=0,r=5",
+            "off cylinder x=0,y=0,z=-5..5,r=5",
+        ] {
+            assert!(step.parse::<Step>().is_err(), "{}", step);
+            assert!(parse_extended_step(step).is_ok(), "{}", step);
+        }
+        assert!(matches!(
+            parse_extended_step("on cone x=0,y=0,z=0,r=5"),
+            Err(ParseError::InvalidShapeFormat)
+        ));
+        assert!(matches!(
+            parse_extended_step("flip x=0..1,y=0..1,z=0..1"),
+            Err(ParseError::InvalidStepFormat)
+        ));
+    }
+
+    #[test]
+    fn test_upper_bound_out_of_range() {
+        let s = format!("x=0..{},y=0..0,z=0..0", i32::MAX);
+        assert!(matches!(
+            s.parse::<Cuboid>(),
+            Err(ParseError::UpperBoundOutOfRange(i32::MAX))
+        ));
+    }
+
+    #[test]
+    fn test_solution_examples() {
+        let (part1, _) = common::solve::<Day22>(include_str!("../example.txt"));
+        assert_eq!(part1.to_string(), "590784");
+        let (part1, part2) = common::solve::<Day22>(include_str!("../example2.txt"));
+        assert_eq!(part1.to_string(), "474140");
+        assert_eq!(part2.to_string(), "2758514936282235");
+    }
+}
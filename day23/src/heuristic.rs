@@ -0,0 +1,238 @@
+use super::{Amphipod, State};
+use std::str::FromStr;
+
+// Parts of the energy an organization of the burrow needs at least. The components count
+// disjoint steps of the remaining moves, so every sum of them is a lower bound as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundComponent {
+    // horizontal distance of every amphipod outside of its final place to its target room
+    // (at least 2 to get around the wall when it has to leave its target room), plus walking
+    // down into the room as deep as needed to fill it
+    DirectMoves,
+    // steps up to the hallway of every amphipod which has to leave its room
+    RoomExits,
+    // hallway amphipods which have to pass each other never get home
+    HallwayBlocking,
+}
+
+impl FromStr for BoundComponent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<BoundComponent, Self::Err> {
+        match s {
+            "direct" => Ok(Self::DirectMoves),
+            "exits" => Ok(Self::RoomExits),
+            "blocking" => Ok(Self::HallwayBlocking),
+            _ => Err(format!(
+                "unknown heuristic '{}', expected direct, exits or blocking",
+                s
+            )),
+        }
+    }
+}
+
+const AMPHIPODS: [Amphipod; 4] = [
+    Amphipod::Amber,
+    Amphipod::Bronze,
+    Amphipod::Copper,
+    Amphipod::Desert,
+];
+
+fn room_x(room: usize) -> usize {
+    2 + room * 2
+}
+
+// Amphipods in a room above the ones already in their final place, bottom to top, with
+// their position counted from the bottom.
+fn leaving_amphipods<const DEPTH: usize>(
+    state: &State<DEPTH>,
+) -> impl Iterator<Item = (usize, usize, Amphipod)> + '_ {
+    state
+        .side_rooms
+        .iter()
+        .enumerate()
+        .flat_map(|(room, amphipods)| {
+            let settled = amphipods
+                .iter()
+                .take_while(|amphipod| amphipod.target_room() == room)
+                .count();
+            amphipods
+                .iter()
+                .enumerate()
+                .skip(settled)
+                .map(move |(slot, &amphipod)| (room, slot, amphipod))
+        })
+}
+
+impl BoundComponent {
+    // None if the state can't be organized at all.
+    pub fn lower_bound<const DEPTH: usize>(self, state: &State<DEPTH>) -> Option<usize> {
+        match self {
+            Self::DirectMoves => {
+                let mut energy = 0;
+                let mut entering = [0; 4];
+                for (x, amphipod) in state.hallway.iter().enumerate() {
+                    if let Some(amphipod) = amphipod {
+                        let target = amphipod.target_room();
+                        energy += x.abs_diff(room_x(target)) * amphipod.energy();
+                        entering[target] += 1;
+                    }
+                }
+                for (room, _, amphipod) in leaving_amphipods(state) {
+                    let target = amphipod.target_room();
+                    let horizontal = match room_x(room).abs_diff(room_x(target)) {
+                        0 => 2,
+                        distance => distance,
+                    };
+                    energy += horizontal * amphipod.energy();
+                    entering[target] += 1;
+                }
+                // the k amphipods entering a room fill its top k places
+                for (amphipod, k) in AMPHIPODS.iter().zip(entering) {
+                    energy += k * (k + 1) / 2 * amphipod.energy();
+                }
+                Some(energy)
+            }
+            Self::RoomExits => Some(
+                leaving_amphipods(state)
+                    .map(|(_, slot, amphipod)| (DEPTH - slot) * amphipod.energy())
+                    .sum(),
+            ),
+            Self::HallwayBlocking => (!state.is_deadlocked()).then_some(0),
+        }
+    }
+}
+
+// Lower bound for the energy still needed, the sum of the selected components.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heuristic {
+    components: Vec<BoundComponent>,
+}
+
+impl Heuristic {
+    pub fn new(components: Vec<BoundComponent>) -> Heuristic {
+        Self { components }
+    }
+
+    // Only prunes by the energy spent so far.
+    pub fn none() -> Heuristic {
+        Self::new(Vec::new())
+    }
+
+    pub fn lower_bound<const DEPTH: usize>(&self, state: &State<DEPTH>) -> Option<usize> {
+        self.components
+            .iter()
+            .map(|component| component.lower_bound(state))
+            .sum()
+    }
+}
+
+impl Default for Heuristic {
+    fn default() -> Self {
+        Self::new(vec![
+            BoundComponent::DirectMoves,
+            BoundComponent::RoomExits,
+            BoundComponent::HallwayBlocking,
+        ])
+    }
+}
+
+// Comma separated components, e.g. "direct,exits", or "none".
+impl FromStr for Heuristic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Heuristic, Self::Err> {
+        if s == "none" {
+            return Ok(Self::none());
+        }
+        s.split(',')
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(Self::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PART1_EXAMPLE: &str = "...........|AB|DC|CB|AD";
+    const PART2_EXAMPLE: &str = "...........|ADDB|DBCC|CABB|ACAD";
+
+    fn selections() -> Vec<Heuristic> {
+        let components = [
+            BoundComponent::DirectMoves,
+            BoundComponent::RoomExits,
+            BoundComponent::HallwayBlocking,
+        ];
+        (0..1 << components.len())
+            .map(|mask| {
+                Heuristic::new(
+                    (0..components.len())
+                        .filter(|i| mask & (1 << i) != 0)
+                        .map(|i| components[i])
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_admissible_on_examples() {
+        let part1 = State::<2>::decode(PART1_EXAMPLE).unwrap();
+        let part2 = State::<4>::decode(PART2_EXAMPLE).unwrap();
+        for heuristic in selections() {
+            assert!(
+                heuristic.lower_bound(&part1).unwrap() <= 12521,
+                "{:?}",
+                heuristic
+            );
+            assert!(
+                heuristic.lower_bound(&part2).unwrap() <= 44169,
+                "{:?}",
+                heuristic
+            );
+        }
+        assert!(Heuristic::default().lower_bound(&part1).unwrap() > 0);
+    }
+
+    #[test]
+    fn test_exact_on_simple_states() {
+        let heuristic = Heuristic::default();
+        let done = State::<2>::decode("...........|AA|BB|CC|DD").unwrap();
+        assert_eq!(heuristic.lower_bound(&done), Some(0));
+
+        // the A walks 2 steps to its room and 1 step down, its only path
+        let state = State::<2>::decode("A..........|A|BB|CC|DD").unwrap();
+        assert_eq!(heuristic.lower_bound(&state), Some(3));
+
+        // swap the top amphipods of the first two rooms: the A steps aside (2), the B walks
+        // over (4) and the A comes back (2)
+        let state = State::<2>::decode("...........|AB|BA|CC|DD").unwrap();
+        assert_eq!(heuristic.lower_bound(&state), Some(2 + 40 + 2));
+    }
+
+    #[test]
+    fn test_blocked_hallway() {
+        let deadlocked = State::<2>::decode("...D.A.....|A|BB|CC|D").unwrap();
+        assert_eq!(
+            BoundComponent::HallwayBlocking.lower_bound(&deadlocked),
+            None
+        );
+        assert_eq!(Heuristic::default().lower_bound(&deadlocked), None);
+        assert!(Heuristic::none().lower_bound(&deadlocked).is_some());
+    }
+
+    #[test]
+    fn test_parse_heuristic() {
+        assert_eq!("none".parse(), Ok(Heuristic::none()));
+        assert_eq!(
+            "direct,blocking".parse(),
+            Ok(Heuristic::new(vec![
+                BoundComponent::DirectMoves,
+                BoundComponent::HallwayBlocking
+            ]))
+        );
+        assert!("direct,magic".parse::<Heuristic>().is_err());
+    }
+}
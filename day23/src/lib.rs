@@ -0,0 +1,643 @@
+pub mod heuristic;
+pub mod rules;
+
+pub use common::cancel::CancellationToken;
+pub use common::hash::{HashMap, HashSet, HashState};
+use common::{Answer, Solution};
+use heuristic::Heuristic;
+use rules::HallwayRules;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use tracing::{debug, debug_span, trace};
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input = (State<2>, State<4>);
+
+    fn parse(input: &str) -> Self::Input {
+        let burrow = State::parse_diagram(input).unwrap();
+        let unfolded_burrow = State::parse_diagram(&unfold_diagram(input)).unwrap();
+        (burrow, unfolded_burrow)
+    }
+
+    fn part1(burrows: &Self::Input) -> Answer {
+        Self::part1_cancellable(burrows, &CancellationToken::default()).unwrap()
+    }
+
+    fn part2(burrows: &Self::Input) -> Answer {
+        Self::part2_cancellable(burrows, &CancellationToken::default()).unwrap()
+    }
+
+    fn part1_cancellable((burrow, _): &Self::Input, cancel: &CancellationToken) -> Option<Answer> {
+        solve_default(burrow.clone(), cancel).map(Answer::from)
+    }
+
+    fn part2_cancellable(
+        (_, unfolded_burrow): &Self::Input,
+        cancel: &CancellationToken,
+    ) -> Option<Answer> {
+        solve_default(unfolded_burrow.clone(), cancel).map(Answer::from)
+    }
+}
+
+fn solve_default<const DEPTH: usize>(
+    state: State<DEPTH>,
+    cancel: &CancellationToken,
+) -> Option<usize> {
+    let mut stats = PruningStats::default();
+    organize(state, &Heuristic::default(), &mut stats, cancel).ok()
+}
+
+// Part 2 unfolds the diagram by inserting two more rows into the side rooms.
+pub fn unfold_diagram(diagram: &str) -> String {
+    let mut lines: Vec<&str> = diagram.lines().collect();
+    lines.splice(3..3, ["  #D#C#B#A#", "  #D#B#A#C#"]);
+    lines.join("\n")
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Amphipod {
+    Amber,
+    Bronze,
+    Copper,
+    Desert,
+}
+
+impl Amphipod {
+    pub fn energy(&self) -> usize {
+        match self {
+            Self::Amber => 1,
+            Self::Bronze => 10,
+            Self::Copper => 100,
+            Self::Desert => 1_000,
+        }
+    }
+
+    pub fn target_room(&self) -> usize {
+        match self {
+            Self::Amber => 0,
+            Self::Bronze => 1,
+            Self::Copper => 2,
+            Self::Desert => 3,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Self::Amber => 'A',
+            Self::Bronze => 'B',
+            Self::Copper => 'C',
+            Self::Desert => 'D',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Amphipod> {
+        match c {
+            'A' => Some(Self::Amber),
+            'B' => Some(Self::Bronze),
+            'C' => Some(Self::Copper),
+            'D' => Some(Self::Desert),
+            _ => None,
+        }
+    }
+}
+
+pub fn abs_diff(x: usize, y: usize) -> usize {
+    x.abs_diff(y)
+}
+
+// Number of states skipped by each pruning rule.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PruningStats {
+    // moves out of rooms which only contain amphipods belonging there
+    pub settled: usize,
+    // moves skipped because an amphipod can move into its target room instead
+    pub dominated: usize,
+    // states with amphipods blocking each other in the hallway
+    pub deadlocked: usize,
+    // states whose lower bound can't improve on the best solution found so far
+    pub bounded: usize,
+}
+
+// Progress of a cancelled search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled {
+    // lowest energy of all complete solutions found so far
+    pub best_energy: Option<usize>,
+    pub visited_states: usize,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct State<const DEPTH: usize> {
+    hallway: [Option<Amphipod>; 11],
+    side_rooms: [Vec<Amphipod>; 4],
+    total_energy: usize,
+}
+
+impl<const DEPTH: usize> State<DEPTH> {
+    pub fn is_done(&self) -> bool {
+        (0..4).all(|room| !self.room_needs_move(room) && self.room_is_full(room))
+    }
+
+    pub fn room_needs_move(&self, room: usize) -> bool {
+        self.side_rooms[room]
+            .iter()
+            .any(|amphipod| amphipod.target_room() != room)
+    }
+
+    pub fn room_is_full(&self, room: usize) -> bool {
+        self.side_rooms[room].len() == DEPTH
+    }
+
+    pub fn move_room_to_hallway(mut self, from: usize, to: usize) -> State<DEPTH> {
+        assert!(!self.side_rooms[from].is_empty());
+        assert!(self.hallway[to].is_none());
+
+        let amphipod = self.side_rooms[from].pop().unwrap();
+        self.hallway[to] = Some(amphipod);
+
+        let steps_up = DEPTH - self.side_rooms[from].len();
+        let room_x = 2 + from * 2;
+        let steps_horizontal = abs_diff(room_x, to);
+        let steps = steps_up + steps_horizontal;
+        let energy = steps * amphipod.energy();
+        self.total_energy += energy;
+
+        trace!(
+            ?amphipod,
+            room = from,
+            hallway = to,
+            energy,
+            "move out of room"
+        );
+
+        self
+    }
+
+    pub fn move_hallway_to_room(mut self, from: usize, to: usize) -> State<DEPTH> {
+        assert!(self.hallway[from].is_some());
+        assert!(self.side_rooms[to].len() < DEPTH);
+
+        let amphipod = self.hallway[from].take().unwrap();
+        self.side_rooms[to].push(amphipod);
+
+        let steps_down = DEPTH - self.side_rooms[to].len() + 1;
+        let room_x = 2 + to * 2;
+        let steps_horizontal = abs_diff(from, room_x);
+        let steps = steps_horizontal + steps_down;
+        let energy = steps * amphipod.energy();
+        self.total_energy += energy;
+
+        trace!(
+            ?amphipod,
+            hallway = from,
+            room = to,
+            energy,
+            "move into room"
+        );
+
+        self
+    }
+
+    // All states reachable with a single move.
+    pub fn next_states(&self) -> Vec<State<DEPTH>> {
+        self.next_states_with(&HallwayRules::default())
+    }
+
+    // All states reachable with a single move allowed by the given rules.
+    pub fn next_states_with(&self, rules: &HallwayRules) -> Vec<State<DEPTH>> {
+        let mut next_states = self.moves_into_rooms(rules);
+
+        for room in 0..4 {
+            if self.room_needs_move(room) {
+                next_states.extend(self.moves_out_of_room(room, rules));
+            }
+        }
+
+        next_states
+    }
+
+    // Like `next_states`, but skips moves which can't lead to a better solution than the
+    // remaining ones and states which can't be finished at all. The pruning relies on the
+    // puzzle rules, hence they can't be relaxed here.
+    pub fn next_states_pruned(&self, stats: &mut PruningStats) -> Vec<State<DEPTH>> {
+        let rules = HallwayRules::default();
+        let mut into_rooms = self.moves_into_rooms(&rules);
+        if !into_rooms.is_empty() {
+            // Moving into the target room costs the same now or later and only frees the
+            // hallway, so any such move dominates all others.
+            stats.dominated += into_rooms.len() - 1;
+            stats.dominated += (0..4)
+                .filter(|&room| self.room_needs_move(room))
+                .map(|room| self.reachable_hallway(room, &rules).len())
+                .sum::<usize>();
+            into_rooms.truncate(1);
+            return into_rooms;
+        }
+
+        let mut next_states = Vec::new();
+        for room in 0..4 {
+            if self.room_needs_move(room) {
+                next_states.extend(self.moves_out_of_room(room, &rules));
+            } else if !self.side_rooms[room].is_empty() {
+                // only amphipods which belong here, they would have to come back anyway
+                stats.settled += self.reachable_hallway(room, &rules).len();
+            }
+        }
+
+        let before = next_states.len();
+        next_states.retain(|state| !state.is_deadlocked());
+        stats.deadlocked += before - next_states.len();
+
+        next_states
+    }
+
+    fn moves_into_rooms(&self, rules: &HallwayRules) -> Vec<State<DEPTH>> {
+        let mut next_states = Vec::new();
+
+        for x in 0..11 {
+            if let Some(amphipod) = self.hallway[x] {
+                let target_room = amphipod.target_room();
+                let rooms = if rules.only_own_room {
+                    target_room..target_room + 1
+                } else {
+                    0..4
+                };
+
+                for room in rooms {
+                    let room_x = 2 + room * 2;
+
+                    if self.room_is_full(room) {
+                        continue;
+                    }
+                    if rules.only_own_room && self.room_needs_move(room) {
+                        continue;
+                    }
+
+                    let hallway_is_free = if x > room_x {
+                        (room_x..x).all(|x| self.hallway[x].is_none())
+                    } else {
+                        ((x + 1)..=room_x).all(|x| self.hallway[x].is_none())
+                    };
+
+                    if hallway_is_free {
+                        next_states.push(self.clone().move_hallway_to_room(x, room));
+                    }
+                }
+            }
+        }
+
+        next_states
+    }
+
+    fn moves_out_of_room(&self, room: usize, rules: &HallwayRules) -> Vec<State<DEPTH>> {
+        self.reachable_hallway(room, rules)
+            .into_iter()
+            .map(|x| self.clone().move_room_to_hallway(room, x))
+            .collect()
+    }
+
+    // Hallway positions the topmost amphipod of the room could move to.
+    fn reachable_hallway(&self, room: usize, rules: &HallwayRules) -> Vec<usize> {
+        let mut positions = Vec::new();
+        if self.side_rooms[room].is_empty() {
+            return positions;
+        }
+
+        let current_x = 2 + room * 2;
+        if self.hallway[current_x].is_some() {
+            // only possible if stopping on doorways is allowed
+            return positions;
+        }
+
+        // move left hallway
+        for x in (0..current_x).rev() {
+            if rules.no_doorway_stops && matches!(x, 2 | 4 | 6 | 8) {
+                continue;
+            }
+            if self.hallway[x].is_some() {
+                break;
+            }
+            positions.push(x);
+        }
+
+        // move right hallway
+        for x in (current_x + 1)..11 {
+            if rules.no_doorway_stops && matches!(x, 2 | 4 | 6 | 8) {
+                continue;
+            }
+            if self.hallway[x].is_some() {
+                break;
+            }
+            positions.push(x);
+        }
+
+        positions
+    }
+
+    // Amphipods in the hallway can only move into their target room, so two of them which
+    // have to pass each other block each other forever.
+    pub fn is_deadlocked(&self) -> bool {
+        let in_hallway: Vec<(usize, usize)> = (0..11)
+            .filter_map(|x| self.hallway[x].map(|amphipod| (x, 2 + amphipod.target_room() * 2)))
+            .collect();
+        in_hallway.iter().enumerate().any(|(i, &(x1, target1))| {
+            in_hallway[i + 1..]
+                .iter()
+                .any(|&(x2, target2)| target1 > x2 && target2 < x1)
+        })
+    }
+
+    // Number of amphipods which already are in their target room and don't have to move
+    // anymore.
+    pub fn settled_count(&self) -> usize {
+        self.side_rooms
+            .iter()
+            .enumerate()
+            .map(|(room, amphipods)| {
+                amphipods
+                    .iter()
+                    .take_while(|amphipod| amphipod.target_room() == room)
+                    .count()
+            })
+            .sum()
+    }
+
+    // The configuration without the energy spent so far, e.g. "...........|AB|DC|CB|AD"
+    // (rooms are listed bottom to top).
+    pub fn encode(&self) -> String {
+        let mut s: String = self
+            .hallway
+            .iter()
+            .map(|amphipod| amphipod.map_or('.', Amphipod::to_char))
+            .collect();
+        for room in &self.side_rooms {
+            s.push('|');
+            s.extend(room.iter().map(|amphipod| amphipod.to_char()));
+        }
+        s
+    }
+
+    // Parses the burrow as drawn in the puzzle, with the rooms from top to bottom.
+    pub fn parse_diagram(s: &str) -> Option<State<DEPTH>> {
+        let lines: Vec<Vec<char>> = s.lines().map(|line| line.chars().collect()).collect();
+        let hallway: String = lines.get(1)?.get(1..12)?.iter().collect();
+        let rows = lines.get(2..lines.len().checked_sub(1)?)?;
+        let rooms: Vec<String> = [3, 5, 7, 9]
+            .iter()
+            .map(|&x| {
+                rows.iter()
+                    .rev()
+                    .filter_map(|row| row.get(x).filter(|c| c.is_ascii_alphabetic()))
+                    .collect()
+            })
+            .collect();
+        Self::decode(&format!("{}|{}", hallway, rooms.join("|")))
+    }
+
+    pub fn decode(s: &str) -> Option<State<DEPTH>> {
+        let mut parts = s.split('|');
+
+        let mut hallway = [None; 11];
+        let hallway_str = parts.next()?;
+        if hallway_str.chars().count() != 11 {
+            return None;
+        }
+        for (x, c) in hallway_str.chars().enumerate() {
+            if c != '.' {
+                hallway[x] = Some(Amphipod::from_char(c)?);
+            }
+        }
+
+        let mut side_rooms: [Vec<Amphipod>; 4] = Default::default();
+        for room in side_rooms.iter_mut() {
+            *room = parts
+                .next()?
+                .chars()
+                .map(Amphipod::from_char)
+                .collect::<Option<_>>()?;
+            if room.len() > DEPTH {
+                return None;
+            }
+        }
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(State {
+            hallway,
+            side_rooms,
+            total_energy: 0,
+        })
+    }
+
+    fn without_energy(&self) -> State<DEPTH> {
+        State {
+            total_energy: 0,
+            ..self.clone()
+        }
+    }
+}
+
+// Whether the state may still lead to a solution below `min_energy`.
+pub fn improves_on<const DEPTH: usize>(
+    state: &State<DEPTH>,
+    min_energy: usize,
+    heuristic: &Heuristic,
+) -> bool {
+    heuristic
+        .lower_bound(state)
+        .is_some_and(|bound| state.total_energy + bound < min_energy)
+}
+
+pub fn organize<const DEPTH: usize>(
+    initial_state: State<DEPTH>,
+    heuristic: &Heuristic,
+    stats: &mut PruningStats,
+    cancel: &CancellationToken,
+) -> Result<usize, Cancelled> {
+    let _span = debug_span!("organize", depth = DEPTH).entered();
+    let mut next_states = vec![initial_state];
+
+    let mut min_energy = usize::MAX; // TODO find useful upper bound
+
+    let mut visited_states = HashSet::default();
+
+    while let Some(state) = next_states.pop() {
+        if cancel.is_cancelled() {
+            return Err(Cancelled {
+                best_energy: (min_energy != usize::MAX).then_some(min_energy),
+                visited_states: visited_states.len(),
+            });
+        }
+
+        if state.is_done() {
+            if state.total_energy < min_energy {
+                debug!(energy = state.total_energy, "found better solution");
+            }
+            min_energy = std::cmp::min(min_energy, state.total_energy);
+        }
+
+        if state.total_energy >= min_energy {
+            continue;
+        }
+
+        if !improves_on(&state, min_energy, heuristic) {
+            stats.bounded += 1;
+            continue;
+        }
+
+        if !visited_states.insert(state.clone()) {
+            trace!(state = %state.encode(), "state visited twice");
+            continue;
+        }
+
+        next_states.extend(state.next_states_pruned(stats));
+    }
+
+    debug!(
+        visited_states = visited_states.len(),
+        ?stats,
+        "search finished"
+    );
+    Ok(min_energy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> State<2> {
+        State::decode("...........|AB|DC|CB|AD").unwrap()
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let state = example().move_room_to_hallway(1, 3);
+        assert_eq!(state.encode(), "...C.......|AB|D|CB|AD");
+        assert_eq!(
+            State::<2>::decode(&state.encode()),
+            Some(state.without_energy())
+        );
+        assert_eq!(State::<2>::decode("...........|AB|DCA|CB|AD"), None);
+        assert_eq!(State::<2>::decode("...........|AB|DC|CB"), None);
+    }
+
+    #[test]
+    fn test_settled_count() {
+        // the amphipods at the bottom of the A and C rooms
+        assert_eq!(example().settled_count(), 2);
+        assert_eq!(
+            State::<2>::decode("...........|AA|BB|CC|DD")
+                .unwrap()
+                .settled_count(),
+            8
+        );
+    }
+
+    #[test]
+    fn test_deadlock() {
+        // D has to pass A to the right and A has to pass D to the left
+        let deadlocked = State::<2>::decode("...D.A.....|A|BB|CC|D").unwrap();
+        assert!(deadlocked.is_deadlocked());
+        let passable = State::<2>::decode("...A.D.....|A|BB|CC|D").unwrap();
+        assert!(!passable.is_deadlocked());
+        // D doesn't have to pass A to reach its room
+        let passable = State::<2>::decode("...D.....A.|A|BB|CC|D").unwrap();
+        assert!(!passable.is_deadlocked());
+    }
+
+    #[test]
+    fn test_dominated_moves_are_pruned() {
+        // the A can move into its room, which makes all other moves redundant
+        let state = State::<2>::decode("A..........|A|CB|BC|DD").unwrap();
+        let mut stats = PruningStats::default();
+        let next_states = state.next_states_pruned(&mut stats);
+        assert_eq!(next_states.len(), 1);
+        assert_eq!(next_states[0].encode(), "...........|AA|CB|BC|DD");
+        assert_eq!(stats.dominated, state.next_states().len() - 1);
+    }
+
+    #[test]
+    fn test_pruning_keeps_optimum() {
+        let mut stats = PruningStats::default();
+        assert_eq!(
+            organize(
+                example(),
+                &Heuristic::none(),
+                &mut stats,
+                &CancellationToken::default()
+            ),
+            Ok(12521)
+        );
+        assert!(stats.settled > 0);
+        assert!(stats.dominated > 0);
+        assert!(stats.deadlocked > 0);
+    }
+
+    #[test]
+    fn test_heuristics_keep_optimum() {
+        let mut unbounded = PruningStats::default();
+        let cancel = CancellationToken::default();
+        for heuristic in [
+            "none",
+            "direct",
+            "exits",
+            "blocking",
+            "direct,exits,blocking",
+        ] {
+            let heuristic: Heuristic = heuristic.parse().unwrap();
+            let mut stats = PruningStats::default();
+            assert_eq!(
+                organize(example(), &heuristic, &mut stats, &cancel),
+                Ok(12521)
+            );
+            if heuristic == Heuristic::none() {
+                unbounded = stats;
+            }
+        }
+
+        let mut stats = PruningStats::default();
+        organize(example(), &Heuristic::default(), &mut stats, &cancel).unwrap();
+        assert!(stats.bounded > unbounded.bounded);
+        assert!(stats.settled < unbounded.settled);
+    }
+
+    #[test]
+    fn test_cancelled_search() {
+        let cancel = CancellationToken::default();
+        cancel.cancel();
+        let mut stats = PruningStats::default();
+        assert_eq!(
+            organize(example(), &Heuristic::default(), &mut stats, &cancel),
+            Err(Cancelled {
+                best_energy: None,
+                visited_states: 0
+            })
+        );
+        assert_eq!(stats, PruningStats::default());
+    }
+
+    #[test]
+    fn test_parse_diagram() {
+        let (burrow, unfolded_burrow) = Day23::parse(include_str!("../example.txt"));
+        assert_eq!(burrow, example());
+        assert_eq!(unfolded_burrow.encode(), "...........|ADDB|DBCC|CABB|ACAD");
+
+        let (burrow, _) = Day23::parse(include_str!("../instance.txt"));
+        assert_eq!(burrow.encode(), "...........|CD|AA|BC|BD");
+        assert_eq!(
+            State::<2>::parse_diagram("#############\n#...........#"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_solution_example() {
+        let burrow = Day23::parse(include_str!("../example.txt"));
+        assert_eq!(Day23::part1(&burrow).to_string(), "12521");
+    }
+}
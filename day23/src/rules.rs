@@ -0,0 +1,141 @@
+use super::{HashSet, State};
+use std::str::FromStr;
+
+// Movement rules of the puzzle which can be switched off individually, to see how much each
+// of them restricts the search space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HallwayRules {
+    // amphipods never stop on the space immediately outside any room
+    pub no_doorway_stops: bool,
+    // amphipods only move from the hallway into their own room, and only once it contains no
+    // amphipods of other types
+    pub only_own_room: bool,
+}
+
+impl HallwayRules {
+    pub fn none() -> HallwayRules {
+        Self {
+            no_doorway_stops: false,
+            only_own_room: false,
+        }
+    }
+}
+
+// The rules of the puzzle.
+impl Default for HallwayRules {
+    fn default() -> Self {
+        Self {
+            no_doorway_stops: true,
+            only_own_room: true,
+        }
+    }
+}
+
+// Comma separated rules to enforce, e.g. "doorway,own-room", or "none".
+impl FromStr for HallwayRules {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<HallwayRules, Self::Err> {
+        let mut rules = Self::none();
+        if s == "none" {
+            return Ok(rules);
+        }
+        for rule in s.split(',') {
+            match rule {
+                "doorway" => rules.no_doorway_stops = true,
+                "own-room" => rules.only_own_room = true,
+                _ => {
+                    return Err(format!(
+                        "unknown rule '{}', expected doorway or own-room",
+                        rule
+                    ))
+                }
+            }
+        }
+        Ok(rules)
+    }
+}
+
+// Number of configurations reachable from the given one when moving by the given rules.
+pub fn reachable_states<const DEPTH: usize>(
+    initial_state: &State<DEPTH>,
+    rules: &HallwayRules,
+) -> usize {
+    let initial_state = initial_state.without_energy();
+    let mut next_states = vec![initial_state.clone()];
+    let mut visited_states = HashSet::default();
+    visited_states.insert(initial_state);
+
+    while let Some(state) = next_states.pop() {
+        for next in state.next_states_with(rules) {
+            let next = next.without_energy();
+            if !visited_states.contains(&next) {
+                visited_states.insert(next.clone());
+                next_states.push(next);
+            }
+        }
+    }
+
+    visited_states.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // All combinations of the rules, from none to all of them.
+    fn variants() -> [HallwayRules; 4] {
+        [(false, false), (true, false), (false, true), (true, true)].map(
+            |(no_doorway_stops, only_own_room)| HallwayRules {
+                no_doorway_stops,
+                only_own_room,
+            },
+        )
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!("none".parse(), Ok(HallwayRules::none()));
+        assert_eq!(
+            "own-room".parse(),
+            Ok(HallwayRules {
+                no_doorway_stops: false,
+                only_own_room: true
+            })
+        );
+        assert_eq!("doorway,own-room".parse(), Ok(HallwayRules::default()));
+        assert!("doorway,teleport".parse::<HallwayRules>().is_err());
+    }
+
+    #[test]
+    fn test_relaxed_moves() {
+        let encoded_next_states = |state: &str, rules: &HallwayRules| -> Vec<String> {
+            State::<2>::decode(state)
+                .unwrap()
+                .next_states_with(rules)
+                .iter()
+                .map(State::encode)
+                .collect()
+        };
+        let puzzle = HallwayRules::default();
+        let doorway: HallwayRules = "doorway".parse().unwrap();
+        let own_room: HallwayRules = "own-room".parse().unwrap();
+
+        // the A stops in front of the B room
+        let stop = "....A......|B|AB|CC|DD".to_string();
+        assert!(encoded_next_states("...........|BA|AB|CC|DD", &own_room).contains(&stop));
+        assert!(!encoded_next_states("...........|BA|AB|CC|DD", &puzzle).contains(&stop));
+
+        // the B enters the empty A room
+        let enter = "A..........|B|AB|CC|DD".to_string();
+        assert!(encoded_next_states("A.........B||AB|CC|DD", &doorway).contains(&enter));
+        assert!(!encoded_next_states("A.........B||AB|CC|DD", &puzzle).contains(&enter));
+    }
+
+    #[test]
+    fn test_reachable_states() {
+        let state = State::<2>::decode("...........|BA|AB|CC|DD").unwrap();
+        let counts = variants().map(|rules| reachable_states(&state, &rules));
+        assert_eq!(counts, [4290, 1092, 3127, 860]);
+    }
+}
@@ -0,0 +1,1261 @@
+pub mod optimize;
+
+pub use common::cancel::CancellationToken;
+use common::smt::{SmtBuilder, Term};
+use common::{Answer, Solution};
+use std::{collections::HashMap, fmt, str};
+use thiserror::Error;
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    type Input = Vec<Instruction>;
+
+    fn parse(input: &str) -> Self::Input {
+        input.lines().map(Instruction::parse).collect()
+    }
+
+    fn part1(program: &Self::Input) -> Answer {
+        match monad_blocks(program) {
+            Ok(blocks) => match smallest_and_largest_models(&blocks) {
+                Some((_, largest)) => model_number(&largest).into(),
+                None => "no valid model number".into(),
+            },
+            Err(e) => e.to_string().into(),
+        }
+    }
+
+    fn part2(program: &Self::Input) -> Answer {
+        match monad_blocks(program) {
+            Ok(blocks) => match smallest_and_largest_models(&blocks) {
+                Some((smallest, _)) => model_number(&smallest).into(),
+                None => "no valid model number".into(),
+            },
+            Err(e) => e.to_string().into(),
+        }
+    }
+}
+
+// Whether the program leaves z = 0 for the given model number digits.
+pub fn accepts_model(program: &[Instruction], digits: &[i64]) -> bool {
+    let mut input = digits.to_vec();
+    let mut alu = ALU::new(&mut input);
+    alu.execute(program);
+    alu.registers()[Variable::Z.index()] == 0
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Variable {
+    W,
+    X,
+    Y,
+    Z,
+}
+
+impl Variable {
+    pub fn index(self) -> usize {
+        match self {
+            Variable::W => 0,
+            Variable::X => 1,
+            Variable::Y => 2,
+            Variable::Z => 3,
+        }
+    }
+}
+
+impl fmt::Display for Variable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Variable::W => "w",
+            Variable::X => "x",
+            Variable::Y => "y",
+            Variable::Z => "z",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Operand {
+    Variable(Variable),
+    Literal(i64),
+}
+
+impl Operand {
+    pub fn read_vars(self) -> Vec<Variable> {
+        match self {
+            Self::Variable(var) => {
+                vec![var]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Variable(var) => write!(f, "{}", var),
+            Self::Literal(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    Inp(Variable),
+    Add(Variable, Operand),
+    Mul(Variable, Operand),
+    Div(Variable, Operand),
+    Mod(Variable, Operand),
+    Eql(Variable, Operand),
+    Custom(CustomOp, Variable, Operand),
+}
+
+impl Instruction {
+    pub fn read_vars(self) -> Vec<Variable> {
+        match self {
+            Instruction::Inp(_) | Instruction::Mul(_, Operand::Literal(0)) => {
+                vec![]
+            }
+            Instruction::Add(a, b)
+            | Instruction::Mul(a, b)
+            | Instruction::Div(a, b)
+            | Instruction::Mod(a, b)
+            | Instruction::Eql(a, b)
+            | Instruction::Custom(_, a, b) => {
+                let mut vars = b.read_vars();
+                vars.push(a);
+                vars
+            }
+        }
+    }
+
+    pub fn written_vars(self) -> Vec<Variable> {
+        match self {
+            Instruction::Inp(a)
+            | Instruction::Add(a, _)
+            | Instruction::Mul(a, _)
+            | Instruction::Div(a, _)
+            | Instruction::Mod(a, _)
+            | Instruction::Eql(a, _)
+            | Instruction::Custom(_, a, _) => {
+                vec![a]
+            }
+        }
+    }
+
+    fn parse_var(mut chars: str::Chars<'_>) -> (str::Chars<'_>, Variable) {
+        let var = match chars.next() {
+            Some('w') => Variable::W,
+            Some('x') => Variable::X,
+            Some('y') => Variable::Y,
+            Some('z') => Variable::Z,
+            _ => panic!(),
+        };
+        (chars, var)
+    }
+
+    fn parse_number(mut chars: str::Chars<'_>) -> (str::Chars<'_>, i64) {
+        let s = chars.as_str();
+        while chars
+            .clone()
+            .next()
+            .is_some_and(|c| c.is_numeric() || c == '-')
+        {
+            chars.next();
+        }
+        let n = &s[..s.len() - chars.as_str().len()];
+        (chars, n.parse().unwrap())
+    }
+
+    fn parse_operand(chars: str::Chars<'_>) -> (str::Chars<'_>, Operand) {
+        match chars.clone().next() {
+            Some(c) if c.is_numeric() || c == '-' => {
+                let (chars, n) = Self::parse_number(chars);
+                (chars, Operand::Literal(n))
+            }
+            Some(_) => {
+                let (chars, var) = Self::parse_var(chars);
+                (chars, Operand::Variable(var))
+            }
+            _ => panic!(),
+        }
+    }
+
+    fn parse_identifier(mut chars: str::Chars<'_>) -> (str::Chars<'_>, &str) {
+        let s = chars.as_str();
+        while chars.clone().next().is_some_and(|c| !c.is_whitespace()) {
+            chars.next();
+        }
+        let n = &s[..s.len() - chars.as_str().len()];
+        (chars, n)
+    }
+
+    fn parse_instruction<'a>(
+        chars: str::Chars<'a>,
+        set: &InstructionSet,
+    ) -> (str::Chars<'a>, Instruction) {
+        let (mut chars, ident) = Self::parse_identifier(chars);
+        match ident {
+            "inp" => {
+                chars.next(); // space
+                let (chars, a) = Self::parse_var(chars);
+                (chars, Instruction::Inp(a))
+            }
+            "add" | "mul" | "div" | "mod" | "eql" => {
+                chars.next(); // space
+                let (mut chars, a) = Self::parse_var(chars);
+                chars.next(); // space
+                let (chars, b) = Self::parse_operand(chars);
+                (
+                    chars,
+                    match ident {
+                        "add" => Instruction::Add(a, b),
+                        "mul" => Instruction::Mul(a, b),
+                        "div" => Instruction::Div(a, b),
+                        "mod" => Instruction::Mod(a, b),
+                        "eql" => Instruction::Eql(a, b),
+                        _ => panic!(),
+                    },
+                )
+            }
+            _ => {
+                let op = set.get(ident).unwrap();
+                chars.next(); // space
+                let (mut chars, a) = Self::parse_var(chars);
+                chars.next(); // space
+                let (chars, b) = Self::parse_operand(chars);
+                (chars, Instruction::Custom(op, a, b))
+            }
+        }
+    }
+
+    pub fn parse(s: &str) -> Instruction {
+        InstructionSet::default().parse(s)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Inp(a) => write!(f, "inp {}", a),
+            Instruction::Add(a, b) => write!(f, "add {} {}", a, b),
+            Instruction::Mul(a, b) => write!(f, "mul {} {}", a, b),
+            Instruction::Div(a, b) => write!(f, "div {} {}", a, b),
+            Instruction::Mod(a, b) => write!(f, "mod {} {}", a, b),
+            Instruction::Eql(a, b) => write!(f, "eql {} {}", a, b),
+            Instruction::Custom(op, a, b) => write!(f, "{} {} {}", op.name, a, b),
+        }
+    }
+}
+
+// Binary instruction `<name> a b` outside of the puzzle's instruction set, computing
+// a = eval(a, b). Two custom instructions are the same if their names are.
+#[derive(Copy, Clone, Debug)]
+pub struct CustomOp {
+    pub name: &'static str,
+    pub eval: fn(i64, i64) -> i64,
+}
+
+impl PartialEq for CustomOp {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for CustomOp {}
+
+const BUILTIN_MNEMONICS: [&str; 6] = ["inp", "add", "mul", "div", "mod", "eql"];
+
+// Registry of custom instructions, consulted by the parser for every mnemonic which isn't
+// one of the built-in ones. The default set is the plain puzzle ALU.
+#[derive(Clone, Debug, Default)]
+pub struct InstructionSet {
+    custom: HashMap<&'static str, CustomOp>,
+}
+
+impl InstructionSet {
+    // Puzzle ALU extended by `min`, `max` and `set`.
+    pub fn extended() -> InstructionSet {
+        Self::default()
+            .with("min", i64::min)
+            .with("max", i64::max)
+            .with("set", |_, b| b)
+    }
+
+    pub fn with(mut self, name: &'static str, eval: fn(i64, i64) -> i64) -> InstructionSet {
+        self.register(name, eval);
+        self
+    }
+
+    pub fn register(&mut self, name: &'static str, eval: fn(i64, i64) -> i64) {
+        assert!(
+            !BUILTIN_MNEMONICS.contains(&name),
+            "'{}' is a built-in instruction",
+            name
+        );
+        self.custom.insert(name, CustomOp { name, eval });
+    }
+
+    pub fn get(&self, name: &str) -> Option<CustomOp> {
+        self.custom.get(name).copied()
+    }
+
+    pub fn parse(&self, s: &str) -> Instruction {
+        let (_, inst) = Instruction::parse_instruction(s.chars(), self);
+        inst
+    }
+}
+
+pub trait Port {
+    fn next(&mut self) -> i64;
+}
+
+#[allow(clippy::upper_case_acronyms)]
+pub struct ALU<'port, InputPort> {
+    w: i64,
+    x: i64,
+    y: i64,
+    z: i64,
+    input: &'port mut InputPort,
+}
+
+impl<'port, InputPort: Port> ALU<'port, InputPort> {
+    pub fn new(input: &'port mut InputPort) -> ALU<'port, InputPort> {
+        Self {
+            w: 0,
+            x: 0,
+            y: 0,
+            z: 0,
+            input,
+        }
+    }
+
+    pub fn execute(&mut self, instructions: &[Instruction]) {
+        instructions.iter().for_each(|inst| self.dispatch(inst));
+    }
+
+    pub fn dispatch(&mut self, inst: &Instruction) {
+        match *inst {
+            Instruction::Inp(a) => {
+                let value = self.input.next();
+                self.write(a, value)
+            }
+            // wrapping, so that every program without a zero divisor has defined semantics
+            Instruction::Add(a, b) => self.write(a, self.read(a).wrapping_add(self.eval(b))),
+            Instruction::Mul(a, b) => self.write(a, self.read(a).wrapping_mul(self.eval(b))),
+            Instruction::Div(a, b) => self.write(a, self.read(a).wrapping_div(self.eval(b))),
+            Instruction::Mod(a, b) => self.write(a, self.read(a).wrapping_rem(self.eval(b))),
+            Instruction::Eql(a, b) => {
+                self.write(a, if self.read(a) == self.eval(b) { 1 } else { 0 })
+            }
+            Instruction::Custom(op, a, b) => self.write(a, (op.eval)(self.read(a), self.eval(b))),
+        }
+    }
+
+    pub fn registers(&self) -> Registers {
+        [self.w, self.x, self.y, self.z]
+    }
+
+    fn eval(&self, op: Operand) -> i64 {
+        match op {
+            Operand::Literal(n) => n,
+            Operand::Variable(var) => self.read(var),
+        }
+    }
+
+    fn read(&self, var: Variable) -> i64 {
+        match var {
+            Variable::W => self.w,
+            Variable::X => self.x,
+            Variable::Y => self.y,
+            Variable::Z => self.z,
+        }
+    }
+
+    fn write(&mut self, var: Variable, value: i64) {
+        match var {
+            Variable::W => {
+                self.w = value;
+            }
+            Variable::X => {
+                self.x = value;
+            }
+            Variable::Y => {
+                self.y = value;
+            }
+            Variable::Z => {
+                self.z = value;
+            }
+        }
+    }
+}
+
+pub type Registers = [i64; 4];
+pub type CompiledInstruction = Box<dyn Fn(&mut Registers, &mut dyn Port)>;
+
+// Same semantics as the ALU, but every instruction is turned into a closure upfront.
+pub struct CompiledProgram {
+    instructions: Vec<CompiledInstruction>,
+}
+
+impl CompiledProgram {
+    pub fn compile(instructions: &[Instruction]) -> CompiledProgram {
+        let instructions = instructions
+            .iter()
+            .map(|&inst| match inst {
+                Instruction::Inp(a) => {
+                    let a = a.index();
+                    Box::new(move |regs: &mut Registers, input: &mut dyn Port| {
+                        regs[a] = input.next()
+                    }) as CompiledInstruction
+                }
+                Instruction::Add(a, b) => Self::compile_binary(a, b, i64::wrapping_add),
+                Instruction::Mul(a, b) => Self::compile_binary(a, b, i64::wrapping_mul),
+                Instruction::Div(a, b) => Self::compile_binary(a, b, i64::wrapping_div),
+                Instruction::Mod(a, b) => Self::compile_binary(a, b, i64::wrapping_rem),
+                Instruction::Eql(a, b) => {
+                    Self::compile_binary(a, b, |a, b| if a == b { 1 } else { 0 })
+                }
+                Instruction::Custom(op, a, b) => Self::compile_binary(a, b, op.eval),
+            })
+            .collect();
+        Self { instructions }
+    }
+
+    fn compile_binary(a: Variable, b: Operand, f: fn(i64, i64) -> i64) -> CompiledInstruction {
+        let a = a.index();
+        match b {
+            Operand::Literal(n) => {
+                Box::new(move |regs: &mut Registers, _: &mut dyn Port| regs[a] = f(regs[a], n))
+            }
+            Operand::Variable(b) => {
+                let b = b.index();
+                Box::new(move |regs: &mut Registers, _: &mut dyn Port| {
+                    regs[a] = f(regs[a], regs[b])
+                })
+            }
+        }
+    }
+
+    pub fn run(&self, input: &mut dyn Port) -> Registers {
+        let mut regs = [0; 4];
+        for inst in &self.instructions {
+            inst(&mut regs, input);
+        }
+        regs
+    }
+}
+
+impl Port for Vec<i64> {
+    fn next(&mut self) -> i64 {
+        self.remove(0)
+    }
+}
+
+// Def-use edges (j, i, var): instruction i reads the value of var written by instruction j.
+pub fn def_use_edges(instructions: &[Instruction]) -> Vec<(usize, usize, Variable)> {
+    let mut last_def: HashMap<Variable, usize> = HashMap::new();
+
+    instructions
+        .iter()
+        .enumerate()
+        .flat_map(|(i, inst)| {
+            let mut deps = Vec::new();
+            for var in inst.read_vars() {
+                if let Some(&j) = last_def.get(&var) {
+                    deps.push((j, i, var));
+                }
+            }
+            for var in inst.written_vars() {
+                last_def.insert(var, i);
+            }
+            deps
+        })
+        .collect()
+}
+
+pub const BLOCK_LEN: usize = 18;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum MonadError {
+    #[error("expected 14 digit blocks of {BLOCK_LEN} instructions, found {0} instructions")]
+    InvalidLength(usize),
+    #[error("digit block {0} doesn't match the MONAD block")]
+    InvalidBlock(usize),
+}
+
+// Per-digit parameters of the MONAD program, the literals of `div z`, `add x` and `add y`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    pub div_z: i64,
+    pub add_x: i64,
+    pub add_y: i64,
+}
+
+impl Block {
+    pub fn instructions(&self) -> Vec<Instruction> {
+        [
+            "inp w".to_string(),
+            "mul x 0".to_string(),
+            "add x z".to_string(),
+            "mod x 26".to_string(),
+            format!("div z {}", self.div_z),
+            format!("add x {}", self.add_x),
+            "eql x w".to_string(),
+            "eql x 0".to_string(),
+            "mul y 0".to_string(),
+            "add y 25".to_string(),
+            "mul y x".to_string(),
+            "add y 1".to_string(),
+            "mul z y".to_string(),
+            "mul y 0".to_string(),
+            "add y w".to_string(),
+            format!("add y {}", self.add_y),
+            "mul y x".to_string(),
+            "add z y".to_string(),
+        ]
+        .iter()
+        .map(|line| Instruction::parse(line))
+        .collect()
+    }
+
+    // z after running the block with input `w`.
+    pub fn step(&self, z: i64, w: i64) -> i64 {
+        let y = z / self.div_z;
+        let x = z % 26 + self.add_x;
+        if x == w {
+            y
+        } else {
+            26 * y + w + self.add_y
+        }
+    }
+}
+
+// Extracts the parameters of the 14 digit blocks of a MONAD program.
+pub fn monad_blocks(program: &[Instruction]) -> Result<[Block; 14], MonadError> {
+    if program.len() != 14 * BLOCK_LEN {
+        return Err(MonadError::InvalidLength(program.len()));
+    }
+    let mut blocks = [Block {
+        div_z: 1,
+        add_x: 0,
+        add_y: 0,
+    }; 14];
+    for (i, chunk) in program.chunks(BLOCK_LEN).enumerate() {
+        let block = match (chunk[4], chunk[5], chunk[15]) {
+            (
+                Instruction::Div(Variable::Z, Operand::Literal(div_z)),
+                Instruction::Add(Variable::X, Operand::Literal(add_x)),
+                Instruction::Add(Variable::Y, Operand::Literal(add_y)),
+            ) => Block {
+                div_z,
+                add_x,
+                add_y,
+            },
+            _ => return Err(MonadError::InvalidBlock(i)),
+        };
+        // the search relies on z being a base 26 stack which only `div z 26` pops from
+        if !matches!(block.div_z, 1 | 26) || chunk != block.instructions() {
+            return Err(MonadError::InvalidBlock(i));
+        }
+        blocks[i] = block;
+    }
+    Ok(blocks)
+}
+
+// Progress of a cancelled model search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled {
+    pub memoized_states: usize,
+    // model numbers visited before the cancellation (enumeration only)
+    pub visited_models: usize,
+}
+
+// Counts and enumerates all valid model numbers with a digit-wise DFS, memoizing the number of
+// valid completions per (block, z) state.
+pub struct ModelSearch {
+    blocks: [Block; 14],
+    // Only blocks with `div z 26` shrink z, each by at most a factor of 26. Hence z has to stay
+    // below 26^(remaining shrinking blocks) before block i to possibly end up at 0.
+    z_limits: [i64; 15],
+    completions: HashMap<(usize, i64), u64>,
+    cancel: CancellationToken,
+}
+
+impl ModelSearch {
+    pub fn new(blocks: [Block; 14]) -> ModelSearch {
+        let mut z_limits = [1; 15];
+        for i in (0..14).rev() {
+            z_limits[i] = z_limits[i + 1] * blocks[i].div_z;
+        }
+        Self {
+            blocks,
+            z_limits,
+            completions: HashMap::new(),
+            cancel: CancellationToken::default(),
+        }
+    }
+
+    // Search over the digit blocks of a MONAD program.
+    pub fn from_program(program: &[Instruction]) -> Result<ModelSearch, MonadError> {
+        Ok(Self::new(monad_blocks(program)?))
+    }
+
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> ModelSearch {
+        self.cancel = cancel;
+        self
+    }
+
+    fn cancelled(&self, visited_models: usize) -> Cancelled {
+        Cancelled {
+            memoized_states: self.states(),
+            visited_models,
+        }
+    }
+
+    fn count_from(&mut self, block: usize, z: i64) -> u64 {
+        if self.cancel.is_cancelled() {
+            return 0;
+        }
+        if block == 14 {
+            return (z == 0) as u64;
+        }
+        if z >= self.z_limits[block] {
+            return 0;
+        }
+        if let Some(&count) = self.completions.get(&(block, z)) {
+            return count;
+        }
+
+        let count = (1..=9)
+            .map(|w| self.count_from(block + 1, self.blocks[block].step(z, w)))
+            .sum();
+        // a cancelled subsearch returns an incomplete count, which must not be memoized
+        if !self.cancel.is_cancelled() {
+            self.completions.insert((block, z), count);
+        }
+        count
+    }
+
+    pub fn count(&mut self) -> Result<u64, Cancelled> {
+        let count = self.count_from(0, 0);
+        if self.cancel.is_cancelled() {
+            return Err(self.cancelled(0));
+        }
+        Ok(count)
+    }
+
+    // Number of memoized (block, z) states.
+    pub fn states(&self) -> usize {
+        self.completions.len()
+    }
+
+    fn enumerate_from<F>(
+        &mut self,
+        block: usize,
+        z: i64,
+        digits: &mut [i64; 14],
+        visited: &mut usize,
+        visit: &mut F,
+    ) where
+        F: FnMut(&[i64; 14]),
+    {
+        if block == 14 {
+            visit(digits);
+            *visited += 1;
+            return;
+        }
+        for w in 1..=9 {
+            let next_z = self.blocks[block].step(z, w);
+            // only descend into states which have at least one valid completion
+            if self.count_from(block + 1, next_z) > 0 {
+                digits[block] = w;
+                self.enumerate_from(block + 1, next_z, digits, visited, visit);
+            }
+            if self.cancel.is_cancelled() {
+                return;
+            }
+        }
+    }
+
+    // Visits all valid model numbers in ascending order.
+    pub fn enumerate<F>(&mut self, mut visit: F) -> Result<(), Cancelled>
+    where
+        F: FnMut(&[i64; 14]),
+    {
+        let mut visited = 0;
+        self.enumerate_from(0, 0, &mut [0; 14], &mut visited, &mut visit);
+        if self.cancel.is_cancelled() {
+            return Err(self.cancelled(visited));
+        }
+        Ok(())
+    }
+}
+
+pub fn model_number(digits: &[i64]) -> i64 {
+    digits.iter().fold(0, |number, &w| number * 10 + w)
+}
+
+// Digits of the smallest and the largest valid model number, None if there are none.
+pub fn smallest_and_largest_models(blocks: &[Block; 14]) -> Option<([i64; 14], [i64; 14])> {
+    let mut search = ModelSearch::new(*blocks);
+    let mut smallest = None;
+    let mut largest = None;
+    search
+        .enumerate(|digits| {
+            smallest.get_or_insert(*digits);
+            largest = Some(*digits);
+        })
+        .ok()?;
+    Some((smallest?, largest?))
+}
+
+pub fn monad_smt(blocks: &[Block; 14]) -> SmtBuilder {
+    let mut smt = SmtBuilder::new();
+
+    let mut z = smt.declare_const("z0", "Int");
+    smt.assert_eq(Term::int(0), z.clone());
+
+    let mut digits = Vec::with_capacity(14);
+    for i in 1..=14 {
+        let Block {
+            div_z: key1,
+            add_x: key2,
+            add_y: key3,
+        } = blocks[i - 1];
+        smt.comment(&format!("Iteration {}", i));
+
+        let w = smt.declare_const(&format!("w{}", i), "Int");
+        smt.assert(Term::lt(vec![Term::int(0), w.clone(), Term::int(10)]));
+
+        let x = smt.declare_const(&format!("x{}", i), "Int");
+        let y = smt.declare_const(&format!("y{}", i), "Int");
+        let next_z = smt.declare_const(&format!("z{}", i), "Int");
+
+        smt.comment(&format!("let {} = {} / {}", y, z, key1))
+            .assert_eq(y.clone(), Term::div(z.clone(), Term::int(key1)));
+
+        smt.comment(&format!("let {} = {} % 26 + {}", x, z, key2))
+            .assert_eq(
+                x.clone(),
+                Term::add(vec![Term::modulo(z, Term::int(26)), Term::int(key2)]),
+            );
+
+        smt.comment(&format!(
+            "let {} = if {} == {} {{ {} }} else {{ 26 * {} + {} + {} }}",
+            next_z, x, w, y, y, w, key3
+        ))
+        .assert_eq(
+            next_z.clone(),
+            Term::ite(
+                Term::eq(x, w.clone()),
+                y.clone(),
+                Term::add(vec![
+                    Term::mul(vec![Term::int(26), y]),
+                    w.clone(),
+                    Term::int(key3),
+                ]),
+            ),
+        );
+
+        digits.push(w);
+        z = next_z;
+    }
+
+    let model_number = smt.declare_const("model_number", "Int");
+    let model = digits
+        .into_iter()
+        .reduce(|model, w| Term::add(vec![Term::mul(vec![model, Term::int(10)]), w]))
+        .unwrap();
+    smt.assert_eq(model_number.clone(), model);
+
+    // valid model
+    smt.assert_eq(z.clone(), Term::int(0));
+
+    // maximize model number
+    smt.push()
+        .maximize(&model_number)
+        .check_sat()
+        .get_value(&[z.clone(), model_number.clone()])
+        .pop();
+
+    // minimize model number
+    smt.push()
+        .minimize(&model_number)
+        .check_sat()
+        .get_value(&[z, model_number])
+        .pop();
+
+    smt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rng::Rng;
+    use optimize::{eliminate_dead_code, optimize};
+
+    const VARIABLES: [Variable; 4] = [Variable::W, Variable::X, Variable::Y, Variable::Z];
+
+    fn random_variable(rng: &mut Rng) -> Variable {
+        VARIABLES[rng.below(4)]
+    }
+
+    fn random_literal(rng: &mut Rng) -> i64 {
+        match rng.below(8) {
+            0 => i64::MAX,
+            1 => i64::MIN,
+            2 => 26,
+            _ => rng.below(64) as i64 - 32,
+        }
+    }
+
+    // Random well-formed program: `div` and `mod` only ever use positive literals as divisor,
+    // hence the program can't trap on any input.
+    fn generate_program(rng: &mut Rng, max_len: usize) -> Vec<Instruction> {
+        let len = 1 + rng.below(max_len);
+        (0..len)
+            .map(|_| {
+                let a = random_variable(rng);
+                let b = if rng.below(2) == 0 {
+                    Operand::Variable(random_variable(rng))
+                } else {
+                    Operand::Literal(random_literal(rng))
+                };
+                match rng.below(6) {
+                    0 => Instruction::Inp(a),
+                    1 => Instruction::Add(a, b),
+                    2 => Instruction::Mul(a, b),
+                    3 => Instruction::Div(a, Operand::Literal(1 + rng.below(30) as i64)),
+                    4 => Instruction::Mod(a, Operand::Literal(1 + rng.below(30) as i64)),
+                    _ => Instruction::Eql(a, b),
+                }
+            })
+            .collect()
+    }
+
+    fn generate_input(rng: &mut Rng, program: &[Instruction]) -> Vec<i64> {
+        let inputs = program
+            .iter()
+            .filter(|inst| matches!(inst, Instruction::Inp(_)))
+            .count();
+        (0..inputs).map(|_| 1 + rng.below(9) as i64).collect()
+    }
+
+    // Smaller variants of the given instruction, most aggressive first.
+    fn shrink_instruction(inst: Instruction) -> Vec<Instruction> {
+        let shrink_operand = |b: Operand| match b {
+            Operand::Variable(_) => vec![Operand::Literal(0), Operand::Literal(1)],
+            Operand::Literal(n) if !(0..=1).contains(&n) => {
+                vec![Operand::Literal(1), Operand::Literal(n / 2)]
+            }
+            Operand::Literal(_) => vec![],
+        };
+        match inst {
+            Instruction::Inp(_) => vec![],
+            Instruction::Add(a, b) => shrink_operand(b)
+                .into_iter()
+                .map(|b| Instruction::Add(a, b))
+                .collect(),
+            Instruction::Mul(a, b) => shrink_operand(b)
+                .into_iter()
+                .map(|b| Instruction::Mul(a, b))
+                .collect(),
+            Instruction::Eql(a, b) => shrink_operand(b)
+                .into_iter()
+                .map(|b| Instruction::Eql(a, b))
+                .collect(),
+            Instruction::Custom(op, a, b) => shrink_operand(b)
+                .into_iter()
+                .map(|b| Instruction::Custom(op, a, b))
+                .collect(),
+            // divisors must stay positive
+            Instruction::Div(a, Operand::Literal(n)) if n > 1 => {
+                vec![Instruction::Div(a, Operand::Literal(n / 2))]
+            }
+            Instruction::Mod(a, Operand::Literal(n)) if n > 1 => {
+                vec![Instruction::Mod(a, Operand::Literal(n / 2))]
+            }
+            Instruction::Div(..) | Instruction::Mod(..) => vec![],
+        }
+    }
+
+    // Greedily shrinks a failing program (and its input) until no single removal or
+    // simplification keeps `fails` true.
+    fn shrink<F>(
+        mut program: Vec<Instruction>,
+        mut input: Vec<i64>,
+        fails: F,
+    ) -> (Vec<Instruction>, Vec<i64>)
+    where
+        F: Fn(&[Instruction], &[i64]) -> bool,
+    {
+        assert!(fails(&program, &input));
+
+        'shrinking: loop {
+            // drop instructions, together with the consumed input if it is an `inp`
+            for i in 0..program.len() {
+                let mut candidate = program.clone();
+                let removed = candidate.remove(i);
+                let mut candidate_input = input.clone();
+                if let Instruction::Inp(_) = removed {
+                    let pos = program[..i]
+                        .iter()
+                        .filter(|inst| matches!(inst, Instruction::Inp(_)))
+                        .count();
+                    candidate_input.remove(pos);
+                }
+                if fails(&candidate, &candidate_input) {
+                    program = candidate;
+                    input = candidate_input;
+                    continue 'shrinking;
+                }
+            }
+
+            // simplify operands
+            for i in 0..program.len() {
+                for inst in shrink_instruction(program[i]) {
+                    let mut candidate = program.clone();
+                    candidate[i] = inst;
+                    if fails(&candidate, &input) {
+                        program = candidate;
+                        continue 'shrinking;
+                    }
+                }
+            }
+
+            // simplify inputs
+            for i in 0..input.len() {
+                if input[i] != 1 {
+                    let mut candidate = input.clone();
+                    candidate[i] = 1;
+                    if fails(&program, &candidate) {
+                        input = candidate;
+                        continue 'shrinking;
+                    }
+                }
+            }
+
+            return (program, input);
+        }
+    }
+
+    fn run_alu(program: &[Instruction], input: &[i64]) -> Registers {
+        let mut input = input.to_vec();
+        let mut alu = ALU::new(&mut input);
+        alu.execute(program);
+        alu.registers()
+    }
+
+    fn run_compiled(program: &[Instruction], input: &[i64]) -> Registers {
+        let mut input = input.to_vec();
+        CompiledProgram::compile(program).run(&mut input)
+    }
+
+    fn instance_blocks() -> [Block; 14] {
+        monad_blocks(&Day24::parse(include_str!("../instance.txt"))).unwrap()
+    }
+
+    #[test]
+    fn test_monad_blocks() {
+        let blocks = instance_blocks();
+        assert_eq!(
+            blocks[3],
+            Block {
+                div_z: 26,
+                add_x: -2,
+                add_y: 4
+            }
+        );
+        let program: Vec<_> = blocks.iter().flat_map(Block::instructions).collect();
+        assert_eq!(monad_blocks(&program), Ok(blocks));
+
+        assert_eq!(
+            monad_blocks(&program[..BLOCK_LEN]),
+            Err(MonadError::InvalidLength(BLOCK_LEN))
+        );
+        let mut program = program;
+        program[2 * BLOCK_LEN + 9] = Instruction::parse("add y 24");
+        assert_eq!(monad_blocks(&program), Err(MonadError::InvalidBlock(2)));
+        program[2 * BLOCK_LEN + 9] = Instruction::parse("add y 25");
+        program[5 * BLOCK_LEN + 4] = Instruction::parse("div z 13");
+        assert_eq!(monad_blocks(&program), Err(MonadError::InvalidBlock(5)));
+    }
+
+    #[test]
+    fn test_monad_smt() {
+        let smt = monad_smt(&instance_blocks());
+        let script = smt.to_string();
+        assert!(script.starts_with("(declare-const z0 Int)\n(assert (= 0 z0))\n; Iteration 1\n"));
+        assert!(script.contains("(assert (= x4 (+ (mod z3 26) (- 2))))\n"));
+        assert!(script.contains("(assert (= z14 (ite (= x14 w14) y14 (+ (* 26 y14) w14 14))))\n"));
+        assert!(script.contains("(assert (= z14 0))\n"));
+        assert_eq!(script.matches("(check-sat)").count(), 2);
+        assert!(script.ends_with(
+            "(push)\n(minimize model_number)\n(check-sat)\n(get-value (z14 model_number))\n(pop)\n"
+        ));
+    }
+
+    #[test]
+    fn test_count_models() {
+        let mut search = ModelSearch::new(instance_blocks());
+        assert_eq!(search.count(), Ok(5880));
+        // memoization keeps the search small
+        assert!(search.states() < 50_000);
+    }
+
+    #[test]
+    fn test_enumerate_models() {
+        let mut models = Vec::new();
+        let blocks = instance_blocks();
+        ModelSearch::new(blocks)
+            .enumerate(|digits| models.push(*digits))
+            .unwrap();
+
+        assert_eq!(models.len(), 5880);
+        assert!(models
+            .windows(2)
+            .all(|w| model_number(&w[0]) < model_number(&w[1])));
+        for digits in &models {
+            let z = (0..14).fold(0, |z, i| blocks[i].step(z, digits[i]));
+            assert_eq!(z, 0, "invalid model number {:?}", digits);
+        }
+
+        // agrees with the SMT models
+        assert_eq!(model_number(&models[0]), 13191913571211);
+        assert_eq!(model_number(&models[models.len() - 1]), 79197919993985);
+    }
+
+    #[test]
+    fn test_model_search_from_program() {
+        let mut blocks = instance_blocks();
+        blocks[0].add_y = 3;
+        let program: Vec<_> = blocks.iter().flat_map(Block::instructions).collect();
+
+        let mut models = Vec::new();
+        ModelSearch::from_program(&program)
+            .unwrap()
+            .enumerate(|digits| models.push(*digits))
+            .unwrap();
+        // the changed offset shifts the first digit by 4
+        assert_eq!(models.len(), 5880);
+        assert_eq!(model_number(&models[0]), 33191911571211);
+        assert_eq!(model_number(&models[5879]), 99197917993985);
+        for digits in &models {
+            assert!(accepts_model(&program, digits), "rejected {:?}", digits);
+        }
+
+        assert_eq!(
+            ModelSearch::from_program(&program[1..]).err(),
+            Some(MonadError::InvalidLength(14 * BLOCK_LEN - 1))
+        );
+    }
+
+    #[test]
+    fn test_cancelled_model_search() {
+        let cancel = CancellationToken::default();
+        cancel.cancel();
+        let mut search = ModelSearch::new(instance_blocks()).with_cancellation(cancel.clone());
+        assert_eq!(
+            search.count(),
+            Err(Cancelled {
+                memoized_states: 0,
+                visited_models: 0
+            })
+        );
+
+        // cancel from within the enumeration, after the fifth model number
+        let mut search =
+            ModelSearch::new(instance_blocks()).with_cancellation(CancellationToken::default());
+        let token = search.cancel.clone();
+        let mut visited = 0;
+        let result = search.enumerate(|_| {
+            visited += 1;
+            if visited == 5 {
+                token.cancel();
+            }
+        });
+        assert_eq!(result.map_err(|c| c.visited_models), Err(5));
+
+        // nothing incomplete was memoized
+        search.cancel = CancellationToken::default();
+        assert_eq!(search.count(), Ok(5880));
+    }
+
+    #[test]
+    fn test_example_programs() {
+        let negate: Vec<_> = ["inp x", "mul x -1"]
+            .iter()
+            .map(|s| Instruction::parse(s))
+            .collect();
+        assert_eq!(run_alu(&negate, &[7]), [0, -7, 0, 0]);
+        assert_eq!(run_compiled(&negate, &[7]), [0, -7, 0, 0]);
+
+        let binary: Vec<_> = [
+            "inp w", "add z w", "mod z 2", "div w 2", "add y w", "mod y 2", "div w 2", "add x w",
+            "mod x 2", "div w 2", "mod w 2",
+        ]
+        .iter()
+        .map(|s| Instruction::parse(s))
+        .collect();
+        assert_eq!(run_alu(&binary, &[13]), [1, 1, 0, 1]);
+        assert_eq!(run_compiled(&binary, &[13]), [1, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_generated_programs_never_trap() {
+        let mut rng = Rng::new(0x2545f4914f6cdd1d);
+        for _ in 0..1000 {
+            let program = generate_program(&mut rng, 50);
+            let input = generate_input(&mut rng, &program);
+            run_alu(&program, &input);
+        }
+    }
+
+    #[test]
+    fn test_fuzz_compiled_against_alu() {
+        let mut rng = Rng::new(0x9e3779b97f4a7c15);
+        for _ in 0..2000 {
+            let program = generate_program(&mut rng, 50);
+            let input = generate_input(&mut rng, &program);
+            let differs = |program: &[Instruction], input: &[i64]| {
+                run_alu(program, input) != run_compiled(program, input)
+            };
+            if differs(&program, &input) {
+                let (program, input) = shrink(program, input, differs);
+                panic!(
+                    "ALU and compiled program differ on {:?} with input {:?}",
+                    program, input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzz_optimized_against_alu() {
+        let mut rng = Rng::new(0x2545f4914f6cdd1d);
+        for _ in 0..2000 {
+            let program = generate_program(&mut rng, 50);
+            let input = generate_input(&mut rng, &program);
+            let differs = |program: &[Instruction], input: &[i64]| {
+                run_alu(program, input) != run_alu(&optimize(program), input)
+            };
+            if differs(&program, &input) {
+                let (program, input) = shrink(program, input, differs);
+                panic!(
+                    "optimized program differs on {:?} with input {:?}",
+                    program, input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzz_dead_code_elimination() {
+        let mut rng = Rng::new(0x853c49e6748fea9b);
+        for _ in 0..2000 {
+            let program = generate_program(&mut rng, 50);
+            let input = generate_input(&mut rng, &program);
+            let differs = |program: &[Instruction], input: &[i64]| {
+                let (eliminated, _) = eliminate_dead_code(program);
+                run_alu(program, input)[3] != run_alu(&eliminated, input)[3]
+            };
+            if differs(&program, &input) {
+                let (program, input) = shrink(program, input, differs);
+                panic!(
+                    "dead code elimination changes z of {:?} with input {:?}",
+                    program, input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_optimize_monad() {
+        let program: Vec<_> = include_str!("../instance.txt")
+            .lines()
+            .map(Instruction::parse)
+            .collect();
+        let optimized = optimize(&program);
+        assert!(optimized.len() < program.len());
+
+        let model = [1, 3, 1, 9, 1, 9, 1, 3, 5, 7, 1, 2, 1, 1];
+        assert_eq!(run_alu(&program, &model), run_alu(&optimized, &model));
+        assert_eq!(run_alu(&optimized, &model)[3], 0);
+    }
+
+    #[test]
+    fn test_instruction_display_round_trip() {
+        for line in [
+            "inp w",
+            "add x -12",
+            "mul y z",
+            "div z 26",
+            "mod x 26",
+            "eql x w",
+        ] {
+            assert_eq!(Instruction::parse(line).to_string(), line);
+        }
+    }
+
+    #[test]
+    fn test_custom_instructions() {
+        let set = InstructionSet::extended().with("sub", i64::wrapping_sub);
+        let program: Vec<_> = [
+            "inp w", "inp x", "set y w", "max y x", "set z w", "min z x", "sub z y",
+        ]
+        .into_iter()
+        .map(|line| set.parse(line))
+        .collect();
+
+        for line in ["set y w", "max y x", "min z -3", "sub z 26"] {
+            assert_eq!(set.parse(line).to_string(), line);
+        }
+        assert_eq!(run_alu(&program, &[3, 8]), [3, 8, 8, -5]);
+        assert_eq!(run_alu(&program, &[9, 2]), [9, 2, 9, -7]);
+
+        let compiled = CompiledProgram::compile(&program);
+        assert_eq!(compiled.run(&mut vec![3, 8]), [3, 8, 8, -5]);
+        assert_eq!(run_alu(&optimize(&program), &[9, 2]), [9, 2, 9, -7]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unknown_instruction() {
+        Instruction::parse("max x 1");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_register_builtin_instruction() {
+        InstructionSet::default().register("add", i64::wrapping_sub);
+    }
+
+    #[test]
+    fn test_shrink() {
+        // a deliberately wrong property: z is never 3 after running
+        let fails = |program: &[Instruction], input: &[i64]| run_alu(program, input)[3] == 3;
+
+        let mut rng = Rng::new(0xdeadbeefcafebabe);
+        let (program, input) = loop {
+            let program = generate_program(&mut rng, 30);
+            let input = generate_input(&mut rng, &program);
+            if fails(&program, &input) && program.len() > 5 {
+                break (program, input);
+            }
+        };
+
+        let (program, input) = shrink(program, input, fails);
+        assert!(fails(&program, &input));
+        assert!(program.len() <= 2, "not minimal: {:?}", program);
+    }
+
+    #[test]
+    fn test_solution() {
+        let (part1, part2) = common::solve::<Day24>(include_str!("../instance.txt"));
+        assert_eq!(part1.to_string(), "79197919993985");
+        assert_eq!(part2.to_string(), "13191913571211");
+    }
+}
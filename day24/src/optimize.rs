@@ -0,0 +1,182 @@
+use super::{def_use_edges, Instruction, Operand, Variable};
+
+// Removes instructions which don't change anything.
+fn simplify(inst: Instruction) -> Option<Instruction> {
+    match inst {
+        Instruction::Add(_, Operand::Literal(0))
+        | Instruction::Mul(_, Operand::Literal(1))
+        | Instruction::Div(_, Operand::Literal(1)) => None,
+        inst => Some(inst),
+    }
+}
+
+// Combines two consecutive instructions with literal operands on the same variable.
+fn fold(first: Instruction, second: Instruction) -> Option<Instruction> {
+    use Instruction::*;
+    use Operand::Literal;
+
+    match (first, second) {
+        (Add(a, Literal(n)), Add(b, Literal(m))) if a == b => {
+            Some(Add(a, Literal(n.wrapping_add(m))))
+        }
+        (Mul(a, Literal(n)), Mul(b, Literal(m))) if a == b => {
+            Some(Mul(a, Literal(n.wrapping_mul(m))))
+        }
+        // truncating divisions by positive divisors compose, unless the divisor overflows
+        (Div(a, Literal(n)), Div(b, Literal(m))) if a == b && n > 0 && m > 0 => {
+            n.checked_mul(m).map(|d| Div(a, Literal(d)))
+        }
+        _ => None,
+    }
+}
+
+fn peephole(instructions: &[Instruction]) -> Vec<Instruction> {
+    let mut optimized: Vec<Instruction> = Vec::with_capacity(instructions.len());
+    for &inst in instructions {
+        let Some(mut inst) = simplify(inst) else {
+            continue;
+        };
+        if let Some(folded) = optimized.last().and_then(|&last| fold(last, inst)) {
+            optimized.pop();
+            match simplify(folded) {
+                Some(folded) => inst = folded,
+                None => continue,
+            }
+        }
+        optimized.push(inst);
+    }
+    optimized
+}
+
+// Removes writes which are overwritten before being read, all registers are considered to be
+// read after the program. `inp` is kept in any case, as it consumes an input value.
+fn eliminate_dead_stores(instructions: &[Instruction]) -> Vec<Instruction> {
+    let mut live = [true; 4];
+    let mut kept = Vec::with_capacity(instructions.len());
+    for &inst in instructions.iter().rev() {
+        let written = inst.written_vars();
+        let dead = written.iter().all(|var| !live[var.index()]);
+        if dead && !matches!(inst, Instruction::Inp(_)) {
+            continue;
+        }
+        for var in written {
+            live[var.index()] = false;
+        }
+        for var in inst.read_vars() {
+            live[var.index()] = true;
+        }
+        kept.push(inst);
+    }
+    kept.reverse();
+    kept
+}
+
+// Smaller program with the same effect on all registers. A removed `div` or `mod` can't trap
+// anymore, which only matters for programs dividing by zero.
+pub fn optimize(instructions: &[Instruction]) -> Vec<Instruction> {
+    let mut program = instructions.to_vec();
+    loop {
+        // every rewrite removes at least one instruction
+        let optimized = eliminate_dead_stores(&peephole(&program));
+        if optimized.len() == program.len() {
+            return optimized;
+        }
+        program = optimized;
+    }
+}
+
+// Removes all instructions whose results never flow into the final z, following the def-use
+// edges backwards from the last write of z. `inp` is kept, as it consumes an input value.
+// Returns the remaining program and the number of eliminated instructions.
+pub fn eliminate_dead_code(instructions: &[Instruction]) -> (Vec<Instruction>, usize) {
+    let mut used_by: Vec<Vec<usize>> = vec![Vec::new(); instructions.len()];
+    for (def, usage, _) in def_use_edges(instructions) {
+        used_by[usage].push(def);
+    }
+
+    let mut needed = vec![false; instructions.len()];
+    let mut pending: Vec<usize> = instructions
+        .iter()
+        .rposition(|inst| inst.written_vars().contains(&Variable::Z))
+        .into_iter()
+        .collect();
+    while let Some(i) = pending.pop() {
+        if !needed[i] {
+            needed[i] = true;
+            pending.extend(&used_by[i]);
+        }
+    }
+
+    let kept: Vec<Instruction> = instructions
+        .iter()
+        .zip(needed)
+        .filter(|&(inst, needed)| needed || matches!(inst, Instruction::Inp(_)))
+        .map(|(&inst, _)| inst)
+        .collect();
+    let eliminated = instructions.len() - kept.len();
+    (kept, eliminated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(program: &str) -> Vec<Instruction> {
+        program.lines().map(Instruction::parse).collect()
+    }
+
+    #[test]
+    fn test_peephole() {
+        let program =
+            parse("inp w\nmul x 1\nadd x 0\ndiv y 1\nadd z 3\nadd z -1\nmul y 2\nmul y 13");
+        assert_eq!(peephole(&program), parse("inp w\nadd z 2\nmul y 26"));
+
+        // folding may produce a no-op, which is removed as well
+        assert_eq!(
+            peephole(&parse("add x 3\nadd x -3\nadd x 1")),
+            parse("add x 1")
+        );
+        assert_eq!(peephole(&parse("div x 4\ndiv x 6")), parse("div x 24"));
+        assert_eq!(
+            peephole(&parse(
+                "div x 4611686018427387904\ndiv x 4\ndiv x -1\ndiv x 2"
+            )),
+            parse("div x 4611686018427387904\ndiv x 4\ndiv x -1\ndiv x 2")
+        );
+        // different variables don't fold
+        assert_eq!(
+            peephole(&parse("add x 1\nadd y 1")),
+            parse("add x 1\nadd y 1")
+        );
+    }
+
+    #[test]
+    fn test_eliminate_dead_stores() {
+        let program = parse("add x 5\nmul x 0\ninp y\ninp y\nadd z y\neql w z\nadd w 1\nmul w 0");
+        assert_eq!(
+            eliminate_dead_stores(&program),
+            parse("mul x 0\ninp y\ninp y\nadd z y\nmul w 0")
+        );
+    }
+
+    #[test]
+    fn test_optimize() {
+        // the dead store only becomes visible after folding, and vice versa
+        let program = parse("add x 2\nmul y 0\nadd y 4\nadd x -2\nmul y 0\nadd z x");
+        assert_eq!(optimize(&program), parse("mul y 0\nadd z x"));
+    }
+
+    #[test]
+    fn test_eliminate_dead_code() {
+        let program = parse("inp w\nadd x w\nmul y 0\nadd y 25\ninp x\nadd z x\nadd y z\nmul w 2");
+        let (kept, eliminated) = eliminate_dead_code(&program);
+        assert_eq!(kept, parse("inp w\ninp x\nadd z x"));
+        assert_eq!(eliminated, 5);
+
+        // without any write to z, only the inputs remain
+        assert_eq!(
+            eliminate_dead_code(&parse("inp x\nadd y x")),
+            (parse("inp x"), 1)
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use common::rng::Rng;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use day25::*;
+
+const STEPS: usize = 50;
+
+fn generate_map(height: usize, width: usize, seed: u64) -> Vec<Vec<char>> {
+    let mut rng = Rng::new(seed);
+    (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| match rng.below(3) {
+                    0 => '>',
+                    1 => 'v',
+                    _ => '.',
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Tracking the active rows and columns against scanning the whole map every step.
+fn active_vs_full(c: &mut Criterion) {
+    let map = generate_map(1000, 1000, 0x9e3779b97f4a7c15);
+    let (height, width) = (map.len(), map[0].len());
+
+    let mut group = c.benchmark_group("50 steps on 1000x1000");
+    group.sample_size(10);
+    group.bench_function("full scan", |b| {
+        b.iter_batched(
+            || map.clone(),
+            |mut map| {
+                for _ in 0..STEPS {
+                    let mut region = ActiveRegion::all(height, width);
+                    step_active(&mut map, &mut region, Topology::Torus);
+                }
+                map
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("active scan", |b| {
+        b.iter_batched(
+            || map.clone(),
+            |mut map| {
+                let mut region = ActiveRegion::all(height, width);
+                for _ in 0..STEPS {
+                    step_active(&mut map, &mut region, Topology::Torus);
+                }
+                map
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, active_vs_full);
+criterion_main!(benches);
@@ -0,0 +1,531 @@
+use common::{Answer, Solution};
+use std::io::{self, BufRead, Write};
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input = Vec<Vec<char>>;
+
+    fn parse(input: &str) -> Self::Input {
+        input.lines().map(|line| line.chars().collect()).collect()
+    }
+
+    fn part1(map: &Self::Input) -> Answer {
+        let mut floor = SeaFloor::new(map.clone(), Topology::default());
+        run_until_stable(&mut floor, |_| {}).into()
+    }
+
+    // there is no second puzzle on the last day
+    fn part2(_: &Self::Input) -> Answer {
+        "-".into()
+    }
+}
+
+// How the edges of the map are connected. Torus wraps in both directions, Cylinder only
+// east-west, and Bounded has walls on all sides.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    #[default]
+    Torus,
+    Cylinder,
+    Bounded,
+}
+
+impl Topology {
+    pub fn parse(s: &str) -> Option<Topology> {
+        match s {
+            "torus" => Some(Topology::Torus),
+            "cylinder" => Some(Topology::Cylinder),
+            "bounded" => Some(Topology::Bounded),
+            _ => None,
+        }
+    }
+
+    fn wraps_east(&self) -> bool {
+        matches!(self, Topology::Torus | Topology::Cylinder)
+    }
+
+    fn wraps_south(&self) -> bool {
+        matches!(self, Topology::Torus)
+    }
+
+    fn east_of(&self, col: usize, width: usize) -> Option<usize> {
+        next_index(col, width, self.wraps_east())
+    }
+
+    fn south_of(&self, row: usize, height: usize) -> Option<usize> {
+        next_index(row, height, self.wraps_south())
+    }
+}
+
+pub fn next_index(i: usize, len: usize, wraps: bool) -> Option<usize> {
+    if i + 1 < len {
+        Some(i + 1)
+    } else if wraps {
+        Some(0)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveRegion {
+    rows: Vec<bool>,
+    cols: Vec<bool>,
+}
+
+impl ActiveRegion {
+    pub fn all(height: usize, width: usize) -> Self {
+        Self {
+            rows: vec![true; height],
+            cols: vec![true; width],
+        }
+    }
+
+    pub fn none(height: usize, width: usize) -> Self {
+        Self {
+            rows: vec![false; height],
+            cols: vec![false; width],
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct StepReport {
+    pub east_moved: usize,
+    pub south_moved: usize,
+    pub active_rows: usize,
+    pub active_cols: usize,
+    pub active_cells: usize,
+}
+
+impl StepReport {
+    pub fn moved(&self) -> usize {
+        self.east_moved + self.south_moved
+    }
+}
+
+// Only re-examines rows/columns which have changed since they were last examined. A row (column) without changes can't have any movable sea cucumbers,
+// as otherwise they would have moved the last time it was examined.
+#[allow(clippy::needless_range_loop)]
+pub fn step_active(
+    map: &mut [Vec<char>],
+    region: &mut ActiveRegion,
+    topology: Topology,
+) -> StepReport {
+    let h = map.len();
+    let w = map[0].len();
+
+    let mut stats = StepReport::default();
+    let mut next_region = ActiveRegion::none(h, w);
+
+    // move east-facing
+    for (r, row) in map.iter_mut().enumerate() {
+        if !region.rows[r] {
+            continue;
+        }
+        stats.active_rows += 1;
+        stats.active_cells += w;
+
+        let mut moveable = Vec::with_capacity(w);
+        for i in 0..w {
+            if let Some(j) = topology.east_of(i, w) {
+                if (row[i], row[j]) == ('>', '.') {
+                    moveable.push((i, j));
+                }
+            }
+        }
+        for (i, j) in moveable {
+            row[i] = '.';
+            row[j] = '>';
+            stats.east_moved += 1;
+            next_region.rows[r] = true;
+            // columns have to be re-examined in this step already
+            region.cols[i] = true;
+            region.cols[j] = true;
+        }
+    }
+
+    // move south-facing
+    for col in 0..w {
+        if !region.cols[col] {
+            continue;
+        }
+        stats.active_cols += 1;
+        stats.active_cells += h;
+
+        let mut moveable = Vec::with_capacity(h);
+        for i in 0..h {
+            if let Some(j) = topology.south_of(i, h) {
+                if (map[i][col], map[j][col]) == ('v', '.') {
+                    moveable.push((i, j));
+                }
+            }
+        }
+        for (i, j) in moveable {
+            map[i][col] = '.';
+            map[j][col] = 'v';
+            stats.south_moved += 1;
+            next_region.cols[col] = true;
+            next_region.rows[i] = true;
+            next_region.rows[j] = true;
+        }
+    }
+
+    *region = next_region;
+
+    stats
+}
+
+// Map after the given number of steps, stored as the step count followed by the map itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub steps: usize,
+    pub map: Vec<Vec<char>>,
+}
+
+impl Checkpoint {
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "{}", self.steps)?;
+        for row in &self.map {
+            writeln!(writer, "{}", row.iter().collect::<String>())?;
+        }
+        Ok(())
+    }
+
+    pub fn read<R: BufRead>(reader: R) -> io::Result<Checkpoint> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+        let mut lines = reader.lines();
+        let steps = lines
+            .next()
+            .ok_or_else(|| invalid("missing step count"))??
+            .parse()
+            .map_err(|_| invalid("invalid step count"))?;
+        let map: Vec<Vec<char>> = lines
+            .map(|line| line.map(|line| line.chars().collect()))
+            .collect::<io::Result<_>>()?;
+        if map.is_empty() || map.iter().any(|row| row.len() != map[0].len()) {
+            return Err(invalid("invalid map"));
+        }
+
+        Ok(Checkpoint { steps, map })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SeaFloor {
+    map: Vec<Vec<char>>,
+    topology: Topology,
+    region: ActiveRegion,
+    steps: usize,
+    stable: bool,
+}
+
+impl SeaFloor {
+    pub fn new(map: Vec<Vec<char>>, topology: Topology) -> Self {
+        Self::resume(Checkpoint { steps: 0, map }, topology)
+    }
+
+    pub fn resume(checkpoint: Checkpoint, topology: Topology) -> Self {
+        let region = ActiveRegion::all(checkpoint.map.len(), checkpoint.map[0].len());
+        Self {
+            map: checkpoint.map,
+            topology,
+            region,
+            steps: checkpoint.steps,
+            stable: false,
+        }
+    }
+
+    pub fn step(&mut self) -> StepReport {
+        let report = step_active(&mut self.map, &mut self.region, self.topology);
+        self.steps += 1;
+        self.stable = report.moved() == 0;
+        report
+    }
+
+    // Whether nothing moved in the last step.
+    pub fn is_stable(&self) -> bool {
+        self.stable
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn grid(&self) -> &[Vec<char>] {
+        &self.map
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            steps: self.steps,
+            map: self.map.clone(),
+        }
+    }
+}
+
+// Steps until the sea floor is stable, the last report is the one of the step in which nothing
+// moved.
+impl Iterator for SeaFloor {
+    type Item = StepReport;
+
+    fn next(&mut self) -> Option<StepReport> {
+        (!self.stable).then(|| self.step())
+    }
+}
+
+// Steps until no sea cucumber moves anymore. Returns the number of the first step in which
+// nothing moved.
+pub fn run_until_stable<F>(floor: &mut SeaFloor, mut after_step: F) -> usize
+where
+    F: FnMut(&SeaFloor),
+{
+    while floor.next().is_some() {
+        if !floor.is_stable() {
+            after_step(floor);
+        }
+    }
+    floor.steps()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::rng::Rng;
+
+    #[allow(clippy::needless_range_loop)]
+    fn step(map: &mut [Vec<char>]) -> bool {
+        let h = map.len();
+        let w = map[0].len();
+
+        let mut has_moved = false;
+
+        // move east-facing
+        for row in map.iter_mut() {
+            let mut moveable = Vec::with_capacity(w);
+            for i in 0..w {
+                if (row[i], row[(i + 1) % w]) == ('>', '.') {
+                    has_moved = true;
+                    moveable.push(i);
+                }
+            }
+            for i in moveable {
+                row[i] = '.';
+                row[(i + 1) % w] = '>';
+            }
+        }
+
+        // move south-facing
+        for col in 0..w {
+            let mut moveable = Vec::with_capacity(w);
+            for i in 0..h {
+                if (map[i][col], map[(i + 1) % h][col]) == ('v', '.') {
+                    has_moved = true;
+                    moveable.push(i);
+                }
+            }
+            for i in moveable {
+                map[i][col] = '.';
+                map[(i + 1) % h][col] = 'v';
+            }
+        }
+
+        has_moved
+    }
+
+    fn parse_map(input: &str) -> Vec<Vec<char>> {
+        input.lines().map(|line| line.chars().collect()).collect()
+    }
+
+    fn generate_map(height: usize, width: usize, seed: u64) -> Vec<Vec<char>> {
+        let mut rng = Rng::new(seed);
+        (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| match rng.below(3) {
+                        0 => '>',
+                        1 => 'v',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    const EXAMPLE: &str = "v...>>.vv>
+.vv>>.vv..
+>>.>v>...v
+>>v>>.>.v.
+v>v.vv.v..
+>.>>..v...
+.vv..>.>v.
+v.v..>>v.v
+....v..v.>";
+
+    #[test]
+    fn test_step_active_example() {
+        let mut map = parse_map(EXAMPLE);
+        let mut region = ActiveRegion::all(map.len(), map[0].len());
+        let mut steps = 1;
+        while step_active(&mut map, &mut region, Topology::Torus).moved() > 0 {
+            steps += 1;
+        }
+        assert_eq!(steps, 58);
+    }
+
+    #[test]
+    fn test_sea_floor_example() {
+        let mut floor = SeaFloor::new(parse_map(EXAMPLE), Topology::Torus);
+        assert!(!floor.is_stable());
+        let reports: Vec<StepReport> = floor.by_ref().collect();
+        assert_eq!(reports.len(), 58);
+        assert_eq!(floor.steps(), 58);
+        assert!(floor.is_stable());
+        assert_eq!(reports.last().unwrap().moved(), 0);
+        assert!(reports[..57].iter().all(|report| report.moved() > 0));
+        // stays stable
+        assert_eq!(floor.next(), None);
+        assert_eq!(floor.steps(), 58);
+    }
+
+    #[test]
+    fn test_step_report_counts_herds() {
+        let mut floor = SeaFloor::new(parse_map("...>>>>>..."), Topology::Torus);
+        let report = floor.step();
+        assert_eq!((report.east_moved, report.south_moved), (1, 0));
+        assert_eq!(floor.grid(), parse_map("...>>>>.>.."));
+        let report = floor.step();
+        assert_eq!((report.east_moved, report.south_moved), (2, 0));
+        assert_eq!(floor.grid(), parse_map("...>>>.>.>."));
+
+        let mut floor = SeaFloor::new(
+            parse_map("..........\n.>v....v..\n.......>..\n.........."),
+            Topology::Torus,
+        );
+        let report = floor.step();
+        assert_eq!((report.east_moved, report.south_moved), (1, 2));
+        assert_eq!(
+            floor.grid(),
+            parse_map("..........\n.>........\n..v....v>.\n..........")
+        );
+        assert_eq!(floor.steps(), 1);
+    }
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let mut uninterrupted = SeaFloor::new(parse_map(EXAMPLE), Topology::Torus);
+        let steps = run_until_stable(&mut uninterrupted, |_| {});
+        assert_eq!(steps, 58);
+
+        for checkpoint_step in [1, 20, 57] {
+            let mut checkpoint = Vec::new();
+            let mut floor = SeaFloor::new(parse_map(EXAMPLE), Topology::Torus);
+            run_until_stable(&mut floor, |floor| {
+                if floor.steps() == checkpoint_step {
+                    floor.checkpoint().write(&mut checkpoint).unwrap();
+                }
+            });
+
+            let checkpoint = Checkpoint::read(&checkpoint[..]).unwrap();
+            assert_eq!(checkpoint.steps, checkpoint_step);
+            let mut floor = SeaFloor::resume(checkpoint, Topology::Torus);
+            assert_eq!(run_until_stable(&mut floor, |_| {}), 58);
+            assert_eq!(floor.grid(), uninterrupted.grid());
+        }
+    }
+
+    #[test]
+    fn test_read_invalid_checkpoint() {
+        assert!(Checkpoint::read("".as_bytes()).is_err());
+        assert!(Checkpoint::read("x\n>.\n".as_bytes()).is_err());
+        assert!(Checkpoint::read("3\n>.\n.\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_step_active_matches_full_step() {
+        let mut full = generate_map(97, 131, 0x2545f4914f6cdd1d);
+        let mut active = full.clone();
+        let mut region = ActiveRegion::all(active.len(), active[0].len());
+        loop {
+            let has_moved = step(&mut full);
+            let stats = step_active(&mut active, &mut region, Topology::Torus);
+            assert_eq!(full, active);
+            assert_eq!(has_moved, stats.moved() > 0);
+            if !has_moved {
+                break;
+            }
+        }
+    }
+
+    fn step_map(input: &str, topology: Topology) -> Vec<Vec<char>> {
+        let mut map = parse_map(input);
+        let mut region = ActiveRegion::all(map.len(), map[0].len());
+        step_active(&mut map, &mut region, topology);
+        map
+    }
+
+    #[test]
+    fn test_topology_torus() {
+        // the south-facing one wraps into the cell left by the east-facing one
+        assert_eq!(
+            step_map("..>\n...\n.v.", Topology::Torus),
+            parse_map(">v.\n...\n...")
+        );
+    }
+
+    #[test]
+    fn test_topology_cylinder() {
+        // wraps east-west, but the south edge is a wall
+        assert_eq!(
+            step_map("..>\n...\n.v.", Topology::Cylinder),
+            parse_map(">..\n...\n.v.")
+        );
+    }
+
+    #[test]
+    fn test_topology_bounded() {
+        assert_eq!(
+            step_map("..>\n...\n.v.", Topology::Bounded),
+            parse_map("..>\n...\n.v.")
+        );
+        assert_eq!(step_map(">.\nv.", Topology::Bounded), parse_map(".>\nv."));
+    }
+
+    #[test]
+    fn test_topology_bounded_piles_up_at_walls() {
+        let map = parse_map(">>.>.\nv....\n.v...\n.....");
+        let mut floor = SeaFloor::new(map, Topology::Bounded);
+        let steps = run_until_stable(&mut floor, |_| {});
+        assert_eq!(steps, 4);
+        assert_eq!(floor.grid(), parse_map("..>>>\n.....\n.....\nvv..."));
+    }
+
+    #[test]
+    fn test_step_active_matches_full_region_for_all_topologies() {
+        for topology in [Topology::Torus, Topology::Cylinder, Topology::Bounded] {
+            let mut active = generate_map(23, 31, 0x2545f4914f6cdd1d);
+            let mut full = active.clone();
+            let mut region = ActiveRegion::all(active.len(), active[0].len());
+            // a cylinder may never become stable, so only compare a fixed number of steps
+            for _ in 0..100 {
+                let stats = step_active(&mut active, &mut region, topology);
+                let full_stats = step_active(
+                    &mut full,
+                    &mut ActiveRegion::all(active.len(), active[0].len()),
+                    topology,
+                );
+                assert_eq!(active, full, "{:?}", topology);
+                assert_eq!(stats.east_moved, full_stats.east_moved, "{:?}", topology);
+                assert_eq!(stats.south_moved, full_stats.south_moved, "{:?}", topology);
+            }
+        }
+    }
+
+    #[test]
+    fn test_solution_example() {
+        let (part1, _) = common::solve::<Day25>(include_str!("../example.txt"));
+        assert_eq!(part1.to_string(), "58");
+    }
+}
@@ -1,54 +1,45 @@
-use std::io::{self, BufRead};
+use common::Solution;
+use day25::*;
+use std::{
+    env,
+    fs::File,
+    io::{BufReader, BufWriter},
+    num::NonZeroUsize,
+};
 
-fn step(map: &mut [Vec<char>]) -> bool {
-    let h = map.len();
-    let w = map[0].len();
-
-    let mut has_moved = false;
-
-    // move east-facing
-    for row in map.iter_mut() {
-        let mut moveable = Vec::with_capacity(w);
-        for i in 0..w {
-            if (row[i], row[(i + 1) % w]) == ('>', '.') {
-                has_moved = true;
-                moveable.push(i);
-            }
-        }
-        for i in moveable {
-            row[i] = '.';
-            row[(i + 1) % w] = '>';
-        }
+fn main() {
+    // there is no second part on the last day
+    if !common::part_selected(1) {
+        return;
     }
 
-    // move south-facing
-    for col in 0..w {
-        let mut moveable = Vec::with_capacity(w);
-        for i in 0..h {
-            if (map[i][col], map[(i + 1) % h][col]) == ('v', '.') {
-                has_moved = true;
-                moveable.push(i);
-            }
-        }
-        for i in moveable {
-            map[i][col] = '.';
-            map[(i + 1) % h][col] = 'v';
-        }
-    }
+    let arg = |name: &str| env::args().skip_while(|arg| arg != name).nth(1);
 
-    has_moved
-}
+    let topology = arg("--topology")
+        .map(|s| Topology::parse(&s).expect("unknown topology"))
+        .unwrap_or_default();
 
-fn main() {
-    let mut map: Vec<Vec<char>> = io::stdin()
-        .lock()
-        .lines()
-        .map(|s| s.unwrap().chars().collect())
-        .collect();
+    let mut floor = match arg("--resume") {
+        Some(path) => {
+            let checkpoint = Checkpoint::read(BufReader::new(File::open(path).unwrap())).unwrap();
+            SeaFloor::resume(checkpoint, topology)
+        }
+        None => SeaFloor::new(Day25::parse(&common::read_input()), topology),
+    };
+    let checkpoint_every: Option<NonZeroUsize> = arg("--checkpoint").map(|n| {
+        n.parse()
+            .expect("--checkpoint expects a positive number of steps")
+    });
+    let checkpoint_file =
+        arg("--checkpoint-file").unwrap_or_else(|| String::from("checkpoint.txt"));
 
-    let mut steps = 1;
-    while step(&mut map) {
-        steps += 1;
+    let steps = run_until_stable(&mut floor, |floor| {
+        if checkpoint_every.is_some_and(|every| floor.steps() % every.get() == 0) {
+            let file = File::create(&checkpoint_file).unwrap();
+            floor.checkpoint().write(BufWriter::new(file)).unwrap();
+        }
+    });
+    if common::part_selected(1) {
+        println!("Part 1: {}", steps);
     }
-    println!("Part 1: {}", steps);
 }
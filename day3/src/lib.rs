@@ -0,0 +1,397 @@
+use common::{Answer, Solution};
+use std::cmp::Ordering;
+use thiserror::Error;
+
+// Solution for numbers with the given number of bits, using the default tie-break.
+pub struct Day3<const BITS: usize = 12>;
+
+impl<const BITS: usize> Solution for Day3<BITS> {
+    type Input = Vec<usize>;
+
+    fn parse(input: &str) -> Self::Input {
+        input
+            .lines()
+            .map(|line| usize::from_str_radix(line, 2).unwrap())
+            .collect()
+    }
+
+    fn part1(numbers: &Self::Input) -> Answer {
+        power_consumption::<BITS>(numbers, TieBreak::default())
+            .unwrap()
+            .into()
+    }
+
+    fn part2(numbers: &Self::Input) -> Answer {
+        life_support_rating::<BITS>(numbers, TieBreak::default())
+            .unwrap()
+            .into()
+    }
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("equally many ones and zeroes at bit {bit}")]
+pub struct Tie {
+    // counted from the least significant bit
+    bit: usize,
+}
+
+// Which bit counts as the most common one if there are equally many ones and zeroes. The least
+// common bit is always the other one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    #[default]
+    PreferOne,
+    PreferZero,
+    Reject,
+}
+
+impl TieBreak {
+    pub fn parse(s: &str) -> Option<TieBreak> {
+        match s {
+            "one" => Some(TieBreak::PreferOne),
+            "zero" => Some(TieBreak::PreferZero),
+            "reject" => Some(TieBreak::Reject),
+            _ => None,
+        }
+    }
+
+    pub fn most_common(&self, ones: usize, zeroes: usize, bit: usize) -> Result<usize, Tie> {
+        match (ones.cmp(&zeroes), self) {
+            (Ordering::Greater, _) | (Ordering::Equal, TieBreak::PreferOne) => Ok(1),
+            (Ordering::Less, _) | (Ordering::Equal, TieBreak::PreferZero) => Ok(0),
+            (Ordering::Equal, TieBreak::Reject) => Err(Tie { bit }),
+        }
+    }
+
+    pub fn least_common(&self, ones: usize, zeroes: usize, bit: usize) -> Result<usize, Tie> {
+        self.most_common(ones, zeroes, bit).map(|bit| bit ^ 1)
+    }
+}
+
+// runtime: O(|numbers| * BITS + BITS)
+// space: O(BITS)
+pub fn power_consumption<const BITS: usize>(
+    numbers: &[usize],
+    tie_break: TieBreak,
+) -> Result<usize, Tie> {
+    let (bit_sum, n) = numbers
+        .iter()
+        .fold(([0; BITS], 0), |(mut bit_sum, n), number| {
+            for (i, sum) in bit_sum.iter_mut().enumerate() {
+                *sum += (number >> (BITS - i - 1)) & 1;
+            }
+            (bit_sum, n + 1)
+        });
+
+    let mut gamma_rate = 0;
+    for (i, &ones) in bit_sum.iter().enumerate() {
+        let bit = BITS - i - 1;
+        gamma_rate |= tie_break.most_common(ones, n - ones, bit)? << bit;
+    }
+    let epsilon_rate = gamma_rate ^ ((1 << BITS) - 1);
+
+    Ok(gamma_rate * epsilon_rate)
+}
+
+pub fn life_support_rating<const BITS: usize>(
+    numbers: &[usize],
+    tie_break: TieBreak,
+) -> Result<usize, Tie> {
+    let oxygen_generator_rating =
+        find_unique_number::<BITS, true>(numbers, tie_break)?.expect("no oxygen generator rating");
+    let co2_scrubber_rating =
+        find_unique_number::<BITS, false>(numbers, tie_break)?.expect("no CO2 scrubber rating");
+    Ok(oxygen_generator_rating * co2_scrubber_rating)
+}
+
+// runtime: O(|numbers| + |numbers| * BITS)
+// space: O(BITS)
+pub fn find_unique_number<const BITS: usize, const MSB: bool>(
+    numbers: &[usize],
+    tie_break: TieBreak,
+) -> Result<Option<usize>, Tie> {
+    let mut prefix: usize = 0;
+
+    for b in (0..=BITS).rev() {
+        let prefix_filter = !((1 << b) - 1);
+
+        let mut last_number_with_matching_prefix = 0;
+        let mut count = 0;
+
+        let mut next_bit_ones = 0;
+        let mut next_bit_zeroes = 0;
+
+        for &number in numbers {
+            let has_prefix = ((number ^ prefix) & prefix_filter) == 0;
+            if has_prefix {
+                last_number_with_matching_prefix = number;
+                count += 1;
+                if b > 0 {
+                    let next_bit = (number >> (b - 1)) & 1;
+                    if next_bit == 1 {
+                        next_bit_ones += 1;
+                    } else {
+                        next_bit_zeroes += 1;
+                    }
+                }
+            }
+        }
+
+        match count {
+            0 => return Ok(None),
+            1 => return Ok(Some(last_number_with_matching_prefix)),
+            _ => {}
+        }
+
+        if b > 0 {
+            let keep = if MSB {
+                tie_break.most_common(next_bit_ones, next_bit_zeroes, b - 1)?
+            } else {
+                tie_break.least_common(next_bit_ones, next_bit_zeroes, b - 1)?
+            };
+            prefix |= keep << (b - 1);
+        }
+    }
+
+    Ok(None)
+}
+
+// Everything computed for the diagnostic report, including the intermediate results.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagnosticReport {
+    pub bits: usize,
+    // number of ones per bit, most significant bit first
+    pub one_counts: Vec<usize>,
+    pub gamma_rate: usize,
+    pub epsilon_rate: usize,
+    pub oxygen_generator_rating: Option<usize>,
+    pub co2_scrubber_rating: Option<usize>,
+    // candidates retained after filtering by each bit, most significant bit first
+    pub oxygen_generator_candidates: Vec<Vec<usize>>,
+    pub co2_scrubber_candidates: Vec<Vec<usize>>,
+}
+
+impl DiagnosticReport {
+    pub fn analyze(
+        numbers: &[usize],
+        bits: usize,
+        tie_break: TieBreak,
+    ) -> Result<DiagnosticReport, Tie> {
+        let one_counts = count_ones(numbers, bits);
+
+        let mut gamma_rate = 0;
+        for (i, &ones) in one_counts.iter().enumerate() {
+            let bit = bits - i - 1;
+            gamma_rate |= tie_break.most_common(ones, numbers.len() - ones, bit)? << bit;
+        }
+        let epsilon_rate = gamma_rate ^ ((1 << bits) - 1);
+
+        let (oxygen_generator_rating, oxygen_generator_candidates) =
+            filter_by_bit_criteria(numbers, bits, true, tie_break)?;
+        let (co2_scrubber_rating, co2_scrubber_candidates) =
+            filter_by_bit_criteria(numbers, bits, false, tie_break)?;
+
+        Ok(DiagnosticReport {
+            bits,
+            one_counts,
+            gamma_rate,
+            epsilon_rate,
+            oxygen_generator_rating,
+            co2_scrubber_rating,
+            oxygen_generator_candidates,
+            co2_scrubber_candidates,
+        })
+    }
+
+    pub fn power_consumption(&self) -> usize {
+        self.gamma_rate * self.epsilon_rate
+    }
+
+    pub fn life_support_rating(&self) -> Option<usize> {
+        Some(self.oxygen_generator_rating? * self.co2_scrubber_rating?)
+    }
+}
+
+pub fn count_ones(numbers: &[usize], bits: usize) -> Vec<usize> {
+    (0..bits)
+        .rev()
+        .map(|b| {
+            numbers
+                .iter()
+                .filter(|&&number| (number >> b) & 1 == 1)
+                .count()
+        })
+        .collect()
+}
+
+// Keeps the numbers with the most (least) common value in each bit position until only one is left.
+// Returns the remaining number together with the candidates after each filtering step.
+pub fn filter_by_bit_criteria(
+    numbers: &[usize],
+    bits: usize,
+    most_common: bool,
+    tie_break: TieBreak,
+) -> Result<(Option<usize>, Vec<Vec<usize>>), Tie> {
+    let mut candidates = numbers.to_vec();
+    let mut steps = Vec::new();
+
+    for b in (0..bits).rev() {
+        if candidates.len() <= 1 {
+            break;
+        }
+
+        let ones = candidates
+            .iter()
+            .filter(|&&number| (number >> b) & 1 == 1)
+            .count();
+        let zeroes = candidates.len() - ones;
+        let keep = if most_common {
+            tie_break.most_common(ones, zeroes, b)?
+        } else {
+            tie_break.least_common(ones, zeroes, b)?
+        };
+
+        candidates.retain(|&number| (number >> b) & 1 == keep);
+        steps.push(candidates.clone());
+    }
+
+    let rating = match candidates[..] {
+        [number] => Some(number),
+        _ => None,
+    };
+    Ok((rating, steps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solution_example() {
+        assert_eq!(
+            common::solve::<Day3<5>>(include_str!("../example.txt")),
+            (Answer::from(198usize), Answer::from(230usize))
+        );
+    }
+
+    const EXAMPLE: &[usize] = &[
+        0b00100, 0b11110, 0b10110, 0b10111, 0b10101, 0b01111, 0b00111, 0b11100, 0b10000, 0b11001,
+        0b00010, 0b01010,
+    ];
+
+    #[test]
+    fn test_diagnostic_report_example() {
+        let report = DiagnosticReport::analyze(EXAMPLE, 5, TieBreak::PreferOne).unwrap();
+        assert_eq!(report.one_counts, vec![7, 5, 8, 7, 5]);
+        assert_eq!(report.gamma_rate, 22);
+        assert_eq!(report.epsilon_rate, 9);
+        assert_eq!(report.power_consumption(), 198);
+
+        assert_eq!(report.oxygen_generator_rating, Some(23));
+        assert_eq!(
+            report.oxygen_generator_candidates,
+            vec![
+                vec![0b11110, 0b10110, 0b10111, 0b10101, 0b11100, 0b10000, 0b11001],
+                vec![0b10110, 0b10111, 0b10101, 0b10000],
+                vec![0b10110, 0b10111, 0b10101],
+                vec![0b10110, 0b10111],
+                vec![0b10111],
+            ]
+        );
+        assert_eq!(report.co2_scrubber_rating, Some(10));
+        assert_eq!(
+            report.co2_scrubber_candidates,
+            vec![
+                vec![0b00100, 0b01111, 0b00111, 0b00010, 0b01010],
+                vec![0b01111, 0b01010],
+                vec![0b01010],
+            ]
+        );
+        assert_eq!(report.life_support_rating(), Some(230));
+    }
+
+    #[test]
+    fn test_diagnostic_report_matches_find_unique_number() {
+        for tie_break in [TieBreak::PreferOne, TieBreak::PreferZero] {
+            let report = DiagnosticReport::analyze(EXAMPLE, 5, tie_break).unwrap();
+            assert_eq!(
+                Ok(report.oxygen_generator_rating),
+                find_unique_number::<5, true>(EXAMPLE, tie_break)
+            );
+            assert_eq!(
+                Ok(report.co2_scrubber_rating),
+                find_unique_number::<5, false>(EXAMPLE, tie_break)
+            );
+            assert_eq!(
+                Ok(report.power_consumption()),
+                power_consumption::<5>(EXAMPLE, tie_break)
+            );
+        }
+    }
+
+    // exact tie in the most significant bit, and again in the least significant one
+    const TIED: &[usize] = &[0b10, 0b01];
+
+    #[test]
+    fn test_tie_break_prefer_one() {
+        let report = DiagnosticReport::analyze(TIED, 2, TieBreak::PreferOne).unwrap();
+        assert_eq!(report.gamma_rate, 0b11);
+        assert_eq!(report.epsilon_rate, 0b00);
+        assert_eq!(report.oxygen_generator_rating, Some(0b10));
+        assert_eq!(report.co2_scrubber_rating, Some(0b01));
+        assert_eq!(
+            find_unique_number::<2, true>(TIED, TieBreak::PreferOne),
+            Ok(Some(0b10))
+        );
+        assert_eq!(
+            find_unique_number::<2, false>(TIED, TieBreak::PreferOne),
+            Ok(Some(0b01))
+        );
+    }
+
+    #[test]
+    fn test_tie_break_prefer_zero() {
+        let report = DiagnosticReport::analyze(TIED, 2, TieBreak::PreferZero).unwrap();
+        assert_eq!(report.gamma_rate, 0b00);
+        assert_eq!(report.epsilon_rate, 0b11);
+        assert_eq!(report.oxygen_generator_rating, Some(0b01));
+        assert_eq!(report.co2_scrubber_rating, Some(0b10));
+        assert_eq!(
+            find_unique_number::<2, true>(TIED, TieBreak::PreferZero),
+            Ok(Some(0b01))
+        );
+        assert_eq!(
+            find_unique_number::<2, false>(TIED, TieBreak::PreferZero),
+            Ok(Some(0b10))
+        );
+    }
+
+    #[test]
+    fn test_tie_break_reject() {
+        assert_eq!(
+            DiagnosticReport::analyze(TIED, 2, TieBreak::Reject),
+            Err(Tie { bit: 1 })
+        );
+        assert_eq!(
+            power_consumption::<2>(TIED, TieBreak::Reject),
+            Err(Tie { bit: 1 })
+        );
+        assert_eq!(
+            find_unique_number::<2, true>(TIED, TieBreak::Reject),
+            Err(Tie { bit: 1 })
+        );
+        // ties after the first filtering step are rejected as well
+        let numbers = &[0b110, 0b101, 0b000];
+        assert_eq!(
+            find_unique_number::<3, true>(numbers, TieBreak::Reject),
+            Err(Tie { bit: 1 })
+        );
+        assert_eq!(
+            find_unique_number::<3, false>(numbers, TieBreak::Reject),
+            Ok(Some(0b000))
+        );
+        // the example is free of ties for part 1 only
+        assert_eq!(power_consumption::<5>(EXAMPLE, TieBreak::Reject), Ok(198));
+        assert!(DiagnosticReport::analyze(EXAMPLE, 5, TieBreak::Reject).is_err());
+    }
+}
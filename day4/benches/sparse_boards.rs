@@ -0,0 +1,43 @@
+use common::rng::Rng;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use day4::*;
+
+const SIZE: usize = 100;
+const RANGE: usize = 2 * SIZE * SIZE;
+
+fn shuffled(rng: &mut Rng, n: usize) -> Vec<usize> {
+    let mut numbers: Vec<_> = (0..n).collect();
+    rng.shuffle(&mut numbers);
+    numbers
+}
+
+fn generate_board(rng: &mut Rng) -> SparseBoard {
+    let lines: Vec<BoardLine> = shuffled(rng, RANGE)[..SIZE * SIZE]
+        .chunks(SIZE)
+        .map(|numbers| {
+            let numbers: Vec<_> = numbers.iter().map(usize::to_string).collect();
+            numbers.join(" ").parse().unwrap()
+        })
+        .collect();
+    SparseBoard::from_lines(1, &lines).unwrap()
+}
+
+fn sparse_boards(c: &mut Criterion) {
+    let mut rng = Rng::new(0x9e3779b97f4a7c15);
+    let boards: Vec<_> = (0..100).map(|_| generate_board(&mut rng)).collect();
+    let random_numbers = &shuffled(&mut rng, RANGE)[..10_000];
+
+    let mut group = c.benchmark_group("100 sparse 100x100 boards");
+    group.sample_size(10);
+    group.bench_function("last win", |b| {
+        b.iter_batched(
+            || boards.clone(),
+            |boards| play_until_last_win(boards, random_numbers),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, sparse_boards);
+criterion_main!(benches);
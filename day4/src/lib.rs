@@ -0,0 +1,1008 @@
+use common::rng::Rng;
+use common::{input, Answer, FromInput, Solution};
+use itertools::Itertools;
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+use thiserror::Error;
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = Game;
+
+    fn parse(input: &str) -> Self::Input {
+        input::parse(input)
+    }
+
+    fn part1(game: &Self::Input) -> Answer {
+        game.first_winner_score()
+            .map_or_else(|| "No winner!".into(), Answer::from)
+    }
+
+    fn part2(game: &Self::Input) -> Answer {
+        game.last_winner_score()
+            .map_or_else(|| "No winner!".into(), Answer::from)
+    }
+}
+
+// Boards, lines, columns and draws are counted from 1, like in the input.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    #[error("board {board} is empty")]
+    Empty { board: usize },
+    #[error("board {board} has {actual} lines, expected {expected}")]
+    WrongLineCount {
+        board: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("line {line} of board {board} has {actual} numbers, expected {expected}")]
+    WrongLineLength {
+        board: usize,
+        line: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("board {board} contains {number} at line {}, column {} and again at line {}, column {}", .first.0, .first.1, .second.0, .second.1)]
+    DuplicateNumber {
+        board: usize,
+        number: usize,
+        first: (usize, usize),
+        second: (usize, usize),
+    },
+    #[error("drawn number {number} (draw #{draw}) is on no board")]
+    UnreferencedNumber { number: usize, draw: usize },
+}
+
+// Structural errors of a single board, or all of its duplicate numbers.
+pub fn board_errors(board: usize, lines: &[BoardLine]) -> Vec<BoardError> {
+    let len = match lines.first() {
+        Some(line) if !line.is_empty() => line.len(),
+        _ => return vec![BoardError::Empty { board }],
+    };
+
+    let mut errors = Vec::new();
+    let mut positions = HashMap::with_capacity(lines.len() * len);
+    for (i, line) in lines.iter().enumerate() {
+        if line.len() != len {
+            return vec![BoardError::WrongLineLength {
+                board,
+                line: i + 1,
+                expected: len,
+                actual: line.len(),
+            }];
+        }
+        for (j, &number) in line.as_slice().iter().enumerate() {
+            let position = (i + 1, j + 1);
+            if let Some(&first) = positions.get(&number) {
+                errors.push(BoardError::DuplicateNumber {
+                    board,
+                    number,
+                    first,
+                    second: position,
+                });
+            } else {
+                positions.insert(number, position);
+            }
+        }
+    }
+    errors
+}
+
+// All problems of the given boards, followed by the drawn numbers which are on none of them.
+pub fn validate_boards(boards: &[&[BoardLine]], random_numbers: &[usize]) -> Vec<BoardError> {
+    let mut errors: Vec<_> = boards
+        .iter()
+        .enumerate()
+        .flat_map(|(i, lines)| board_errors(i + 1, lines))
+        .collect();
+
+    let referenced: HashSet<usize> = boards
+        .iter()
+        .flat_map(|lines| lines.iter())
+        .flat_map(|line| line.as_slice().iter().copied())
+        .collect();
+    errors.extend(
+        random_numbers
+            .iter()
+            .enumerate()
+            .filter(|(_, number)| !referenced.contains(number))
+            .map(|(i, &number)| BoardError::UnreferencedNumber {
+                number,
+                draw: i + 1,
+            }),
+    );
+    errors
+}
+
+pub trait BingoBoard {
+    fn mark(&mut self, number: usize);
+    fn won(&self) -> bool;
+    fn sum_of_unmarked_numbers(&self) -> usize;
+    // Unmarked numbers of a row or column with the fewest of them, sorted.
+    fn closest_line(&self) -> Vec<usize>;
+}
+
+// Ties are broken by the numbers themselves, independent of the board layout.
+pub fn shortest_line(lines: impl Iterator<Item = Vec<usize>>) -> Vec<usize> {
+    lines
+        .map(|mut line| {
+            line.sort_unstable();
+            line
+        })
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+        .unwrap_or_default()
+}
+
+// Serialized as the grid of numbers, marked numbers are null.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "BoardRepr", into = "BoardRepr")
+)]
+pub struct Board<const ROWS: usize, const COLS: usize> {
+    numbers: [[Option<usize>; ROWS]; COLS],
+    // The following is just a small optimization for won()
+    marks_per_row: [usize; ROWS],
+    marks_per_col: [usize; COLS],
+}
+
+impl<const ROWS: usize, const COLS: usize> Board<ROWS, COLS> {
+    fn new(numbers: [[Option<usize>; ROWS]; COLS]) -> Self {
+        Self {
+            numbers,
+            marks_per_row: [0; ROWS],
+            marks_per_col: [0; COLS],
+        }
+    }
+
+    // `board` is only used for error reporting.
+    pub fn from_lines(board: usize, lines: &[BoardLine]) -> Result<Self, BoardError> {
+        let mut numbers = [[None; ROWS]; COLS];
+
+        if lines.len() != COLS {
+            return Err(BoardError::WrongLineCount {
+                board,
+                expected: COLS,
+                actual: lines.len(),
+            });
+        }
+        if let Some(line) = lines.iter().position(|line| line.len() != ROWS) {
+            return Err(BoardError::WrongLineLength {
+                board,
+                line: line + 1,
+                expected: ROWS,
+                actual: lines[line].len(),
+            });
+        }
+        if let Some(error) = board_errors(board, lines).into_iter().next() {
+            return Err(error);
+        }
+
+        for (col, line) in lines.iter().enumerate() {
+            for (row, &number) in line.as_slice().iter().enumerate() {
+                numbers[col][row] = Some(number);
+            }
+        }
+
+        Ok(Self::new(numbers))
+    }
+
+    fn any_row_done(&self) -> bool {
+        self.marks_per_row.contains(&ROWS)
+    }
+
+    fn any_col_done(&self) -> bool {
+        self.marks_per_col.contains(&COLS)
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> BingoBoard for Board<ROWS, COLS> {
+    fn mark(&mut self, number: usize) {
+        (0..COLS).cartesian_product(0..ROWS).for_each(|(col, row)| {
+            if self.numbers[col][row] == Some(number) {
+                self.numbers[col][row] = None;
+                self.marks_per_row[row] += 1;
+                self.marks_per_col[col] += 1;
+            }
+        });
+    }
+
+    fn won(&self) -> bool {
+        self.any_row_done() || self.any_col_done()
+    }
+
+    fn sum_of_unmarked_numbers(&self) -> usize {
+        self.numbers
+            .map(|row| row.iter().filter_map(|&n| n).sum())
+            .iter()
+            .sum()
+    }
+
+    fn closest_line(&self) -> Vec<usize> {
+        let rows = (0..ROWS).map(|row| {
+            (0..COLS)
+                .filter_map(|col| self.numbers[col][row])
+                .collect::<Vec<_>>()
+        });
+        let cols = (0..COLS).map(|col| self.numbers[col].iter().filter_map(|&n| n).collect());
+        shortest_line(rows.chain(cols))
+    }
+}
+
+// Board of arbitrary size, which only keeps track of the positions of its numbers and the marks
+// per row/column. Marking is a single lookup instead of a scan over the whole board.
+#[derive(Clone)]
+pub struct SparseBoard {
+    positions: HashMap<usize, (usize, usize)>,
+    rows: usize,
+    cols: usize,
+    marks_per_row: Vec<usize>,
+    marks_per_col: Vec<usize>,
+    unmarked_sum: usize,
+    won: bool,
+}
+
+impl SparseBoard {
+    // `board` is only used for error reporting.
+    pub fn from_lines(board: usize, lines: &[BoardLine]) -> Result<Self, BoardError> {
+        if let Some(error) = board_errors(board, lines).into_iter().next() {
+            return Err(error);
+        }
+
+        let rows = lines.len();
+        let cols = lines[0].len();
+        let mut positions = HashMap::with_capacity(rows * cols);
+        for (row, line) in lines.iter().enumerate() {
+            for (col, &number) in line.as_slice().iter().enumerate() {
+                positions.insert(number, (row, col));
+            }
+        }
+
+        Ok(Self {
+            unmarked_sum: positions.keys().sum(),
+            positions,
+            rows,
+            cols,
+            marks_per_row: vec![0; rows],
+            marks_per_col: vec![0; cols],
+            won: false,
+        })
+    }
+}
+
+impl BingoBoard for SparseBoard {
+    fn mark(&mut self, number: usize) {
+        if let Some((row, col)) = self.positions.remove(&number) {
+            self.unmarked_sum -= number;
+            self.marks_per_row[row] += 1;
+            self.marks_per_col[col] += 1;
+            self.won |=
+                self.marks_per_row[row] == self.cols || self.marks_per_col[col] == self.rows;
+        }
+    }
+
+    fn won(&self) -> bool {
+        self.won
+    }
+
+    fn sum_of_unmarked_numbers(&self) -> usize {
+        self.unmarked_sum
+    }
+
+    fn closest_line(&self) -> Vec<usize> {
+        let mut rows = vec![Vec::new(); self.rows];
+        let mut cols = vec![Vec::new(); self.cols];
+        for (&number, &(row, col)) in &self.positions {
+            rows[row].push(number);
+            cols[col].push(number);
+        }
+        shortest_line(rows.into_iter().chain(cols))
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub struct BoardRepr {
+    numbers: Vec<Vec<Option<usize>>>,
+}
+
+#[cfg(feature = "serde")]
+impl<const ROWS: usize, const COLS: usize> From<Board<ROWS, COLS>> for BoardRepr {
+    fn from(board: Board<ROWS, COLS>) -> Self {
+        Self {
+            numbers: board.numbers.iter().map(|col| col.to_vec()).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const ROWS: usize, const COLS: usize> TryFrom<BoardRepr> for Board<ROWS, COLS> {
+    type Error = &'static str;
+
+    fn try_from(repr: BoardRepr) -> Result<Self, Self::Error> {
+        if repr.numbers.len() != COLS {
+            return Err("Wrong number of colums");
+        }
+
+        let mut board = Self::new([[None; ROWS]; COLS]);
+        for (col, line) in repr.numbers.iter().enumerate() {
+            if line.len() != ROWS {
+                return Err("Wrong number of rows");
+            }
+            for (row, &number) in line.iter().enumerate() {
+                board.numbers[col][row] = number;
+                if number.is_none() {
+                    board.marks_per_row[row] += 1;
+                    board.marks_per_col[col] += 1;
+                }
+            }
+        }
+        Ok(board)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardLine {
+    numbers: Vec<usize>,
+}
+
+impl BoardLine {
+    pub fn as_slice(&self) -> &[usize] {
+        &self.numbers[..]
+    }
+
+    pub fn len(&self) -> usize {
+        self.numbers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.numbers.is_empty()
+    }
+}
+
+impl FromStr for BoardLine {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<BoardLine, Self::Err> {
+        let numbers = input
+            .split_whitespace()
+            .filter_map(|n| n.parse::<usize>().ok())
+            .collect();
+        Ok(BoardLine { numbers })
+    }
+}
+
+pub fn play_until_first_win<B: BingoBoard>(
+    mut boards: Vec<B>,
+    random_numbers: &[usize],
+) -> Option<usize> {
+    for &number in random_numbers {
+        boards.iter_mut().for_each(|board| board.mark(number));
+        if let Some(winner) = boards.iter().find(|board| board.won()) {
+            let sum = winner.sum_of_unmarked_numbers();
+            let final_score = sum * number;
+            return Some(final_score);
+        }
+    }
+    None
+}
+
+pub fn play_until_last_win<B: BingoBoard>(
+    mut boards: Vec<B>,
+    random_numbers: &[usize],
+) -> Option<usize> {
+    for &number in random_numbers {
+        boards.iter_mut().for_each(|board| board.mark(number));
+        if boards.len() == 1 && boards[0].won() {
+            let sum = boards[0].sum_of_unmarked_numbers();
+            let final_score = sum * number;
+            return Some(final_score);
+        }
+        boards.retain(|board| !board.won());
+    }
+    None
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardOutcome {
+    Won {
+        // index into the drawn numbers
+        draw_index: usize,
+        score: usize,
+    },
+    Lost {
+        // draws which would have completed the closest row or column
+        missing: Vec<usize>,
+    },
+}
+
+// Draw index and score of every board which wins, in the order of the boards. Stops drawing
+// once all boards have won.
+fn play_all<B: BingoBoard>(
+    boards: &mut [B],
+    random_numbers: &[usize],
+) -> Vec<Option<(usize, usize)>> {
+    let mut wins = vec![None; boards.len()];
+    let mut remaining = boards.len();
+    for (draw_index, &number) in random_numbers.iter().enumerate() {
+        if remaining == 0 {
+            break;
+        }
+        for (board, win) in boards.iter_mut().zip(wins.iter_mut()) {
+            if win.is_some() {
+                continue;
+            }
+            board.mark(number);
+            if board.won() {
+                *win = Some((draw_index, board.sum_of_unmarked_numbers() * number));
+                remaining -= 1;
+            }
+        }
+    }
+    wins
+}
+
+// Plays all numbers and reports the outcome of every board, in the order of the boards.
+pub fn analyze_game<B: BingoBoard>(
+    mut boards: Vec<B>,
+    random_numbers: &[usize],
+) -> Vec<BoardOutcome> {
+    let wins = play_all(&mut boards, random_numbers);
+    boards
+        .iter()
+        .zip(wins)
+        .map(|(board, win)| match win {
+            Some((draw_index, score)) => BoardOutcome::Won { draw_index, score },
+            None => BoardOutcome::Lost {
+                missing: board.closest_line(),
+            },
+        })
+        .collect()
+}
+
+// How a board fared over all games of a tournament.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoardStats {
+    pub games: usize,
+    pub wins: usize,
+    // games in which the board won before all others, ties count for every board involved
+    pub first_wins: usize,
+    // games in which every board won and this one was the last, ties as above
+    pub last_wins: usize,
+    pub best_score: Option<usize>,
+    // sum of the draw indices of all wins
+    draws_to_win: usize,
+}
+
+impl BoardStats {
+    // Average draw (counted from 1) at which the board won, None if it never did.
+    pub fn average_winning_draw(&self) -> Option<f64> {
+        (self.wins > 0).then(|| (self.draws_to_win + self.wins) as f64 / self.wins as f64)
+    }
+
+    fn record(&mut self, win: Option<(usize, usize)>, first: bool, last: bool) {
+        self.games += 1;
+        if let Some((draw_index, score)) = win {
+            self.wins += 1;
+            self.first_wins += first as usize;
+            self.last_wins += last as usize;
+            self.best_score = self.best_score.max(Some(score));
+            self.draws_to_win += draw_index;
+        }
+    }
+
+    fn merge(mut self, other: &BoardStats) -> Self {
+        self.games += other.games;
+        self.wins += other.wins;
+        self.first_wins += other.first_wins;
+        self.last_wins += other.last_wins;
+        self.best_score = self.best_score.max(other.best_score);
+        self.draws_to_win += other.draws_to_win;
+        self
+    }
+}
+
+// Plays the same boards once per sequence of drawn numbers, in parallel, and reports the
+// statistics of every board, in the order of the boards.
+pub fn play_tournament<B, S>(boards: &[B], sequences: S) -> Vec<BoardStats>
+where
+    B: BingoBoard + Clone + Send + Sync,
+    S: IntoParallelIterator,
+    S::Item: AsRef<[usize]>,
+{
+    let empty = || vec![BoardStats::default(); boards.len()];
+    sequences
+        .into_par_iter()
+        .fold(empty, |mut stats, random_numbers| {
+            let wins = play_all(&mut boards.to_vec(), random_numbers.as_ref());
+            let draw = |win: &Option<(usize, usize)>| win.map(|(draw_index, _)| draw_index);
+            let first = wins.iter().filter_map(draw).min();
+            // there is only a last winner if every board won
+            let last = wins.iter().map(draw).collect::<Option<Vec<_>>>();
+            let last = last.and_then(|draws| draws.into_iter().max());
+            for (stats, win) in stats.iter_mut().zip(&wins) {
+                stats.record(*win, draw(win) == first, draw(win) == last);
+            }
+            stats
+        })
+        .reduce(empty, |a, b| {
+            a.into_iter().zip(&b).map(|(a, b)| a.merge(b)).collect()
+        })
+}
+
+// The drawn numbers in a random order, the same seed always gives the same order.
+pub fn shuffled_draws(random_numbers: &[usize], seed: u64) -> Vec<usize> {
+    let mut rng = Rng::new(seed);
+    let mut numbers = random_numbers.to_vec();
+    rng.shuffle(&mut numbers);
+    numbers
+}
+
+pub const GRID_SIZE: usize = 5;
+
+// The drawn numbers and the lines of every board, in the order of the input. Boards are
+// separated by blank lines.
+pub fn parse_game(input: &str) -> (Vec<usize>, Vec<Vec<BoardLine>>) {
+    let (random_numbers, boards) = input::header_and_body(input).unwrap_or((input, ""));
+    let random_numbers = random_numbers
+        .trim()
+        .split(',')
+        .map(|n| n.parse::<usize>().unwrap())
+        .collect();
+    let board_lines = input::blocks(boards)
+        .into_iter()
+        .map(input::parse)
+        .collect();
+    (random_numbers, board_lines)
+}
+
+// Boards of the usual size are stored densely, all others sparsely.
+#[derive(Clone)]
+pub enum Boards {
+    Grid(Vec<Board<GRID_SIZE, GRID_SIZE>>),
+    Sparse(Vec<SparseBoard>),
+}
+
+impl Boards {
+    pub fn from_lines(board_lines: &[Vec<BoardLine>]) -> Result<Boards, BoardError> {
+        let enumerated = board_lines.iter().enumerate();
+        if board_lines.first().map_or(0, Vec::len) == GRID_SIZE {
+            enumerated
+                .map(|(i, lines)| Board::from_lines(i + 1, lines))
+                .collect::<Result<_, _>>()
+                .map(Boards::Grid)
+        } else {
+            enumerated
+                .map(|(i, lines)| SparseBoard::from_lines(i + 1, lines))
+                .collect::<Result<_, _>>()
+                .map(Boards::Sparse)
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Game {
+    pub random_numbers: Vec<usize>,
+    pub boards: Boards,
+}
+
+impl FromInput for Game {
+    type Err = BoardError;
+
+    fn from_input(input: &str) -> Result<Self, Self::Err> {
+        let (random_numbers, board_lines) = parse_game(input);
+        Ok(Game {
+            random_numbers,
+            boards: Boards::from_lines(&board_lines)?,
+        })
+    }
+}
+
+impl Game {
+    pub fn first_winner_score(&self) -> Option<usize> {
+        match &self.boards {
+            Boards::Grid(boards) => play_until_first_win(boards.clone(), &self.random_numbers),
+            Boards::Sparse(boards) => play_until_first_win(boards.clone(), &self.random_numbers),
+        }
+    }
+
+    pub fn last_winner_score(&self) -> Option<usize> {
+        match &self.boards {
+            Boards::Grid(boards) => play_until_last_win(boards.clone(), &self.random_numbers),
+            Boards::Sparse(boards) => play_until_last_win(boards.clone(), &self.random_numbers),
+        }
+    }
+
+    // Plays `games` games, each drawing the numbers of this game in a different seeded order.
+    pub fn tournament(&self, games: usize, seed: u64) -> Vec<BoardStats> {
+        let sequences = (0..games as u64)
+            .into_par_iter()
+            .map(|game| shuffled_draws(&self.random_numbers, seed.wrapping_add(game)));
+        match &self.boards {
+            Boards::Grid(boards) => play_tournament(boards, sequences),
+            Boards::Sparse(boards) => play_tournament(boards, sequences),
+        }
+    }
+
+    pub fn analyze(&self) -> Vec<BoardOutcome> {
+        match &self.boards {
+            Boards::Grid(boards) => analyze_game(boards.clone(), &self.random_numbers),
+            Boards::Sparse(boards) => analyze_game(boards.clone(), &self.random_numbers),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solution_example() {
+        assert_eq!(
+            common::solve::<Day4>(include_str!("../example.txt")),
+            (Answer::from(4512usize), Answer::from(1924usize))
+        );
+    }
+
+    fn shuffled(rng: &mut Rng, n: usize) -> Vec<usize> {
+        let mut numbers: Vec<_> = (0..n).collect();
+        rng.shuffle(&mut numbers);
+        numbers
+    }
+
+    fn generate_board_lines(rng: &mut Rng, size: usize, range: usize) -> Vec<BoardLine> {
+        shuffled(rng, range)[..size * size]
+            .chunks(size)
+            .map(|numbers| BoardLine {
+                numbers: numbers.to_vec(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sparse_board_matches_dense_board() {
+        const SIZE: usize = 7;
+        let mut rng = Rng::new(0x2545f4914f6cdd1d);
+        for _ in 0..20 {
+            let board_lines: Vec<_> = (0..10)
+                .map(|_| generate_board_lines(&mut rng, SIZE, 100))
+                .collect();
+            let dense: Vec<_> = board_lines
+                .iter()
+                .map(|lines| Board::<SIZE, SIZE>::from_lines(1, lines).unwrap())
+                .collect();
+            let sparse: Vec<_> = board_lines
+                .iter()
+                .map(|lines| SparseBoard::from_lines(1, lines).unwrap())
+                .collect();
+            let random_numbers = shuffled(&mut rng, 100);
+
+            assert_eq!(
+                play_until_first_win(dense.clone(), &random_numbers),
+                play_until_first_win(sparse.clone(), &random_numbers)
+            );
+            assert_eq!(
+                play_until_last_win(dense, &random_numbers),
+                play_until_last_win(sparse, &random_numbers)
+            );
+        }
+    }
+
+    const EXAMPLE_NUMBERS: &[usize] = &[
+        7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8, 19, 3,
+        26, 1,
+    ];
+
+    const EXAMPLE_BOARDS: &[&[&str]] = &[
+        &[
+            "22 13 17 11  0",
+            " 8  2 23  4 24",
+            "21  9 14 16  7",
+            " 6 10  3 18  5",
+            " 1 12 20 15 19",
+        ],
+        &[
+            " 3 15  0  2 22",
+            " 9 18 13 17  5",
+            "19  8  7 25 23",
+            "20 11 10 24  4",
+            "14 21 16 12  6",
+        ],
+        &[
+            "14 21 17 24  4",
+            "10 16 15  9 19",
+            "18  8 23 26 20",
+            "22 11 13  6  5",
+            " 2  0 12  3  7",
+        ],
+    ];
+
+    fn example_board_lines() -> Vec<Vec<BoardLine>> {
+        EXAMPLE_BOARDS
+            .iter()
+            .map(|board| board.iter().map(|line| line.parse().unwrap()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_analyze_game_example() {
+        let boards: Vec<_> = example_board_lines()
+            .iter()
+            .map(|lines| Board::<5, 5>::from_lines(1, lines).unwrap())
+            .collect();
+
+        assert_eq!(
+            analyze_game(boards, EXAMPLE_NUMBERS),
+            vec![
+                BoardOutcome::Won {
+                    draw_index: 13,
+                    score: 2192
+                },
+                BoardOutcome::Won {
+                    draw_index: 14,
+                    score: 1924
+                },
+                BoardOutcome::Won {
+                    draw_index: 11,
+                    score: 4512
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_game_losing_boards() {
+        // only the first 12 numbers are drawn, so only the third board wins
+        let numbers = &EXAMPLE_NUMBERS[..12];
+        let dense: Vec<_> = example_board_lines()
+            .iter()
+            .map(|lines| Board::<5, 5>::from_lines(1, lines).unwrap())
+            .collect();
+        let sparse: Vec<_> = example_board_lines()
+            .iter()
+            .map(|lines| SparseBoard::from_lines(1, lines).unwrap())
+            .collect();
+
+        let outcomes = analyze_game(dense, numbers);
+        assert_eq!(
+            outcomes,
+            vec![
+                BoardOutcome::Lost { missing: vec![8] },
+                BoardOutcome::Lost {
+                    missing: vec![6, 22]
+                },
+                BoardOutcome::Won {
+                    draw_index: 11,
+                    score: 4512
+                },
+            ]
+        );
+        assert_eq!(analyze_game(sparse, numbers), outcomes);
+    }
+
+    #[test]
+    fn test_tournament_with_fixed_draws() {
+        let boards: Vec<_> = example_board_lines()
+            .iter()
+            .map(|lines| Board::<5, 5>::from_lines(1, lines).unwrap())
+            .collect();
+        let stats = play_tournament(&boards, vec![EXAMPLE_NUMBERS; 4]);
+
+        assert!(stats
+            .iter()
+            .all(|stats| stats.games == 4 && stats.wins == 4));
+        assert_eq!(
+            stats
+                .iter()
+                .map(|stats| stats.first_wins)
+                .collect::<Vec<_>>(),
+            vec![0, 0, 4]
+        );
+        assert_eq!(
+            stats
+                .iter()
+                .map(|stats| stats.last_wins)
+                .collect::<Vec<_>>(),
+            vec![0, 4, 0]
+        );
+        assert_eq!(stats[2].best_score, Some(4512));
+        assert_eq!(stats[2].average_winning_draw(), Some(12.0));
+
+        // without the last draws the second board never wins, so there is no last winner
+        let stats = play_tournament(&boards, [&EXAMPLE_NUMBERS[..14]]);
+        assert_eq!(stats[1].wins, 0);
+        assert_eq!(stats[1].best_score, None);
+        assert_eq!(stats[1].average_winning_draw(), None);
+        assert!(stats.iter().all(|stats| stats.last_wins == 0));
+    }
+
+    #[test]
+    fn test_tournament_over_all_permutations() {
+        // two boards with disjoint numbers are equally likely to win first or last
+        let lines: Vec<BoardLine> = ["1 2", "3 4", "5 6", "7 8"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let boards = vec![
+            SparseBoard::from_lines(1, &lines[..2]).unwrap(),
+            SparseBoard::from_lines(2, &lines[2..]).unwrap(),
+        ];
+        let permutations: Vec<Vec<usize>> = (1..=8).permutations(8).collect();
+        let stats = play_tournament(&boards, &permutations);
+
+        for stats in &stats {
+            assert_eq!(stats.games, 40320);
+            assert_eq!(stats.wins, 40320);
+            assert_eq!(stats.first_wins, 20160);
+            assert_eq!(stats.last_wins, 20160);
+        }
+        assert_eq!(
+            stats[0].average_winning_draw(),
+            stats[1].average_winning_draw()
+        );
+        // 7 completing the column 5 7, with 6 and 8 left
+        assert_eq!(stats[1].best_score, Some(98));
+    }
+
+    #[test]
+    fn test_seeded_tournament() {
+        let game = Day4::parse(include_str!("../example.txt"));
+        let stats = game.tournament(200, 7);
+        assert_eq!(game.tournament(200, 7), stats);
+        assert!(stats.iter().all(|stats| stats.games == 200));
+
+        // the same as playing the shuffled draws one after another
+        let draws: Vec<_> = (0..200)
+            .map(|game_index| shuffled_draws(&game.random_numbers, 7 + game_index))
+            .collect();
+        let boards: Vec<_> = example_board_lines()
+            .iter()
+            .map(|lines| SparseBoard::from_lines(1, lines).unwrap())
+            .collect();
+        assert_eq!(play_tournament(&boards, &draws), stats);
+
+        let mut sorted = draws[0].clone();
+        sorted.sort_unstable();
+        let mut expected = game.random_numbers.clone();
+        expected.sort_unstable();
+        assert_eq!(sorted, expected);
+        assert_ne!(draws[0], draws[1]);
+    }
+
+    #[test]
+    fn test_sparse_board_rejects_invalid_boards() {
+        let lines: Vec<BoardLine> = ["1 2", "3"].iter().map(|s| s.parse().unwrap()).collect();
+        assert_eq!(
+            SparseBoard::from_lines(2, &lines).err(),
+            Some(BoardError::WrongLineLength {
+                board: 2,
+                line: 2,
+                expected: 2,
+                actual: 1
+            })
+        );
+        let lines: Vec<BoardLine> = ["1 2", "3 1"].iter().map(|s| s.parse().unwrap()).collect();
+        assert_eq!(
+            SparseBoard::from_lines(1, &lines).err(),
+            Some(BoardError::DuplicateNumber {
+                board: 1,
+                number: 1,
+                first: (1, 1),
+                second: (2, 2)
+            })
+        );
+        assert_eq!(
+            SparseBoard::from_lines(3, &[]).err(),
+            Some(BoardError::Empty { board: 3 })
+        );
+    }
+
+    #[test]
+    fn test_dense_board_rejects_invalid_boards() {
+        let parse = |lines: &[&str]| -> Vec<BoardLine> {
+            lines.iter().map(|s| s.parse().unwrap()).collect()
+        };
+        assert_eq!(
+            Board::<2, 2>::from_lines(4, &parse(&["1 2"])).err(),
+            Some(BoardError::WrongLineCount {
+                board: 4,
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            Board::<2, 2>::from_lines(1, &parse(&["1 2", "3 4 5"])).err(),
+            Some(BoardError::WrongLineLength {
+                board: 1,
+                line: 2,
+                expected: 2,
+                actual: 3
+            })
+        );
+        assert!(matches!(
+            Board::<2, 2>::from_lines(1, &parse(&["1 2", "2 4"])),
+            Err(BoardError::DuplicateNumber { number: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_boards() {
+        let boards = example_board_lines();
+        let boards: Vec<&[BoardLine]> = boards.iter().map(|lines| &lines[..]).collect();
+        assert!(validate_boards(&boards, EXAMPLE_NUMBERS).is_empty());
+
+        let lines: Vec<BoardLine> = ["1 2 1", "4 2 6"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let errors = validate_boards(&[boards[0], &lines], &[7, 1, 99, 4]);
+        assert_eq!(
+            errors,
+            vec![
+                BoardError::DuplicateNumber {
+                    board: 2,
+                    number: 1,
+                    first: (1, 1),
+                    second: (1, 3)
+                },
+                BoardError::DuplicateNumber {
+                    board: 2,
+                    number: 2,
+                    first: (1, 2),
+                    second: (2, 2)
+                },
+                BoardError::UnreferencedNumber {
+                    number: 99,
+                    draw: 3
+                },
+            ]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "board 2 contains 1 at line 1, column 1 and again at line 1, column 3"
+        );
+        assert_eq!(
+            errors[2].to_string(),
+            "drawn number 99 (draw #3) is on no board"
+        );
+    }
+
+    #[test]
+    fn test_board_mark_and_win() {
+        let lines: Vec<BoardLine> = ["1 2", "3 4"].iter().map(|s| s.parse().unwrap()).collect();
+        let mut board = Board::<2, 2>::from_lines(1, &lines).unwrap();
+        board.mark(1);
+        assert!(!board.won());
+        board.mark(4);
+        assert!(!board.won());
+        board.mark(3);
+        assert!(board.won());
+        assert_eq!(board.sum_of_unmarked_numbers(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_board_serde_round_trip() {
+        let lines: Vec<BoardLine> = ["1 2", "3 4"].iter().map(|s| s.parse().unwrap()).collect();
+        let mut board = Board::<2, 2>::from_lines(1, &lines).unwrap();
+        board.mark(1);
+
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(json, r#"{"numbers":[[null,2],[3,4]]}"#);
+
+        let mut board: Board<2, 2> = serde_json::from_str(&json).unwrap();
+        assert!(!board.won());
+        board.mark(2);
+        assert!(board.won());
+        assert_eq!(board.sum_of_unmarked_numbers(), 7);
+
+        assert!(serde_json::from_str::<Board<3, 2>>(&json).is_err());
+    }
+}
@@ -0,0 +1,872 @@
+use common::vec3::{Vec3, WideVec3};
+use common::{Answer, Solution};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt,
+    io::{self, Write},
+    iter::FromIterator,
+    ops,
+    str::FromStr,
+};
+use thiserror::Error;
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = Vec<LineSegment>;
+
+    fn parse(input: &str) -> Self::Input {
+        input.lines().filter_map(|s| s.parse().ok()).collect()
+    }
+
+    fn part1(lines: &Self::Input) -> Answer {
+        let segments: Vec<_> = lines.iter().map(|&line| (line, 1)).collect();
+        diagram(&segments, false).positions_with_at_least(2).into()
+    }
+
+    fn part2(lines: &Self::Input) -> Answer {
+        let segments: Vec<_> = lines.iter().map(|&line| (line, 1)).collect();
+        diagram(&segments, true).positions_with_at_least(2).into()
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ParseError {
+    #[error("invalid point format, expected 'x,y' or 'x,y,z'")]
+    InvalidPointFormat,
+    #[error("invalid line segment format, expected 'x1,y1 -> x2,y2' or 'x1,y1,z1 -> x2,y2,z2'")]
+    InvalidLineSegmentFormat,
+    #[error("invalid number")]
+    InvalidNumber(#[from] std::num::ParseIntError),
+}
+
+// Positions of 2D vents lie in the z = 0 plane.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    x: isize,
+    y: isize,
+    z: isize,
+}
+
+impl Position {
+    pub fn new(x: isize, y: isize, z: isize) -> Position {
+        Self { x, y, z }
+    }
+
+    fn to_vec(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+impl ops::Add<Vec3> for Position {
+    type Output = Position;
+
+    fn add(self, v: Vec3) -> Self::Output {
+        Self::new(self.x + v.x, self.y + v.y, self.z + v.z)
+    }
+}
+
+impl ops::Sub for Position {
+    type Output = Vec3;
+
+    fn sub(self, other: Position) -> Self::Output {
+        self.to_vec() - other.to_vec()
+    }
+}
+
+// Positions in the z = 0 plane are written as 2D positions.
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.z == 0 {
+            write!(f, "{},{}", self.x, self.y)
+        } else {
+            write!(f, "{},{},{}", self.x, self.y, self.z)
+        }
+    }
+}
+
+impl FromStr for Position {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Position, Self::Err> {
+        let coordinates: Vec<&str> = s.split(',').collect();
+        let (x, y, z) = match coordinates[..] {
+            [x, y] => (x, y, "0"),
+            [x, y, z] => (x, y, z),
+            _ => return Err(ParseError::InvalidPointFormat),
+        };
+        Ok(Position::new(x.parse()?, y.parse()?, z.parse()?))
+    }
+}
+
+// Number of steps in the given direction making up the offset, which must be a multiple of it.
+fn steps_along(direction: Vec3, offset: Vec3) -> isize {
+    direction
+        .components()
+        .into_iter()
+        .zip(offset.components())
+        .find(|&(d, _)| d != 0)
+        .map_or(0, |(d, o)| o / d)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intersection {
+    Point(Position),
+    // collinear segments sharing more than one position
+    Overlap(LineSegment),
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct LineSegment {
+    start: Position,
+    end: Position,
+}
+
+impl LineSegment {
+    pub fn new(start: Position, end: Position) -> LineSegment {
+        Self { start, end }
+    }
+
+    fn delta(&self) -> Vec3 {
+        self.end - self.start
+    }
+
+    // Number of steps between consecutive lattice positions from start to end.
+    pub fn length(&self) -> usize {
+        self.delta().gcd() as usize
+    }
+
+    // Smallest integer step from start towards end, zero for a single position.
+    pub fn direction(&self) -> Vec3 {
+        self.delta().reduced()
+    }
+
+    fn position_at(&self, step: isize) -> Position {
+        self.start + self.direction() * step
+    }
+
+    // Number of direction steps from start to a position on the line through the segment.
+    fn step_of(&self, pos: Position) -> isize {
+        steps_along(self.direction(), pos - self.start)
+    }
+
+    // All lattice positions on the segment.
+    pub fn lattice_positions(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..=self.length() as isize).map(move |step| self.position_at(step))
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        let within = |a: isize, b: isize, c: isize| (a.min(b)..=a.max(b)).contains(&c);
+        self.delta().cross(pos - self.start) == WideVec3::ZERO
+            && within(self.start.x, self.end.x, pos.x)
+            && within(self.start.y, self.end.y, pos.y)
+            && within(self.start.z, self.end.z, pos.z)
+    }
+
+    // Shared lattice positions of both segments. Segments crossing in between lattice
+    // positions don't intersect.
+    pub fn intersects(&self, other: &LineSegment) -> Option<Intersection> {
+        if self.start == self.end {
+            return other
+                .contains(self.start)
+                .then_some(Intersection::Point(self.start));
+        }
+        if other.start == other.end {
+            return self
+                .contains(other.start)
+                .then_some(Intersection::Point(other.start));
+        }
+
+        let d1 = self.delta();
+        let d2 = other.delta();
+        let offset = other.start - self.start;
+        let normal = d1.cross(d2);
+
+        if normal == WideVec3::ZERO {
+            if d1.cross(offset) != WideVec3::ZERO {
+                return None; // parallel
+            }
+            // collinear, hence the other's end points are whole steps along this segment
+            let (a, b) = (self.step_of(other.start), self.step_of(other.end));
+            let first = a.min(b).max(0);
+            let last = a.max(b).min(self.length() as isize);
+            return match first.cmp(&last) {
+                Ordering::Greater => None,
+                Ordering::Equal => Some(Intersection::Point(self.position_at(first))),
+                Ordering::Less => Some(Intersection::Overlap(LineSegment::new(
+                    self.position_at(first),
+                    self.position_at(last),
+                ))),
+            };
+        }
+
+        if WideVec3::from(offset).dot(normal) != 0 {
+            return None; // skew, only possible in 3D
+        }
+
+        // self.start + t * d1 = other.start + u * d2, hence offset x d2 = t * normal and
+        // offset x d1 = u * normal, solved in any component in which the normal isn't zero
+        let k = normal.components().iter().position(|&n| n != 0).unwrap();
+        let sign = normal.components()[k].signum();
+        let denominator = sign * normal.components()[k];
+        let t_num = sign * offset.cross(d2).components()[k];
+        let u_num = sign * offset.cross(d1).components()[k];
+        if !(0..=denominator).contains(&t_num) || !(0..=denominator).contains(&u_num) {
+            return None;
+        }
+        let mut step = [0; 3];
+        for (step, d) in step.iter_mut().zip(d1.components()) {
+            let num = t_num * d as i128;
+            if num % denominator != 0 {
+                return None;
+            }
+            *step = (num / denominator) as isize;
+        }
+        Some(Intersection::Point(
+            self.start + Vec3::new(step[0], step[1], step[2]),
+        ))
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.start.x == self.end.x
+    }
+
+    // Only one coordinate changes, same as horizontal or vertical for 2D segments.
+    pub fn is_axis_aligned(&self) -> bool {
+        self.delta()
+            .components()
+            .iter()
+            .filter(|&&d| d != 0)
+            .count()
+            <= 1
+    }
+
+    pub fn positions(&self) -> LineInterpolator {
+        LineInterpolator::new_end_inclusive(self.start, self.end)
+    }
+
+    // Same segment with the smaller end point (by x, then y, then z) as start.
+    pub fn normalized(&self) -> LineSegment {
+        if self.end < self.start {
+            LineSegment::new(self.end, self.start)
+        } else {
+            *self
+        }
+    }
+
+    // The line through the segment, same for all collinear segments.
+    fn line(&self) -> Line {
+        let normalized = self.normalized();
+        match normalized.direction() {
+            Vec3::ZERO => Line::Point(self.start),
+            direction => Line::Through {
+                direction,
+                moment: direction.cross(normalized.start.to_vec()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for LineSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.start, self.end)
+    }
+}
+
+impl FromStr for LineSegment {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<LineSegment, Self::Err> {
+        let (start, end) = s
+            .split_once(" -> ")
+            .ok_or(ParseError::InvalidLineSegmentFormat)?;
+        let start = start.parse()?;
+        let end = end.parse()?;
+        Ok(LineSegment { start, end })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Line {
+    // positions p with direction x p == moment
+    Through { direction: Vec3, moment: WideVec3 },
+    // degenerate segment of a single position
+    Point(Position),
+}
+
+// Normalized segments grouped by the line they are on.
+pub fn collinear_groups(lines: &[LineSegment]) -> HashMap<Line, Vec<LineSegment>> {
+    let mut groups: HashMap<Line, Vec<LineSegment>> = HashMap::new();
+    for line in lines {
+        groups
+            .entry(line.line())
+            .or_default()
+            .push(line.normalized());
+    }
+    groups
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SegmentReport {
+    // segments which were given with the larger end point first
+    pub reversed: usize,
+    // normalized segments given more than once, with their number of occurrences
+    pub duplicates: Vec<(LineSegment, usize)>,
+    // distinct collinear segments sharing more than one position
+    pub overlaps: Vec<(LineSegment, LineSegment)>,
+}
+
+pub fn sort_key(line: &LineSegment) -> (Position, Position) {
+    (line.start, line.end)
+}
+
+pub fn check_segments(lines: &[LineSegment]) -> SegmentReport {
+    let reversed = lines
+        .iter()
+        .filter(|&line| line.normalized() != *line)
+        .count();
+
+    let mut occurrences: HashMap<LineSegment, usize> = HashMap::new();
+    for line in lines {
+        *occurrences.entry(line.normalized()).or_insert(0) += 1;
+    }
+    let mut duplicates: Vec<_> = occurrences
+        .into_iter()
+        .filter(|&(_, count)| count > 1)
+        .collect();
+    duplicates.sort_by_key(|(line, _)| sort_key(line));
+
+    let mut overlaps = Vec::new();
+    for mut group in collinear_groups(lines).into_values() {
+        group.sort_by_key(sort_key);
+        group.dedup();
+        for (i, a) in group.iter().enumerate() {
+            for b in &group[i + 1..] {
+                if let Some(Intersection::Overlap(_)) = a.intersects(b) {
+                    overlaps.push((*a, *b));
+                }
+            }
+        }
+    }
+    overlaps.sort_by_key(|(a, b)| (sort_key(a), sort_key(b)));
+
+    SegmentReport {
+        reversed,
+        duplicates,
+        overlaps,
+    }
+}
+
+// Merges collinear segments into disjoint pieces, each weighted by the number of segments
+// covering it. Every position is covered by the same total weight as before.
+pub fn merge_segments(lines: &[LineSegment]) -> Vec<(LineSegment, usize)> {
+    let mut merged = Vec::new();
+    for (line, group) in collinear_groups(lines) {
+        let direction = match line {
+            Line::Point(pos) => {
+                merged.push((LineSegment::new(pos, pos), group.len()));
+                continue;
+            }
+            Line::Through { direction, .. } => direction,
+        };
+
+        // positions along the line are origin + t * direction
+        let origin = group[0].start;
+        let step_of = |pos: Position| steps_along(direction, pos - origin);
+        let position_at = |t: isize| origin + direction * t;
+
+        let mut events: Vec<(isize, isize)> = group
+            .iter()
+            .flat_map(|line| [(step_of(line.start), 1), (step_of(line.end) + 1, -1)])
+            .collect();
+        events.sort_unstable();
+
+        let mut coverage = 0;
+        let mut prev = isize::MIN;
+        for (t, delta) in events {
+            if coverage > 0 && t > prev {
+                let piece = LineSegment::new(position_at(prev), position_at(t - 1));
+                merged.push((piece, coverage as usize));
+            }
+            coverage += delta;
+            prev = t;
+        }
+    }
+    merged.sort_by_key(|(line, _)| sort_key(line));
+    merged
+}
+
+// Steps through the lattice positions between two points, the gcd-reduced delta at a time.
+pub struct LineInterpolator {
+    curr: Position,
+    end: Position,
+    step: Vec3,
+    end_inclusive: bool,
+}
+
+impl LineInterpolator {
+    fn new_end_inclusive(start: Position, end: Position) -> LineInterpolator {
+        Self {
+            curr: start,
+            end,
+            step: (end - start).reduced(),
+            end_inclusive: true,
+        }
+    }
+}
+
+impl Iterator for LineInterpolator {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.curr == self.end {
+            if self.end_inclusive {
+                self.end_inclusive = false;
+                return Some(self.end);
+            }
+            return None;
+        }
+
+        let pos = self.curr;
+        self.curr = self.curr + self.step;
+        Some(pos)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Diagram {
+    overlaps: HashMap<Position, usize>,
+}
+
+impl Diagram {
+    pub fn positions_with_at_least(&self, n: usize) -> usize {
+        self.overlaps.values().filter(|&count| *count >= n).count()
+    }
+
+    pub fn max_overlap(&self) -> usize {
+        self.overlaps.values().copied().max().unwrap_or(0)
+    }
+
+    // Writes the overlap counts as binary PGM image, brighter means more overlaps. 3D diagrams
+    // are seen from above, showing the largest count along z.
+    pub fn write_heatmap<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut from_above: HashMap<(isize, isize), usize> = HashMap::new();
+        for (pos, &count) in &self.overlaps {
+            let max = from_above.entry((pos.x, pos.y)).or_insert(0);
+            *max = (*max).max(count);
+        }
+
+        let (min_x, max_x, min_y, max_y) = from_above.keys().fold(
+            (isize::MAX, isize::MIN, isize::MAX, isize::MIN),
+            |(min_x, max_x, min_y, max_y), &(x, y)| {
+                (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y))
+            },
+        );
+        if self.overlaps.is_empty() {
+            return writeln!(writer, "P5\n0 0\n255");
+        }
+
+        let width = (max_x - min_x + 1) as usize;
+        let height = (max_y - min_y + 1) as usize;
+        let max_overlap = self.max_overlap();
+
+        writeln!(writer, "P5\n{} {}\n255", width, height)?;
+        let mut row = vec![0u8; width];
+        for y in min_y..=max_y {
+            for (i, x) in (min_x..=max_x).enumerate() {
+                let count = from_above.get(&(x, y)).copied().unwrap_or(0);
+                row[i] = (count * 255 / max_overlap) as u8;
+            }
+            writer.write_all(&row)?;
+        }
+        Ok(())
+    }
+}
+
+// Number of positions covered by at least two segments, based on pairwise intersections
+// instead of rasterizing every segment.
+pub fn count_overlaps_analytic(lines: &[LineSegment]) -> usize {
+    let mut overlaps = HashSet::new();
+    for (i, a) in lines.iter().enumerate() {
+        for b in &lines[i + 1..] {
+            match a.intersects(b) {
+                Some(Intersection::Point(pos)) => {
+                    overlaps.insert(pos);
+                }
+                Some(Intersection::Overlap(segment)) => {
+                    overlaps.extend(segment.lattice_positions());
+                }
+                None => {}
+            }
+        }
+    }
+    overlaps.len()
+}
+
+impl FromIterator<(Position, usize)> for Diagram {
+    fn from_iter<I: IntoIterator<Item = (Position, usize)>>(iter: I) -> Self {
+        let mut overlaps = HashMap::new();
+        for (pos, weight) in iter {
+            *overlaps.entry(pos).or_insert(0) += weight;
+        }
+        Self { overlaps }
+    }
+}
+
+impl FromIterator<Position> for Diagram {
+    fn from_iter<I: IntoIterator<Item = Position>>(iter: I) -> Self {
+        let mut overlaps = HashMap::new();
+        for pos in iter {
+            *overlaps.entry(pos).or_insert(0) += 1;
+        }
+        Self { overlaps }
+    }
+}
+
+// Segments weighted by the number of segments covering them, diagonal ones only if requested.
+// Overlaps of 3D segments are counted in space.
+pub fn diagram(segments: &[(LineSegment, usize)], diagonals: bool) -> Diagram {
+    segments
+        .iter()
+        .filter(|(line, _)| diagonals || line.is_axis_aligned())
+        .flat_map(|&(line, weight)| line.positions().map(move |pos| (pos, weight)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solution_example() {
+        assert_eq!(
+            common::solve::<Day5>(include_str!("../example.txt")),
+            (Answer::from(5usize), Answer::from(12usize))
+        );
+    }
+
+    const EXAMPLE: &[&str] = &[
+        "0,9 -> 5,9",
+        "8,0 -> 0,8",
+        "9,4 -> 3,4",
+        "2,2 -> 2,1",
+        "7,0 -> 7,4",
+        "6,4 -> 2,0",
+        "0,9 -> 2,9",
+        "3,4 -> 1,4",
+        "0,0 -> 8,8",
+        "5,5 -> 8,2",
+    ];
+
+    #[test]
+    fn test_diagram_example() {
+        let lines: Vec<LineSegment> = EXAMPLE.iter().map(|s| s.parse().unwrap()).collect();
+        let diagram: Diagram = lines.iter().flat_map(|line| line.positions()).collect();
+
+        assert_eq!(diagram.positions_with_at_least(1), 39);
+        assert_eq!(diagram.positions_with_at_least(2), 12);
+        assert_eq!(diagram.positions_with_at_least(3), 2);
+        assert_eq!(diagram.positions_with_at_least(4), 0);
+        assert_eq!(diagram.max_overlap(), 3);
+
+        let mut image = Vec::new();
+        diagram.write_heatmap(&mut image).unwrap();
+        assert!(image.starts_with(b"P5\n10 10\n255\n"));
+        assert_eq!(image.len(), b"P5\n10 10\n255\n".len() + 100);
+    }
+
+    fn segment(s: &str) -> LineSegment {
+        s.parse().unwrap()
+    }
+
+    fn pos(x: isize, y: isize) -> Position {
+        Position::new(x, y, 0)
+    }
+
+    #[test]
+    fn test_length_and_direction() {
+        assert_eq!(segment("0,9 -> 5,9").length(), 5);
+        assert_eq!(segment("0,9 -> 5,9").direction(), Vec3::new(1, 0, 0));
+        assert_eq!(segment("8,0 -> 0,8").length(), 8);
+        assert_eq!(segment("8,0 -> 0,8").direction(), Vec3::new(-1, 1, 0));
+        assert_eq!(segment("0,0 -> 6,4").length(), 2);
+        assert_eq!(segment("0,0 -> 6,4").direction(), Vec3::new(3, 2, 0));
+        assert_eq!(segment("3,3 -> 3,3").length(), 0);
+        assert_eq!(segment("3,3 -> 3,3").direction(), Vec3::ZERO);
+
+        for line in EXAMPLE.iter().map(|s| segment(s)) {
+            assert!(line.positions().eq(line.lattice_positions()));
+        }
+        assert_eq!(
+            segment("0,0 -> 6,4")
+                .lattice_positions()
+                .collect::<Vec<_>>(),
+            vec![pos(0, 0), pos(3, 2), pos(6, 4)]
+        );
+    }
+
+    #[test]
+    fn test_contains() {
+        let line = segment("0,0 -> 6,4");
+        assert!(line.contains(pos(0, 0)));
+        assert!(line.contains(pos(3, 2)));
+        assert!(line.contains(pos(6, 4)));
+        assert!(!line.contains(pos(1, 1)));
+        assert!(!line.contains(pos(9, 6)));
+        assert!(!line.contains(pos(-3, -2)));
+        assert!(segment("3,3 -> 3,3").contains(pos(3, 3)));
+    }
+
+    #[test]
+    fn test_intersects() {
+        // crossing
+        assert_eq!(
+            segment("0,0 -> 8,8").intersects(&segment("8,0 -> 0,8")),
+            Some(Intersection::Point(pos(4, 4)))
+        );
+        // crossing in between lattice positions
+        assert_eq!(
+            segment("0,0 -> 1,1").intersects(&segment("1,0 -> 0,1")),
+            None
+        );
+        // touching at an end point
+        assert_eq!(
+            segment("0,9 -> 5,9").intersects(&segment("5,9 -> 5,0")),
+            Some(Intersection::Point(pos(5, 9)))
+        );
+        // disjoint and parallel
+        assert_eq!(
+            segment("0,0 -> 5,0").intersects(&segment("7,0 -> 7,5")),
+            None
+        );
+        assert_eq!(
+            segment("0,0 -> 5,0").intersects(&segment("0,1 -> 5,1")),
+            None
+        );
+        // collinear
+        assert_eq!(
+            segment("0,9 -> 5,9").intersects(&segment("2,9 -> 0,9")),
+            Some(Intersection::Overlap(segment("0,9 -> 2,9")))
+        );
+        assert_eq!(
+            segment("9,4 -> 3,4").intersects(&segment("3,4 -> 1,4")),
+            Some(Intersection::Point(pos(3, 4)))
+        );
+        assert_eq!(
+            segment("0,0 -> 2,2").intersects(&segment("3,3 -> 5,5")),
+            None
+        );
+        assert_eq!(
+            segment("0,0 -> 6,4").intersects(&segment("9,6 -> 3,2")),
+            Some(Intersection::Overlap(segment("3,2 -> 6,4")))
+        );
+        // single positions
+        assert_eq!(
+            segment("2,2 -> 2,2").intersects(&segment("0,0 -> 4,4")),
+            Some(Intersection::Point(pos(2, 2)))
+        );
+        assert_eq!(
+            segment("0,0 -> 4,4").intersects(&segment("2,3 -> 2,3")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_intersects_large_coordinates() {
+        // products of the coordinates exceed isize
+        assert_eq!(
+            segment("0,0 -> 5000000000,5000000000")
+                .intersects(&segment("0,5000000000 -> 5000000000,0")),
+            Some(Intersection::Point(pos(2500000000, 2500000000)))
+        );
+        assert_eq!(
+            segment("0,0,0 -> 4000000000,4000000000,4000000000")
+                .intersects(&segment("4000000000,0,0 -> 0,4000000000,4000000000")),
+            Some(Intersection::Point(Position::new(
+                2000000000, 2000000000, 2000000000
+            )))
+        );
+        assert_eq!(
+            segment("0,0 -> 5000000000,5000000000")
+                .intersects(&segment("1,0 -> 5000000001,5000000000")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_count_overlaps_analytic() {
+        let lines: Vec<LineSegment> = EXAMPLE.iter().map(|s| segment(s)).collect();
+        assert_eq!(count_overlaps_analytic(&lines), 12);
+
+        let straight: Vec<LineSegment> = lines
+            .into_iter()
+            .filter(|line| line.is_horizontal() || line.is_vertical())
+            .collect();
+        assert_eq!(count_overlaps_analytic(&straight), 5);
+    }
+
+    #[test]
+    fn test_non_diagonal_positions() {
+        assert_eq!(
+            segment("0,0 -> 6,4").positions().collect::<Vec<_>>(),
+            vec![pos(0, 0), pos(3, 2), pos(6, 4)]
+        );
+
+        // (3,2) is shared by three segments, (1,1) doesn't lie on the first one
+        let lines: Vec<LineSegment> = ["0,0 -> 6,4", "3,0 -> 3,5", "0,4 -> 6,0", "1,1 -> 1,0"]
+            .iter()
+            .map(|s| segment(s))
+            .collect();
+        let segments: Vec<_> = lines.iter().map(|&line| (line, 1)).collect();
+        assert_eq!(diagram(&segments, true).positions_with_at_least(2), 1);
+        assert_eq!(count_overlaps_analytic(&lines), 1);
+    }
+
+    #[test]
+    fn test_normalized() {
+        assert_eq!(segment("5,9 -> 0,9").normalized(), segment("0,9 -> 5,9"));
+        assert_eq!(segment("8,0 -> 0,8").normalized(), segment("0,8 -> 8,0"));
+        assert_eq!(segment("2,2 -> 2,1").normalized(), segment("2,1 -> 2,2"));
+        assert_eq!(segment("0,0 -> 8,8").normalized(), segment("0,0 -> 8,8"));
+        assert_eq!(segment("8,0 -> 0,8").to_string(), "8,0 -> 0,8");
+    }
+
+    #[test]
+    fn test_check_segments() {
+        let lines: Vec<LineSegment> = [
+            "0,9 -> 5,9",
+            "5,9 -> 0,9",
+            "0,9 -> 5,9",
+            "3,9 -> 8,9",
+            "6,9 -> 7,9",
+            "0,0 -> 2,2",
+            "2,2 -> 4,4",
+            "1,1 -> 1,1",
+        ]
+        .iter()
+        .map(|s| segment(s))
+        .collect();
+        assert_eq!(
+            check_segments(&lines),
+            SegmentReport {
+                reversed: 1,
+                duplicates: vec![(segment("0,9 -> 5,9"), 3)],
+                overlaps: vec![
+                    (segment("0,9 -> 5,9"), segment("3,9 -> 8,9")),
+                    (segment("3,9 -> 8,9"), segment("6,9 -> 7,9")),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_segments() {
+        let lines: Vec<LineSegment> = ["0,9 -> 5,9", "9,9 -> 3,9", "5,9 -> 0,9", "1,1 -> 1,1"]
+            .iter()
+            .map(|s| segment(s))
+            .collect();
+        assert_eq!(
+            merge_segments(&lines),
+            vec![
+                (segment("0,9 -> 2,9"), 2),
+                (segment("1,1 -> 1,1"), 1),
+                (segment("3,9 -> 5,9"), 3),
+                (segment("6,9 -> 9,9"), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merging_keeps_overlaps() {
+        let mut lines: Vec<LineSegment> = EXAMPLE.iter().map(|s| segment(s)).collect();
+        // degenerate additions: duplicates, reversed copies and collinear pieces
+        lines.extend(
+            [
+                "5,9 -> 0,9",
+                "0,9 -> 5,9",
+                "1,9 -> 3,9",
+                "4,4 -> 0,0",
+                "6,4 -> 9,4",
+                "7,7 -> 7,7",
+            ]
+            .iter()
+            .map(|s| segment(s)),
+        );
+        let expected: Diagram = lines.iter().flat_map(|line| line.positions()).collect();
+
+        let merged: Diagram = merge_segments(&lines)
+            .into_iter()
+            .flat_map(|(line, weight)| line.positions().map(move |pos| (pos, weight)))
+            .collect();
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn test_parse_3d() {
+        assert_eq!(segment("1,2,3 -> 4,5,-6").to_string(), "1,2,3 -> 4,5,-6");
+        // 2D vents lie in the z = 0 plane
+        assert_eq!(segment("1,2 -> 4,5"), segment("1,2,0 -> 4,5,0"));
+        assert_eq!(segment("1,2,0 -> 4,5,7").to_string(), "1,2 -> 4,5,7");
+        assert!("1,2,3,4 -> 4,5,6".parse::<LineSegment>().is_err());
+        assert!("1 -> 4".parse::<LineSegment>().is_err());
+
+        assert_eq!(segment("0,0,0 -> 4,2,6").length(), 2);
+        assert_eq!(segment("0,0,0 -> 4,2,6").direction(), Vec3::new(2, 1, 3));
+        assert!(segment("3,3,0 -> 3,3,5").is_axis_aligned());
+        assert!(!segment("3,3,0 -> 3,4,5").is_axis_aligned());
+    }
+
+    #[test]
+    fn test_intersects_3d() {
+        assert_eq!(
+            segment("0,0,0 -> 2,2,2").intersects(&segment("2,0,0 -> 0,2,2")),
+            Some(Intersection::Point(Position::new(1, 1, 1)))
+        );
+        // skew
+        assert_eq!(
+            segment("0,0,0 -> 2,0,0").intersects(&segment("1,1,-1 -> 1,1,1")),
+            None
+        );
+        // crossing when seen from above, but at different heights
+        assert_eq!(
+            segment("0,0,0 -> 2,2,0").intersects(&segment("2,0,1 -> 0,2,1")),
+            None
+        );
+        assert_eq!(
+            segment("0,0,0 -> 3,3,3").intersects(&segment("5,5,5 -> 2,2,2")),
+            Some(Intersection::Overlap(segment("2,2,2 -> 3,3,3")))
+        );
+        assert!(segment("0,0,0 -> 4,2,6").contains(Position::new(2, 1, 3)));
+        assert!(!segment("0,0,0 -> 4,2,6").contains(Position::new(2, 1, 2)));
+    }
+
+    #[test]
+    fn test_overlaps_3d() {
+        let lines: Vec<LineSegment> = ["0,0,0 -> 0,0,4", "0,0,2 -> 3,0,2", "0,0,0 -> 3,3,3"]
+            .iter()
+            .map(|s| segment(s))
+            .collect();
+        let segments: Vec<_> = lines.iter().map(|&line| (line, 1)).collect();
+        assert_eq!(diagram(&segments, false).positions_with_at_least(2), 1);
+        assert_eq!(diagram(&segments, true).positions_with_at_least(2), 2);
+        assert_eq!(count_overlaps_analytic(&lines), 2);
+
+        let lines: Vec<LineSegment> = ["0,0,0 -> 0,0,4", "0,0,6 -> 0,0,2"]
+            .iter()
+            .map(|s| segment(s))
+            .collect();
+        assert_eq!(
+            merge_segments(&lines),
+            vec![
+                (segment("0,0,0 -> 0,0,1"), 1),
+                (segment("0,0,2 -> 0,0,4"), 2),
+                (segment("0,0,5 -> 0,0,6"), 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use common::{Answer, Solution};
+use std::{mem, ops::AddAssign};
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = Vec<usize>;
+
+    fn parse(input: &str) -> Self::Input {
+        input
+            .trim()
+            .split(',')
+            .map(|timer| timer.parse().unwrap())
+            .collect()
+    }
+
+    fn part1(initial_state: &Self::Input) -> Answer {
+        simulate(initial_state, 80).into()
+    }
+
+    fn part2(initial_state: &Self::Input) -> Answer {
+        simulate(initial_state, 256).into()
+    }
+}
+
+pub fn simulate(initital_state: &[usize], days: usize) -> usize {
+    let mut school = School::new(vec![Species::LANTERNFISH]);
+    for &timer in initital_state {
+        school.add(0, timer);
+    }
+    school.simulate(days);
+    school.total()
+}
+
+// Once its timer reaches 0 a member of a species spawns a member of the offspring species,
+// whose timer starts at the offspring's `new_timer`, and the own timer is reset to `reset`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Species {
+    pub reset: usize,
+    pub new_timer: usize,
+    pub offspring: usize,
+}
+
+impl Species {
+    pub const LANTERNFISH: Species = Species {
+        reset: 6,
+        new_timer: 8,
+        offspring: 0,
+    };
+
+    fn max_timer(&self) -> usize {
+        self.reset.max(self.new_timer)
+    }
+}
+
+// Population counted per species and timer value, which keeps the simulation independent
+// of the population size. Counts are generic so that they can be big integers.
+#[derive(Debug, Clone)]
+pub struct School<T> {
+    species: Vec<Species>,
+    timers: Vec<Vec<T>>,
+}
+
+impl<T> School<T>
+where
+    T: Clone + Default + for<'a> AddAssign<&'a T> + From<u8>,
+{
+    pub fn new(species: Vec<Species>) -> Self {
+        assert!(species.iter().all(|s| s.offspring < species.len()));
+        let timers = species
+            .iter()
+            .map(|s| vec![T::default(); s.max_timer() + 1])
+            .collect();
+        Self { species, timers }
+    }
+
+    pub fn add(&mut self, species: usize, timer: usize) {
+        self.timers[species][timer] += &T::from(1);
+    }
+
+    pub fn step(&mut self) {
+        let spawning: Vec<T> = self
+            .timers
+            .iter_mut()
+            .map(|timers| {
+                timers.rotate_left(1);
+                mem::take(timers.last_mut().unwrap())
+            })
+            .collect();
+
+        for (species, count) in spawning.iter().enumerate() {
+            let Species {
+                reset, offspring, ..
+            } = self.species[species];
+            let new_timer = self.species[offspring].new_timer;
+            self.timers[species][reset] += count;
+            self.timers[offspring][new_timer] += count;
+        }
+    }
+
+    pub fn simulate(&mut self, days: usize) {
+        for _ in 0..days {
+            self.step();
+        }
+    }
+
+    pub fn count(&self, species: usize) -> T {
+        let mut count = T::default();
+        for c in &self.timers[species] {
+            count += c;
+        }
+        count
+    }
+
+    pub fn total(&self) -> T {
+        let mut total = T::default();
+        for species in 0..self.species.len() {
+            total += &self.count(species);
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+use num_bigint::BigUint;
+
+#[test]
+fn test_solution() {
+    assert_eq!(
+        common::solve::<Day6>("3,4,3,1,2\n"),
+        (Answer::from(5934usize), Answer::from(26984457539usize))
+    );
+}
+
+#[test]
+fn test_simulate_80_days() {
+    assert_eq!(simulate(&[3, 4, 3, 1, 2], 80), 5934);
+}
+
+#[test]
+fn test_simulate_256_days() {
+    assert_eq!(simulate(&[3, 4, 3, 1, 2], 256), 26984457539);
+}
+
+#[test]
+fn test_simulate_big_int_256_days() {
+    let mut school: School<BigUint> = School::new(vec![Species::LANTERNFISH]);
+    for timer in [3, 4, 3, 1, 2] {
+        school.add(0, timer);
+    }
+    school.simulate(256);
+    assert_eq!(school.total(), BigUint::from(26984457539u64));
+}
+
+#[test]
+fn test_simulate_big_int_10000_days() {
+    let mut school: School<BigUint> = School::new(vec![Species::LANTERNFISH]);
+    let instance = Day6::parse(include_str!("../instance.txt"));
+    for &timer in &instance {
+        school.add(0, timer);
+    }
+
+    // x^9 = x^2 + 1 is the characteristic polynomial of the lanternfish step, hence the
+    // totals satisfy n(d + 9) = n(d + 2) + n(d)
+    let mut totals = Vec::new();
+    for _ in 0..10_000 {
+        totals.push(school.total());
+        school.step();
+    }
+    totals.push(school.total());
+
+    assert_eq!(totals[256], BigUint::from(simulate(&instance, 256)));
+    assert_eq!(totals[10_000], &totals[9_993] + &totals[9_991]);
+    assert!(totals[10_000].bits() > 1000);
+}
+
+#[test]
+fn test_simulate_custom_rules() {
+    // spawns every other day, so the population doubles every two days
+    let mut school: School<u64> = School::new(vec![Species {
+        reset: 1,
+        new_timer: 1,
+        offspring: 0,
+    }]);
+    school.add(0, 0);
+    school.simulate(9);
+    assert_eq!(school.total(), 32);
+}
+
+#[test]
+fn test_simulate_multiple_species() {
+    // two lanternfish species spawning each other behave like a single one in total
+    let species = vec![
+        Species {
+            offspring: 1,
+            ..Species::LANTERNFISH
+        },
+        Species {
+            offspring: 0,
+            ..Species::LANTERNFISH
+        },
+    ];
+    let mut school: School<u128> = School::new(species);
+    for timer in [3, 4, 3, 1, 2] {
+        school.add(0, timer);
+    }
+    school.simulate(80);
+    assert_eq!(school.total(), 5934);
+    // the initial fish are species 0, the first generation species 1 and so on
+    assert!(school.count(0) > 0 && school.count(1) > 0);
+
+    // species 0 only spawns species 1, so it never grows
+    let mut school: School<u64> = School::new(vec![
+        Species {
+            reset: 2,
+            new_timer: 3,
+            offspring: 1,
+        },
+        Species {
+            reset: 2,
+            new_timer: 3,
+            offspring: 1,
+        },
+    ]);
+    school.add(0, 0);
+    school.simulate(4);
+    // species 0 spawns on days 1 and 4, the first offspring hasn't spawned yet
+    assert_eq!(school.count(0), 1);
+    assert_eq!(school.count(1), 2);
+}
@@ -0,0 +1,35 @@
+use common::rng::Rng;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use day7::fuel::*;
+
+fn generate_positions(n: usize, max: usize, seed: u64) -> Vec<usize> {
+    let mut rng = Rng::new(seed);
+    (0..n).map(|_| rng.below(max)).collect()
+}
+
+// The fast paths of the fuel models against scanning every position.
+fn fast_paths(c: &mut Criterion) {
+    let positions = generate_positions(10_000, 10_000, 0x9e3779b97f4a7c15);
+
+    let mut group = c.benchmark_group("10k crabs");
+    group.sample_size(10);
+    group.bench_function("constant median", |b| {
+        b.iter(|| Constant.optimal(black_box(&positions)))
+    });
+    group.bench_function("constant full scan", |b| {
+        b.iter(|| full_scan(&Constant, black_box(&positions)))
+    });
+    group.bench_function("triangular mean", |b| {
+        b.iter(|| Triangular.optimal(black_box(&positions)))
+    });
+    group.bench_function("triangular ternary", |b| {
+        b.iter(|| ternary_search(&Triangular, black_box(&positions)))
+    });
+    group.bench_function("triangular full scan", |b| {
+        b.iter(|| full_scan(&Triangular, black_box(&positions)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, fast_paths);
+criterion_main!(benches);
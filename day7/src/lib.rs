@@ -0,0 +1,362 @@
+use common::{Answer, Solution};
+use fuel::FuelModel;
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = Vec<usize>;
+
+    fn parse(input: &str) -> Self::Input {
+        input
+            .trim()
+            .split(',')
+            .map(|position| position.parse().unwrap())
+            .collect()
+    }
+
+    fn part1(positions: &Self::Input) -> Answer {
+        part1::compute_fuel(positions).unwrap().into()
+    }
+
+    fn part2(positions: &Self::Input) -> Answer {
+        let (_, fuel) = fuel::Triangular.optimal(positions).unwrap();
+        fuel.into()
+    }
+}
+
+#[cfg(test)]
+fn instance() -> Vec<usize> {
+    Day7::parse(include_str!("../instance.txt"))
+}
+
+pub fn abs_diff<T>(x: T, y: T) -> T
+where
+    T: std::ops::Sub<Output = T> + std::cmp::PartialOrd,
+{
+    if x > y {
+        x - y
+    } else {
+        y - x
+    }
+}
+
+pub mod part1 {
+    use super::abs_diff;
+
+    pub fn compute_fuel(positions: &[usize]) -> Option<usize> {
+        let median_pos = median(positions)?;
+        Some(positions.iter().map(|&pos| abs_diff(pos, median_pos)).sum())
+    }
+
+    fn median(xs: &[usize]) -> Option<usize> {
+        if xs.is_empty() {
+            return None;
+        }
+
+        let mut xs = xs.to_vec();
+        xs.sort_unstable();
+
+        if xs.len().is_multiple_of(2) {
+            Some((xs[xs.len() / 2 - 1] + xs[xs.len() / 2]) / 2)
+        } else {
+            Some(xs[xs.len() / 2])
+        }
+    }
+
+    #[test]
+    fn test_compute_fuel_for_example_input() {
+        let positions = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        assert_eq!(compute_fuel(&positions), Some(37));
+    }
+
+    #[test]
+    fn test_compute_fuel_for_instance() {
+        assert_eq!(compute_fuel(&super::instance()), Some(325528));
+    }
+}
+
+pub mod part2 {
+    use super::abs_diff;
+
+    pub fn compute_fuel_brute_force(positions: &[usize]) -> Option<usize> {
+        let lb = positions.iter().min().copied()?;
+        let ub = positions.iter().max().copied()?;
+        (lb..ub)
+            .map(|target| compute_fuel_for_target_position(positions, target))
+            .min()
+    }
+
+    fn compute_fuel_for_target_position(positions: &[usize], target: usize) -> usize {
+        positions
+            .iter()
+            .map(|&pos| gaussian_sumformula(abs_diff(pos, target)))
+            .sum()
+    }
+
+    fn gaussian_sumformula(n: usize) -> usize {
+        n * (n + 1) / 2
+    }
+
+    pub fn compute_fuel_log_down_hill(positions: &[usize]) -> Option<usize> {
+        let lb = positions.iter().min().copied()?;
+        let ub = positions.iter().max().copied()?;
+        let mid = (lb + ub) / 2;
+        let fuel = compute_fuel_for_target_position(positions, mid);
+        let step_size = (ub - lb) / 2;
+        Some(compute_fuel_log_down_hill_rec(
+            positions, mid, fuel, step_size,
+        ))
+    }
+
+    fn compute_fuel_log_down_hill_rec(
+        positions: &[usize],
+        current_target: usize,
+        current_fuel: usize,
+        step_size: usize,
+    ) -> usize {
+        //dbg!(current_target, current_fuel, step_size);
+
+        if step_size < 1 {
+            return current_fuel;
+        }
+
+        let left_target = current_target - step_size;
+        let left_fuel = compute_fuel_for_target_position(positions, left_target);
+        let right_target = current_target + step_size;
+        let right_fuel = compute_fuel_for_target_position(positions, right_target);
+
+        if left_fuel < right_fuel && left_fuel < current_fuel {
+            // move left
+            compute_fuel_log_down_hill_rec(positions, left_target, left_fuel, step_size / 2)
+        } else if right_fuel < left_fuel && right_fuel < current_fuel {
+            // move right
+            compute_fuel_log_down_hill_rec(positions, right_target, right_fuel, step_size / 2)
+        } else {
+            // stay
+            compute_fuel_log_down_hill_rec(positions, current_target, current_fuel, step_size / 2)
+        }
+    }
+
+    #[test]
+    fn test_compute_fuel_brute_force_for_example_input() {
+        let positions = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        assert_eq!(compute_fuel_brute_force(&positions), Some(168));
+    }
+
+    #[test]
+    fn test_compute_fuel_brute_force_for_instance() {
+        assert_eq!(compute_fuel_brute_force(&super::instance()), Some(85015836));
+    }
+
+    #[test]
+    fn test_compute_fuel_log_down_hill_for_example_input() {
+        let positions = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        assert_eq!(compute_fuel_log_down_hill(&positions), Some(168));
+    }
+
+    #[test]
+    fn test_compute_fuel_log_down_hill_for_instance() {
+        assert_eq!(
+            compute_fuel_log_down_hill(&super::instance()),
+            Some(85015836)
+        );
+    }
+}
+
+pub mod fuel {
+    use super::abs_diff;
+
+    // Fuel needed to move a crab by the given distance. The default optimization is a ternary
+    // search, which requires the total fuel to be convex in the target position. This holds
+    // for every model whose cost is convex in the distance.
+    pub trait FuelModel {
+        fn cost(&self, distance: usize) -> usize;
+
+        // Returns the best target position together with the fuel needed to reach it.
+        fn optimal(&self, positions: &[usize]) -> Option<(usize, usize)> {
+            ternary_search(self, positions)
+        }
+    }
+
+    // Every step costs the same, the median is optimal.
+    pub struct Constant;
+
+    impl FuelModel for Constant {
+        fn cost(&self, distance: usize) -> usize {
+            distance
+        }
+
+        fn optimal(&self, positions: &[usize]) -> Option<(usize, usize)> {
+            if positions.is_empty() {
+                return None;
+            }
+            let mut positions = positions.to_vec();
+            positions.sort_unstable();
+            // any position between the two middle ones is optimal for an even count
+            let target = positions[(positions.len() - 1) / 2];
+            Some((target, total_fuel(self, &positions, target)))
+        }
+    }
+
+    // The n-th step costs n. The total fuel's derivative sum(x - p) + sum(sign(x - p)) / 2
+    // vanishes within 1/2 of the mean, so only the integers around it have to be checked.
+    pub struct Triangular;
+
+    impl FuelModel for Triangular {
+        fn cost(&self, distance: usize) -> usize {
+            distance * (distance + 1) / 2
+        }
+
+        fn optimal(&self, positions: &[usize]) -> Option<(usize, usize)> {
+            if positions.is_empty() {
+                return None;
+            }
+            let sum: usize = positions.iter().sum();
+            let n = positions.len();
+            // floor(mean - 1/2) ..= ceil(mean + 1/2)
+            let lb = (2 * sum).saturating_sub(n) / (2 * n);
+            let ub = (2 * sum + n).div_ceil(2 * n);
+            scan(self, positions, lb, ub)
+        }
+    }
+
+    pub struct Custom<F>(pub F);
+
+    impl<F> FuelModel for Custom<F>
+    where
+        F: Fn(usize) -> usize,
+    {
+        fn cost(&self, distance: usize) -> usize {
+            (self.0)(distance)
+        }
+    }
+
+    pub fn total_fuel<M: FuelModel + ?Sized>(
+        model: &M,
+        positions: &[usize],
+        target: usize,
+    ) -> usize {
+        positions
+            .iter()
+            .map(|&pos| model.cost(abs_diff(pos, target)))
+            .sum()
+    }
+
+    fn scan<M: FuelModel + ?Sized>(
+        model: &M,
+        positions: &[usize],
+        lb: usize,
+        ub: usize,
+    ) -> Option<(usize, usize)> {
+        (lb..=ub)
+            .map(|target| (target, total_fuel(model, positions, target)))
+            .min_by_key(|&(target, fuel)| (fuel, target))
+    }
+
+    // Checks every position between the leftmost and the rightmost crab.
+    pub fn full_scan<M: FuelModel + ?Sized>(
+        model: &M,
+        positions: &[usize],
+    ) -> Option<(usize, usize)> {
+        let lb = positions.iter().min().copied()?;
+        let ub = positions.iter().max().copied()?;
+        scan(model, positions, lb, ub)
+    }
+
+    pub fn ternary_search<M: FuelModel + ?Sized>(
+        model: &M,
+        positions: &[usize],
+    ) -> Option<(usize, usize)> {
+        let mut lb = positions.iter().min().copied()?;
+        let mut ub = positions.iter().max().copied()?;
+        while ub - lb > 2 {
+            let m1 = lb + (ub - lb) / 3;
+            let m2 = ub - (ub - lb) / 3;
+            let f1 = total_fuel(model, positions, m1);
+            let f2 = total_fuel(model, positions, m2);
+            if f1 < f2 {
+                ub = m2 - 1;
+            } else if f1 > f2 {
+                lb = m1 + 1;
+            } else {
+                // by convexity a minimum lies in between
+                lb = m1;
+                ub = m2;
+            }
+        }
+        scan(model, positions, lb, ub)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use common::rng::Rng;
+
+        const EXAMPLE: &[usize] = &[16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+
+        fn generate_positions(n: usize, max: usize, seed: u64) -> Vec<usize> {
+            let mut rng = Rng::new(seed);
+            (0..n).map(|_| rng.below(max)).collect()
+        }
+
+        fn fuel(result: Option<(usize, usize)>) -> Option<usize> {
+            result.map(|(_, fuel)| fuel)
+        }
+
+        #[test]
+        fn test_constant() {
+            assert_eq!(Constant.optimal(EXAMPLE), Some((2, 37)));
+            assert_eq!(
+                fuel(Constant.optimal(&super::super::instance())),
+                Some(325528)
+            );
+            assert_eq!(Constant.optimal(&[]), None);
+        }
+
+        #[test]
+        fn test_triangular() {
+            assert_eq!(Triangular.optimal(EXAMPLE), Some((5, 168)));
+            assert_eq!(
+                fuel(Triangular.optimal(&super::super::instance())),
+                Some(85015836)
+            );
+            assert_eq!(Triangular.optimal(&[7]), Some((7, 0)));
+        }
+
+        #[test]
+        fn test_custom() {
+            let quadratic = Custom(|d: usize| d * d);
+            assert_eq!(quadratic.optimal(EXAMPLE), full_scan(&quadratic, EXAMPLE));
+            let triangular = Custom(|d: usize| d * (d + 1) / 2);
+            assert_eq!(triangular.optimal(EXAMPLE), Some((5, 168)));
+        }
+
+        #[test]
+        fn test_fast_paths_match_full_scan() {
+            for seed in 1..20 {
+                let positions = generate_positions(1 + seed as usize * 7, 500, seed);
+                assert_eq!(
+                    fuel(Constant.optimal(&positions)),
+                    fuel(full_scan(&Constant, &positions))
+                );
+                assert_eq!(
+                    Triangular.optimal(&positions),
+                    full_scan(&Triangular, &positions)
+                );
+                let cubic = Custom(|d: usize| d * d * d);
+                assert_eq!(
+                    ternary_search(&cubic, &positions),
+                    full_scan(&cubic, &positions)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_solution() {
+    assert_eq!(
+        common::solve::<Day7>("16,1,2,0,4,2,7,1,2,14\n"),
+        (Answer::from(37usize), Answer::from(168usize))
+    );
+}
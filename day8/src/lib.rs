@@ -0,0 +1,526 @@
+use common::{Answer, Solution};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+use thiserror::Error;
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Vec<Entry>;
+
+    fn parse(input: &str) -> Self::Input {
+        input
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                line.parse()
+                    .unwrap_or_else(|e| panic!("invalid entry at line {}: {}", i + 1, e))
+            })
+            .collect()
+    }
+
+    fn part1(entries: &Self::Input) -> Answer {
+        count_one_four_seven_and_eight(entries).into()
+    }
+
+    fn part2(entries: &Self::Input) -> Answer {
+        repair_and_sum_up(entries)
+            .expect("an entry has no consistent wiring")
+            .into()
+    }
+}
+
+pub struct Entry {
+    pub signal_patterns: Vec<String>,
+    pub output_values: Vec<String>,
+}
+
+pub const SIGNAL_PATTERNS: usize = 10;
+pub const OUTPUT_VALUES: usize = 4;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("missing '|' between the signal patterns and the output values")]
+    MissingSeparator,
+    #[error("{0} signal patterns, expected {SIGNAL_PATTERNS}")]
+    PatternCount(usize),
+    #[error("{0} output values, expected {OUTPUT_VALUES}")]
+    OutputCount(usize),
+    #[error("invalid segment '{0}', expected a to g")]
+    InvalidCharacter(char),
+}
+
+impl FromStr for Entry {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Entry, Self::Err> {
+        let (patterns, outputs) = s.split_once('|').ok_or(ParseError::MissingSeparator)?;
+        let segments = |s: &str| -> Result<Vec<String>, ParseError> {
+            s.split_whitespace()
+                .map(
+                    |pattern| match pattern.chars().find(|c| !('a'..='g').contains(c)) {
+                        Some(c) => Err(ParseError::InvalidCharacter(c)),
+                        None => Ok(pattern.to_string()),
+                    },
+                )
+                .collect()
+        };
+
+        let signal_patterns = segments(patterns)?;
+        if signal_patterns.len() != SIGNAL_PATTERNS {
+            return Err(ParseError::PatternCount(signal_patterns.len()));
+        }
+        let output_values = segments(outputs)?;
+        if output_values.len() != OUTPUT_VALUES {
+            return Err(ParseError::OutputCount(output_values.len()));
+        }
+
+        Ok(Self {
+            signal_patterns,
+            output_values,
+        })
+    }
+}
+
+pub fn count_one_four_seven_and_eight(entries: &[Entry]) -> usize {
+    entries
+        .iter()
+        .map(|entry| {
+            entry
+                .output_values
+                .iter()
+                .filter(|s| matches!(s.len(), 2 | 3 | 4 | 7))
+                .count()
+        })
+        .sum()
+}
+
+// None if any entry has no consistent wiring.
+pub fn repair_and_sum_up(entries: &[Entry]) -> Option<usize> {
+    entries
+        .iter()
+        .map(|entry| decode_output(entry, &reconstruct_wiring(entry)?))
+        .sum()
+}
+
+// None if an output value isn't a digit of the wiring.
+pub fn decode_output(entry: &Entry, wiring: &HashMap<Vec<char>, usize>) -> Option<usize> {
+    entry.output_values.iter().try_fold(0, |agg, value| {
+        Some(agg * 10 + digit_with_correction(value, wiring)?)
+    })
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct SegmentCount {
+    pub count: [u8; 10],
+}
+
+impl SegmentCount {
+    pub fn new(s: &str) -> SegmentCount {
+        let mut count = [0; 10];
+        for &c in s.as_bytes() {
+            count[Self::index_of_char(c)] += 1;
+        }
+        SegmentCount { count }
+    }
+
+    pub fn union(mut self, other: &SegmentCount) -> SegmentCount {
+        for i in 0..10 {
+            self.count[i] += other.count[i];
+        }
+        self
+    }
+
+    pub fn intersect(mut self, other: &SegmentCount) -> SegmentCount {
+        for i in 0..10 {
+            self.count[i] = self.count[i].min(other.count[i]);
+        }
+        self
+    }
+
+    pub fn expect(mut self, other: &SegmentCount) -> SegmentCount {
+        for i in 0..10 {
+            self.count[i] = self.count[i].saturating_sub(other.count[i]);
+        }
+        self
+    }
+
+    pub fn filter_count(mut self, n: u8) -> SegmentCount {
+        for i in 0..10 {
+            if self.count[i] != n {
+                self.count[i] = 0;
+            }
+        }
+        self
+    }
+
+    pub fn without(mut self, c: char) -> SegmentCount {
+        self.count[Self::index_of_char(c as u8)] = 0;
+        self
+    }
+
+    pub fn expect_unique(&self) -> Option<char> {
+        let mut c = None;
+        for i in 0..10 {
+            if self.count[i] > 0 {
+                if c.is_some() {
+                    return None;
+                }
+                c = Some(Self::char_of_index(i as u8));
+            }
+        }
+        c
+    }
+
+    fn index_of_char(c: u8) -> usize {
+        (c - b'a') as usize
+    }
+
+    fn char_of_index(i: u8) -> char {
+        (i + b'a') as char
+    }
+}
+
+pub fn sorted(mut v: Vec<char>) -> Vec<char> {
+    v.sort_unstable();
+    v
+}
+
+// Identifies the wire of one segment.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DeductionStep {
+    segment: char,
+    wire: char,
+    rule: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deduction {
+    // in the order of deduction
+    steps: Vec<DeductionStep>,
+}
+
+impl Deduction {
+    fn wire(&self, segment: char) -> char {
+        self.steps
+            .iter()
+            .find(|step| step.segment == segment)
+            .unwrap()
+            .wire
+    }
+
+    // wire -> segment
+    pub fn mapping(&self) -> BTreeMap<char, char> {
+        self.steps
+            .iter()
+            .map(|step| (step.wire, step.segment))
+            .collect()
+    }
+
+    pub fn wiring(&self) -> HashMap<Vec<char>, usize> {
+        let wires = |segments: &str| sorted(segments.chars().map(|s| self.wire(s)).collect());
+
+        let mut wiring = HashMap::new();
+        wiring.insert(wires("abcefg"), 0);
+        wiring.insert(wires("cf"), 1);
+        wiring.insert(wires("acdeg"), 2);
+        wiring.insert(wires("acdfg"), 3);
+        wiring.insert(wires("bcdf"), 4);
+        wiring.insert(wires("abdfg"), 5);
+        wiring.insert(wires("abdefg"), 6);
+        wiring.insert(wires("acf"), 7);
+        wiring.insert(wires("abcdefg"), 8);
+        wiring.insert(wires("abcdfg"), 9);
+        wiring
+    }
+}
+
+// None if the signal patterns aren't the ten digits under any wiring.
+pub fn deduce_wiring(entry: &Entry) -> Option<Deduction> {
+    let one = entry
+        .signal_patterns
+        .iter()
+        .filter(|p| p.len() == 2)
+        .map(|p| SegmentCount::new(p))
+        .next()?;
+    let seven = entry
+        .signal_patterns
+        .iter()
+        .filter(|p| p.len() == 3)
+        .map(|p| SegmentCount::new(p))
+        .next()?;
+    let four = entry
+        .signal_patterns
+        .iter()
+        .filter(|p| p.len() == 4)
+        .map(|p| SegmentCount::new(p))
+        .next()?;
+    let two_tree_five = entry
+        .signal_patterns
+        .iter()
+        .filter(|p| p.len() == 5)
+        .map(|p| SegmentCount::new(p))
+        .fold(SegmentCount::default(), |agg, pattern| agg.union(&pattern));
+    let zero_six_nine = entry
+        .signal_patterns
+        .iter()
+        .filter(|p| p.len() == 6)
+        .map(|p| SegmentCount::new(p))
+        .fold(SegmentCount::default(), |agg, pattern| agg.union(&pattern));
+    let eight = entry
+        .signal_patterns
+        .iter()
+        .filter(|p| p.len() == 7)
+        .map(|p| SegmentCount::new(p))
+        .next()?;
+
+    let mut steps = Vec::with_capacity(7);
+    let mut step = |segment: char, wire: char, rule: &'static str| {
+        steps.push(DeductionStep {
+            segment,
+            wire,
+            rule,
+        });
+        wire
+    };
+
+    // 1. a
+    let a = step(
+        'a',
+        seven.clone().expect(&one).filter_count(1).expect_unique()?,
+        "in 7 but not in 1",
+    );
+
+    // 2. e
+    let e = step(
+        'e',
+        two_tree_five
+            .clone()
+            .union(&zero_six_nine)
+            .filter_count(3)
+            .expect_unique()?,
+        "the only wire used 3 times by 2, 3, 5, 0, 6 and 9",
+    );
+
+    // 3. b
+    let b_and_e = two_tree_five
+        .clone()
+        .intersect(&zero_six_nine)
+        .filter_count(1);
+    let b = step(
+        'b',
+        b_and_e.without(e).expect_unique()?,
+        "used by only one of 2, 3 and 5, other than e",
+    );
+
+    // 4. c
+    let b_and_c = two_tree_five.clone().union(&zero_six_nine).filter_count(4);
+    let c = step(
+        'c',
+        b_and_c.without(b).expect_unique()?,
+        "used 4 times by 2, 3, 5, 0, 6 and 9, other than b",
+    );
+
+    // 5. f
+    let f = step(
+        'f',
+        one.clone().without(c).expect_unique()?,
+        "in 1, other than c",
+    );
+
+    // 6. d
+    let d = step(
+        'd',
+        four.without(b).without(c).without(f).expect_unique()?,
+        "in 4, other than b, c and f",
+    );
+
+    // 7. g
+    step(
+        'g',
+        eight
+            .without(a)
+            .without(b)
+            .without(c)
+            .without(d)
+            .without(e)
+            .without(f)
+            .expect_unique()?,
+        "the remaining wire of 8",
+    );
+
+    // garbage patterns can still get through the steps above
+    let deduction = Deduction { steps };
+    let wiring = deduction.wiring();
+    let digits: HashSet<usize> = entry
+        .signal_patterns
+        .iter()
+        .map(|pattern| digit_with_correction(pattern, &wiring))
+        .collect::<Option<_>>()?;
+    (digits.len() == SIGNAL_PATTERNS).then_some(deduction)
+}
+
+pub fn reconstruct_wiring(entry: &Entry) -> Option<HashMap<Vec<char>, usize>> {
+    deduce_wiring(entry).map(|deduction| deduction.wiring())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Explanation {
+    output_values: Vec<String>,
+    value: usize,
+    // wire -> segment
+    mapping: BTreeMap<char, char>,
+    steps: Vec<DeductionStep>,
+}
+
+// None if the entry has no consistent wiring.
+pub fn explain_entry(entry: &Entry) -> Option<Explanation> {
+    let deduction = deduce_wiring(entry)?;
+    let wiring = deduction.wiring();
+    Some(Explanation {
+        output_values: entry.output_values.clone(),
+        value: decode_output(entry, &wiring)?,
+        mapping: deduction.mapping(),
+        steps: deduction.steps,
+    })
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} = {}", self.output_values.join(" "), self.value)?;
+        for step in &self.steps {
+            writeln!(f, "  {}: wire {} ({})", step.segment, step.wire, step.rule)?;
+        }
+        let mapping: Vec<String> = self
+            .mapping
+            .iter()
+            .map(|(wire, segment)| format!("{}\u{2192}{}", wire, segment))
+            .collect();
+        writeln!(f, "  mapping: {}", mapping.join(" "))
+    }
+}
+
+pub fn digit_with_correction(output: &str, wiring: &HashMap<Vec<char>, usize>) -> Option<usize> {
+    let mut s: Vec<char> = output.chars().collect();
+    s.sort_unstable();
+    wiring.get(&s).copied()
+}
+
+#[cfg(test)]
+const EXAMPLE_ENTRY: &str =
+    "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+
+#[test]
+fn test_solution_example() {
+    assert_eq!(
+        common::solve::<Day8>(include_str!("../example.txt")),
+        (Answer::from(26usize), Answer::from(61229usize))
+    );
+}
+
+#[test]
+fn test_reconstuct_wiring() {
+    let entry: Entry = EXAMPLE_ENTRY.parse().unwrap();
+    let wiring = reconstruct_wiring(&entry).unwrap();
+
+    assert_eq!(digit_with_correction("cdfeb", &wiring), Some(5));
+    assert_eq!(digit_with_correction("fcadb", &wiring), Some(3));
+    assert_eq!(digit_with_correction("cdfeb", &wiring), Some(5));
+    assert_eq!(digit_with_correction("cdbaf", &wiring), Some(3));
+    assert_eq!(digit_with_correction("abcd", &wiring), None);
+}
+
+#[test]
+fn test_explain_entry() {
+    let explanation = explain_entry(&EXAMPLE_ENTRY.parse().unwrap()).unwrap();
+    assert_eq!(explanation.value, 5353);
+    // the puzzle's example wiring: d is segment a, e is b, a is c, ...
+    assert_eq!(
+        explanation.mapping,
+        BTreeMap::from([
+            ('a', 'c'),
+            ('b', 'f'),
+            ('c', 'g'),
+            ('d', 'a'),
+            ('e', 'b'),
+            ('f', 'd'),
+            ('g', 'e'),
+        ])
+    );
+    assert_eq!(
+        explanation.steps[0],
+        DeductionStep {
+            segment: 'a',
+            wire: 'd',
+            rule: "in 7 but not in 1",
+        }
+    );
+
+    let text = explanation.to_string();
+    assert!(text.starts_with("cdfeb fcadb cdfeb cdbaf = 5353\n  a: wire d (in 7 but not in 1)\n"));
+    assert!(text.ends_with(
+        "  mapping: a\u{2192}c b\u{2192}f c\u{2192}g d\u{2192}a e\u{2192}b f\u{2192}d g\u{2192}e\n"
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_explain_entry_json() {
+    let explanation = explain_entry(&EXAMPLE_ENTRY.parse().unwrap()).unwrap();
+    let json = serde_json::to_value(&explanation).unwrap();
+    assert_eq!(json["value"], 5353);
+    assert_eq!(json["mapping"]["d"], "a");
+    assert_eq!(json["steps"][6]["segment"], "g");
+    assert_eq!(json["steps"][6]["wire"], "c");
+}
+
+#[test]
+fn test_parse_invalid_entry() {
+    let parse = |s: &str| s.parse::<Entry>().err();
+    assert_eq!(parse(EXAMPLE_ENTRY), None);
+    assert_eq!(
+        parse("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab cdfeb fcadb"),
+        Some(ParseError::MissingSeparator)
+    );
+    assert_eq!(
+        parse("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb ab | cdfeb fcadb cdfeb cdbaf"),
+        Some(ParseError::PatternCount(9))
+    );
+    assert_eq!(
+        parse("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb"),
+        Some(ParseError::OutputCount(2))
+    );
+    assert_eq!(
+        parse(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaz"
+        ),
+        Some(ParseError::InvalidCharacter('z'))
+    );
+}
+
+#[test]
+fn test_inconsistent_entry() {
+    // eight patterns of 8 and no 1
+    let entry: Entry =
+        "abcdefg abcdefg abcdefg abcdefg abcdefg abcdefg abcdefg abcdefg dab cefabd | ab ab ab ab"
+            .parse()
+            .unwrap();
+    assert!(deduce_wiring(&entry).is_none());
+    assert!(explain_entry(&entry).is_none());
+    assert_eq!(repair_and_sum_up(&[entry]), None);
+
+    // the patterns are fine, but an output value isn't a digit
+    let entry: Entry =
+        "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdb"
+            .parse()
+            .unwrap();
+    assert!(deduce_wiring(&entry).is_some());
+    assert_eq!(repair_and_sum_up(&[entry]), None);
+}
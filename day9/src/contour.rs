@@ -0,0 +1,217 @@
+use super::HeightLookup;
+use common::grid::cells;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+// Size of a heightmap cell in the SVG output.
+const CELL_SIZE: f64 = 10.0;
+
+type Cell = (usize, usize);
+
+// Point on the edge between two neighboring cell centers, the upper or left cell comes first.
+type EdgePoint = (Cell, Cell);
+
+type Segment = (EdgePoint, EdgePoint);
+
+// Marching squares over the squares spanned by four neighboring cell centers. Squares with an
+// unknown corner height are skipped.
+fn contour_segments<H>(heightmap: &H, level: f64) -> Vec<Segment>
+where
+    H: HeightLookup + ?Sized,
+{
+    let mut segments = Vec::new();
+    let squares = cells(
+        heightmap.width().saturating_sub(1),
+        heightmap.height().saturating_sub(1),
+    );
+    for (x, y) in squares {
+        let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)]
+            .map(|(x, y)| heightmap.get(x, y).map(|height| height as f64));
+        let [Some(tl), Some(tr), Some(br), Some(bl)] = corners else {
+            continue;
+        };
+
+        let above = |height: f64| (height > level) as usize;
+        let case = above(tl) << 3 | above(tr) << 2 | above(br) << 1 | above(bl);
+        let center_above = (tl + tr + br + bl) / 4.0 > level;
+
+        let top = ((x, y), (x + 1, y));
+        let right = ((x + 1, y), (x + 1, y + 1));
+        let bottom = ((x, y + 1), (x + 1, y + 1));
+        let left = ((x, y), (x, y + 1));
+
+        match case {
+            0 | 15 => {}
+            1 | 14 => segments.push((left, bottom)),
+            2 | 13 => segments.push((bottom, right)),
+            3 | 12 => segments.push((left, right)),
+            4 | 11 => segments.push((top, right)),
+            6 | 9 => segments.push((top, bottom)),
+            7 | 8 => segments.push((left, top)),
+            // saddles, decided by the average height in the center
+            5 if center_above => segments.extend([(left, top), (bottom, right)]),
+            5 => segments.extend([(top, right), (left, bottom)]),
+            10 if center_above => segments.extend([(top, right), (left, bottom)]),
+            10 => segments.extend([(left, top), (bottom, right)]),
+            _ => unreachable!(),
+        }
+    }
+    segments
+}
+
+// Chains the segments into polylines. Closed lines end with their first point again.
+fn join_segments(segments: &[Segment]) -> Vec<Vec<EdgePoint>> {
+    let mut adjacent: HashMap<EdgePoint, Vec<usize>> = HashMap::new();
+    for (i, &(a, b)) in segments.iter().enumerate() {
+        adjacent.entry(a).or_default().push(i);
+        adjacent.entry(b).or_default().push(i);
+    }
+
+    // open lines have to start at one of their ends
+    let mut starts: Vec<EdgePoint> = segments.iter().flat_map(|&(a, b)| [a, b]).collect();
+    starts.sort_by_key(|p| adjacent[p].len() != 1);
+
+    let mut used = HashSet::new();
+    let mut lines = Vec::new();
+    for start in starts {
+        let mut line = vec![start];
+        let mut current = start;
+        while let Some(&i) = adjacent[&current].iter().find(|&i| !used.contains(i)) {
+            used.insert(i);
+            let (a, b) = segments[i];
+            current = if a == current { b } else { a };
+            line.push(current);
+        }
+        if line.len() > 1 {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+fn edge_point_position<H>(heightmap: &H, point: EdgePoint, level: f64) -> (f64, f64)
+where
+    H: HeightLookup + ?Sized,
+{
+    let (from, to) = point;
+    let height = |(x, y): Cell| heightmap.get(x, y).unwrap() as f64;
+    let t = (level - height(from)) / (height(to) - height(from));
+    (
+        from.0 as f64 + t * (to.0 as f64 - from.0 as f64),
+        from.1 as f64 + t * (to.1 as f64 - from.1 as f64),
+    )
+}
+
+// Contour lines separating heights <= level from heights > level, in cell coordinates.
+pub fn contour_lines<H>(heightmap: &H, level: f64) -> Vec<Vec<(f64, f64)>>
+where
+    H: HeightLookup + ?Sized,
+{
+    join_segments(&contour_segments(heightmap, level))
+        .into_iter()
+        .map(|line| {
+            line.into_iter()
+                .map(|point| edge_point_position(heightmap, point, level))
+                .collect()
+        })
+        .collect()
+}
+
+// One group of polylines per height level, colored from blue (low) to red (high).
+pub fn contours_to_svg<H>(heightmap: &H, levels: &[f64]) -> String
+where
+    H: HeightLookup + ?Sized,
+{
+    let width = heightmap.width() as f64 * CELL_SIZE;
+    let height = heightmap.height() as f64 * CELL_SIZE;
+    let max_level = levels.iter().copied().fold(f64::MIN, f64::max);
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+        width, height, width, height
+    )
+    .unwrap();
+    for &level in levels {
+        let hue = 240.0 * (1.0 - level / max_level.max(1.0));
+        writeln!(
+            svg,
+            "<g class=\"level\" data-level=\"{}\" fill=\"none\" stroke=\"hsl({:.0}, 80%, 45%)\">",
+            level, hue
+        )
+        .unwrap();
+        for line in contour_lines(heightmap, level) {
+            let points: Vec<String> = line
+                .iter()
+                .map(|(x, y)| {
+                    let (x, y) = ((x + 0.5) * CELL_SIZE, (y + 0.5) * CELL_SIZE);
+                    format!("{:.2},{:.2}", x, y)
+                })
+                .collect();
+            writeln!(svg, "<polyline points=\"{}\"/>", points.join(" ")).unwrap();
+        }
+        writeln!(svg, "</g>").unwrap();
+    }
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEAK: &[&[usize]] = &[&[0, 0, 0], &[0, 9, 0], &[0, 0, 0]];
+
+    #[test]
+    fn test_contour_around_peak() {
+        let lines = contour_lines(PEAK, 4.5);
+        assert_eq!(lines.len(), 1);
+
+        let line = &lines[0];
+        assert_eq!(line.len(), 5);
+        assert_eq!(line.first(), line.last());
+        for &(x, y) in line {
+            let distance = (x - 1.0).abs() + (y - 1.0).abs();
+            assert!((distance - 0.5).abs() < 1e-9, "({}, {})", x, y);
+        }
+
+        assert!(contour_lines(PEAK, 9.5).is_empty());
+    }
+
+    #[test]
+    fn test_open_contour() {
+        let slope: &[&[usize]] = &[&[0, 2, 4], &[0, 2, 4]];
+        let lines = contour_lines(slope, 1.0);
+        assert_eq!(lines.len(), 1);
+        let mut line = lines[0].clone();
+        line.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(line, vec![(0.5, 0.0), (0.5, 1.0)]);
+    }
+
+    #[test]
+    fn test_saddle() {
+        let saddle: &[&[usize]] = &[&[9, 0], &[0, 9]];
+        // center average 4.5 is above 4, hence the high corners are connected
+        let lines = contour_lines(saddle, 4.0);
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() == 2));
+    }
+
+    #[test]
+    fn test_contours_to_svg() {
+        let levels: Vec<f64> = (0..9).map(|level| level as f64 + 0.5).collect();
+        let svg = contours_to_svg(PEAK, &levels);
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"30\" height=\"30\" viewBox=\"0 0 30 30\">\n"
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<g class=\"level\"").count(), 9);
+        assert_eq!(svg.matches("<polyline ").count(), 9);
+        assert!(svg.contains(
+            "<g class=\"level\" data-level=\"8.5\" fill=\"none\" stroke=\"hsl(0, 80%, 45%)\">"
+        ));
+    }
+}
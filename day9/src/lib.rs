@@ -0,0 +1,489 @@
+use common::grid::flood_fill;
+use common::{Answer, Solution};
+use itertools::Itertools;
+use std::io::{self, BufRead};
+use std::{cmp, collections::HashMap, iter::FromIterator};
+use thiserror::Error;
+
+pub mod contour;
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input = Heightmap;
+
+    fn parse(input: &str) -> Self::Input {
+        Heightmap::parse(input.as_bytes()).unwrap()
+    }
+
+    fn part1(heightmap: &Self::Input) -> Answer {
+        total_risk_level(heightmap).into()
+    }
+
+    fn part2(heightmap: &Self::Input) -> Answer {
+        top_k_basin_product(heightmap, 3).into()
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ParseError {
+    #[error("invalid height '{character}' at line {line}, column {column}")]
+    InvalidHeight {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+    #[error("line {line} has {actual} columns, expected {expected}")]
+    NotRectangular {
+        line: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("empty heightmap")]
+    Empty,
+    #[error("failed to read input")]
+    Io(#[from] io::Error),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heightmap {
+    rows: Vec<Vec<usize>>,
+}
+
+impl Heightmap {
+    pub fn parse<R: BufRead>(reader: R) -> Result<Heightmap, ParseError> {
+        Self::parse_with_radix(reader, 10)
+    }
+
+    // Heights are single hex digits (0-15).
+    pub fn parse_hex<R: BufRead>(reader: R) -> Result<Heightmap, ParseError> {
+        Self::parse_with_radix(reader, 16)
+    }
+
+    fn parse_with_radix<R: BufRead>(reader: R, radix: u32) -> Result<Heightmap, ParseError> {
+        let mut rows: Vec<Vec<usize>> = Vec::new();
+
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let row = line
+                .chars()
+                .enumerate()
+                .map(|(j, c)| {
+                    c.to_digit(radix).map(|height| height as usize).ok_or(
+                        ParseError::InvalidHeight {
+                            line: i + 1,
+                            column: j + 1,
+                            character: c,
+                        },
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if let Some(first_row) = rows.first() {
+                if row.len() != first_row.len() {
+                    return Err(ParseError::NotRectangular {
+                        line: i + 1,
+                        expected: first_row.len(),
+                        actual: row.len(),
+                    });
+                }
+            }
+            rows.push(row);
+        }
+
+        if rows.is_empty() || rows[0].is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        Ok(Heightmap { rows })
+    }
+
+    pub fn rows(&self) -> &[Vec<usize>] {
+        &self.rows
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    pub x: usize,
+    pub y: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalMinimum {
+    pub pos: Position,
+    pub height: usize,
+}
+
+// Heights within a width x height grid. Positions without a known height are treated like
+// positions outside of the grid.
+pub trait HeightLookup {
+    fn get(&self, x: usize, y: usize) -> Option<usize>;
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+}
+
+impl<Row: AsRef<[usize]>> HeightLookup for [Row] {
+    fn get(&self, x: usize, y: usize) -> Option<usize> {
+        self.get(y)?.as_ref().get(x).copied()
+    }
+
+    fn width(&self) -> usize {
+        self.first().map_or(0, |row| row.as_ref().len())
+    }
+
+    fn height(&self) -> usize {
+        self.len()
+    }
+}
+
+impl HeightLookup for Heightmap {
+    fn get(&self, x: usize, y: usize) -> Option<usize> {
+        HeightLookup::get(&self.rows[..], x, y)
+    }
+
+    fn width(&self) -> usize {
+        HeightLookup::width(&self.rows[..])
+    }
+
+    fn height(&self) -> usize {
+        HeightLookup::height(&self.rows[..])
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SparseHeightmap {
+    heights: HashMap<Position, usize>,
+    width: usize,
+    height: usize,
+}
+
+impl SparseHeightmap {
+    pub fn insert(&mut self, pos: Position, height: usize) {
+        self.width = cmp::max(self.width, pos.x + 1);
+        self.height = cmp::max(self.height, pos.y + 1);
+        self.heights.insert(pos, height);
+    }
+}
+
+impl FromIterator<(Position, usize)> for SparseHeightmap {
+    fn from_iter<I: IntoIterator<Item = (Position, usize)>>(iter: I) -> Self {
+        let mut heightmap = SparseHeightmap::default();
+        for (pos, height) in iter {
+            heightmap.insert(pos, height);
+        }
+        heightmap
+    }
+}
+
+impl HeightLookup for SparseHeightmap {
+    fn get(&self, x: usize, y: usize) -> Option<usize> {
+        self.heights.get(&Position { x, y }).copied()
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+pub fn adjacent_positions_2d<H>(heightmap: &H, pos: Position) -> Vec<Position>
+where
+    H: HeightLookup + ?Sized,
+{
+    let cols = heightmap.height();
+    let rows = heightmap.width();
+
+    let mut adjacent_positions = Vec::with_capacity(4);
+    if pos.x > 0 {
+        adjacent_positions.push(Position {
+            x: pos.x - 1,
+            ..pos
+        });
+    }
+    if pos.x < rows - 1 {
+        adjacent_positions.push(Position {
+            x: pos.x + 1,
+            ..pos
+        });
+    }
+    if pos.y > 0 {
+        adjacent_positions.push(Position {
+            y: pos.y - 1,
+            ..pos
+        });
+    }
+    if pos.y < cols - 1 {
+        adjacent_positions.push(Position {
+            y: pos.y + 1,
+            ..pos
+        });
+    }
+    adjacent_positions
+}
+
+pub fn min_adjacent_height_2d<H>(heightmap: &H, pos: Position) -> Option<usize>
+where
+    H: HeightLookup + ?Sized,
+{
+    adjacent_positions_2d(heightmap, pos)
+        .into_iter()
+        .filter_map(|pos| heightmap.get(pos.x, pos.y))
+        .min()
+}
+
+pub fn find_local_minimas_2d<H>(heightmap: &H) -> Vec<LocalMinimum>
+where
+    H: HeightLookup + ?Sized,
+{
+    let cols = heightmap.height();
+    let rows = heightmap.width();
+
+    (0..cols)
+        .cartesian_product(0..rows)
+        .filter_map(|(y, x)| {
+            let pos = Position { x, y };
+            let height = heightmap.get(x, y)?;
+            let min_adjacent_height = min_adjacent_height_2d(heightmap, pos)?;
+            if height < min_adjacent_height {
+                Some(LocalMinimum { pos, height })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+pub fn basin_size<H>(heightmap: &H, low_point: &LocalMinimum) -> usize
+where
+    H: HeightLookup + ?Sized,
+{
+    let height = |pos: Position| heightmap.get(pos.x, pos.y);
+    flood_fill(
+        [low_point.pos],
+        |pos| adjacent_positions_2d(heightmap, pos),
+        |from, to| match (height(from), height(to)) {
+            (Some(from), Some(to)) => to >= from && to < 9,
+            _ => false,
+        },
+        |_| {},
+    )
+    .len()
+}
+
+// The basin of every low point together with its size, largest first. Basins of the same
+// size stay in the order of their low points.
+pub fn basins_sorted_by_size<H>(heightmap: &H) -> Vec<(LocalMinimum, usize)>
+where
+    H: HeightLookup + ?Sized,
+{
+    find_local_minimas_2d(heightmap)
+        .into_iter()
+        .map(|low_point| (low_point, basin_size(heightmap, &low_point)))
+        .sorted_by_key(|&(_, size)| cmp::Reverse(size))
+        .collect()
+}
+
+// Product of the sizes of the k largest basins (or of all basins if there are fewer).
+pub fn top_k_basin_product<H>(heightmap: &H, k: usize) -> usize
+where
+    H: HeightLookup + ?Sized,
+{
+    basins_sorted_by_size(heightmap)
+        .into_iter()
+        .take(k)
+        .map(|(_, size)| size)
+        .product()
+}
+
+pub fn total_risk_level<H>(heightmap: &H) -> usize
+where
+    H: HeightLookup + ?Sized,
+{
+    find_local_minimas_2d(heightmap)
+        .iter()
+        .map(|low_point| low_point.height + 1)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solution_example() {
+        assert_eq!(
+            common::solve::<Day9>(include_str!("../example.txt")),
+            (Answer::from(15usize), Answer::from(1134usize))
+        );
+    }
+    use rstest::rstest;
+
+    const TEST_HEIGHTMAP: &[&[usize]] = &[
+        &[2, 1, 9, 9, 9, 4, 3, 2, 1, 0],
+        &[3, 9, 8, 7, 8, 9, 4, 9, 2, 1],
+        &[9, 8, 5, 6, 7, 8, 9, 8, 9, 2],
+        &[8, 7, 6, 7, 8, 9, 6, 7, 8, 9],
+        &[9, 8, 9, 9, 9, 6, 5, 6, 7, 8],
+    ];
+
+    #[test]
+    fn test_find_local_minimas_2d() {
+        assert_eq!(
+            vec![
+                LocalMinimum {
+                    pos: Position { x: 1, y: 0 },
+                    height: 1
+                },
+                LocalMinimum {
+                    pos: Position { x: 9, y: 0 },
+                    height: 0
+                },
+                LocalMinimum {
+                    pos: Position { x: 2, y: 2 },
+                    height: 5
+                },
+                LocalMinimum {
+                    pos: Position { x: 6, y: 4 },
+                    height: 5
+                },
+            ],
+            find_local_minimas_2d(TEST_HEIGHTMAP)
+        )
+    }
+
+    #[rstest]
+    #[case(LocalMinimum{pos: Position{x:1, y:0}, height:1}, 3)]
+    #[case(LocalMinimum{pos: Position{x:9, y:0}, height:0}, 9)]
+    #[case(LocalMinimum{pos: Position{x:2, y:2}, height:5}, 14)]
+    #[case(LocalMinimum{pos: Position{x:6, y:4}, height:5}, 9)]
+    fn test_basin_size(#[case] low_point: LocalMinimum, #[case] expected_size: usize) {
+        assert_eq!(expected_size, basin_size(TEST_HEIGHTMAP, &low_point));
+    }
+
+    #[test]
+    fn test_basins_sorted_by_size() {
+        let sizes: Vec<_> = basins_sorted_by_size(TEST_HEIGHTMAP)
+            .into_iter()
+            .map(|(low_point, size)| (low_point.pos, size))
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![
+                (Position { x: 2, y: 2 }, 14),
+                (Position { x: 9, y: 0 }, 9),
+                (Position { x: 6, y: 4 }, 9),
+                (Position { x: 1, y: 0 }, 3),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(0, 1)]
+    #[case(1, 14)]
+    #[case(3, 1134)]
+    #[case(4, 3402)]
+    #[case(10, 3402)]
+    fn test_top_k_basin_product(#[case] k: usize, #[case] expected: usize) {
+        assert_eq!(top_k_basin_product(TEST_HEIGHTMAP, k), expected);
+    }
+
+    fn sparse_test_heightmap() -> SparseHeightmap {
+        TEST_HEIGHTMAP
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(move |(x, &height)| (Position { x, y }, height))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sparse_heightmap_matches_dense() {
+        let sparse = sparse_test_heightmap();
+        assert_eq!(sparse.width(), 10);
+        assert_eq!(sparse.height(), 5);
+
+        let low_points = find_local_minimas_2d(&sparse);
+        assert_eq!(low_points, find_local_minimas_2d(TEST_HEIGHTMAP));
+        for low_point in &low_points {
+            assert_eq!(
+                basin_size(&sparse, low_point),
+                basin_size(TEST_HEIGHTMAP, low_point)
+            );
+        }
+    }
+
+    #[test]
+    fn test_partial_sparse_heightmap() {
+        // unknown heights act like walls
+        let mut sparse = sparse_test_heightmap();
+        sparse.heights.remove(&Position { x: 3, y: 2 });
+        sparse.heights.remove(&Position { x: 0, y: 0 });
+
+        let low_points = find_local_minimas_2d(&sparse);
+        assert_eq!(
+            low_points
+                .iter()
+                .map(|m| (m.pos.x, m.pos.y))
+                .collect::<Vec<_>>(),
+            vec![(1, 0), (9, 0), (0, 1), (3, 1), (2, 2), (4, 2), (6, 4)]
+        );
+        assert_eq!(basin_size(&sparse, &low_points[0]), 1);
+        assert_eq!(basin_size(&sparse, &low_points[2]), 1);
+        assert_eq!(basin_size(&sparse, &low_points[4]), 9);
+    }
+
+    #[test]
+    fn test_parse_heightmap() {
+        let heightmap = Heightmap::parse("21999\n39878\n".as_bytes()).unwrap();
+        assert_eq!(
+            heightmap.rows(),
+            &[vec![2, 1, 9, 9, 9], vec![3, 9, 8, 7, 8]]
+        );
+    }
+
+    #[test]
+    fn test_parse_heightmap_hex() {
+        let heightmap = Heightmap::parse_hex("0af\nF19\n".as_bytes()).unwrap();
+        assert_eq!(heightmap.rows(), &[vec![0, 10, 15], vec![15, 1, 9]]);
+
+        assert!(matches!(
+            Heightmap::parse("0af\n".as_bytes()),
+            Err(ParseError::InvalidHeight {
+                line: 1,
+                column: 2,
+                character: 'a'
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_heightmap_errors() {
+        assert!(matches!(
+            Heightmap::parse("219\n39x\n".as_bytes()),
+            Err(ParseError::InvalidHeight {
+                line: 2,
+                column: 3,
+                character: 'x'
+            })
+        ));
+        assert!(matches!(
+            Heightmap::parse("219\n3987\n".as_bytes()),
+            Err(ParseError::NotRectangular {
+                line: 2,
+                expected: 3,
+                actual: 4
+            })
+        ));
+        assert!(matches!(
+            Heightmap::parse("".as_bytes()),
+            Err(ParseError::Empty)
+        ));
+    }
+}
@@ -0,0 +1,84 @@
+use common::{Answer, Solution};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+type Bench = fn(&mut Criterion, usize);
+type Part<S> = fn(&<S as Solution>::Input) -> Answer;
+
+const BENCHES: [Bench; 25] = [
+    bench_day::<day1::Day1>,
+    bench_day::<day2::Day2>,
+    bench_day::<day3::Day3>,
+    bench_day::<day4::Day4>,
+    bench_day::<day5::Day5>,
+    bench_day::<day6::Day6>,
+    bench_day::<day7::Day7>,
+    bench_day::<day8::Day8>,
+    bench_day::<day9::Day9>,
+    bench_day::<day10::Day10>,
+    bench_day::<day11::Day11>,
+    bench_day::<day12::Day12>,
+    bench_day::<day13::Day13>,
+    bench_day::<day14::Day14>,
+    bench_day::<day15::Day15>,
+    bench_day::<day16::Day16>,
+    bench_day::<day17::Day17>,
+    bench_day::<day18::Day18>,
+    bench_day::<day19::Day19>,
+    bench_day::<day20::Day20>,
+    bench_day::<day21::Day21>,
+    bench_day::<day22::Day22>,
+    bench_day::<day23::Day23>,
+    bench_day::<day24::Day24>,
+    bench_day::<day25::Day25>,
+];
+
+// The puzzle input stored in the crate of the day, like the runner uses it.
+fn stored_input(day: usize) -> Option<String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join(format!("day{}", day));
+    ["input.txt", "instance.txt"]
+        .iter()
+        .find_map(|name| fs::read_to_string(dir.join(name)).ok())
+}
+
+// Benchmarks parsing and both parts separately, e.g. `cargo bench -- day19/part1`.
+fn bench_day<S: Solution>(c: &mut Criterion, day: usize) {
+    let input = match stored_input(day) {
+        Some(input) => input,
+        None => {
+            eprintln!("day{}: no stored input, skipped", day);
+            return;
+        }
+    };
+
+    let mut group = c.benchmark_group(format!("day{}", day));
+    // some days take seconds, the minimal sample size keeps the whole run bearable
+    group.sample_size(10);
+    group.bench_function("parse", |b| b.iter(|| S::parse(black_box(&input))));
+
+    let parsed = S::parse(&input);
+    let parts: [(&str, Part<S>); 2] = [("part1", S::part1), ("part2", S::part2)];
+    for (name, part) in parts {
+        // a part failing on the stored input must not abort the benchmarks of the other days
+        if panic::catch_unwind(AssertUnwindSafe(|| part(&parsed))).is_err() {
+            eprintln!("day{}/{}: panicked on the stored input, skipped", day, name);
+            continue;
+        }
+        group.bench_function(name, |b| b.iter(|| part(black_box(&parsed))));
+    }
+    group.finish();
+}
+
+fn solutions(c: &mut Criterion) {
+    for (day, bench) in BENCHES.iter().enumerate() {
+        bench(c, day + 1);
+    }
+}
+
+criterion_group!(benches, solutions);
+criterion_main!(benches);
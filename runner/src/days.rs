@@ -0,0 +1,120 @@
+use common::cancel::CancellationToken;
+use common::{solve_timed, Answer, Solution, Timings};
+use std::path::{Path, PathBuf};
+
+pub const DAYS: usize = 25;
+
+// Parses the puzzle input and solves both parts, timing each phase. None if the token was
+// cancelled before both parts were solved.
+pub type Solver = fn(&str, &CancellationToken) -> Option<((Answer, Answer), Timings)>;
+
+const SOLVERS: [Solver; DAYS] = [
+    solve_timed::<day1::Day1>,
+    solve_timed::<day2::Day2>,
+    solve_timed::<day3::Day3>,
+    solve_timed::<day4::Day4>,
+    solve_timed::<day5::Day5>,
+    solve_timed::<day6::Day6>,
+    solve_timed::<day7::Day7>,
+    solve_timed::<day8::Day8>,
+    solve_timed::<day9::Day9>,
+    solve_timed::<day10::Day10>,
+    solve_timed::<day11::Day11>,
+    solve_timed::<day12::Day12>,
+    solve_timed::<day13::Day13>,
+    solve_timed::<day14::Day14>,
+    solve_timed::<day15::Day15>,
+    solve_timed::<day16::Day16>,
+    solve_timed::<day17::Day17>,
+    solve_timed::<day18::Day18>,
+    solve_timed::<day19::Day19>,
+    solve_timed::<day20::Day20>,
+    solve_timed::<day21::Day21>,
+    solve_timed::<day22::Day22>,
+    solve_timed::<day23::Day23>,
+    solve_timed::<day24::Day24>,
+    solve_timed::<day25::Day25>,
+];
+
+// Parses the puzzle input and solves both parts.
+pub type Solve = dyn Fn(&str) -> (Answer, Answer);
+
+// An alternative implementation of a day.
+pub struct Implementation {
+    pub name: &'static str,
+    pub solve: Box<Solve>,
+}
+
+// The alternative implementations of a day, all of them have to agree with its solver.
+pub fn implementations(day: usize) -> Vec<Implementation> {
+    match day {
+        15 => day15::Solver::ALL
+            .into_iter()
+            .map(|solver| Implementation {
+                name: solver.name(),
+                solve: Box::new(move |input| {
+                    let map = day15::Day15::parse(input);
+                    let part1 = day15::lowest_total_risk(&map, solver).unwrap();
+                    let part2 = day15::lowest_total_risk(&map.expand(5), solver).unwrap();
+                    (part1.into(), part2.into())
+                }),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Names of the stored puzzle inputs, in order of preference.
+const INPUT_FILES: [&str; 2] = ["input.txt", "instance.txt"];
+
+pub fn solver(day: usize) -> Option<Solver> {
+    SOLVERS.get(day.checked_sub(1)?).copied()
+}
+
+// The repository containing the day crates, next to the runner crate.
+pub fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+pub fn crate_dir(root: &Path, day: usize) -> PathBuf {
+    root.join(format!("day{}", day))
+}
+
+// The puzzle input stored in the crate of the day, if any.
+pub fn stored_input(root: &Path, day: usize) -> Option<PathBuf> {
+    INPUT_FILES
+        .iter()
+        .map(|name| crate_dir(root, day).join(name))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_of_repository() {
+        assert!((1..=DAYS).all(|day| solver(day).is_some()));
+        assert!(solver(0).is_none());
+        assert!(solver(DAYS + 1).is_none());
+
+        let root = root();
+        assert_eq!(stored_input(&root, 1), Some(root.join("day1/input.txt")));
+        assert_eq!(
+            stored_input(&root, 19),
+            Some(root.join("day19/instance.txt"))
+        );
+        assert!((1..=DAYS).all(|day| stored_input(&root, day).is_some()));
+        assert_eq!(stored_input(&root.join("nowhere"), 1), None);
+
+        let names: Vec<_> = implementations(15).iter().map(|imp| imp.name).collect();
+        assert_eq!(
+            names,
+            ["dijkstra", "astar", "bidirectional", "alt", "bucket"]
+        );
+        assert!(implementations(1).is_empty());
+    }
+}
@@ -0,0 +1,53 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+// Personal puzzle inputs need the session cookie of a logged in user.
+pub const SESSION_VAR: &str = "AOC_SESSION";
+
+const USER_AGENT: &str = "github.com/emmanuel099/aoc21 runner";
+
+#[derive(Debug, Error)]
+pub enum DownloadError {
+    #[error("The website answered with status {0}, is {SESSION_VAR} still valid?")]
+    Status(u16),
+    #[error("Failed to reach the website: {0}")]
+    Transport(String),
+    #[error("Failed to store the input: {0}")]
+    Io(#[from] io::Error),
+}
+
+pub fn input_url(day: usize) -> String {
+    format!("https://adventofcode.com/2021/day/{}/input", day)
+}
+
+// Downloaded inputs are cached under `inputs/` in the repository.
+pub fn cached_input(root: &Path, day: usize) -> PathBuf {
+    root.join("inputs").join(format!("day{}.txt", day))
+}
+
+// Downloads the input of the day into the cache, unless it is cached already.
+pub fn fetch_input(root: &Path, day: usize, session: &str) -> Result<PathBuf, DownloadError> {
+    let path = cached_input(root, day);
+    if path.is_file() {
+        return Ok(path);
+    }
+
+    let response = ureq::get(&input_url(day))
+        .set("Cookie", &format!("session={}", session))
+        .set("User-Agent", USER_AGENT)
+        .call()
+        .map_err(|e| match e {
+            ureq::Error::Status(status, _) => DownloadError::Status(status),
+            ureq::Error::Transport(e) => DownloadError::Transport(e.to_string()),
+        })?;
+    let input = response.into_string()?;
+
+    fs::create_dir_all(path.parent().unwrap())?;
+    // an interrupted download must not end up in the cache
+    let partial = path.with_extension("part");
+    fs::write(&partial, input)?;
+    fs::rename(&partial, &path)?;
+    Ok(path)
+}
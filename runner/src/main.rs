@@ -0,0 +1,883 @@
+mod days;
+mod download;
+mod scrub;
+mod verify;
+
+use common::cancel::CancellationToken;
+use common::{Answer, Timings};
+use download::DownloadError;
+use rayon::prelude::*;
+use std::any::Any;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tracing::{info_span, Level};
+
+const USAGE: &str = "\
+Usage: aoc21 [-v|-vv] list
+       aoc21 [-v|-vv] run --day <n> [--input <path>] [--time] [--timeout <seconds>]
+       aoc21 [-v|-vv] run-all [--timeout <seconds>]
+       aoc21 [-v|-vv] verify [--day <n>]
+       aoc21 [-v|-vv] scrub [--write]
+
+Without --input, the input downloaded to inputs/ is used, otherwise the input stored in
+the crate of the day. If there is neither, it is downloaded when AOC_SESSION holds the
+session cookie of the website. With --time, the time spent parsing the input and solving
+each part is printed as well.
+
+run-all solves all days at once, in parallel, and prints a summary.
+
+With --timeout, the searches of the slow days are aborted once a day took longer than
+the given number of seconds.
+
+verify solves every day with expected answers in answers.toml on the input stored in its
+crate, and fails if any answer differs. Days marked as known failures are skipped and
+reported with their reason. With --day, all implementations of the day are run on its
+stored input instead, and it fails if any of them disagrees with the day's solver.
+
+scrub checks the inputs stored in the crates for broken line endings, trailing whitespace
+and truncation, and prints statistics of each. With --write, the inputs are normalized.
+Fails if an input has a problem normalizing can't fix.
+
+-v prints the diagnostics of the solvers to stderr, -vv every step of them as well.";
+
+#[derive(Debug, Error)]
+enum RunnerError {
+    #[error("{0}\n\n{USAGE}")]
+    Usage(String),
+    #[error("Day {0} is not implemented")]
+    NotImplemented(usize),
+    #[error(
+        "Day {0} has no stored input, pass one with --input or set {} to download it",
+        download::SESSION_VAR
+    )]
+    MissingInput(usize),
+    #[error("Failed to download the input of day {0}: {1}")]
+    Download(usize, DownloadError),
+    #[error("Failed to read the input of day {0}: {1}")]
+    Io(usize, io::Error),
+    #[error("Failed to scrub the input of day {0}: {1}")]
+    Scrub(usize, scrub::ScrubError),
+    #[error("Failed to load the expected answers: {0}")]
+    Answers(String),
+    #[error("Day {0} panicked: {1}")]
+    Panicked(usize, String),
+    #[error("Day {0} was aborted after {1:?}")]
+    TimedOut(usize, Duration),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    List,
+    Run {
+        day: usize,
+        input: Option<PathBuf>,
+        time: bool,
+        timeout: Option<Duration>,
+    },
+    RunAll {
+        timeout: Option<Duration>,
+    },
+    Verify {
+        day: Option<usize>,
+    },
+    Scrub {
+        write: bool,
+    },
+}
+
+fn parse_command(args: &[String]) -> Result<Command, RunnerError> {
+    let flag = |name: &str| args.iter().skip_while(|arg| *arg != name).nth(1);
+    let timeout = || {
+        flag("--timeout")
+            .map(|seconds| {
+                seconds
+                    .parse()
+                    .ok()
+                    .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                    .ok_or_else(|| RunnerError::Usage(format!("Invalid timeout '{}'", seconds)))
+            })
+            .transpose()
+    };
+    let day = || {
+        flag("--day")
+            .map(|day| {
+                day.parse()
+                    .ok()
+                    .filter(|day| (1..=days::DAYS).contains(day))
+                    .ok_or_else(|| RunnerError::Usage(format!("Invalid day '{}'", day)))
+            })
+            .transpose()
+    };
+
+    match args.first().map(String::as_str) {
+        Some("list") => Ok(Command::List),
+        Some("run-all") => Ok(Command::RunAll {
+            timeout: timeout()?,
+        }),
+        Some("verify") => Ok(Command::Verify { day: day()? }),
+        Some("scrub") => Ok(Command::Scrub {
+            write: args.iter().any(|arg| arg == "--write"),
+        }),
+        Some("run") => {
+            let day = day()?.ok_or_else(|| RunnerError::Usage("Missing --day".to_string()))?;
+            Ok(Command::Run {
+                day,
+                input: flag("--input").map(PathBuf::from),
+                time: args.iter().any(|arg| arg == "--time"),
+                timeout: timeout()?,
+            })
+        }
+        Some(command) => Err(RunnerError::Usage(format!("Unknown command '{}'", command))),
+        None => Err(RunnerError::Usage("Missing command".to_string())),
+    }
+}
+
+// Removes the -v/-vv flags from the arguments and returns how verbose the output should be,
+// each v counts once.
+fn take_verbosity(args: &mut Vec<String>) -> usize {
+    let mut verbosity = 0;
+    args.retain(|arg| match arg.strip_prefix('-') {
+        Some(vs) if !vs.is_empty() && vs.chars().all(|c| c == 'v') => {
+            verbosity += vs.len();
+            false
+        }
+        _ => true,
+    });
+    verbosity
+}
+
+fn init_logging(verbosity: usize) {
+    let level = match verbosity {
+        0 => return,
+        1 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .without_time()
+        .init();
+}
+
+fn list(root: &Path) {
+    for day in 1..=days::DAYS {
+        if days::solver(day).is_none() {
+            println!("day {:>2}  not implemented", day);
+            continue;
+        }
+        let cached = download::cached_input(root, day);
+        let input = Some(cached)
+            .filter(|path| path.is_file())
+            .or_else(|| days::stored_input(root, day))
+            .map_or_else(
+                || "no stored input".to_string(),
+                |path| {
+                    path.strip_prefix(root)
+                        .unwrap_or(&path)
+                        .display()
+                        .to_string()
+                },
+            );
+        println!("day {:>2}  {}", day, input);
+    }
+}
+
+// The downloaded input of the day, or else the one stored in the crate of the day. Only if
+// there is neither, it is fetched with the session token.
+fn find_input(root: &Path, day: usize, session: Option<&str>) -> Result<PathBuf, RunnerError> {
+    let cached = download::cached_input(root, day);
+    if cached.is_file() {
+        return Ok(cached);
+    }
+    if let Some(stored) = days::stored_input(root, day) {
+        return Ok(stored);
+    }
+    match session {
+        Some(session) => {
+            download::fetch_input(root, day, session).map_err(|e| RunnerError::Download(day, e))
+        }
+        None => Err(RunnerError::MissingInput(day)),
+    }
+}
+
+// Solves both parts of the day, on the given input or else the one found for the day.
+fn run(
+    root: &Path,
+    day: usize,
+    input: Option<PathBuf>,
+    session: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<((Answer, Answer), Timings), RunnerError> {
+    let solve = days::solver(day).ok_or(RunnerError::NotImplemented(day))?;
+    let input = match input {
+        Some(input) => input,
+        None => find_input(root, day, session)?,
+    };
+    let input = fs::read_to_string(input).map_err(|e| RunnerError::Io(day, e))?;
+    let _span = info_span!("day", day).entered();
+    solve_until(day, solve, &input, timeout)
+}
+
+type Solved = ((Answer, Answer), Timings);
+
+// Solves the input, aborting the solver once the timeout is over.
+fn solve_until(
+    day: usize,
+    solve: days::Solver,
+    input: &str,
+    timeout: Option<Duration>,
+) -> Result<Solved, RunnerError> {
+    let cancel = CancellationToken::default();
+    if let Some(timeout) = timeout {
+        cancel.cancel_after(timeout);
+    }
+    solve(input, &cancel).ok_or_else(|| RunnerError::TimedOut(day, timeout.unwrap_or_default()))
+}
+
+// Runs `f` without printing the messages of panics, which are caught and reported instead.
+fn silence_panics<T>(f: impl FnOnce() -> T) -> T {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = f();
+    panic::set_hook(hook);
+    result
+}
+
+// Solves the input, turning a panic of the solver into an error.
+fn solve_catching(
+    day: usize,
+    solve: days::Solver,
+    input: &str,
+    timeout: Option<Duration>,
+) -> Result<Solved, RunnerError> {
+    panic::catch_unwind(|| {
+        let _span = info_span!("day", day).entered();
+        solve_until(day, solve, input, timeout)
+    })
+    .map_err(|payload| RunnerError::Panicked(day, panic_message(payload)))?
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default()
+}
+
+// Solves all implemented days in parallel. The inputs are looked up (and downloaded) one after
+// the other beforehand.
+fn run_all(
+    root: &Path,
+    session: Option<&str>,
+    timeout: Option<Duration>,
+) -> Vec<(usize, Result<Solved, RunnerError>)> {
+    let inputs: Vec<_> = (1..=days::DAYS)
+        .filter_map(|day| Some((day, days::solver(day)?)))
+        .map(|(day, solve)| {
+            let input = find_input(root, day, session)
+                .and_then(|input| fs::read_to_string(input).map_err(|e| RunnerError::Io(day, e)));
+            (day, solve, input)
+        })
+        .collect();
+
+    silence_panics(|| {
+        inputs
+            .into_par_iter()
+            .map(|(day, solve, input)| {
+                (
+                    day,
+                    input.and_then(|input| solve_catching(day, solve, &input, timeout)),
+                )
+            })
+            .collect()
+    })
+}
+
+// Prints one row per day, multi-line answers follow below the table. Returns whether all days
+// were solved.
+fn print_summary(results: &[(usize, Result<Solved, RunnerError>)]) -> bool {
+    let is_multi_line = |answer: &Answer| answer.to_string().trim_end().contains('\n');
+    let single_line = |answer: &Answer| {
+        if is_multi_line(answer) {
+            "(below)".to_string()
+        } else {
+            answer.to_string()
+        }
+    };
+    let width = results
+        .iter()
+        .filter_map(|(_, result)| result.as_ref().ok())
+        .flat_map(|((part1, part2), _)| [single_line(part1).len(), single_line(part2).len()])
+        .chain(["part 1".len()])
+        .max()
+        .unwrap();
+
+    println!(
+        "day  {:<w$}  {:<w$}  {:>12}",
+        "part 1",
+        "part 2",
+        "time",
+        w = width
+    );
+    let mut multi_line = Vec::new();
+    let mut failures = 0;
+    let mut total = Duration::ZERO;
+    for (day, result) in results {
+        match result {
+            Ok(((part1, part2), timings)) => {
+                println!(
+                    "{:>3}  {:<w$}  {:<w$}  {:>12?}",
+                    day,
+                    single_line(part1),
+                    single_line(part2),
+                    timings.total(),
+                    w = width
+                );
+                for (part, answer) in [(1, part1), (2, part2)] {
+                    if is_multi_line(answer) {
+                        multi_line.push((day, part, answer));
+                    }
+                }
+                total += timings.total();
+            }
+            Err(e) => {
+                failures += 1;
+                // only the first line, to keep the table readable
+                let e = e.to_string();
+                println!("{:>3}  {}", day, e.lines().next().unwrap_or_default());
+            }
+        }
+    }
+    println!("{:>w$}  {:>12?}", "total", total, w = 2 * width + 7);
+
+    for (day, part, answer) in multi_line {
+        println!(
+            "\nDay {} part {}:\n{}",
+            day,
+            part,
+            answer.to_string().trim_end()
+        );
+    }
+    failures == 0
+}
+
+#[derive(Debug)]
+enum Verdict {
+    Passed,
+    Failed(Vec<verify::Mismatch>),
+    Panicked(String),
+}
+
+// Solves the day on its stored input and compares the answers with the expected ones.
+fn verify_day(
+    root: &Path,
+    day: usize,
+    expected: &verify::Expected,
+) -> Result<Verdict, RunnerError> {
+    let solve = days::solver(day).ok_or(RunnerError::NotImplemented(day))?;
+    let input = days::stored_input(root, day).ok_or(RunnerError::MissingInput(day))?;
+    let input = fs::read_to_string(input).map_err(|e| RunnerError::Io(day, e))?;
+
+    // a panicking day is a regression like any other and must not end the verification
+    Ok(
+        match silence_panics(|| solve_catching(day, solve, &input, None)) {
+            Ok(((part1, part2), _)) => {
+                let mismatches = verify::check(expected, &part1, &part2);
+                if mismatches.is_empty() {
+                    Verdict::Passed
+                } else {
+                    Verdict::Failed(mismatches)
+                }
+            }
+            Err(RunnerError::Panicked(_, message)) => Verdict::Panicked(message),
+            Err(e) => return Err(e),
+        },
+    )
+}
+
+// Verifies all days with expected answers, returns whether there was no regression.
+fn verify(root: &Path) -> Result<bool, RunnerError> {
+    let answers = fs::read_to_string(verify::answers_file(root))
+        .map_err(|e| RunnerError::Answers(e.to_string()))?;
+    let answers =
+        verify::parse_answers(&answers).map_err(|e| RunnerError::Answers(e.to_string()))?;
+
+    let mut verified = 0;
+    let mut regressions = 0;
+    let mut known_failures = 0;
+    for day in 1..=days::DAYS {
+        let expected = match answers.get(&day) {
+            Some(expected) => expected,
+            None => {
+                println!("day {:>2}  no expected answers", day);
+                continue;
+            }
+        };
+        if let Some(reason) = &expected.known_failure {
+            known_failures += 1;
+            println!("day {:>2}  known failure: {}", day, reason);
+            continue;
+        }
+        verified += 1;
+        match verify_day(root, day, expected) {
+            Ok(Verdict::Passed) => println!("day {:>2}  ok", day),
+            Ok(Verdict::Failed(mismatches)) => {
+                regressions += 1;
+                for mismatch in mismatches {
+                    println!("day {:>2}  {}", day, mismatch);
+                }
+            }
+            Ok(Verdict::Panicked(message)) => {
+                regressions += 1;
+                println!("day {:>2}  panicked: {}", day, message);
+            }
+            Err(e) => {
+                regressions += 1;
+                println!("day {:>2}  {}", day, e);
+            }
+        }
+    }
+
+    println!(
+        "\n{} of {} days verified, {} regressions, {} known failures",
+        verified - regressions,
+        verified,
+        regressions,
+        known_failures
+    );
+    Ok(regressions == 0)
+}
+
+// Runs all implementations of the day on its stored input and compares their answers with the
+// ones of the day's solver, returns whether all of them agree.
+fn verify_implementations(root: &Path, day: usize) -> Result<bool, RunnerError> {
+    let solve = days::solver(day).ok_or(RunnerError::NotImplemented(day))?;
+    let input = days::stored_input(root, day).ok_or(RunnerError::MissingInput(day))?;
+    let input = fs::read_to_string(input).map_err(|e| RunnerError::Io(day, e))?;
+
+    let implementations = days::implementations(day);
+    if implementations.is_empty() {
+        println!("day {:>2}  has no alternative implementations", day);
+        return Ok(true);
+    }
+
+    let ((part1, part2), _) = solve_catching(day, solve, &input, None)?;
+    let reference = verify::Expected {
+        part1: Some(part1.to_string()),
+        part2: Some(part2.to_string()),
+        known_failure: None,
+    };
+
+    let mut disagreements = 0;
+    for implementation in &implementations {
+        let solved = silence_panics(|| {
+            panic::catch_unwind(panic::AssertUnwindSafe(|| (implementation.solve)(&input)))
+        });
+        let name = implementation.name;
+        match solved {
+            Ok((part1, part2)) => {
+                let mismatches = verify::check(&reference, &part1, &part2);
+                if mismatches.is_empty() {
+                    println!("day {:>2}  {}  ok", day, name);
+                } else {
+                    disagreements += 1;
+                }
+                for mismatch in mismatches {
+                    println!("day {:>2}  {}  {}", day, name, mismatch);
+                }
+            }
+            Err(payload) => {
+                disagreements += 1;
+                println!(
+                    "day {:>2}  {}  panicked: {}",
+                    day,
+                    name,
+                    panic_message(payload)
+                );
+            }
+        }
+    }
+
+    println!(
+        "\n{} of {} implementations agree",
+        implementations.len() - disagreements,
+        implementations.len()
+    );
+    Ok(disagreements == 0)
+}
+
+// Checks (and with `write` normalizes) the stored input of every day, returns whether all of
+// them are fine afterwards.
+fn scrub(root: &Path, write: bool) -> Result<bool, RunnerError> {
+    let mut clean = true;
+    for day in 1..=days::DAYS {
+        let Some(path) = days::stored_input(root, day) else {
+            println!("day {:>2}  no stored input", day);
+            continue;
+        };
+        let raw = fs::read(&path).map_err(|e| RunnerError::Io(day, e))?;
+        let scrubbed = scrub::scrub(&raw).map_err(|e| RunnerError::Scrub(day, e))?;
+        println!("day {:>2}  {}", day, scrubbed.stats);
+        for issue in &scrubbed.issues {
+            let fixed = write && issue.is_fixed_by_normalizing();
+            clean &= fixed;
+            println!("        {}{}", issue, if fixed { " (fixed)" } else { "" });
+        }
+        if write && scrubbed.normalized.as_bytes() != raw {
+            fs::write(&path, &scrubbed.normalized).map_err(|e| RunnerError::Io(day, e))?;
+        }
+    }
+    Ok(clean)
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    init_logging(take_verbosity(&mut args));
+    let result = parse_command(&args).and_then(|command| match command {
+        Command::List => {
+            list(&days::root());
+            Ok(())
+        }
+        Command::Run {
+            day,
+            input,
+            time,
+            timeout,
+        } => {
+            let session = env::var(download::SESSION_VAR).ok();
+            let ((part1, part2), timings) =
+                run(&days::root(), day, input, session.as_deref(), timeout)?;
+            println!("Part 1: {}", part1);
+            println!("Part 2: {}", part2);
+            if time {
+                println!();
+                println!("parse   {:>12?}", timings.parse);
+                println!("part 1  {:>12?}", timings.part1);
+                println!("part 2  {:>12?}", timings.part2);
+                println!("total   {:>12?}", timings.total());
+            }
+            Ok(())
+        }
+        Command::RunAll { timeout } => {
+            let session = env::var(download::SESSION_VAR).ok();
+            let start = Instant::now();
+            let results = run_all(&days::root(), session.as_deref(), timeout);
+            let solved = print_summary(&results);
+            println!("\nSolved in {:?} of wall-clock time", start.elapsed());
+            if !solved {
+                process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Verify { day } => {
+            let verified = match day {
+                Some(day) => verify_implementations(&days::root(), day)?,
+                None => verify(&days::root())?,
+            };
+            if !verified {
+                process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Scrub { write } => {
+            if !scrub(&days::root(), write)? {
+                process::exit(1);
+            }
+            Ok(())
+        }
+    });
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse_command(&args("list")).unwrap(), Command::List);
+        assert_eq!(
+            parse_command(&args("verify")).unwrap(),
+            Command::Verify { day: None }
+        );
+        assert_eq!(
+            parse_command(&args("verify --day 15")).unwrap(),
+            Command::Verify { day: Some(15) }
+        );
+        assert_eq!(
+            parse_command(&args("scrub --write")).unwrap(),
+            Command::Scrub { write: true }
+        );
+        assert_eq!(
+            parse_command(&args("run-all")).unwrap(),
+            Command::RunAll { timeout: None }
+        );
+        assert_eq!(
+            parse_command(&args("run-all --timeout 1.5")).unwrap(),
+            Command::RunAll {
+                timeout: Some(Duration::from_millis(1500))
+            }
+        );
+        assert_eq!(
+            parse_command(&args("run --day 19 --input inputs/day19.txt")).unwrap(),
+            Command::Run {
+                day: 19,
+                input: Some(PathBuf::from("inputs/day19.txt")),
+                time: false,
+                timeout: None,
+            }
+        );
+        assert_eq!(
+            parse_command(&args("run --day 23 --time --timeout 10")).unwrap(),
+            Command::Run {
+                day: 23,
+                input: None,
+                time: true,
+                timeout: Some(Duration::from_secs(10)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_take_verbosity() {
+        let mut quiet = args("run --day 5 --time");
+        assert_eq!(take_verbosity(&mut quiet), 0);
+        assert_eq!(quiet, args("run --day 5 --time"));
+
+        let mut verbose = args("-v run --day 5");
+        assert_eq!(take_verbosity(&mut verbose), 1);
+        assert_eq!(verbose, args("run --day 5"));
+
+        let mut very_verbose = args("run-all -vv");
+        assert_eq!(take_verbosity(&mut very_verbose), 2);
+        assert_eq!(very_verbose, args("run-all"));
+        assert_eq!(take_verbosity(&mut args("-v verify -v")), 2);
+
+        // neither a lone dash nor other flags count
+        let mut other = args("run --day 5 - -x");
+        assert_eq!(take_verbosity(&mut other), 0);
+        assert_eq!(other.len(), 5);
+    }
+
+    #[test]
+    fn test_parse_invalid_command() {
+        for invalid in [
+            "",
+            "solve",
+            "run",
+            "run --day",
+            "run --day 0",
+            "run --day 26",
+            "run --day x",
+            "run --day 1 --timeout x",
+            "run-all --timeout -1",
+        ] {
+            assert!(
+                matches!(parse_command(&args(invalid)), Err(RunnerError::Usage(_))),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let root = days::root();
+        let ((part1, part2), _) = run(&root, 1, None, None, None).unwrap();
+        assert_eq!(
+            (part1.to_string(), part2.to_string()),
+            ("1529".into(), "1567".into())
+        );
+
+        let example = days::crate_dir(&root, 1).join("example.txt");
+        let ((part1, part2), _) = run(&root, 1, Some(example), None, None).unwrap();
+        assert_eq!(
+            (part1.to_string(), part2.to_string()),
+            ("7".into(), "5".into())
+        );
+    }
+
+    #[test]
+    fn test_run_on_downloaded_input() {
+        let root = env::temp_dir().join(format!("aoc21-runner-{}", process::id()));
+        let cached = download::cached_input(&root, 1);
+        fs::create_dir_all(cached.parent().unwrap()).unwrap();
+        fs::write(&cached, "1\n2\n3\n5\n").unwrap();
+
+        // cached inputs are never downloaded again
+        let found = find_input(&root, 1, Some("no session"));
+        let result = run(&root, 1, None, Some("no session"), None);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(found.unwrap(), cached);
+        let ((part1, part2), _) = result.unwrap();
+        assert_eq!(
+            (part1.to_string(), part2.to_string()),
+            ("3".into(), "1".into())
+        );
+    }
+
+    #[test]
+    fn test_find_stored_input() {
+        let root = env::temp_dir().join(format!("aoc21-stored-{}", process::id()));
+        let stored = days::crate_dir(&root, 1).join("instance.txt");
+        fs::create_dir_all(stored.parent().unwrap()).unwrap();
+        fs::write(&stored, "1\n2\n").unwrap();
+
+        // stored inputs are used without downloading
+        let found = find_input(&root, 1, Some("no session"));
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(found.unwrap(), stored);
+    }
+
+    #[test]
+    fn test_run_all() {
+        let root = env::temp_dir().join(format!("aoc21-run-all-{}", process::id()));
+        let cached = download::cached_input(&root, 1);
+        fs::create_dir_all(cached.parent().unwrap()).unwrap();
+        fs::write(&cached, "1\n2\n3\n5\n").unwrap();
+
+        let results = run_all(&root, None, None);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            results.iter().map(|(day, _)| *day).collect::<Vec<_>>(),
+            (1..=days::DAYS).collect::<Vec<_>>()
+        );
+        let ((part1, part2), _) = results[0].1.as_ref().unwrap();
+        assert_eq!(
+            (part1.to_string(), part2.to_string()),
+            ("3".into(), "1".into())
+        );
+        assert!(results[1..]
+            .iter()
+            .all(|(day, result)| matches!(result, Err(RunnerError::MissingInput(d)) if d == day)));
+        assert!(!print_summary(&results));
+    }
+
+    #[test]
+    fn test_solve_catching() {
+        let solve: days::Solver = |input, cancel| {
+            assert!(!input.is_empty(), "empty input");
+            common::solve_timed::<day1::Day1>(input, cancel)
+        };
+        assert!(solve_catching(1, solve, "1\n2\n", None).is_ok());
+        assert!(matches!(
+            silence_panics(|| solve_catching(1, solve, "", None)),
+            Err(RunnerError::Panicked(1, message)) if message == "empty input"
+        ));
+    }
+
+    #[test]
+    fn test_solve_until_timeout() {
+        // searches until it is aborted
+        let solve: days::Solver = |_, cancel| {
+            while !cancel.is_cancelled() {
+                std::thread::yield_now();
+            }
+            None
+        };
+        assert!(matches!(
+            solve_until(23, solve, "", Some(Duration::from_millis(10))),
+            Err(RunnerError::TimedOut(23, timeout)) if timeout == Duration::from_millis(10)
+        ));
+        let solve = days::solver(1).unwrap();
+        assert!(solve_until(1, solve, "1\n2\n", Some(Duration::from_secs(60))).is_ok());
+    }
+
+    #[test]
+    fn test_verify_day() {
+        let root = days::root();
+        let expected = verify::Expected {
+            part1: Some("1529".into()),
+            part2: Some("1567".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            verify_day(&root, 1, &expected),
+            Ok(Verdict::Passed)
+        ));
+
+        let expected = verify::Expected {
+            part1: Some("1529".into()),
+            part2: Some("1566".into()),
+            ..Default::default()
+        };
+        match verify_day(&root, 1, &expected) {
+            Ok(Verdict::Failed(mismatches)) => {
+                assert_eq!(mismatches.len(), 1);
+                assert_eq!(mismatches[0].part, 2);
+            }
+            verdict => panic!("unexpected verdict {:?}", verdict),
+        }
+
+        assert!(matches!(
+            verify_day(&root.join("nowhere"), 1, &expected),
+            Err(RunnerError::MissingInput(1))
+        ));
+    }
+
+    #[test]
+    fn test_verify_implementations() {
+        let root = days::root();
+        assert!(verify_implementations(&root, 15).unwrap());
+        assert!(verify_implementations(&root, 1).unwrap());
+        assert!(matches!(
+            verify_implementations(&root.join("nowhere"), 15),
+            Err(RunnerError::MissingInput(15))
+        ));
+    }
+
+    #[test]
+    fn test_scrub() {
+        let root = env::temp_dir().join(format!("aoc21-scrub-{}", process::id()));
+        let stored = days::crate_dir(&root, 1).join("input.txt");
+        fs::create_dir_all(stored.parent().unwrap()).unwrap();
+        fs::write(&stored, "1\r\n2  \n\n").unwrap();
+
+        let checked = scrub(&root, false);
+        let unchanged = fs::read_to_string(&stored).unwrap();
+        let written = scrub(&root, true);
+        let normalized = fs::read_to_string(&stored).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(!checked.unwrap());
+        assert_eq!(unchanged, "1\r\n2  \n\n");
+        assert!(written.unwrap());
+        assert_eq!(normalized, "1\n2\n");
+    }
+
+    #[test]
+    fn test_run_errors() {
+        let root = days::root();
+        assert!(matches!(
+            run(&root, 26, None, None, None),
+            Err(RunnerError::NotImplemented(26))
+        ));
+        assert!(matches!(
+            run(&root.join("nowhere"), 1, None, None, None),
+            Err(RunnerError::MissingInput(1))
+        ));
+        assert!(matches!(
+            run(&root, 1, Some(root.join("nowhere.txt")), None, None),
+            Err(RunnerError::Io(1, _))
+        ));
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt;
+use std::str;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ScrubError {
+    #[error("Not valid UTF-8 after byte {0}")]
+    InvalidUtf8(usize),
+}
+
+// Problem of a stored input. Line endings, trailing whitespace and blank lines are fixed by
+// normalizing, the others have to be fixed by hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    Empty,
+    CarriageReturns,
+    TrailingWhitespace { lines: usize },
+    MissingFinalNewline,
+    TrailingBlankLines,
+    // the last line of an otherwise rectangular block is shorter than the others
+    Truncated { line: usize },
+}
+
+impl Issue {
+    pub fn is_fixed_by_normalizing(&self) -> bool {
+        !matches!(self, Issue::Empty | Issue::Truncated { .. })
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::Empty => write!(f, "empty"),
+            Issue::CarriageReturns => write!(f, "carriage returns"),
+            Issue::TrailingWhitespace { lines } => {
+                write!(f, "trailing whitespace on {} lines", lines)
+            }
+            Issue::MissingFinalNewline => write!(f, "missing final newline"),
+            Issue::TrailingBlankLines => write!(f, "trailing blank lines"),
+            Issue::Truncated { line } => write!(f, "line {} looks truncated", line),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputStats {
+    pub bytes: usize,
+    pub lines: usize,
+    // integers which aren't part of a word, e.g. not the digits of a hex string
+    pub numbers: usize,
+    pub range: Option<(i64, i64)>,
+}
+
+impl fmt::Display for InputStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} lines, {} bytes", self.lines, self.bytes)?;
+        if let Some((min, max)) = self.range {
+            write!(f, ", {} numbers in {}..={}", self.numbers, min, max)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scrubbed {
+    pub normalized: String,
+    // of the normalized input
+    pub stats: InputStats,
+    pub issues: Vec<Issue>,
+}
+
+// Unix line endings, no trailing whitespace and exactly one newline at the end.
+pub fn normalize(input: &str) -> String {
+    let lines: Vec<_> = input.lines().map(str::trim_end).collect();
+    let len = lines.len()
+        - lines
+            .iter()
+            .rev()
+            .take_while(|line| line.is_empty())
+            .count();
+    let mut normalized = lines[..len].join("\n");
+    if !normalized.is_empty() {
+        normalized.push('\n');
+    }
+    normalized
+}
+
+pub fn scrub(raw: &[u8]) -> Result<Scrubbed, ScrubError> {
+    let input = str::from_utf8(raw).map_err(|e| ScrubError::InvalidUtf8(e.valid_up_to()))?;
+    let normalized = normalize(input);
+
+    let mut issues = Vec::new();
+    if normalized.is_empty() {
+        issues.push(Issue::Empty);
+    }
+    if input.contains('\r') {
+        issues.push(Issue::CarriageReturns);
+    }
+    let untrimmed = input
+        .lines()
+        .filter(|line| line.len() != line.trim_end().len())
+        .count();
+    if untrimmed > 0 {
+        issues.push(Issue::TrailingWhitespace { lines: untrimmed });
+    }
+    if !input.is_empty() && !input.ends_with('\n') {
+        issues.push(Issue::MissingFinalNewline);
+    }
+    if !normalized.is_empty() && input.trim_end_matches([' ', '\t', '\r']).ends_with("\n\n") {
+        issues.push(Issue::TrailingBlankLines);
+    }
+    if let Some(line) = truncated_line(&normalized) {
+        issues.push(Issue::Truncated { line });
+    }
+
+    Ok(Scrubbed {
+        stats: stats(&normalized),
+        normalized,
+        issues,
+    })
+}
+
+// The last line of the last block if it is shorter than all other lines of the block, which are
+// equally long. Blocks are separated by blank lines, a grid below a header is checked on its own.
+fn truncated_line(input: &str) -> Option<usize> {
+    let lines: Vec<_> = input.lines().collect();
+    let start = lines
+        .iter()
+        .rposition(|line| line.is_empty())
+        .map_or(0, |i| i + 1);
+    let (last, block) = lines[start..].split_last()?;
+    let width = block.first()?.len();
+    (block.len() >= 2 && block.iter().all(|line| line.len() == width) && last.len() < width)
+        .then_some(lines.len())
+}
+
+// Integers which aren't part of a word like a hex string. A minus is a sign unless it follows a
+// word, "x=-5" is negative while "5-10" are two positive numbers.
+fn numbers(input: &str) -> Vec<i64> {
+    let bytes = input.as_bytes();
+    let is_word = |i: usize| bytes[i].is_ascii_alphanumeric();
+    let mut numbers = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() || (i > 0 && is_word(i - 1)) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i < bytes.len() && is_word(i) {
+            continue;
+        }
+        let negative = start > 0 && bytes[start - 1] == b'-' && (start < 2 || !is_word(start - 2));
+        // numbers too large for an i64 aren't values anyway
+        if let Ok(value) = input[start..i].parse::<i64>() {
+            numbers.push(if negative { -value } else { value });
+        }
+    }
+    numbers
+}
+
+fn stats(input: &str) -> InputStats {
+    let numbers = numbers(input);
+    let range = numbers.iter().min().zip(numbers.iter().max());
+    InputStats {
+        bytes: input.len(),
+        lines: input.lines().count(),
+        numbers: numbers.len(),
+        range: range.map(|(&min, &max)| (min, max)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("1\r\n2  \n\n3\t\n\n\n"), "1\n2\n\n3\n");
+        assert_eq!(normalize("1\n2"), "1\n2\n");
+        assert_eq!(normalize("\n \n"), "");
+        assert_eq!(normalize(""), "");
+    }
+
+    #[test]
+    fn test_scrub() {
+        let scrubbed = scrub(b"on x=-5..10,y=3\r\n#.#  \n").unwrap();
+        assert_eq!(scrubbed.normalized, "on x=-5..10,y=3\n#.#\n");
+        assert_eq!(
+            scrubbed.issues,
+            vec![
+                Issue::CarriageReturns,
+                Issue::TrailingWhitespace { lines: 1 }
+            ]
+        );
+        assert_eq!(
+            scrubbed.stats,
+            InputStats {
+                bytes: 20,
+                lines: 2,
+                numbers: 3,
+                range: Some((-5, 10)),
+            }
+        );
+
+        let scrubbed = scrub(b"9C0141080250320F1802104A08\n\n").unwrap();
+        assert_eq!(scrubbed.issues, vec![Issue::TrailingBlankLines]);
+        assert_eq!(scrubbed.stats.numbers, 0);
+        assert_eq!(scrubbed.stats.range, None);
+
+        assert_eq!(scrub(b"").unwrap().issues, vec![Issue::Empty]);
+        assert_eq!(scrub(b"1\n\xff\n"), Err(ScrubError::InvalidUtf8(2)));
+    }
+
+    #[test]
+    fn test_scrub_truncated() {
+        let scrubbed = scrub(b"algorithm\n\n#..#\n.##.\n#.").unwrap();
+        assert_eq!(
+            scrubbed.issues,
+            vec![Issue::MissingFinalNewline, Issue::Truncated { line: 5 }]
+        );
+        assert!(!scrubbed.issues[1].is_fixed_by_normalizing());
+
+        // lines of different lengths are fine
+        assert_eq!(scrub(b"forward 5\ndown 5\nup 3\n").unwrap().issues, vec![]);
+    }
+
+    #[test]
+    fn test_scrub_stored_inputs() {
+        let root = crate::days::root();
+        for day in 1..=crate::days::DAYS {
+            let path = crate::days::stored_input(&root, day).unwrap();
+            let scrubbed = scrub(&std::fs::read(path).unwrap()).unwrap();
+            assert!(
+                scrubbed.issues.iter().all(Issue::is_fixed_by_normalizing),
+                "day {}: {:?}",
+                day,
+                scrubbed.issues
+            );
+        }
+    }
+}
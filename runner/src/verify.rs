@@ -0,0 +1,183 @@
+use common::Answer;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AnswersError {
+    #[error("{0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("Invalid day '{0}', expected a table like [day1]")]
+    InvalidDay(String),
+}
+
+// Expected answers of one day, a part without an answer is not checked. A day with a known
+// failure isn't solved at all, the reason is reported instead.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expected {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+    pub known_failure: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub part: usize,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // multi-line answers start on their own line
+        let separator = |answer: &str| if answer.contains('\n') { "\n" } else { " " };
+        write!(
+            f,
+            "part {}: expected{}{}, got{}{}",
+            self.part,
+            separator(&self.expected),
+            self.expected,
+            separator(&self.actual),
+            self.actual
+        )
+    }
+}
+
+pub fn answers_file(root: &Path) -> PathBuf {
+    root.join("answers.toml")
+}
+
+pub fn parse_answers(s: &str) -> Result<BTreeMap<usize, Expected>, AnswersError> {
+    let tables: BTreeMap<String, Expected> = toml::from_str(s)?;
+    tables
+        .into_iter()
+        .map(|(name, expected)| {
+            let day = name
+                .strip_prefix("day")
+                .and_then(|day| day.parse().ok())
+                .ok_or(AnswersError::InvalidDay(name))?;
+            Ok((day, expected))
+        })
+        .collect()
+}
+
+// Compares the answers of both parts with the expected ones, ignoring trailing whitespace.
+pub fn check(expected: &Expected, part1: &Answer, part2: &Answer) -> Vec<Mismatch> {
+    [(1, &expected.part1, part1), (2, &expected.part2, part2)]
+        .into_iter()
+        .filter_map(|(part, expected, actual)| {
+            let expected = expected.as_deref()?.trim_end();
+            let actual = actual.to_string();
+            let actual = actual.trim_end();
+            (expected != actual).then(|| Mismatch {
+                part,
+                expected: expected.to_string(),
+                actual: actual.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() {
+        let answers = parse_answers(
+            "[day1]\npart1 = \"7\"\npart2 = \"5\"\n\n[day25]\npart1 = \"58\"\n\n[day13]\npart2 = '''\n#.\n.#\n'''\n\n[day19]\nknown_failure = \"panics\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            answers.into_iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    1,
+                    Expected {
+                        part1: Some("7".into()),
+                        part2: Some("5".into()),
+                        known_failure: None,
+                    }
+                ),
+                (
+                    13,
+                    Expected {
+                        part1: None,
+                        part2: Some("#.\n.#\n".into()),
+                        known_failure: None,
+                    }
+                ),
+                (
+                    19,
+                    Expected {
+                        part1: None,
+                        part2: None,
+                        known_failure: Some("panics".into()),
+                    }
+                ),
+                (
+                    25,
+                    Expected {
+                        part1: Some("58".into()),
+                        part2: None,
+                        known_failure: None,
+                    }
+                ),
+            ]
+        );
+
+        assert!(matches!(
+            parse_answers("[first]\npart1 = \"7\"\n"),
+            Err(AnswersError::InvalidDay(_))
+        ));
+        assert!(matches!(
+            parse_answers("[day1]\npart3 = \"7\"\n"),
+            Err(AnswersError::Toml(_))
+        ));
+    }
+
+    #[test]
+    fn test_answers_of_repository() {
+        let root = crate::days::root();
+        let answers =
+            parse_answers(&std::fs::read_to_string(answers_file(&root)).unwrap()).unwrap();
+        assert!(answers
+            .keys()
+            .all(|day| (1..=crate::days::DAYS).contains(day)));
+        assert_eq!(answers[&1].part1.as_deref(), Some("1529"));
+    }
+
+    #[test]
+    fn test_check() {
+        let expected = Expected {
+            part1: Some("7".into()),
+            part2: Some("#.\n.#\n".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            check(&expected, &Answer::from(7), &Answer::from("#.\n.#")),
+            vec![]
+        );
+        assert_eq!(
+            check(&expected, &Answer::from(8), &Answer::from("#.\n.#\n")),
+            vec![Mismatch {
+                part: 1,
+                expected: "7".into(),
+                actual: "8".into(),
+            }]
+        );
+        assert_eq!(
+            check(&Expected::default(), &Answer::from(8), &Answer::from(9)),
+            vec![]
+        );
+
+        let mismatches = check(&expected, &Answer::from(7), &Answer::from("##\n.#"));
+        assert_eq!(
+            mismatches[0].to_string(),
+            "part 2: expected\n#.\n.#, got\n##\n.#"
+        );
+    }
+}